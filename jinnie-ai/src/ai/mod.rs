@@ -4,5 +4,5 @@ pub mod response_generator;
 pub mod prompt_builder;
 
 // Re-export main functionality
-pub use response_generator::generate_agent_response;
-pub use prompt_builder::build_agent_system_prompt;
\ No newline at end of file
+pub use response_generator::{generate_agent_response, generate_agent_reply, generate_agent_reply_with_memories};
+pub use prompt_builder::{build_agent_system_prompt, build_memory_context};
\ No newline at end of file
@@ -22,6 +22,34 @@ pub fn build_agent_system_prompt(agent: &Agent) -> String {
     prompt
 }
 
+/// Format recalled memories as a prompt section; empty when there is nothing to add
+pub fn build_memory_context(memories: &[String]) -> String {
+    if memories.is_empty() {
+        return String::new();
+    }
+
+    let mut context = String::from("Things you remember from earlier conversations with this user:\n");
+    for memory in memories {
+        context.push_str(&format!("- {}\n", memory.trim()));
+    }
+    context.push_str("Use these only when they are relevant to the current message.\n\n");
+    context
+}
+
+/// Format facts the user has confirmed about themselves; empty when there are none
+pub fn build_profile_context(facts: &[String]) -> String {
+    if facts.is_empty() {
+        return String::new();
+    }
+
+    let mut context = String::from("What the user has confirmed about themselves, in their own words:\n");
+    for fact in facts {
+        context.push_str(&format!("- {}\n", fact.trim()));
+    }
+    context.push('\n');
+    context
+}
+
 /// Get personality-specific prompt components
 fn get_personality_prompt(personality: &str) -> &'static str {
     match personality.to_lowercase().as_str() {
@@ -214,6 +242,23 @@ mod tests {
         assert!(prompt.contains("Focus on Python programming"));
     }
 
+    #[test]
+    fn test_build_profile_context() {
+        assert!(build_profile_context(&[]).is_empty());
+
+        let context = build_profile_context(&["I prefer TypeScript over JavaScript".to_string()]);
+        assert!(context.contains("confirmed"));
+        assert!(context.contains("- I prefer TypeScript over JavaScript\n"));
+    }
+
+    #[test]
+    fn test_build_memory_context() {
+        assert!(build_memory_context(&[]).is_empty());
+
+        let context = build_memory_context(&["Prefers Rust over Go ".to_string()]);
+        assert!(context.contains("- Prefers Rust over Go\n"));
+    }
+
     #[test]
     fn test_personality_prompts() {
         assert!(get_personality_prompt("professional").contains("professional"));
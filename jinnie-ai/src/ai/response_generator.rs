@@ -1,18 +1,40 @@
 use crate::types::Agent;
-use crate::ai::prompt_builder::build_agent_system_prompt;
+use crate::ai::prompt_builder::{build_agent_system_prompt, build_memory_context, build_profile_context};
+use crate::llm::reasoning::{split_reasoning, ParsedReply, StreamChunk};
+use crate::services::ollama::{OllamaClient, OllamaRequest};
 use crate::utils::error::{LocalMindError, Result};
 use serde_json;
 
 /// Generate an AI response for the given agent and user message
 pub async fn generate_agent_response(agent: &Agent, user_message: &str) -> Result<String> {
+    Ok(generate_agent_reply(agent, user_message).await?.content)
+}
+
+/// Generate an AI response, keeping the model's reasoning separate from the answer
+pub async fn generate_agent_reply(agent: &Agent, user_message: &str) -> Result<ParsedReply> {
+    generate_agent_reply_with_memories(agent, user_message, &[], &[]).await
+}
+
+/// Generate an AI response with the user's verified profile and recalled memories added to the prompt
+pub async fn generate_agent_reply_with_memories(
+    agent: &Agent,
+    user_message: &str,
+    profile: &[String],
+    memories: &[String],
+) -> Result<ParsedReply> {
     // Build the prompt based on agent's personality and specialization
-    let system_prompt = build_agent_system_prompt(agent);
+    let system_prompt = format!(
+        "{}\n\n{}{}",
+        build_agent_system_prompt(agent),
+        build_profile_context(profile),
+        build_memory_context(memories)
+    );
     
     // Prepare the request to Ollama
     let client = reqwest::Client::new();
     let ollama_request = serde_json::json!({
         "model": "llama3.1:8b",
-        "prompt": format!("{}\n\nUser: {}\nAssistant:", system_prompt, user_message),
+        "prompt": format!("{}\n\nUser: {}\nAssistant:", system_prompt.trim_end(), user_message),
         "stream": false,
         "options": {
             "temperature": 0.7,
@@ -39,36 +61,39 @@ pub async fn generate_agent_response(agent: &Agent, user_message: &str) -> Resul
 
     let ai_response = response_json["response"]
         .as_str()
-        .unwrap_or("I apologize, but I'm having trouble generating a response right now.")
-        .trim();
+        .unwrap_or("I apologize, but I'm having trouble generating a response right now.");
+
+    let thinking = response_json["thinking"].as_str().map(|t| t.to_string());
 
-    Ok(ai_response.to_string())
+    Ok(split_reasoning(ai_response).with_extra_reasoning(thinking))
 }
 
-/// Generate a streaming AI response (for future implementation)
+/// Generate a streaming AI response.
+///
+/// Thinking tokens are delivered as `StreamChunk::Thinking` and answer tokens as
+/// `StreamChunk::Answer`, so the caller can render them separately.
 pub async fn generate_streaming_response(
     agent: &Agent,
     user_message: &str,
-    callback: impl Fn(String) -> Result<()>,
-) -> Result<String> {
-    // This would implement streaming responses in a future version
-    // For now, fall back to regular response
-    let response = generate_agent_response(agent, user_message).await?;
-    
-    // Simulate streaming by calling callback with chunks
-    let words: Vec<&str> = response.split_whitespace().collect();
-    let mut accumulated = String::new();
-    
-    for word in words {
-        accumulated.push_str(word);
-        accumulated.push(' ');
-        callback(word.to_string())?;
-        
-        // Small delay to simulate streaming
-        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-    }
-    
-    Ok(response)
+    callback: impl Fn(StreamChunk) -> Result<()>,
+) -> Result<ParsedReply> {
+    let system_prompt = build_agent_system_prompt(agent);
+
+    let request = OllamaRequest {
+        model: "llama3.1:8b".to_string(),
+        prompt: format!("{}\n\nUser: {}\nAssistant:", system_prompt, user_message),
+        stream: true,
+        options: Some(crate::services::ollama::create_generation_options(Some(0.7), Some(1000))),
+    };
+
+    let response = OllamaClient::new()
+        .generate_stream(request, |chunk| callback(chunk))
+        .await?;
+
+    Ok(ParsedReply {
+        content: response.response,
+        reasoning: response.thinking,
+    })
 }
 
 /// Check if Ollama is available and responsive
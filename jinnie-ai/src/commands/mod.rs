@@ -1,6 +1,7 @@
 use anyhow::Result;
 use crate::types::{Agent, Message, Document};
 use crate::state::AppState;
+use crate::vector::text_index::{tokenize, Bm25Index};
 
 /// Commands module for handling application commands
 /// These were previously Tauri commands, now integrated directly
@@ -89,11 +90,14 @@ pub async fn get_agent_messages(
     Ok(messages.get(&agent_id).cloned().unwrap_or_default())
 }
 
+/// Number of memories recalled into the prompt for each message
+const MEMORY_RECALL_LIMIT: usize = 5;
+
 pub async fn send_message_to_agent(
     state: &AppState,
     agent_id: String,
     message: String,
-) -> Result<String> {
+) -> Result<Message> {
     // Get the agent
     let agents = state.agents.lock().await;
     let agent = agents.get(&agent_id)
@@ -101,26 +105,69 @@ pub async fn send_message_to_agent(
         .clone();
     drop(agents);
     
-    // Generate AI response
-    let response = crate::ai::generate_agent_response(&agent, &message).await?;
+    // Recall what the agent already knows that bears on this message
+    let recalled = match &state.memory_system {
+        Some(memory_system) => memory_system
+            .recall(&agent_id, &message, MEMORY_RECALL_LIMIT)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Memory recall failed for agent {}: {}", agent_id, e);
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+    // Only facts the user has confirmed make it into the profile
+    let profile = match &state.memory_system {
+        Some(memory_system) => memory_system.user_profile(&agent_id).await.unwrap_or_else(|e| {
+            log::warn!("Failed to load the user profile for agent {}: {}", agent_id, e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let profile_ids: std::collections::HashSet<uuid::Uuid> = profile.iter().map(|memory| memory.id).collect();
+    let profile_facts: Vec<String> = profile.iter().map(|memory| memory.content.clone()).collect();
+    let memory_context: Vec<String> = recalled.iter()
+        .filter(|r| !profile_ids.contains(&r.memory.id))
+        .map(|r| r.memory.content.clone())
+        .collect();
+    let memory_ids: Vec<String> = recalled.iter().map(|r| r.memory.id.to_string()).collect();
+    
+    // Generate AI response, keeping the model's reasoning apart from the answer
+    let reply = crate::ai::generate_agent_reply_with_memories(&agent, &message, &profile_facts, &memory_context).await?;
+    
+    // Recalled memories the reply drew on teach the importance scorer what matters
+    if let Some(memory_system) = &state.memory_system {
+        let used: Vec<uuid::Uuid> = recalled.iter()
+            .filter(|r| crate::memory::importance_scorer::memory_was_used(&r.memory.content, &reply.content))
+            .map(|r| r.memory.id)
+            .collect();
+        if let Err(e) = memory_system.record_memory_use(&used).await {
+            log::warn!("Failed to record memory use for agent {}: {}", agent_id, e);
+        }
+    }
+    
+    // Remember the exchange; a memory failure should not lose the reply
+    if let Some(memory_system) = &state.memory_system {
+        if let Err(e) = memory_system
+            .remember_exchange(&agent_id, &state.session_id, &message, &reply.content)
+            .await
+        {
+            log::warn!("Failed to store exchange in memory for agent {}: {}", agent_id, e);
+        }
+    }
     
     // Store messages
     let mut messages = state.messages.lock().await;
     let agent_messages = messages.entry(agent_id.clone()).or_insert_with(Vec::new);
     
     // Add user message
-    agent_messages.push(Message::new(
-        message,
-        agent_id.clone(),
-        crate::types::message::MessageRole::User,
-    ));
+    agent_messages.push(Message::new_user_message(message, agent_id.clone()));
     
     // Add AI response
-    agent_messages.push(Message::new(
-        response.clone(),
-        agent_id,
-        crate::types::message::MessageRole::Assistant,
-    ));
+    let response = Message::new_agent_message(reply.content, agent_id)
+        .with_reasoning(reply.reasoning)
+        .with_memory_accessed(memory_ids);
+    agent_messages.push(response.clone());
     
     // Save to storage
     crate::storage::MessageStorage::save(&messages).await?;
@@ -141,6 +188,57 @@ pub async fn clear_chat(
     Ok(())
 }
 
+/// Search messages containing the query phrase or all of its terms, best BM25 match
+/// first and newest first among equals; a date phrase like "yesterday afternoon" narrows
+/// by when they were sent, read in the user's timezone
+pub async fn search_messages(
+    state: &AppState,
+    query: String,
+    agent_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<Message>> {
+    let (text, range) = match crate::memory::DateParser::from_config(&state.config).parse(&query) {
+        Some(found) => (found.remainder.to_lowercase(), Some(found.range)),
+        None => (query.trim().to_lowercase(), None),
+    };
+    if text.is_empty() && range.is_none() {
+        return Err(anyhow::anyhow!("Search query cannot be empty"));
+    }
+
+    let messages = state.messages.lock().await;
+    let in_range: Vec<&Message> = messages.iter()
+        .filter(|(id, _)| agent_id.as_ref().map_or(true, |agent_id| agent_id == *id))
+        .flat_map(|(_, agent_messages)| agent_messages.iter())
+        .filter(|message| match &range {
+            Some(range) => chrono::DateTime::parse_from_rfc3339(&message.timestamp)
+                .map(|sent| sent >= range.start && sent <= range.end)
+                .unwrap_or(false),
+            None => true,
+        })
+        .collect();
+
+    let query_terms: std::collections::HashSet<String> = tokenize(&text).into_iter().collect();
+    let index = Bm25Index::from_texts(in_range.iter().enumerate().map(|(i, message)| (i, message.content.as_str())));
+    let scores = index.scores(&text);
+
+    let mut matching: Vec<(f32, &Message)> = in_range.iter()
+        .enumerate()
+        .filter(|(_, message)| {
+            text.is_empty() || message.content.to_lowercase().contains(&text) || {
+                let terms: std::collections::HashSet<String> = tokenize(&message.content).into_iter().collect();
+                !query_terms.is_empty() && query_terms.is_subset(&terms)
+            }
+        })
+        .map(|(i, message)| (scores.get(&i).copied().unwrap_or(0.0), *message))
+        .collect();
+
+    matching.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| b.1.timestamp.cmp(&a.1.timestamp)));
+    Ok(matching.into_iter()
+        .take(limit.unwrap_or(50).min(500))
+        .map(|(_, message)| message.clone())
+        .collect())
+}
+
 pub async fn search_memories(
     state: &AppState,
     query: String,
@@ -155,6 +253,198 @@ pub async fn search_memories(
     }
 }
 
+/// Memories relevant to `query` and those linked to them, each explaining how it was reached
+pub async fn recall_associated_memories(
+    state: &AppState,
+    agent_id: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<crate::memory::AssociativeResult>> {
+    match &state.memory_system {
+        Some(memory_coordinator) => memory_coordinator.recall_associated(&agent_id, &query, limit.unwrap_or(10)).await,
+        None => Ok(vec![]),
+    }
+}
+
+/// Browse memories for the inspector, without counting it as an access
+pub async fn browse_memories(
+    state: &AppState,
+    query: crate::memory::MemoryQuery,
+) -> Result<Vec<crate::memory::Memory>> {
+    match &state.memory_system {
+        Some(memory_coordinator) => memory_coordinator.browse(query).await,
+        None => Ok(vec![]),
+    }
+}
+
+/// Edit a memory's content, tags, importance or pin
+pub async fn update_memory(
+    state: &AppState,
+    memory_id: uuid::Uuid,
+    update: crate::memory::MemoryUpdate,
+) -> Result<crate::memory::Memory> {
+    let memory_coordinator = state.memory_system.as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Memory system is not enabled"))?;
+    memory_coordinator.update_memory(memory_id, update).await
+}
+
+/// Share a memory with a team or every agent, or take it back to the agent's private scope
+pub async fn promote_memory(
+    state: &AppState,
+    memory_id: uuid::Uuid,
+    scope: crate::memory::MemoryScope,
+    agent_id: String,
+) -> Result<crate::memory::Memory> {
+    let memory_coordinator = state.memory_system.as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Memory system is not enabled"))?;
+    memory_coordinator.promote_memory(memory_id, scope, &agent_id).await
+}
+
+/// Facts the system inferred that the user hasn't confirmed yet
+pub async fn pending_verifications(state: &AppState) -> Result<Vec<crate::memory::VerificationRequest>> {
+    match &state.memory_system {
+        Some(memory_coordinator) => memory_coordinator.pending_verifications().await,
+        None => Ok(vec![]),
+    }
+}
+
+/// Confirm, reject or correct an inferred fact
+pub async fn answer_verification(
+    state: &AppState,
+    subject: crate::memory::VerificationSubject,
+    answer: crate::memory::VerificationAnswer,
+) -> Result<Option<crate::memory::Memory>> {
+    let memory_coordinator = state.memory_system.as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Memory system is not enabled"))?;
+    memory_coordinator.answer_verification(subject, answer).await
+}
+
+/// Pin or unpin memories, returning how many changed
+pub async fn pin_memories(
+    state: &AppState,
+    memory_ids: Vec<uuid::Uuid>,
+    pinned: bool,
+) -> Result<usize> {
+    match &state.memory_system {
+        Some(memory_coordinator) => memory_coordinator.set_pinned(&memory_ids, pinned).await,
+        None => Ok(0),
+    }
+}
+
+/// Forget memories, returning how many were removed
+pub async fn forget_memories(
+    state: &AppState,
+    memory_ids: Vec<uuid::Uuid>,
+) -> Result<usize> {
+    match &state.memory_system {
+        Some(memory_coordinator) => memory_coordinator.forget_memories(&memory_ids).await,
+        None => Ok(0),
+    }
+}
+
+/// Associations touching a memory, for the inspector's detail view
+pub async fn memory_associations(
+    state: &AppState,
+    memory_id: uuid::Uuid,
+) -> Result<Vec<crate::memory::MemoryAssociation>> {
+    match &state.memory_system {
+        Some(memory_coordinator) => Ok(memory_coordinator.associations_of(memory_id).await),
+        None => Ok(vec![]),
+    }
+}
+
+/// Run memory maintenance immediately instead of waiting for the next scheduled run
+pub async fn run_memory_maintenance(
+    state: &AppState,
+) -> Result<Option<crate::memory::MaintenanceReport>> {
+    match &state.memory_system {
+        Some(memory_coordinator) => Ok(Some(memory_coordinator.run_maintenance_now().await)),
+        None => Ok(None),
+    }
+}
+
+/// Rate how much a memory matters, from 0.0 (irrelevant) to 1.0 (essential)
+pub async fn rate_memory(
+    state: &AppState,
+    memory_id: uuid::Uuid,
+    rating: f32,
+) -> Result<crate::memory::Memory> {
+    let memory_coordinator = state.memory_system.as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Memory system is not enabled"))?;
+    memory_coordinator.rate_memory(memory_id, rating).await
+}
+
+/// Importance trends and what feedback has taught the scorer, for one agent or all of them
+pub async fn importance_report(
+    state: &AppState,
+    agent_id: Option<String>,
+) -> Result<Option<crate::memory::ImportanceReport>> {
+    match &state.memory_system {
+        Some(memory_coordinator) => Ok(Some(memory_coordinator.importance_report(agent_id.as_deref()).await?)),
+        None => Ok(None),
+    }
+}
+
+/// List what retention would remove right now, without removing it
+pub async fn preview_retention(state: &AppState) -> Result<crate::privacy::RetentionReport> {
+    crate::privacy::RetentionEngine::for_state(state).preview(state).await
+}
+
+/// Remove everything past its retention period now
+pub async fn enforce_retention(state: &AppState) -> Result<crate::privacy::RetentionReport> {
+    crate::privacy::RetentionEngine::for_state(state).enforce(state).await
+}
+
+/// Score retriever configurations on a JSONL query set over a JSONL fixture corpus.
+///
+/// With `previous_report`, a JSON report saved from an earlier evaluation, each run is also
+/// compared with the earlier run of the same name.
+pub async fn evaluate_retrieval(
+    state: &AppState,
+    queries_path: String,
+    corpus_path: String,
+    configs: Option<Vec<crate::evaluation::RetrieverConfig>>,
+    k: Option<usize>,
+    previous_report: Option<String>,
+) -> Result<crate::evaluation::EvalReport> {
+    use crate::evaluation::{self, RetrieverConfig};
+    use std::path::Path;
+
+    let queries = evaluation::load_queries(Path::new(&queries_path))?;
+    let corpus: Vec<evaluation::CorpusItem> = evaluation::load_jsonl(Path::new(&corpus_path))?;
+    let configs = configs.unwrap_or_else(|| vec![
+        RetrieverConfig::Memory { name: "memory".to_string(), ranking: None, spreading: None, associative: false },
+        RetrieverConfig::Knowledge { name: "knowledge".to_string() },
+    ]);
+    let embeddings = match &state.vector_store {
+        Some(vector_store) => vector_store.embedding_engine(),
+        None => std::sync::Arc::new(crate::vector::EmbeddingEngine::hashed(&state.config.vector.embedding_model)),
+    };
+
+    let mut retrievers = Vec::with_capacity(configs.len());
+    for config in &configs {
+        retrievers.push(config.build(&corpus, &state.config, embeddings.clone(), state.vector_store.clone()).await?);
+    }
+    let mut report = evaluation::evaluate_all(&retrievers, &queries, k.unwrap_or(evaluation::DEFAULT_CUTOFF)).await?;
+
+    if let Some(path) = previous_report {
+        let previous: evaluation::EvalReport = serde_json::from_str(&tokio::fs::read_to_string(&path).await?)?;
+        report.diffs.extend(evaluation::compare_reports(&previous, &report, &queries));
+    }
+    Ok(report)
+}
+
+/// Change the passphrase protecting encrypted storage; stored data is not rewritten
+pub async fn change_passphrase(old: String, new: String) -> Result<()> {
+    Ok(crate::storage::encryption::change_passphrase(&old, &new)?)
+}
+
+/// Switch to a fresh encryption key, returning its id; stored data is re-encrypted with it
+/// at the next start
+pub async fn rotate_encryption_key(passphrase: String) -> Result<u32> {
+    Ok(crate::storage::encryption::rotate_data_key(&passphrase)?)
+}
+
 /// Agent update structure
 #[derive(Clone, Debug)]
 pub struct AgentUpdate {
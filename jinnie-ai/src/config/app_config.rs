@@ -1,8 +1,15 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use super::model_config::LLMConfig;
+use crate::memory::activation::SpreadingConfig;
+use crate::memory::ranking::RankingConfig;
+use crate::memory::scopes::ScopeConfig;
+use crate::privacy::{PiiKind, RedactionPolicy, RetentionAction};
+use crate::vector::hnsw_index::HnswParams;
+use crate::vector::search_engine::HybridWeights;
 use super::platform_config::get_platform_paths;
 use super::ConfigDefaults;
 
@@ -34,6 +41,22 @@ pub struct MemoryConfig {
     pub consolidation_threshold: usize,
     pub consolidation_interval: u64, // seconds
     pub importance_decay_rate: f32,
+    #[serde(default = "default_true")]
+    pub persistence_enabled: bool, // keep memories in <data_dir>/memory.sqlite
+    #[serde(default = "default_true")]
+    pub entity_recognition_enabled: bool, // ask the local model for people/projects/organizations
+    /// Teams, per-agent permissions and retrieval weights for shared memory scopes
+    #[serde(default)]
+    pub scopes: ScopeConfig,
+    /// Seeds, hop decay and edge-type weights for associative retrieval
+    #[serde(default)]
+    pub spreading: SpreadingConfig,
+    /// Score weights, recency half-life, MMR diversity and cross-encoder reranking
+    #[serde(default)]
+    pub ranking: RankingConfig,
+    /// Whose learned importance weights are loaded and updated
+    #[serde(default = "default_user_id")]
+    pub user_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +67,58 @@ pub struct VectorConfig {
     pub collection_prefix: String,
     pub embedding_model: String,
     pub embedding_dimension: usize,
+    #[serde(default = "default_embedding_backend")]
+    pub embedding_backend: String, // "auto", "ollama", "onnx", "candle" or "hash"
+    #[serde(default = "default_embedding_endpoint")]
+    pub embedding_endpoint: String, // Ollama URL for Ollama-served embedding models
+    #[serde(default = "default_ollama_embedding_model")]
+    pub ollama_embedding_model: String, // tried by "auto" before hash embeddings
+    #[serde(default = "default_embedding_batch_size")]
+    pub embedding_batch_size: usize,
+    #[serde(default = "default_true")]
+    pub embedding_cache_enabled: bool,
+    #[serde(default = "default_embedding_cache_max_mb")]
+    pub embedding_cache_max_mb: u64,
+    #[serde(default = "default_index_backend")]
+    pub index_backend: String, // "auto", "qdrant" or "embedded"
+    #[serde(default)]
+    pub hnsw: HnswParams, // embedded index, kept in <data_dir>/vector_index
+    #[serde(default)]
+    pub hybrid: HybridWeights, // how memory and document search fuse vector and keyword matches
+}
+
+fn default_embedding_backend() -> String {
+    "auto".to_string()
+}
+
+fn default_embedding_endpoint() -> String {
+    ConfigDefaults::DEFAULT_EMBEDDING_ENDPOINT.to_string()
+}
+
+fn default_ollama_embedding_model() -> String {
+    ConfigDefaults::DEFAULT_OLLAMA_EMBEDDING_MODEL.to_string()
+}
+
+fn default_embedding_batch_size() -> usize {
+    ConfigDefaults::DEFAULT_EMBEDDING_BATCH_SIZE
+}
+
+fn default_embedding_cache_max_mb() -> u64 {
+    ConfigDefaults::DEFAULT_EMBEDDING_CACHE_MB
+}
+
+fn default_index_backend() -> String {
+    "auto".to_string()
+}
+
+fn default_user_id() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "default".to_string())
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,8 +133,21 @@ pub struct PerformanceConfig {
 pub struct PrivacyConfig {
     pub telemetry_enabled: bool,
     pub crash_reports_enabled: bool,
+    /// Encrypt the data directory with a passphrase entered at startup
     pub memory_encryption: bool,
     pub auto_cleanup_days: Option<u32>,
+    /// Per-agent retention in days, overriding `auto_cleanup_days`; 0 keeps the agent's data forever
+    #[serde(default)]
+    pub retention_overrides: HashMap<String, u32>,
+    /// Whether expired messages and memories are archived or deleted outright
+    #[serde(default)]
+    pub retention_action: RetentionAction,
+    /// Redact personal data from memories, exports and logs
+    #[serde(default = "default_true")]
+    pub pii_redaction_enabled: bool,
+    /// Per-kind overrides; kinds not listed are masked
+    #[serde(default)]
+    pub pii_policies: HashMap<PiiKind, RedactionPolicy>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +156,12 @@ pub struct UIConfig {
     pub language: String,
     pub show_model_indicator: bool,
     pub show_performance_stats: bool,
+    #[serde(default = "default_timezone")]
+    pub timezone: String, // "local", an IANA name like "Europe/Berlin", or an offset like "+02:00"
+}
+
+fn default_timezone() -> String {
+    "local".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +191,12 @@ impl Default for AppConfig {
                 consolidation_threshold: 50,
                 consolidation_interval: 3600, // 1 hour
                 importance_decay_rate: 0.95,
+                persistence_enabled: true,
+                entity_recognition_enabled: true,
+                scopes: ScopeConfig::default(),
+                spreading: SpreadingConfig::default(),
+                ranking: RankingConfig::default(),
+                user_id: default_user_id(),
             },
             vector: VectorConfig {
                 qdrant_host: ConfigDefaults::DEFAULT_QDRANT_HOST.to_string(),
@@ -105,6 +205,15 @@ impl Default for AppConfig {
                 collection_prefix: "localmind".to_string(),
                 embedding_model: ConfigDefaults::DEFAULT_EMBEDDING_MODEL.to_string(),
                 embedding_dimension: 384,
+                embedding_backend: default_embedding_backend(),
+                embedding_endpoint: default_embedding_endpoint(),
+                ollama_embedding_model: default_ollama_embedding_model(),
+                embedding_batch_size: default_embedding_batch_size(),
+                embedding_cache_enabled: true,
+                embedding_cache_max_mb: default_embedding_cache_max_mb(),
+                index_backend: default_index_backend(),
+                hnsw: HnswParams::default(),
+                hybrid: HybridWeights::default(),
             },
             performance: PerformanceConfig {
                 cache_size_mb: 512,
@@ -115,14 +224,19 @@ impl Default for AppConfig {
             privacy: PrivacyConfig {
                 telemetry_enabled: false,
                 crash_reports_enabled: false,
-                memory_encryption: true,
+                memory_encryption: false,
                 auto_cleanup_days: Some(90),
+                retention_overrides: HashMap::new(),
+                retention_action: RetentionAction::Archive,
+                pii_redaction_enabled: true,
+                pii_policies: HashMap::new(),
             },
             ui: UIConfig {
                 theme: "auto".to_string(),
                 language: "en".to_string(),
                 show_model_indicator: true,
                 show_performance_stats: false,
+                timezone: default_timezone(),
             },
             paths: PathConfig {
                 data_dir: platform_paths.data_dir.to_string_lossy().to_string(),
@@ -206,6 +320,14 @@ impl AppConfig {
             self.vector.qdrant_api_key = Some(api_key);
         }
 
+        if let Ok(backend) = std::env::var("LOCALMIND_EMBEDDING_BACKEND") {
+            self.vector.embedding_backend = backend;
+        }
+
+        if let Ok(endpoint) = std::env::var("LOCALMIND_EMBEDDING_ENDPOINT") {
+            self.vector.embedding_endpoint = endpoint;
+        }
+
         if let Ok(telemetry) = std::env::var("LOCALMIND_TELEMETRY") {
             self.privacy.telemetry_enabled = telemetry.parse().unwrap_or(false);
         }
@@ -325,7 +447,8 @@ mod tests {
         assert_eq!(config.app.name, "LocalMind");
         assert_eq!(config.app.version, "0.3.0");
         assert!(!config.privacy.telemetry_enabled);
-        assert!(config.privacy.memory_encryption);
+        // Encryption needs a passphrase, so it is opt-in
+        assert!(!config.privacy.memory_encryption);
     }
 
     #[test]
@@ -24,6 +24,10 @@ impl ConfigDefaults {
     pub const VERSION: &'static str = "0.3.0";
     pub const DEFAULT_MODEL: &'static str = "TinyLlama";
     pub const DEFAULT_EMBEDDING_MODEL: &'static str = "all-MiniLM-L6-v2";
+    pub const DEFAULT_EMBEDDING_ENDPOINT: &'static str = "http://localhost:11434";
+    pub const DEFAULT_OLLAMA_EMBEDDING_MODEL: &'static str = "all-minilm";
+    pub const DEFAULT_EMBEDDING_BATCH_SIZE: usize = 32;
+    pub const DEFAULT_EMBEDDING_CACHE_MB: u64 = 256;
     pub const DEFAULT_CONTEXT_LENGTH: usize = 4096;
     pub const DEFAULT_QDRANT_HOST: &'static str = "localhost";
     pub const DEFAULT_QDRANT_PORT: u16 = 6333;
@@ -236,6 +240,14 @@ impl ConfigManager {
             result.add_error("Embedding dimension must be greater than 0".to_string());
         }
 
+        if !["auto", "ollama", "onnx", "candle", "hash"].contains(&vector.embedding_backend.as_str()) {
+            result.add_error(format!("Unknown embedding backend: {}", vector.embedding_backend));
+        }
+
+        if vector.embedding_batch_size == 0 {
+            result.add_error("Embedding batch size must be greater than 0".to_string());
+        }
+
         Ok(result)
     }
 
@@ -0,0 +1,388 @@
+//! Offline retrieval evaluation: run a fixed set of queries with known relevant ids through
+//! one or more retriever configurations and score them with recall@k, MRR and nDCG.
+//!
+//! Queries and corpora are JSONL, one object per line:
+//!
+//! ```text
+//! {"id": "q1", "query": "when is standup", "relevant": ["m1"]}
+//! {"id": "m1", "content": "Standup is at 9:30 every weekday"}
+//! ```
+
+pub mod retrievers;
+
+pub use retrievers::{KnowledgeRetriever, MemoryRetriever, RetrieverConfig, VectorRetriever};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Cutoff used when none is given
+pub const DEFAULT_CUTOFF: usize = 10;
+
+/// Metric changes smaller than this are reported as unchanged
+const DIFF_EPSILON: f64 = 1e-9;
+
+/// A query and the ids that should come back for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalQuery {
+    pub id: String,
+    pub query: String,
+    pub relevant: Vec<String>,
+    /// Agent to search as; memory retrievers search every agent without one
+    #[serde(default)]
+    pub agent_id: Option<String>,
+}
+
+/// One memory or document in a fixture corpus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusItem {
+    pub id: String,
+    pub content: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub importance: Option<f32>,
+}
+
+/// Something that can answer an evaluation query with a ranked list of ids
+#[async_trait]
+pub trait Retriever: Send + Sync {
+    fn name(&self) -> String;
+
+    /// At most `k` ids, best first
+    async fn retrieve(&self, query: &EvalQuery, k: usize) -> Result<Vec<String>>;
+}
+
+/// Scores for a single query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryScore {
+    pub query_id: String,
+    pub retrieved: Vec<String>,
+    pub recall: f64,
+    pub reciprocal_rank: f64,
+    pub ndcg: f64,
+}
+
+/// Scores for one retriever over the whole query set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalRun {
+    pub name: String,
+    pub k: usize,
+    pub recall: f64,
+    pub mrr: f64,
+    pub ndcg: f64,
+    pub queries: Vec<QueryScore>,
+}
+
+/// How one query changed between two runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryDiff {
+    pub query_id: String,
+    pub recall_delta: f64,
+    pub reciprocal_rank_delta: f64,
+    pub ndcg_delta: f64,
+    /// Relevant ids the candidate found and the baseline missed
+    pub gained: Vec<String>,
+    /// Relevant ids the baseline found and the candidate missed
+    pub lost: Vec<String>,
+}
+
+/// Comparison of a candidate run against a baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunDiff {
+    pub baseline: String,
+    pub candidate: String,
+    pub recall_delta: f64,
+    pub mrr_delta: f64,
+    pub ndcg_delta: f64,
+    pub improved: usize,
+    pub regressed: usize,
+    /// Only queries whose scores changed
+    pub queries: Vec<QueryDiff>,
+}
+
+/// Every run of an evaluation, plus each later run compared with the first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub runs: Vec<EvalRun>,
+    pub diffs: Vec<RunDiff>,
+}
+
+/// Read a JSONL file, skipping blank lines
+pub fn load_jsonl<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_jsonl(&content).with_context(|| format!("Invalid JSONL in {}", path.display()))
+}
+
+pub fn parse_jsonl<T: DeserializeOwned>(content: &str) -> Result<Vec<T>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| serde_json::from_str(line).map_err(|e| anyhow!("line {}: {}", index + 1, e)))
+        .collect()
+}
+
+/// Load a query set; a query without relevant ids can't be scored
+pub fn load_queries(path: &Path) -> Result<Vec<EvalQuery>> {
+    let queries: Vec<EvalQuery> = load_jsonl(path)?;
+    if let Some(query) = queries.iter().find(|query| query.relevant.is_empty()) {
+        return Err(anyhow!("Query '{}' lists no relevant ids", query.id));
+    }
+    Ok(queries)
+}
+
+/// Share of the relevant ids found in the top `k`
+pub fn recall_at_k(retrieved: &[String], relevant: &HashSet<&str>, k: usize) -> f64 {
+    if relevant.is_empty() {
+        return 0.0;
+    }
+    let found = retrieved.iter().take(k).filter(|id| relevant.contains(id.as_str())).count();
+    found as f64 / relevant.len() as f64
+}
+
+/// One over the rank of the first relevant id in the top `k`, or 0
+pub fn reciprocal_rank(retrieved: &[String], relevant: &HashSet<&str>, k: usize) -> f64 {
+    retrieved
+        .iter()
+        .take(k)
+        .position(|id| relevant.contains(id.as_str()))
+        .map_or(0.0, |index| 1.0 / (index + 1) as f64)
+}
+
+/// Normalised discounted cumulative gain at `k` with binary relevance
+pub fn ndcg_at_k(retrieved: &[String], relevant: &HashSet<&str>, k: usize) -> f64 {
+    let discount = |index: usize| 1.0 / ((index + 2) as f64).log2();
+    let dcg: f64 = retrieved
+        .iter()
+        .take(k)
+        .enumerate()
+        .filter(|(_, id)| relevant.contains(id.as_str()))
+        .map(|(index, _)| discount(index))
+        .sum();
+    let ideal: f64 = (0..relevant.len().min(k)).map(discount).sum();
+    if ideal == 0.0 {
+        return 0.0;
+    }
+    dcg / ideal
+}
+
+/// Run every query through `retriever` and score the results at cutoff `k`
+pub async fn evaluate(retriever: &dyn Retriever, queries: &[EvalQuery], k: usize) -> Result<EvalRun> {
+    let mut scores = Vec::with_capacity(queries.len());
+    for query in queries {
+        let mut retrieved = retriever.retrieve(query, k).await
+            .with_context(|| format!("{} failed on query '{}'", retriever.name(), query.id))?;
+        retrieved.truncate(k);
+
+        let relevant: HashSet<&str> = query.relevant.iter().map(String::as_str).collect();
+        scores.push(QueryScore {
+            query_id: query.id.clone(),
+            recall: recall_at_k(&retrieved, &relevant, k),
+            reciprocal_rank: reciprocal_rank(&retrieved, &relevant, k),
+            ndcg: ndcg_at_k(&retrieved, &relevant, k),
+            retrieved,
+        });
+    }
+
+    let mean = |metric: fn(&QueryScore) -> f64| {
+        if scores.is_empty() {
+            0.0
+        } else {
+            scores.iter().map(metric).sum::<f64>() / scores.len() as f64
+        }
+    };
+    Ok(EvalRun {
+        name: retriever.name(),
+        k,
+        recall: mean(|score| score.recall),
+        mrr: mean(|score| score.reciprocal_rank),
+        ndcg: mean(|score| score.ndcg),
+        queries: scores,
+    })
+}
+
+/// Evaluate each retriever in turn and compare every run after the first against it
+pub async fn evaluate_all(retrievers: &[Box<dyn Retriever>], queries: &[EvalQuery], k: usize) -> Result<EvalReport> {
+    let mut runs = Vec::with_capacity(retrievers.len());
+    for retriever in retrievers {
+        runs.push(evaluate(retriever.as_ref(), queries, k).await?);
+    }
+    let diffs = match runs.split_first() {
+        Some((baseline, rest)) => rest.iter().map(|run| compare(baseline, run, queries)).collect(),
+        None => Vec::new(),
+    };
+    Ok(EvalReport { runs, diffs })
+}
+
+/// Per-query differences between two runs over the same query set.
+///
+/// Queries are judged on nDCG: higher is an improvement, lower a regression.
+pub fn compare(baseline: &EvalRun, candidate: &EvalRun, queries: &[EvalQuery]) -> RunDiff {
+    let mut diff = RunDiff {
+        baseline: baseline.name.clone(),
+        candidate: candidate.name.clone(),
+        recall_delta: candidate.recall - baseline.recall,
+        mrr_delta: candidate.mrr - baseline.mrr,
+        ndcg_delta: candidate.ndcg - baseline.ndcg,
+        improved: 0,
+        regressed: 0,
+        queries: Vec::new(),
+    };
+
+    for before in &baseline.queries {
+        let Some(after) = candidate.queries.iter().find(|score| score.query_id == before.query_id) else {
+            continue;
+        };
+        let relevant: HashSet<&str> = queries
+            .iter()
+            .find(|query| query.id == before.query_id)
+            .map(|query| query.relevant.iter().map(String::as_str).collect())
+            .unwrap_or_default();
+        let found = |score: &QueryScore| -> HashSet<String> {
+            score.retrieved.iter().filter(|id| relevant.contains(id.as_str())).cloned().collect()
+        };
+        let (found_before, found_after) = (found(before), found(after));
+
+        let query_diff = QueryDiff {
+            query_id: before.query_id.clone(),
+            recall_delta: after.recall - before.recall,
+            reciprocal_rank_delta: after.reciprocal_rank - before.reciprocal_rank,
+            ndcg_delta: after.ndcg - before.ndcg,
+            gained: sorted(found_after.difference(&found_before)),
+            lost: sorted(found_before.difference(&found_after)),
+        };
+        if query_diff.ndcg_delta > DIFF_EPSILON {
+            diff.improved += 1;
+        } else if query_diff.ndcg_delta < -DIFF_EPSILON {
+            diff.regressed += 1;
+        }
+
+        let changed = [query_diff.recall_delta, query_diff.reciprocal_rank_delta, query_diff.ndcg_delta]
+            .iter()
+            .any(|delta| delta.abs() > DIFF_EPSILON);
+        if changed {
+            diff.queries.push(query_diff);
+        }
+    }
+    diff
+}
+
+/// Compare each run in `current` with the run of the same name in an earlier report
+pub fn compare_reports(previous: &EvalReport, current: &EvalReport, queries: &[EvalQuery]) -> Vec<RunDiff> {
+    current
+        .runs
+        .iter()
+        .filter_map(|run| {
+            let before = previous.runs.iter().find(|before| before.name == run.name && before.k == run.k)?;
+            let mut diff = compare(before, run, queries);
+            diff.baseline = format!("{} (previous)", before.name);
+            Some(diff)
+        })
+        .collect()
+}
+
+fn sorted<'a>(ids: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut ids: Vec<String> = ids.cloned().collect();
+    ids.sort();
+    ids
+}
+
+impl EvalReport {
+    /// Plain-text summary: one line per run, then the per-query changes
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        for run in &self.runs {
+            lines.push(format!(
+                "{:<24} recall@{} {:.3}  MRR {:.3}  nDCG@{} {:.3}",
+                run.name, run.k, run.recall, run.mrr, run.k, run.ndcg
+            ));
+        }
+        for diff in &self.diffs {
+            lines.push(String::new());
+            lines.push(format!(
+                "{} vs {}: recall {:+.3}  MRR {:+.3}  nDCG {:+.3}  ({} better, {} worse)",
+                diff.candidate, diff.baseline, diff.recall_delta, diff.mrr_delta, diff.ndcg_delta, diff.improved, diff.regressed
+            ));
+            for query in &diff.queries {
+                let mut line = format!("  {:<16} nDCG {:+.3}  RR {:+.3}", query.query_id, query.ndcg_delta, query.reciprocal_rank_delta);
+                if !query.gained.is_empty() {
+                    line.push_str(&format!("  +[{}]", query.gained.join(", ")));
+                }
+                if !query.lost.is_empty() {
+                    line.push_str(&format!("  -[{}]", query.lost.join(", ")));
+                }
+                lines.push(line);
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_metrics() {
+        let relevant: HashSet<&str> = ["a", "c"].into_iter().collect();
+        let retrieved = ids(&["b", "a", "d", "c"]);
+
+        assert_eq!(recall_at_k(&retrieved, &relevant, 2), 0.5);
+        assert_eq!(recall_at_k(&retrieved, &relevant, 4), 1.0);
+        assert_eq!(reciprocal_rank(&retrieved, &relevant, 4), 0.5);
+        assert_eq!(reciprocal_rank(&retrieved, &relevant, 1), 0.0);
+
+        let expected = (1.0 / 3f64.log2() + 1.0 / 5f64.log2()) / (1.0 + 1.0 / 3f64.log2());
+        assert!((ndcg_at_k(&retrieved, &relevant, 4) - expected).abs() < 1e-12);
+        assert_eq!(ndcg_at_k(&ids(&["a", "c"]), &relevant, 4), 1.0);
+    }
+
+    struct Fixed(&'static str, Vec<String>);
+
+    #[async_trait]
+    impl Retriever for Fixed {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        async fn retrieve(&self, _query: &EvalQuery, _k: usize) -> Result<Vec<String>> {
+            Ok(self.1.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_diff_reports_gained_and_lost_ids() {
+        let queries: Vec<EvalQuery> = parse_jsonl(concat!(
+            r#"{"id": "q1", "query": "standup", "relevant": ["a"]}"#, "\n",
+            "\n",
+            r#"{"id": "q2", "query": "dentist", "relevant": ["b", "c"]}"#, "\n",
+        )).unwrap();
+        let retrievers: Vec<Box<dyn Retriever>> = vec![
+            Box::new(Fixed("before", ids(&["a", "b"]))),
+            Box::new(Fixed("after", ids(&["c", "a"]))),
+        ];
+
+        let report = evaluate_all(&retrievers, &queries, 2).await.unwrap();
+        let diff = &report.diffs[0];
+        assert_eq!((diff.improved, diff.regressed), (1, 1));
+
+        let q1 = diff.queries.iter().find(|q| q.query_id == "q1").unwrap();
+        assert_eq!(q1.reciprocal_rank_delta, -0.5);
+        assert!(q1.gained.is_empty() && q1.lost.is_empty());
+        let q2 = diff.queries.iter().find(|q| q.query_id == "q2").unwrap();
+        assert_eq!((q2.gained.clone(), q2.lost.clone()), (ids(&["c"]), ids(&["b"])));
+        assert_eq!(q2.recall_delta, 0.0);
+        assert!(report.to_text().contains("after vs before"));
+    }
+}
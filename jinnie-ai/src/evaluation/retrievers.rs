@@ -0,0 +1,297 @@
+//! Retrievers under evaluation: the memory system, the knowledge base and a vector collection.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::{CorpusItem, EvalQuery, Retriever};
+use crate::config::AppConfig;
+use crate::knowledge::{Document, KnowledgeBase};
+use crate::memory::{
+    Memory, MemoryLayer, MemoryManager, MemoryMetadata, MemoryQuery, MemorySource, RankingConfig, SpreadingConfig,
+    VerificationStatus,
+};
+use crate::vector::{EmbeddingEngine, HybridWeights, VectorStore};
+
+/// Agent that owns fixture memories without an `agent_id`
+const DEFAULT_AGENT: &str = "eval";
+
+/// One retriever configuration to evaluate, as read from a JSON config list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "retriever", rename_all = "snake_case")]
+pub enum RetrieverConfig {
+    /// `MemoryRetrieval` over the fixture corpus; unset settings come from the app config
+    Memory {
+        name: String,
+        #[serde(default)]
+        ranking: Option<RankingConfig>,
+        #[serde(default)]
+        spreading: Option<SpreadingConfig>,
+        /// Use spreading-activation search instead of ranked search
+        #[serde(default)]
+        associative: bool,
+    },
+    /// `KnowledgeBase::search` over the fixture corpus
+    Knowledge { name: String },
+    /// Semantic search over an existing collection in the vector store
+    Vector {
+        name: String,
+        collection: String,
+        /// Payload field holding the id the queries refer to; the point id otherwise
+        #[serde(default)]
+        id_field: Option<String>,
+        /// Fuse with BM25 keyword matches using these weights
+        #[serde(default)]
+        hybrid: Option<HybridWeights>,
+    },
+}
+
+impl RetrieverConfig {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Memory { name, .. } | Self::Knowledge { name } | Self::Vector { name, .. } => name,
+        }
+    }
+
+    /// Build the retriever, loading `corpus` into it where it keeps its own data
+    pub async fn build(
+        &self,
+        corpus: &[CorpusItem],
+        config: &AppConfig,
+        embeddings: Arc<EmbeddingEngine>,
+        vector_store: Option<Arc<VectorStore>>,
+    ) -> Result<Box<dyn Retriever>> {
+        match self {
+            Self::Memory { name, ranking, spreading, associative } => {
+                let mut config = config.clone();
+                if let Some(ranking) = ranking {
+                    config.memory.ranking = *ranking;
+                }
+                if let Some(spreading) = spreading {
+                    config.memory.spreading = *spreading;
+                }
+                let retriever = MemoryRetriever::from_corpus(name, corpus, config, embeddings).await?;
+                Ok(Box::new(retriever.associative(*associative)))
+            }
+            Self::Knowledge { name } => Ok(Box::new(KnowledgeRetriever::from_corpus(name, corpus).await?)),
+            Self::Vector { name, collection, id_field, hybrid } => {
+                let vector_store = vector_store
+                    .ok_or_else(|| anyhow::anyhow!("Retriever '{}' needs the vector store, which is not running", name))?;
+                Ok(Box::new(VectorRetriever::new(name, vector_store, collection, id_field.clone()).hybrid(*hybrid)))
+            }
+        }
+    }
+}
+
+/// The memory system, holding only the fixture corpus and never touching stored memories
+pub struct MemoryRetriever {
+    name: String,
+    manager: Mutex<MemoryManager>,
+    embeddings: Arc<EmbeddingEngine>,
+    /// Memory id to corpus id
+    ids: HashMap<Uuid, String>,
+    associative: bool,
+}
+
+impl MemoryRetriever {
+    pub async fn from_corpus(name: &str, corpus: &[CorpusItem], config: AppConfig, embeddings: Arc<EmbeddingEngine>) -> Result<Self> {
+        let mut manager = MemoryManager::new(config, None).await?;
+        let mut ids = HashMap::new();
+        for item in corpus {
+            let metadata = MemoryMetadata {
+                source: MemorySource::ExternalImport,
+                agent_id: item.agent_id.clone().unwrap_or_else(|| DEFAULT_AGENT.to_string()),
+                conversation_id: None,
+                session_id: None,
+                topics: vec![],
+                entities: vec![],
+                sentiment: None,
+                context_window: None,
+                verification_status: VerificationStatus::Unverified,
+                custom_fields: HashMap::new(),
+            };
+            let mut memory = Memory::new(item.content.clone(), MemoryLayer::LongTerm, metadata);
+            memory.importance_score = item.importance.unwrap_or(0.5);
+            memory.embedding = Some(embeddings.generate_embedding(&item.content).await?);
+
+            let memory = manager.insert_memory(memory).await?;
+            ids.insert(memory.id, item.id.clone());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            manager: Mutex::new(manager),
+            embeddings,
+            ids,
+            associative: false,
+        })
+    }
+
+    pub fn associative(mut self, associative: bool) -> Self {
+        self.associative = associative;
+        self
+    }
+}
+
+#[async_trait]
+impl Retriever for MemoryRetriever {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn retrieve(&self, query: &EvalQuery, k: usize) -> Result<Vec<String>> {
+        let mut memory_query = MemoryQuery::new().with_text(query.query.clone()).with_limit(k);
+        memory_query.semantic_query = Some(self.embeddings.generate_embedding(&query.query).await?);
+        memory_query.agent_id = query.agent_id.clone();
+
+        let mut manager = self.manager.lock().await;
+        let memories: Vec<Memory> = if self.associative {
+            manager.search_associative(memory_query).await?.into_iter().map(|result| result.memory).collect()
+        } else {
+            // Browsing doesn't count as an access, so earlier queries can't shift later scores
+            manager.browse(memory_query).await?
+        };
+        Ok(memories.iter().filter_map(|memory| self.ids.get(&memory.id).cloned()).collect())
+    }
+}
+
+/// The knowledge base's local search over the fixture corpus
+pub struct KnowledgeRetriever {
+    name: String,
+    knowledge_base: KnowledgeBase,
+}
+
+impl KnowledgeRetriever {
+    pub async fn from_corpus(name: &str, corpus: &[CorpusItem]) -> Result<Self> {
+        let mut knowledge_base = KnowledgeBase::local();
+        let documents = corpus
+            .iter()
+            .map(|item| Document {
+                id: item.id.clone(),
+                title: item.title.clone().unwrap_or_else(|| item.id.clone()),
+                source: format!("eval:{}", item.id),
+                content: item.content.clone(),
+                content_preview: item.content.chars().take(200).collect(),
+                doc_type: "TXT".to_string(),
+                size: item.content.len() as u64,
+                created_at: chrono::Utc::now(),
+                metadata: HashMap::new(),
+                embedding: None,
+                categories: vec![],
+            })
+            .collect();
+        knowledge_base.import_documents(documents).await?;
+
+        Ok(Self { name: name.to_string(), knowledge_base })
+    }
+}
+
+#[async_trait]
+impl Retriever for KnowledgeRetriever {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn retrieve(&self, query: &EvalQuery, k: usize) -> Result<Vec<String>> {
+        let documents = self.knowledge_base.search(&query.query, k).await?;
+        Ok(documents.into_iter().map(|document| document.id).collect())
+    }
+}
+
+/// Semantic search over a collection already in the vector store
+pub struct VectorRetriever {
+    name: String,
+    vector_store: Arc<VectorStore>,
+    collection: String,
+    id_field: Option<String>,
+    hybrid: Option<HybridWeights>,
+}
+
+impl VectorRetriever {
+    pub fn new(name: &str, vector_store: Arc<VectorStore>, collection: &str, id_field: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            vector_store,
+            collection: collection.to_string(),
+            id_field,
+            hybrid: None,
+        }
+    }
+
+    pub fn hybrid(mut self, weights: Option<HybridWeights>) -> Self {
+        self.hybrid = weights;
+        self
+    }
+}
+
+#[async_trait]
+impl Retriever for VectorRetriever {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn retrieve(&self, query: &EvalQuery, k: usize) -> Result<Vec<String>> {
+        let results = match self.hybrid {
+            Some(weights) => self.vector_store.hybrid_search_text(&self.collection, &query.query, k, weights).await?,
+            None => self.vector_store.search_text(&self.collection, &query.query, k).await?,
+        };
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                self.id_field
+                    .as_ref()
+                    .and_then(|field| result.payload.get(field))
+                    .map(|value| value.as_str().map_or_else(|| value.to_string(), str::to_string))
+                    .unwrap_or_else(|| result.id.to_string())
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::{evaluate_all, parse_jsonl};
+
+    const CORPUS: &str = include_str!("fixtures/corpus.jsonl");
+    const QUERIES: &str = include_str!("fixtures/queries.jsonl");
+
+    #[tokio::test]
+    async fn test_fixture_runs_are_scored_and_compared() {
+        let corpus: Vec<CorpusItem> = parse_jsonl(CORPUS).unwrap();
+        let queries: Vec<EvalQuery> = parse_jsonl(QUERIES).unwrap();
+        let config = AppConfig::default();
+        let embeddings = Arc::new(EmbeddingEngine::hashed("all-MiniLM-L6-v2"));
+
+        let configs: Vec<RetrieverConfig> = serde_json::from_str(r#"[
+            {"retriever": "memory", "name": "memory"},
+            {"retriever": "memory", "name": "memory-again"},
+            {"retriever": "knowledge", "name": "knowledge"},
+            {"retriever": "memory", "name": "importance-heavy", "ranking": {"importance_weight": 0.9, "relevance_weight": 0.1}}
+        ]"#).unwrap();
+        let mut retrievers = Vec::new();
+        for retriever_config in &configs {
+            retrievers.push(retriever_config.build(&corpus, &config, embeddings.clone(), None).await.unwrap());
+        }
+
+        let report = evaluate_all(&retrievers, &queries, 3).await.unwrap();
+        let memory = &report.runs[0];
+        assert_eq!(memory.recall, 1.0);
+        // BM25 puts the memory matching both "ana" and "birthday" first
+        assert_eq!(memory.mrr, 1.0);
+
+        // Hash embeddings make repeated runs identical
+        assert!(report.diffs[0].queries.is_empty());
+        assert!(report.diffs[1].queries.is_empty());
+
+        // Weighting importance over relevance lets the sister memory outrank the birthday one
+        let importance_heavy = &report.diffs[2];
+        assert_eq!((importance_heavy.improved, importance_heavy.regressed), (0, 1));
+        assert_eq!(importance_heavy.queries[0].query_id, "birthday");
+        assert_eq!(importance_heavy.queries[0].reciprocal_rank_delta, -0.5);
+    }
+}
@@ -4,6 +4,16 @@ use std::path::Path;
 use std::fs;
 use uuid::Uuid;
 use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::vector::text_index::Bm25Index;
+use crate::vector::{CollectionSchema, HybridWeights, VectorPoint, VectorStore};
+
+/// Words per indexed chunk, so long documents are scored by their best passage
+const CHUNK_WORDS: usize = 200;
+
+/// A document's title is indexed as chunk 0, its content from chunk 1 on
+type ChunkKey = (String, usize);
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Document {
@@ -29,6 +39,9 @@ pub struct SearchResult {
 
 pub struct KnowledgeBase {
     documents: HashMap<String, Document>,
+    text_index: Bm25Index<ChunkKey>,
+    vector_store: Option<Arc<VectorStore>>,
+    hybrid: HybridWeights,
     use_chroma: bool,
 }
 
@@ -45,10 +58,32 @@ impl KnowledgeBase {
         
         Ok(KnowledgeBase {
             documents: HashMap::new(),
+            text_index: Bm25Index::default(),
+            vector_store: None,
+            hybrid: HybridWeights::default(),
             use_chroma,
         })
     }
     
+    /// Knowledge base using local text search only, without probing for ChromaDB
+    pub fn local() -> Self {
+        KnowledgeBase {
+            documents: HashMap::new(),
+            text_index: Bm25Index::default(),
+            vector_store: None,
+            hybrid: HybridWeights::default(),
+            use_chroma: false,
+        }
+    }
+    
+    /// Also embed documents into the vector store's document collection and search them
+    /// there, fusing vector and keyword matches per `hybrid`
+    pub fn with_vector_store(mut self, vector_store: Arc<VectorStore>, hybrid: HybridWeights) -> Self {
+        self.vector_store = Some(vector_store);
+        self.hybrid = hybrid;
+        self
+    }
+    
     async fn test_chroma_connection() -> bool {
         match reqwest::get("http://localhost:8000/api/v1/heartbeat").await {
             Ok(response) => response.status().is_success(),
@@ -99,12 +134,12 @@ impl KnowledgeBase {
             size: metadata.len(),
             created_at: chrono::Utc::now(),
             metadata: HashMap::new(),
-            embedding: None, // TODO: Generate embeddings if ChromaDB is available
+            embedding: None, // Filled in by `add_document` when there is a vector store
             categories: self.extract_categories(&content),
         };
         
         // Store document
-        self.documents.insert(document.id.clone(), document.clone());
+        let document = self.add_document(document).await;
         
         // Index in ChromaDB if available
         if self.use_chroma {
@@ -115,6 +150,9 @@ impl KnowledgeBase {
     }
     
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<Document>> {
+        if let Some(ref vector_store) = self.vector_store {
+            return self.search_hybrid(vector_store, query, limit).await;
+        }
         if self.use_chroma {
             self.search_with_chroma(query, limit).await
         } else {
@@ -122,45 +160,93 @@ impl KnowledgeBase {
         }
     }
     
+    /// Hybrid search of the document collection, keeping documents this knowledge base holds
+    async fn search_hybrid(&self, vector_store: &VectorStore, query: &str, limit: usize) -> Result<Vec<Document>> {
+        let collection = CollectionSchema::document_collection().name;
+        let results = vector_store.hybrid_search_text(&collection, query, limit, self.hybrid).await?;
+        Ok(results.into_iter()
+            .filter_map(|result| {
+                let doc_id = result.payload.get("document_id")?.as_str()?;
+                self.documents.get(doc_id).cloned()
+            })
+            .collect())
+    }
+    
+    /// BM25 over title and content chunks; a document scores as its best chunk
     fn search_local(&self, query: &str, limit: usize) -> Vec<Document> {
-        let query_lower = query.to_lowercase();
-        let mut results: Vec<(Document, f64)> = Vec::new();
-        
-        for document in self.documents.values() {
-            let title_score = if document.title.to_lowercase().contains(&query_lower) { 2.0 } else { 0.0 };
-            let content_score = self.calculate_content_score(&document.content, &query_lower);
-            let total_score = title_score + content_score;
-            
-            if total_score > 0.0 {
-                results.push((document.clone(), total_score));
+        let mut best: HashMap<&str, f32> = HashMap::new();
+        for ((doc_id, _), score) in self.text_index.scores(query) {
+            if let Some((id, _)) = self.documents.get_key_value(&doc_id) {
+                let entry = best.entry(id.as_str()).or_insert(0.0);
+                *entry = entry.max(score);
             }
         }
         
         // Sort by score (highest first) and take top results
-        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        let mut results: Vec<(&str, f32)> = best.into_iter().collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
         results.into_iter()
             .take(limit)
-            .map(|(doc, _)| doc)
+            .filter_map(|(id, _)| self.documents.get(id).cloned())
             .collect()
     }
     
-    fn calculate_content_score(&self, content: &str, query: &str) -> f64 {
-        let content_lower = content.to_lowercase();
-        let query_words: Vec<&str> = query.split_whitespace().collect();
+    /// Index a document for text search and, with a vector store, for semantic search
+    async fn add_document(&mut self, mut document: Document) -> Document {
+        self.remove_chunks(&document.id);
+        self.text_index.insert((document.id.clone(), 0), &document.title);
+        for (i, chunk) in chunk_words(&document.content).into_iter().enumerate() {
+            self.text_index.insert((document.id.clone(), i + 1), &chunk);
+        }
         
-        let mut score = 0.0;
-        for word in query_words {
-            if word.len() > 2 { // Skip very short words
-                let occurrences = content_lower.matches(word).count();
-                score += occurrences as f64 * word.len() as f64;
+        // Text search still works without the vector, so failures only warn
+        if let Some(ref vector_store) = self.vector_store {
+            if let Err(e) = Self::index_vector(vector_store, &mut document).await {
+                log::warn!("Failed to index document {} in the vector store: {}", document.id, e);
             }
         }
         
-        // Normalize by content length
-        if content.len() > 0 {
-            score / (content.len() as f64).sqrt()
-        } else {
-            0.0
+        self.documents.insert(document.id.clone(), document.clone());
+        document
+    }
+    
+    async fn index_vector(vector_store: &VectorStore, document: &mut Document) -> Result<()> {
+        let embedding = match document.embedding.clone() {
+            Some(embedding) => embedding,
+            None => vector_store.generate_embedding(&format!("{}\n\n{}", document.title, document.content)).await?,
+        };
+        document.embedding = Some(embedding.clone());
+        
+        let payload = HashMap::from([
+            ("document_id".to_string(), serde_json::json!(document.id)),
+            ("title".to_string(), serde_json::json!(document.title)),
+            ("content".to_string(), serde_json::json!(document.content)),
+            ("doc_type".to_string(), serde_json::json!(document.doc_type)),
+            ("file_path".to_string(), serde_json::json!(document.source)),
+            ("file_size".to_string(), serde_json::json!(document.size)),
+            ("created_at".to_string(), serde_json::json!(document.created_at.to_rfc3339())),
+            ("categories".to_string(), serde_json::json!(document.categories)),
+        ]);
+        let point = VectorPoint { id: point_id(&document.id), vector: embedding, payload };
+        vector_store.upsert_points(&CollectionSchema::document_collection().name, vec![point]).await
+    }
+    
+    async fn remove_vectors(&self, doc_ids: &[String]) {
+        let Some(ref vector_store) = self.vector_store else { return };
+        let collection = CollectionSchema::document_collection().name;
+        for doc_id in doc_ids {
+            if let Err(e) = vector_store.delete_point(&collection, point_id(doc_id)).await {
+                log::warn!("Failed to remove document {} from the vector store: {}", doc_id, e);
+            }
+        }
+    }
+    
+    fn remove_chunks(&mut self, doc_id: &str) {
+        if let Some(document) = self.documents.get(doc_id) {
+            let chunks = chunk_words(&document.content).len();
+            for i in 0..=chunks {
+                self.text_index.remove(&(doc_id.to_string(), i));
+            }
         }
     }
     
@@ -188,7 +274,11 @@ impl KnowledgeBase {
     }
     
     pub async fn delete_document(&mut self, doc_id: &str) -> Result<bool> {
+        self.remove_chunks(doc_id);
         let removed = self.documents.remove(doc_id).is_some();
+        if removed {
+            self.remove_vectors(&[doc_id.to_string()]).await;
+        }
         
         if removed && self.use_chroma {
             self.delete_from_chroma(doc_id).await?;
@@ -289,7 +379,7 @@ impl KnowledgeBase {
                 .any(|existing| existing.source == document.source);
             
             if !exists {
-                self.documents.insert(document.id.clone(), document.clone());
+                let document = self.add_document(document).await;
                 
                 // Index in ChromaDB if available
                 if self.use_chroma {
@@ -334,7 +424,10 @@ impl KnowledgeBase {
     
     pub async fn clear_all_documents(&mut self) -> Result<usize> {
         let count = self.documents.len();
+        let doc_ids: Vec<String> = self.documents.keys().cloned().collect();
+        self.remove_vectors(&doc_ids).await;
         self.documents.clear();
+        self.text_index.clear();
         
         // TODO: Clear ChromaDB collection if available
         if self.use_chroma {
@@ -356,4 +449,15 @@ impl KnowledgeBase {
             )
         })
     }
-}
\ No newline at end of file
+}
+
+/// Vector point id for a document; ids that aren't UUIDs are hashed into one
+fn point_id(doc_id: &str) -> Uuid {
+    Uuid::parse_str(doc_id).unwrap_or_else(|_| Uuid::new_v5(&Uuid::NAMESPACE_OID, doc_id.as_bytes()))
+}
+
+/// Split content into runs of `CHUNK_WORDS` words
+fn chunk_words(content: &str) -> Vec<String> {
+    let words: Vec<&str> = content.split_whitespace().collect();
+    words.chunks(CHUNK_WORDS).map(|chunk| chunk.join(" ")).collect()
+}
@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
 use std::path::Path;
 use std::fs;
+use crate::config::AppConfig;
 use crate::knowledge::{KnowledgeBase, Document};
+use crate::privacy::{RedactionReport, Redactor};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportOptions {
@@ -29,6 +31,9 @@ pub struct PackageMetadata {
     pub total_documents: usize,
     pub total_conversations: usize,
     pub checksum: String,
+    /// Personal data removed from the package before it was written
+    #[serde(default)]
+    pub redactions: RedactionReport,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -56,11 +61,14 @@ pub struct AgentConfigExport {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-pub struct KnowledgeTransfer;
+pub struct KnowledgeTransfer {
+    redactor: Redactor,
+}
 
 impl KnowledgeTransfer {
-    pub fn new() -> Self {
-        KnowledgeTransfer
+    /// Exports are redacted with the policies from the privacy settings
+    pub fn new(config: &AppConfig) -> Self {
+        KnowledgeTransfer { redactor: Redactor::from_config(&config.privacy) }
     }
     
     pub async fn export_knowledge(
@@ -68,7 +76,7 @@ impl KnowledgeTransfer {
         knowledge_base: &KnowledgeBase,
         export_path: &str,
         options: ExportOptions,
-    ) -> Result<()> {
+    ) -> Result<RedactionReport> {
         // Get documents filtered by categories
         let documents = if options.categories.is_empty() {
             knowledge_base.get_all_documents().await?
@@ -84,6 +92,13 @@ impl KnowledgeTransfer {
             filtered_docs
         };
         
+        // Keep personal data out of the package
+        let mut documents = documents;
+        let mut redactions = RedactionReport::default();
+        for document in &mut documents {
+            redactions.merge(&self.redact_document(document));
+        }
+        
         // Create package metadata
         let metadata = PackageMetadata {
             version: "1.0.0".to_string(),
@@ -93,6 +108,7 @@ impl KnowledgeTransfer {
             total_documents: documents.len(),
             total_conversations: 0, // TODO: Add conversation count
             checksum: self.calculate_checksum(&documents)?,
+            redactions: redactions.clone(),
         };
         
         // Create knowledge package
@@ -114,7 +130,24 @@ impl KnowledgeTransfer {
         }
         
         println!("Exported {} documents to {}", package.documents.len(), export_path);
-        Ok(())
+        if !redactions.is_empty() {
+            log::info!("Redacted from export: {}", redactions.summary());
+        }
+        Ok(redactions)
+    }
+    
+    fn redact_document(&self, document: &mut Document) -> RedactionReport {
+        let mut report = RedactionReport::default();
+        report.merge(&self.redactor.redact_in_place(&mut document.title));
+        report.merge(&self.redactor.redact_in_place(&mut document.content));
+        report.merge(&self.redactor.redact_in_place(&mut document.content_preview));
+        for value in document.metadata.values_mut() {
+            report.merge(&self.redactor.redact_in_place(value));
+        }
+        if !report.is_empty() {
+            document.size = document.content.len() as u64;
+        }
+        report
     }
     
     pub async fn import_knowledge(
@@ -12,6 +12,8 @@ pub mod vector;
 pub mod ai;
 pub mod services;
 pub mod commands;
+pub mod privacy;
+pub mod evaluation;
 
 // Platform modules
 #[cfg(target_os = "windows")]
@@ -39,11 +41,26 @@ use anyhow::Result;
 
 /// Initialize the Jinnie AI application
 pub async fn initialize_app() -> Result<AppState> {
+    initialize_app_with_passphrase(None).await
+}
+
+/// Initialize the application, unlocking encrypted storage with `passphrase` instead of
+/// reading it from the environment or a terminal
+pub async fn initialize_app_with_passphrase(passphrase: Option<String>) -> Result<AppState> {
+    let passphrase = passphrase.map(zeroize::Zeroizing::new);
+
     // Initialize logging
     log::info!("Starting Jinnie AI Assistant");
 
     // Load configuration
     let config = config::ConfigManager::initialize().await?;
+    privacy::set_log_redactor(privacy::Redactor::from_config(&config.privacy));
+
+    // Unlock encrypted storage before anything reads from the data directory, with the
+    // keyring in the same directory as the data it protects
+    storage::set_data_dir(&config.data_dir_path())?;
+    storage::move_legacy_data(&config.data_dir_path())?;
+    storage::unlock_at_startup(&config, passphrase.as_deref().map(String::as_str)).await?;
     
     // Initialize application state
     let state = state::initialize_app_state(config).await?;
@@ -54,8 +71,7 @@ pub async fn initialize_app() -> Result<AppState> {
     // Initialize LLM engine
     llm::initialize_llm_engine(&state).await?;
     
-    // Initialize vector store if feature is enabled
-    #[cfg(feature = "vector-db")]
+    // Report which vector backend is in use
     vector::initialize_vector_store(&state).await?;
     
     // Initialize memory system
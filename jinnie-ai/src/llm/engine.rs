@@ -2,9 +2,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
-use crate::config::{ModelType, ModelConfig};
+use crate::config::{AppConfig, ModelType, ModelConfig};
 use crate::utils::error::{LocalMindError, Result};
 use crate::llm::{ModelManager, ModelSelector, TaskClassifier, SessionManager};
+use crate::llm::reasoning::split_reasoning;
 
 /// Core LLM inference engine
 pub struct LLMEngine {
@@ -39,7 +40,8 @@ pub struct InferenceResponse {
     pub model_used: String,
     pub tokens_generated: Option<u32>,
     pub generation_time_ms: u64,
-    pub reasoning: Option<String>, // Why this model was selected
+    pub reasoning: Option<String>, // Model's thinking trace, kept out of `content`
+    pub selection_reasoning: Option<String>, // Why this model was selected
     pub confidence: Option<f32>,
     pub finish_reason: FinishReason,
     pub usage: TokenUsage,
@@ -85,11 +87,11 @@ pub struct ModelUsageStats {
 
 impl LLMEngine {
     /// Create a new LLM engine
-    pub async fn new() -> Result<Self> {
+    pub async fn new(config: &AppConfig) -> Result<Self> {
         let model_manager = Arc::new(Mutex::new(ModelManager::new().await?));
         let model_selector = Arc::new(ModelSelector::new().await?);
         let task_classifier = Arc::new(TaskClassifier::new());
-        let session_manager = Arc::new(Mutex::new(SessionManager::new()));
+        let session_manager = Arc::new(Mutex::new(SessionManager::from_config(config)));
 
         Ok(Self {
             model_manager,
@@ -161,7 +163,8 @@ impl LLMEngine {
             model_used: model_selection.model_type.display_name(),
             tokens_generated: response.tokens_generated,
             generation_time_ms: generation_time,
-            reasoning: Some(model_selection.reasoning),
+            reasoning: response.reasoning,
+            selection_reasoning: Some(model_selection.reasoning),
             confidence: model_selection.confidence,
             finish_reason: response.finish_reason,
             usage: response.usage,
@@ -198,17 +201,22 @@ impl LLMEngine {
         let ollama_response = ollama_client.generate(generation_request).await
             .map_err(|e| LocalMindError::AiService(format!("Model generation failed: {}", e)))?;
 
+        // Separate the thinking trace from the final answer
+        let parsed = split_reasoning(&ollama_response.response)
+            .with_extra_reasoning(ollama_response.thinking.clone());
+
         // Parse token usage (simplified - would need actual token counting)
         let prompt_tokens = estimate_tokens(&request.prompt);
         let completion_tokens = estimate_tokens(&ollama_response.response);
 
         Ok(InferenceResponse {
             session_id,
-            content: ollama_response.response,
+            content: parsed.content,
             model_used: model_selection.model_type.display_name(),
             tokens_generated: Some(completion_tokens),
             generation_time_ms: ollama_response.total_duration.unwrap_or(0) / 1_000_000, // Convert to ms
-            reasoning: Some(model_selection.reasoning.clone()),
+            reasoning: parsed.reasoning,
+            selection_reasoning: Some(model_selection.reasoning.clone()),
             confidence: model_selection.confidence,
             finish_reason: if ollama_response.done {
                 FinishReason::Completed
@@ -326,6 +334,11 @@ impl LLMEngine {
         Ok(())
     }
 
+    /// Session manager shared with the engine, e.g. for retention
+    pub fn session_manager(&self) -> Arc<Mutex<SessionManager>> {
+        self.session_manager.clone()
+    }
+
     /// Get active sessions count
     pub async fn get_active_sessions_count(&self) -> usize {
         self.active_sessions.read().await.len()
@@ -442,7 +455,7 @@ mod tests {
     #[tokio::test]
     async fn test_engine_creation() {
         // This test might fail without proper setup, but tests the interface
-        match LLMEngine::new().await {
+        match LLMEngine::new(&AppConfig::default()).await {
             Ok(engine) => {
                 assert_eq!(engine.get_active_sessions_count().await, 0);
             },
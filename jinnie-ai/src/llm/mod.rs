@@ -4,6 +4,7 @@ pub mod model_manager;
 pub mod model_selectors;
 pub mod task_classifiers;
 pub mod model_downloader;
+pub mod reasoning;
 
 pub use engine::{LLMEngine, InferenceRequest, InferenceResponse};
 pub use session_manager::SessionManager;
@@ -11,6 +12,7 @@ pub use model_manager::ModelManager;
 pub use model_selectors::ModelSelector;
 pub use task_classifiers::TaskClassifier;
 pub use model_downloader::{ModelDownloader, DownloadProgress};
+pub use reasoning::{split_reasoning, strip_reasoning, ParsedReply, ReasoningStreamSplitter, StreamChunk};
 
 use crate::state::AppState;
 use anyhow::Result;
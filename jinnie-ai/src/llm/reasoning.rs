@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+/// Opening tag emitted by reasoning models (deepseek-r1, qwen3, ...)
+pub const THINK_OPEN_TAG: &str = "<think>";
+/// Closing tag emitted by reasoning models
+pub const THINK_CLOSE_TAG: &str = "</think>";
+
+/// Model output split into the reasoning trace and the final answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedReply {
+    pub content: String,
+    pub reasoning: Option<String>,
+}
+
+/// A piece of streamed output, routed by whether it belongs to the thinking block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StreamChunk {
+    Thinking(String),
+    Answer(String),
+}
+
+impl ParsedReply {
+    /// Combine a reasoning trace reported separately by the backend with one parsed from the text
+    pub fn with_extra_reasoning(mut self, extra: Option<String>) -> Self {
+        let extra = extra
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty());
+
+        self.reasoning = match (extra, self.reasoning.take()) {
+            (Some(extra), Some(parsed)) => Some(format!("{}\n\n{}", extra, parsed)),
+            (Some(extra), None) => Some(extra),
+            (None, parsed) => parsed,
+        };
+        self
+    }
+}
+
+/// Split `<think>…</think>` blocks out of a complete model response.
+///
+/// Multiple blocks are joined in order. An unterminated block (generation cut off
+/// mid-thought) is treated as reasoning up to the end of the text. Some models omit
+/// the opening tag and start directly with the thinking text, so a closing tag without
+/// a matching opener marks everything before it as reasoning.
+pub fn split_reasoning(text: &str) -> ParsedReply {
+    let mut splitter = ReasoningStreamSplitter::new();
+    if !text.contains(THINK_OPEN_TAG) && text.contains(THINK_CLOSE_TAG) {
+        splitter.in_thinking = true;
+    }
+
+    let mut reasoning_parts = Vec::new();
+    let mut answer = String::new();
+
+    let chunks = splitter.push(text).into_iter().chain(splitter.finish());
+    for chunk in chunks {
+        match chunk {
+            StreamChunk::Thinking(t) => reasoning_parts.push(t),
+            StreamChunk::Answer(a) => answer.push_str(&a),
+        }
+    }
+
+    let reasoning = reasoning_parts.concat();
+    let reasoning = reasoning.trim();
+
+    ParsedReply {
+        content: answer.trim().to_string(),
+        reasoning: if reasoning.is_empty() { None } else { Some(reasoning.to_string()) },
+    }
+}
+
+/// Remove any thinking blocks from text, keeping only the answer.
+///
+/// Used before content is fed back to a model as conversation history.
+pub fn strip_reasoning(text: &str) -> String {
+    if !text.contains(THINK_OPEN_TAG) && !text.contains(THINK_CLOSE_TAG) {
+        return text.to_string();
+    }
+    split_reasoning(text).content
+}
+
+/// Incremental splitter for streamed tokens.
+///
+/// Tags may arrive split across several tokens (`"<thi"`, `"nk>"`), so any suffix that
+/// could be the start of a tag is held back until the next push resolves it.
+#[derive(Debug, Default)]
+pub struct ReasoningStreamSplitter {
+    buffer: String,
+    in_thinking: bool,
+}
+
+impl ReasoningStreamSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the splitter is currently inside a thinking block
+    pub fn is_thinking(&self) -> bool {
+        self.in_thinking
+    }
+
+    /// Feed a token and get back the chunks that can be emitted so far
+    pub fn push(&mut self, token: &str) -> Vec<StreamChunk> {
+        self.buffer.push_str(token);
+        let mut chunks = Vec::new();
+
+        loop {
+            let tag = if self.in_thinking { THINK_CLOSE_TAG } else { THINK_OPEN_TAG };
+
+            if let Some(pos) = self.buffer.find(tag) {
+                let before: String = self.buffer[..pos].to_string();
+                self.emit(&mut chunks, before);
+                self.buffer.drain(..pos + tag.len());
+                self.in_thinking = !self.in_thinking;
+                continue;
+            }
+
+            // Hold back a possible partial tag at the end of the buffer
+            let keep = partial_tag_suffix_len(&self.buffer, tag);
+            let emit_len = self.buffer.len() - keep;
+            if emit_len > 0 {
+                let ready: String = self.buffer[..emit_len].to_string();
+                self.emit(&mut chunks, ready);
+                self.buffer.drain(..emit_len);
+            }
+            break;
+        }
+
+        chunks
+    }
+
+    /// Flush whatever is still buffered once the stream has ended
+    pub fn finish(&mut self) -> Vec<StreamChunk> {
+        let mut chunks = Vec::new();
+        let rest = std::mem::take(&mut self.buffer);
+        self.emit(&mut chunks, rest);
+        chunks
+    }
+
+    fn emit(&self, chunks: &mut Vec<StreamChunk>, text: String) {
+        if text.is_empty() {
+            return;
+        }
+
+        let chunk = if self.in_thinking {
+            StreamChunk::Thinking(text)
+        } else {
+            StreamChunk::Answer(text)
+        };
+
+        // Merge with the previous chunk of the same kind to keep output compact
+        match (chunks.last_mut(), chunk) {
+            (Some(StreamChunk::Thinking(prev)), StreamChunk::Thinking(t)) => prev.push_str(&t),
+            (Some(StreamChunk::Answer(prev)), StreamChunk::Answer(a)) => prev.push_str(&a),
+            (_, chunk) => chunks.push(chunk),
+        }
+    }
+}
+
+/// Length of the longest suffix of `buffer` that is a proper prefix of `tag`
+fn partial_tag_suffix_len(buffer: &str, tag: &str) -> usize {
+    let max = tag.len().saturating_sub(1).min(buffer.len());
+    (1..=max)
+        .rev()
+        .find(|&len| buffer.is_char_boundary(buffer.len() - len) && tag.starts_with(&buffer[buffer.len() - len..]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_with_think_block() {
+        let parsed = split_reasoning("<think>The user wants a greeting.</think>\n\nHello there!");
+        assert_eq!(parsed.content, "Hello there!");
+        assert_eq!(parsed.reasoning.as_deref(), Some("The user wants a greeting."));
+    }
+
+    #[test]
+    fn test_split_without_think_block() {
+        let parsed = split_reasoning("Just an answer.");
+        assert_eq!(parsed.content, "Just an answer.");
+        assert!(parsed.reasoning.is_none());
+    }
+
+    #[test]
+    fn test_split_unterminated_block() {
+        let parsed = split_reasoning("<think>Still thinking about");
+        assert_eq!(parsed.content, "");
+        assert_eq!(parsed.reasoning.as_deref(), Some("Still thinking about"));
+    }
+
+    #[test]
+    fn test_split_missing_open_tag() {
+        let parsed = split_reasoning("Let me consider this.</think>The answer is 4.");
+        assert_eq!(parsed.content, "The answer is 4.");
+        assert_eq!(parsed.reasoning.as_deref(), Some("Let me consider this."));
+    }
+
+    #[test]
+    fn test_strip_reasoning_for_history() {
+        assert_eq!(strip_reasoning("<think>hidden</think>visible"), "visible");
+        assert_eq!(strip_reasoning("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_stream_splitter_handles_split_tags() {
+        let mut splitter = ReasoningStreamSplitter::new();
+        let mut chunks = Vec::new();
+
+        for token in ["<th", "ink>plan", "ning</th", "ink>", "Answer", " here"] {
+            chunks.extend(splitter.push(token));
+        }
+        chunks.extend(splitter.finish());
+
+        let thinking: String = chunks.iter().filter_map(|c| match c {
+            StreamChunk::Thinking(t) => Some(t.as_str()),
+            _ => None,
+        }).collect();
+        let answer: String = chunks.iter().filter_map(|c| match c {
+            StreamChunk::Answer(a) => Some(a.as_str()),
+            _ => None,
+        }).collect();
+
+        assert_eq!(thinking, "planning");
+        assert_eq!(answer, "Answer here");
+    }
+
+    #[test]
+    fn test_extra_reasoning_is_merged() {
+        let parsed = split_reasoning("Final answer").with_extra_reasoning(Some("from backend".to_string()));
+        assert_eq!(parsed.reasoning.as_deref(), Some("from backend"));
+        assert_eq!(parsed.content, "Final answer");
+    }
+}
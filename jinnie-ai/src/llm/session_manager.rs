@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::config::ModelType;
+use crate::config::{AppConfig, ModelType};
 use crate::utils::error::{LocalMindError, Result};
+use crate::llm::reasoning::strip_reasoning;
+use crate::privacy::{RedactionReport, Redactor};
 
 /// Manages conversation sessions and context
 pub struct SessionManager {
@@ -9,6 +11,7 @@ pub struct SessionManager {
     max_sessions: usize,
     max_context_length: usize,
     session_timeout_minutes: u64,
+    redactor: Redactor,
 }
 
 /// A conversation session with an agent
@@ -72,6 +75,15 @@ impl SessionManager {
             max_sessions: 100,
             max_context_length: 4096, // Maximum tokens in context
             session_timeout_minutes: 60, // 1 hour timeout
+            redactor: Redactor::default(),
+        }
+    }
+
+    /// Create a session manager that redacts exports with the configured privacy policies
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            redactor: Redactor::from_config(&config.privacy),
+            ..Self::new()
         }
     }
 
@@ -185,13 +197,14 @@ impl SessionManager {
             None,
         ).await?;
 
-        // Add assistant response
+        // Add assistant response (thinking blocks are never replayed to the model)
+        let assistant_response = strip_reasoning(assistant_response);
         self.add_message(
             session_id,
             MessageRole::Assistant,
-            assistant_response.to_string(),
+            assistant_response.clone(),
             Some(model_used.display_name()),
-            Some(estimate_tokens(assistant_response)),
+            Some(estimate_tokens(&assistant_response)),
             None,
         ).await?;
 
@@ -253,7 +266,11 @@ impl SessionManager {
                 MessageRole::Assistant => "Assistant",
                 MessageRole::System => "System",
             };
-            formatted.push_str(&format!("{}: {}\n", role_str, message.content));
+            let content = match message.role {
+                MessageRole::Assistant => strip_reasoning(&message.content),
+                _ => message.content.clone(),
+            };
+            formatted.push_str(&format!("{}: {}\n", role_str, content));
         }
 
         formatted
@@ -325,6 +342,11 @@ impl SessionManager {
         Ok(removed)
     }
 
+    /// All active sessions
+    pub fn sessions(&self) -> impl Iterator<Item = &ConversationSession> {
+        self.active_sessions.values()
+    }
+
     /// Get active session count
     pub fn get_active_session_count(&self) -> usize {
         self.active_sessions.len()
@@ -404,12 +426,24 @@ impl SessionManager {
         Ok(())
     }
 
-    /// Export session for backup or analysis
+    /// Export session for backup or analysis, with personal data redacted
     pub async fn export_session(&self, session_id: &str) -> Result<String> {
-        let session = self.active_sessions.get(session_id)
-            .ok_or_else(|| LocalMindError::Message(format!("Session not found: {}", session_id)))?;
+        let mut session = self.active_sessions.get(session_id)
+            .ok_or_else(|| LocalMindError::Message(format!("Session not found: {}", session_id)))?
+            .clone();
 
-        serde_json::to_string_pretty(session)
+        let mut redactions = RedactionReport::default();
+        for message in &mut session.messages {
+            redactions.merge(&self.redactor.redact_in_place(&mut message.content));
+        }
+        if let Some(ref mut summary) = session.context_summary {
+            redactions.merge(&self.redactor.redact_in_place(summary));
+        }
+        if !redactions.is_empty() {
+            log::info!("Redacted from session export: {}", redactions.summary());
+        }
+
+        serde_json::to_string_pretty(&session)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to export session: {}", e)))
     }
 
@@ -513,6 +547,48 @@ mod tests {
         assert_eq!(session.session_metadata.total_tokens, 5);
     }
 
+    #[tokio::test]
+    async fn test_export_redacts_personal_data() {
+        let mut manager = SessionManager::new();
+        let session_id = manager.start_session("test_agent".to_string()).await.unwrap();
+        manager.add_message(
+            &session_id,
+            MessageRole::User,
+            "My card is 4111 1111 1111 1111".to_string(),
+            None,
+            None,
+            None,
+        ).await.unwrap();
+
+        let exported = manager.export_session(&session_id).await.unwrap();
+        assert!(exported.contains("My card is [CARD]"));
+        assert!(!exported.contains("4111 1111"));
+
+        // The live session keeps the original text
+        let session = manager.get_session(&session_id).await.unwrap().unwrap();
+        assert!(session.messages[0].content.contains("4111"));
+    }
+
+    #[tokio::test]
+    async fn test_export_follows_configured_policies() {
+        let mut config = AppConfig::default();
+        config.privacy.pii_policies.insert(crate::privacy::PiiKind::CreditCard, crate::privacy::RedactionPolicy::Keep);
+        let mut manager = SessionManager::from_config(&config);
+        let session_id = manager.start_session("test_agent".to_string()).await.unwrap();
+        manager.add_message(
+            &session_id,
+            MessageRole::User,
+            "My card is 4111 1111 1111 1111, mail me at sam@example.com".to_string(),
+            None,
+            None,
+            None,
+        ).await.unwrap();
+
+        let exported = manager.export_session(&session_id).await.unwrap();
+        assert!(exported.contains("4111 1111 1111 1111"));
+        assert!(!exported.contains("sam@example.com"));
+    }
+
     #[tokio::test]
     async fn test_session_cleanup() {
         let mut manager = SessionManager::new();
@@ -552,6 +628,25 @@ mod tests {
         assert!(context_str.contains("Message"));
     }
 
+    #[tokio::test]
+    async fn test_context_excludes_reasoning() {
+        let mut manager = SessionManager::new();
+        let session_id = manager.start_session("test_agent".to_string()).await.unwrap();
+
+        manager.add_message(
+            &session_id,
+            MessageRole::Assistant,
+            "<think>private scratchpad</think>Visible answer".to_string(),
+            None,
+            Some(10),
+            None,
+        ).await.unwrap();
+
+        let context_str = manager.get_context(&session_id, 5).await.unwrap().unwrap();
+        assert!(context_str.contains("Visible answer"));
+        assert!(!context_str.contains("scratchpad"));
+    }
+
     #[test]
     fn test_token_estimation() {
         assert_eq!(estimate_tokens("Hello world"), 3);
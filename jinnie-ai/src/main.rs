@@ -6,11 +6,13 @@ use dioxus_desktop::{Config, WindowBuilder, LogicalSize};
 use tokio::runtime::Runtime;
 
 // Import from your own crate (jinnie_ai) instead of local_ai_agent
-use jinnie_ai::initialize_app;
+use jinnie_ai::{initialize_app, initialize_app_with_passphrase};
+use jinnie_ai::utils::error::LocalMindError;
 
 // UI imports
 mod ui;
 use ui::app::App;
+use ui::components::unlock::UnlockScreen;
 
 /// Application state that bridges your backend with the Dioxus frontend
 #[derive(Clone)]
@@ -23,9 +25,14 @@ pub struct AppState {
 
 fn main() {
     // Initialize logging first
-    env_logger::Builder::from_default_env()
+    // Personal data is redacted from log messages before they are written
+    let logger = env_logger::Builder::from_default_env()
         .filter_level(log::LevelFilter::Info)
-        .init();
+        .build();
+    let max_level = logger.filter();
+    jinnie_ai::privacy::RedactingLogger::new(logger)
+        .install(max_level)
+        .expect("Failed to install logger");
 
     log::info!("🦀 Starting Jinnie.ai - 100% Rust-Powered AI Assistant");
 
@@ -37,16 +44,32 @@ fn main() {
     let backend_state = runtime.block_on(async {
         log::info!("Initializing Jinnie AI backend...");
         initialize_app().await
-            .expect("Failed to initialize backend")
     });
+    let (backend_state, unlock_setup) = match backend_state {
+        Ok(state) => (Some(state), false),
+        Err(error) => match error.downcast_ref::<LocalMindError>() {
+            // Started without a terminal: ask for the passphrase in the window instead
+            Some(LocalMindError::PassphraseRequired { setup }) => {
+                log::info!("🔐 Waiting for the storage passphrase");
+                (None, *setup)
+            }
+            _ => {
+                eprintln!("{}", startup_error_message(&error));
+                std::process::exit(1);
+            }
+        },
+    };
 
-    log::info!("✅ Backend initialized successfully");
+    if backend_state.is_some() {
+        log::info!("✅ Backend initialized successfully");
+    }
 
     // Create shared app state
-    let app_state = AppState {
+    let app_state = backend_state.map(|backend_state| AppState {
         backend_state: Arc::new(backend_state),
         runtime_handle: runtime.handle().clone(),
-    };
+    });
+    let runtime_handle = runtime.handle().clone();
 
     // Launch Dioxus desktop application
     log::info!("🚀 Launching Dioxus UI...");
@@ -55,8 +78,10 @@ fn main() {
         move || {
             // Provide app state to the UI
             rsx! {
-                AppWithState {
-                    app_state: app_state.clone()
+                Root {
+                    app_state: app_state.clone(),
+                    runtime_handle: runtime_handle.clone(),
+                    unlock_setup,
                 }
             }
         },
@@ -81,6 +106,73 @@ fn main() {
     );
 }
 
+/// A wrong passphrase is a user error, not a crash
+fn startup_error_message(error: &anyhow::Error) -> String {
+    match error.downcast_ref::<LocalMindError>() {
+        Some(LocalMindError::WrongPassphrase) => {
+            "❌ Wrong passphrase. Your data was not changed; try again.".to_string()
+        }
+        Some(LocalMindError::Locked(reason)) => format!("🔐 Cannot start: {}", reason),
+        _ => format!("❌ Failed to initialize backend: {:#}", error),
+    }
+}
+
+#[derive(Props, Clone)]
+struct RootProps {
+    app_state: Option<AppState>,
+    runtime_handle: tokio::runtime::Handle,
+    unlock_setup: bool,
+}
+
+/// Shows the unlock screen until the backend is running, then the app
+fn Root(props: RootProps) -> Element {
+    let mut app_state = use_signal(|| props.app_state.clone());
+    let mut unlock_error = use_signal(|| None::<String>);
+    let mut unlocking = use_signal(|| false);
+
+    if let Some(app_state) = app_state() {
+        return rsx! {
+            AppWithState { app_state }
+        };
+    }
+
+    let runtime_handle = props.runtime_handle.clone();
+    let unlock = move |passphrase: String| {
+        unlocking.set(true);
+        unlock_error.set(None);
+
+        let runtime_handle = runtime_handle.clone();
+        spawn(async move {
+            let handle = runtime_handle.clone();
+            let result = runtime_handle
+                .spawn_blocking(move || handle.block_on(initialize_app_with_passphrase(Some(passphrase))))
+                .await;
+
+            match result {
+                Ok(Ok(backend_state)) => {
+                    log::info!("✅ Backend initialized successfully");
+                    app_state.set(Some(AppState {
+                        backend_state: Arc::new(backend_state),
+                        runtime_handle,
+                    }));
+                }
+                Ok(Err(error)) => unlock_error.set(Some(startup_error_message(&error))),
+                Err(error) => unlock_error.set(Some(format!("❌ Failed to initialize backend: {}", error))),
+            }
+            unlocking.set(false);
+        });
+    };
+
+    rsx! {
+        UnlockScreen {
+            setup: props.unlock_setup,
+            busy: unlocking(),
+            error: unlock_error(),
+            on_submit: unlock,
+        }
+    }
+}
+
 /// Wrapper component that provides app state to the UI
 #[derive(Props, Clone)]
 struct AppWithStateProps {
@@ -103,40 +195,20 @@ impl AppState {
         &self,
         agent_id: String,
         message: String,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Use your existing backend functionality
-        let agents = self.backend_state.agents.lock().await;
-        let agent = agents.get(&agent_id)
-            .ok_or("Agent not found")?;
-        
-        // Generate AI response using your AI module
-        let response = jinnie_ai::ai::generate_agent_response(agent, &message).await
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-        
-        // Store the message exchange
-        let mut messages = self.backend_state.messages.lock().await;
-        
-        // Add user message
-        let user_message = jinnie_ai::Message::new(
-            message,
-            agent_id.clone(),
-            jinnie_ai::types::message::MessageRole::User,
-        );
-        messages.entry(agent_id.clone())
-            .or_insert_with(Vec::new)
-            .push(user_message);
-        
-        // Add AI response
-        let ai_message = jinnie_ai::Message::new(
-            response.clone(),
-            agent_id.clone(),
-            jinnie_ai::types::message::MessageRole::Assistant,
-        );
-        messages.entry(agent_id)
-            .or_insert_with(Vec::new)
-            .push(ai_message);
-        
-        Ok(response)
+    ) -> Result<jinnie_ai::Message, Box<dyn std::error::Error + Send + Sync>> {
+        // Shared path: recalls memories, generates the reply and stores the exchange
+        let ai_message = jinnie_ai::commands::send_message_to_agent(&self.backend_state, agent_id, message).await?;
+        Ok(ai_message)
+    }
+
+    /// Search messages by text and date phrases like "last Tuesday"
+    pub async fn search_messages(
+        &self,
+        query: String,
+        agent_id: Option<String>,
+        limit: Option<usize>,
+    ) -> Result<Vec<jinnie_ai::Message>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::search_messages(&self.backend_state, query, agent_id, limit).await?)
     }
 
     /// Get agent messages
@@ -190,6 +262,144 @@ impl AppState {
         Ok(())
     }
 
+    /// Browse memories for the inspector panel
+    pub async fn browse_memories(
+        &self,
+        query: jinnie_ai::memory::MemoryQuery,
+    ) -> Result<Vec<jinnie_ai::memory::Memory>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::browse_memories(&self.backend_state, query).await?)
+    }
+
+    /// Save an edit made in the memory inspector
+    pub async fn update_memory(
+        &self,
+        memory_id: uuid::Uuid,
+        update: jinnie_ai::memory::MemoryUpdate,
+    ) -> Result<jinnie_ai::memory::Memory, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::update_memory(&self.backend_state, memory_id, update).await?)
+    }
+
+    /// Pin or unpin memories
+    pub async fn pin_memories(
+        &self,
+        memory_ids: Vec<uuid::Uuid>,
+        pinned: bool,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::pin_memories(&self.backend_state, memory_ids, pinned).await?)
+    }
+
+    /// Preview what the retention policy would remove
+    pub async fn preview_retention(
+        &self,
+    ) -> Result<jinnie_ai::privacy::RetentionReport, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::preview_retention(&self.backend_state).await?)
+    }
+
+    /// Apply the retention policy now
+    pub async fn enforce_retention(
+        &self,
+    ) -> Result<jinnie_ai::privacy::RetentionReport, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::enforce_retention(&self.backend_state).await?)
+    }
+
+    /// Change the storage encryption passphrase
+    pub async fn change_passphrase(
+        &self,
+        old: String,
+        new: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::change_passphrase(old, new).await?)
+    }
+
+    /// Rotate the storage encryption key; stored data is re-encrypted at the next start
+    pub async fn rotate_encryption_key(
+        &self,
+        passphrase: String,
+    ) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::rotate_encryption_key(passphrase).await?)
+    }
+
+    /// Forget memories
+    pub async fn forget_memories(
+        &self,
+        memory_ids: Vec<uuid::Uuid>,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::forget_memories(&self.backend_state, memory_ids).await?)
+    }
+
+    /// Recall memories along with ones linked to them by associations
+    pub async fn recall_associated_memories(
+        &self,
+        agent_id: String,
+        query: String,
+        limit: Option<usize>,
+    ) -> Result<Vec<jinnie_ai::memory::AssociativeResult>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::recall_associated_memories(&self.backend_state, agent_id, query, limit).await?)
+    }
+
+    /// Inferred facts awaiting confirmation
+    pub async fn pending_verifications(
+        &self,
+    ) -> Result<Vec<jinnie_ai::memory::VerificationRequest>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::pending_verifications(&self.backend_state).await?)
+    }
+
+    /// Confirm, reject or correct an inferred fact
+    pub async fn answer_verification(
+        &self,
+        subject: jinnie_ai::memory::VerificationSubject,
+        answer: jinnie_ai::memory::VerificationAnswer,
+    ) -> Result<Option<jinnie_ai::memory::Memory>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::answer_verification(&self.backend_state, subject, answer).await?)
+    }
+
+    /// Move a memory to another scope
+    pub async fn promote_memory(
+        &self,
+        memory_id: uuid::Uuid,
+        scope: jinnie_ai::memory::MemoryScope,
+        agent_id: String,
+    ) -> Result<jinnie_ai::memory::Memory, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::promote_memory(&self.backend_state, memory_id, scope, agent_id).await?)
+    }
+
+    /// Score retrieval configurations on a labelled query set
+    pub async fn evaluate_retrieval(
+        &self,
+        queries_path: String,
+        corpus_path: String,
+        configs: Option<Vec<jinnie_ai::evaluation::RetrieverConfig>>,
+        k: Option<usize>,
+        previous_report: Option<String>,
+    ) -> Result<jinnie_ai::evaluation::EvalReport, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::evaluate_retrieval(&self.backend_state, queries_path, corpus_path, configs, k, previous_report).await?)
+    }
+
+    /// Rate how much a memory matters
+    pub async fn rate_memory(
+        &self,
+        memory_id: uuid::Uuid,
+        rating: f32,
+    ) -> Result<jinnie_ai::memory::Memory, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::rate_memory(&self.backend_state, memory_id, rating).await?)
+    }
+
+    /// What the assistant has learned to treat as important
+    pub async fn importance_report(
+        &self,
+        agent_id: Option<String>,
+    ) -> Result<Option<jinnie_ai::memory::ImportanceReport>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::importance_report(&self.backend_state, agent_id).await?)
+    }
+
+    /// Associations of one memory
+    pub async fn memory_associations(
+        &self,
+        memory_id: uuid::Uuid,
+    ) -> Result<Vec<jinnie_ai::memory::MemoryAssociation>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(jinnie_ai::commands::memory_associations(&self.backend_state, memory_id).await?)
+    }
+
     /// Execute async operations on the runtime
     pub fn spawn<F>(&self, future: F) -> tokio::task::JoinHandle<F::Output>
     where
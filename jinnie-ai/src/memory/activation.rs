@@ -0,0 +1,216 @@
+//! Spreading activation over the association graph: activation starts at the best direct
+//! matches and flows along association edges, weakening with each hop.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::memory_types::{AssociationType, MemoryAssociation};
+
+/// How much activation each kind of edge passes on, before its own strength
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeWeights {
+    pub causal: f32,
+    pub supporting: f32,
+    pub temporal: f32,
+    pub semantic: f32,
+    pub topical: f32,
+    pub user_defined: f32,
+    /// Zero by default: a contradiction is no reason to bring a memory up
+    pub contradictory: f32,
+}
+
+impl Default for EdgeWeights {
+    fn default() -> Self {
+        Self {
+            causal: 0.9,
+            supporting: 0.8,
+            temporal: 0.5,
+            semantic: 0.6,
+            topical: 0.5,
+            user_defined: 0.7,
+            contradictory: 0.0,
+        }
+    }
+}
+
+impl EdgeWeights {
+    pub fn weight(&self, association_type: &AssociationType) -> f32 {
+        match association_type {
+            AssociationType::Causal => self.causal,
+            AssociationType::Supporting => self.supporting,
+            AssociationType::Temporal => self.temporal,
+            AssociationType::Semantic => self.semantic,
+            AssociationType::TopicalRelation => self.topical,
+            AssociationType::UserDefined(_) => self.user_defined,
+            AssociationType::Contradictory => self.contradictory,
+        }
+    }
+}
+
+/// Tuning for associative retrieval
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpreadingConfig {
+    /// How many of the best direct matches activation starts from
+    pub seeds: usize,
+    pub max_hops: usize,
+    /// Multiplier applied on every hop
+    pub decay: f32,
+    /// Activation below this is dropped rather than spread further
+    pub min_activation: f32,
+    pub edge_weights: EdgeWeights,
+}
+
+impl Default for SpreadingConfig {
+    fn default() -> Self {
+        Self {
+            seeds: 5,
+            max_hops: 3,
+            decay: 0.7,
+            min_activation: 0.05,
+            edge_weights: EdgeWeights::default(),
+        }
+    }
+}
+
+/// One edge followed on the way to a memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivationStep {
+    pub from: Uuid,
+    pub to: Uuid,
+    pub association_type: AssociationType,
+    /// Edge strength times its type weight
+    pub weight: f32,
+    pub notes: Option<String>,
+}
+
+/// A memory reached by spreading activation, with the path that reached it
+#[derive(Debug, Clone)]
+pub struct Activation {
+    pub id: Uuid,
+    pub activation: f32,
+    /// Empty for the seeds themselves
+    pub path: Vec<ActivationStep>,
+}
+
+/// Spread activation from `seeds` along `edges`, visiting only memories `reachable` allows.
+///
+/// Edges are followed in both directions. Each memory keeps its strongest activation and
+/// the path that produced it. Results are strongest first.
+pub fn spread<'a>(
+    seeds: &[(Uuid, f32)],
+    edges: impl IntoIterator<Item = &'a MemoryAssociation>,
+    reachable: impl Fn(Uuid) -> bool,
+    config: &SpreadingConfig,
+) -> Vec<Activation> {
+    let mut neighbours: HashMap<Uuid, Vec<(Uuid, &MemoryAssociation, f32)>> = HashMap::new();
+    for edge in edges {
+        let weight = edge.strength.clamp(0.0, 1.0) * config.edge_weights.weight(&edge.association_type);
+        if weight <= 0.0 {
+            continue;
+        }
+        neighbours.entry(edge.memory_a).or_default().push((edge.memory_b, edge, weight));
+        neighbours.entry(edge.memory_b).or_default().push((edge.memory_a, edge, weight));
+    }
+
+    let mut best: HashMap<Uuid, Activation> = HashMap::new();
+    for &(id, score) in seeds {
+        if best.get(&id).map_or(true, |existing| score > existing.activation) {
+            best.insert(id, Activation { id, activation: score, path: Vec::new() });
+        }
+    }
+
+    // Relax hop by hop; a node goes back on the frontier whenever its activation improves
+    let mut frontier: Vec<Uuid> = best.keys().copied().collect();
+    for _ in 0..config.max_hops {
+        let mut next = Vec::new();
+        for id in frontier {
+            let current = best[&id].clone();
+            for &(neighbour, edge, weight) in neighbours.get(&id).into_iter().flatten() {
+                let activation = current.activation * weight * config.decay;
+                if activation < config.min_activation || current.path.iter().any(|step| step.from == neighbour) {
+                    continue;
+                }
+                if best.get(&neighbour).is_some_and(|existing| existing.activation >= activation) || !reachable(neighbour) {
+                    continue;
+                }
+
+                let mut path = current.path.clone();
+                path.push(ActivationStep {
+                    from: id,
+                    to: neighbour,
+                    association_type: edge.association_type.clone(),
+                    weight,
+                    notes: edge.notes.clone(),
+                });
+                best.insert(neighbour, Activation { id: neighbour, activation, path });
+                next.push(neighbour);
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+
+    let mut activations: Vec<Activation> = best.into_values().collect();
+    activations.sort_by(|a, b| b.activation.total_cmp(&a.activation));
+    activations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn edge(a: Uuid, b: Uuid, association_type: AssociationType, strength: f32) -> MemoryAssociation {
+        MemoryAssociation {
+            id: Uuid::new_v4(),
+            memory_a: a,
+            memory_b: b,
+            association_type,
+            strength,
+            created_at: Utc::now(),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_activation_decays_per_hop_and_weights_edge_types() {
+        let [seed, cause, effect, rival, far] = [(); 5].map(|_| Uuid::new_v4());
+        let edges = vec![
+            edge(seed, cause, AssociationType::Causal, 1.0),
+            edge(effect, cause, AssociationType::Supporting, 0.5),
+            edge(seed, rival, AssociationType::Contradictory, 1.0),
+            edge(effect, far, AssociationType::Temporal, 1.0),
+        ];
+        let config = SpreadingConfig { max_hops: 2, ..SpreadingConfig::default() };
+
+        let activations = spread(&[(seed, 1.0)], &edges, |_| true, &config);
+        let by_id: HashMap<Uuid, &Activation> = activations.iter().map(|a| (a.id, a)).collect();
+
+        assert!(by_id[&seed].path.is_empty());
+        assert!((by_id[&cause].activation - 0.9 * 0.7).abs() < 1e-6);
+        // Second hop, followed against the edge's direction
+        let to_effect = &by_id[&effect].path;
+        assert_eq!(to_effect.iter().map(|step| step.to).collect::<Vec<_>>(), vec![cause, effect]);
+        assert!((by_id[&effect].activation - 0.63 * 0.4 * 0.7).abs() < 1e-6);
+        // Contradictions don't spread and the hop limit stops the rest
+        assert!(!by_id.contains_key(&rival));
+        assert!(!by_id.contains_key(&far));
+    }
+
+    #[test]
+    fn test_unreachable_memories_block_the_path() {
+        let [seed, archived, beyond] = [(); 3].map(|_| Uuid::new_v4());
+        let edges = vec![
+            edge(seed, archived, AssociationType::Causal, 1.0),
+            edge(archived, beyond, AssociationType::Causal, 1.0),
+        ];
+
+        let activations = spread(&[(seed, 1.0)], &edges, |id| id != archived, &SpreadingConfig::default());
+        assert_eq!(activations.len(), 1);
+    }
+}
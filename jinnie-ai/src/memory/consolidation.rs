@@ -1,15 +1,138 @@
-use anyhow::Result;
-use std::collections::HashMap;
-use chrono::Utc;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use chrono::{Timelike, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use uuid::Uuid;
 
 use super::memory_types::*;
 use super::memory_manager::MemoryManager;
+use crate::llm::reasoning::strip_reasoning;
+use crate::services::ollama::{create_generation_options, OllamaClient, OllamaRequest};
+
+/// Model used to summarize memory groups unless another is configured
+pub const DEFAULT_SUMMARY_MODEL: &str = "llama3.1:8b";
+
+/// Custom field listing the memories a consolidated memory was built from
+pub const CONSOLIDATED_FROM_FIELD: &str = "consolidated_from";
+
+/// Working memories untouched for this long are moved out to short-term
+const WORKING_MEMORY_IDLE_HOURS: i64 = 24;
+
+/// Memories a recurring term must appear in to count as a theme
+const MIN_THEME_MEMORIES: usize = 3;
+
+/// Distinct conversations a theme must span
+const MIN_THEME_CONVERSATIONS: usize = 2;
+
+/// Overlap between memory sets at which two recurring terms share a theme
+const THEME_CLUSTER_OVERLAP: f32 = 0.5;
+
+/// Share of a stated reason that must match an earlier memory to call it the cause
+const MIN_CAUSAL_OVERLAP: f32 = 0.5;
+
+/// How far back a "so ..." follow-up looks for its cause
+const CAUSAL_FOLLOW_UP_MINUTES: i64 = 30;
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "your", "with", "this", "that", "have", "has",
+    "was", "were", "will", "would", "could", "should", "about", "from", "they", "them", "their",
+    "what", "when", "where", "which", "there", "these", "those", "then", "than", "just", "really",
+    "some", "also", "into", "been", "being", "very", "like", "can", "its", "it's", "i'm", "don't",
+    "because", "today", "thing", "things", "think", "going", "want",
+];
+
+const NEGATIONS: &[&str] = &[
+    "not", "no", "never", "don't", "dont", "doesn't", "doesnt", "didn't", "didnt", "isn't", "isnt",
+    "aren't", "wasn't", "won't", "can't", "cannot", "anymore",
+];
+
+const AUXILIARIES: &[&str] = &["do", "does", "did", "am", "is", "are", "really"];
+
+/// Tools that fill the same role, so using one replaces another
+const TOOL_CATEGORIES: &[(&str, &[&str])] = &[
+    ("editor", &["vim", "neovim", "nvim", "helix", "emacs", "vscode", "vs code", "nano", "sublime", "intellij", "zed"]),
+    ("operating system", &["linux", "windows", "macos", "ubuntu", "fedora", "debian"]),
+    ("browser", &["firefox", "chrome", "safari", "edge", "brave"]),
+    ("shell", &["bash", "zsh", "fish", "powershell", "nushell"]),
+];
+
+static PERSONAL_FACT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"my (?:favou?rite|preferred) ([a-z]+) is ([a-z0-9][a-z0-9 +#'-]*)|i live in ([a-z][a-z '-]*)|i work (?:at|for) ([a-z0-9][a-z0-9 &'-]*)|my name is ([a-z][a-z'-]*)").unwrap()
+});
+
+static TOOL_USAGE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"i(?:'m| am)?\s+(?:now\s+|mostly\s+|mainly\s+)?(?:use|using|switched|moved|changed|migrated|prefer|run|running)").unwrap()
+});
+
+static CAUSE_CLAUSE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:because|due to|as a result of|thanks to)\s+(.+)").unwrap()
+});
+
+static EFFECT_LEAD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:so|therefore|as a result|that's why|which is why|because of that)\b").unwrap()
+});
+
+/// Turns a group of related memories into one summary
+#[async_trait]
+pub trait MemorySummarizer: Send + Sync {
+    async fn summarize(&self, memories: &[Memory]) -> Result<String>;
+}
+
+/// Summarizer backed by a local Ollama model
+pub struct OllamaSummarizer {
+    client: OllamaClient,
+    model: String,
+}
+
+impl OllamaSummarizer {
+    pub fn new(model: &str) -> Self {
+        Self {
+            client: OllamaClient::new(),
+            model: model.to_string(),
+        }
+    }
+}
+
+impl Default for OllamaSummarizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_SUMMARY_MODEL)
+    }
+}
+
+#[async_trait]
+impl MemorySummarizer for OllamaSummarizer {
+    async fn summarize(&self, memories: &[Memory]) -> Result<String> {
+        let mut prompt = String::from(
+            "Combine the following related memories into one concise memory. Keep every concrete fact \
+             (names, dates, preferences, decisions) and drop repetition. Reply with the memory text only.\n\n",
+        );
+        for memory in memories {
+            prompt.push_str(&format!("- {}\n", memory.content.trim()));
+        }
+        prompt.push_str("\nCombined memory:");
+
+        let request = OllamaRequest {
+            model: self.model.clone(),
+            prompt,
+            stream: false,
+            options: Some(create_generation_options(Some(0.2), Some(300))),
+        };
+
+        let response = self.client.generate(request).await
+            .map_err(|e| anyhow!("Memory summarization failed: {}", e))?;
+
+        Ok(strip_reasoning(&response.response).trim().to_string())
+    }
+}
 
 /// Engine for consolidating memories and generating insights
 pub struct ConsolidationEngine {
     similarity_threshold: f32,
     consolidation_batch_size: usize,
+    summarizer: Option<Arc<dyn MemorySummarizer>>,
 }
 
 impl ConsolidationEngine {
@@ -17,40 +140,50 @@ impl ConsolidationEngine {
         Self {
             similarity_threshold: 0.8,
             consolidation_batch_size: 50,
+            summarizer: None,
         }
     }
 
-    /// Consolidate memories to manage capacity and create insights
-    pub async fn consolidate_memories(&self, memory_manager: &mut MemoryManager) -> Result<ConsolidationReport> {
+    /// Use a model to write summaries instead of the extractive fallback
+    pub fn set_summarizer(&mut self, summarizer: Arc<dyn MemorySummarizer>) {
+        self.summarizer = Some(summarizer);
+    }
+
+    /// Minimum number of memories a layer needs before it is consolidated
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.consolidation_batch_size = batch_size;
+        self
+    }
+
+    /// Consolidate every agent's memories, or only `agent_id`'s, journaling every change in `run`
+    pub async fn consolidate_memories(&self, memory_manager: &mut MemoryManager, run: &mut ConsolidationRun, agent_id: Option<&str>) -> Result<ConsolidationReport> {
         let start_time = std::time::Instant::now();
-        let initial_count = memory_manager.get_stats().total_memories;
-        
-        let mut report = ConsolidationReport {
-            memories_processed: 0,
-            memories_consolidated: 0,
-            memories_archived: 0,
-            memories_deleted: 0,
-            new_insights: Vec::new(),
-            processing_time_ms: 0,
-            space_saved_bytes: 0,
-        };
+        let mut report = ConsolidationReport::new(None);
 
         // Process each layer
         for layer in [MemoryLayer::Working, MemoryLayer::ShortTerm, MemoryLayer::Episodic] {
-            let layer_report = self.consolidate_layer(memory_manager, layer).await?;
+            let layer_report = self.consolidate_layer(memory_manager, layer, run, agent_id).await?;
             self.merge_reports(&mut report, layer_report);
         }
 
-        // Generate insights from consolidated memories
-        let insights = self.generate_insights(memory_manager).await?;
-        report.new_insights = insights;
-
         // Move memories between layers based on importance and access patterns
-        self.rebalance_memory_layers(memory_manager).await?;
+        report.memories_moved = self.rebalance_memory_layers(memory_manager, run, agent_id).await?;
 
-        report.processing_time_ms = start_time.elapsed().as_millis() as u64;
-        report.space_saved_bytes = (initial_count - memory_manager.get_stats().total_memories) * 1024; // Rough estimate
+        report.new_insights = run.created
+            .iter()
+            .filter_map(|id| memory_manager.get_memory(*id).cloned())
+            .collect();
 
+        // Bytes no longer held in the active layers
+        let archived_bytes: usize = run.archived
+            .iter()
+            .filter_map(|id| memory_manager.get_archived(*id))
+            .map(|entry| entry.memory.content.len())
+            .sum();
+        let created_bytes: usize = report.new_insights.iter().map(|m| m.content.len()).sum();
+        report.space_saved_bytes = archived_bytes.saturating_sub(created_bytes);
+
+        report.processing_time_ms = start_time.elapsed().as_millis() as u64;
         Ok(report)
     }
 
@@ -70,18 +203,16 @@ impl ConsolidationEngine {
 
     // Private helper methods
 
-    async fn consolidate_layer(&self, memory_manager: &mut MemoryManager, layer: MemoryLayer) -> Result<ConsolidationReport> {
-        let mut report = ConsolidationReport {
-            memories_processed: 0,
-            memories_consolidated: 0,
-            memories_archived: 0,
-            memories_deleted: 0,
-            new_insights: Vec::new(),
-            processing_time_ms: 0,
-            space_saved_bytes: 0,
-        };
+    async fn consolidate_layer(&self, memory_manager: &mut MemoryManager, layer: MemoryLayer, run: &mut ConsolidationRun, agent_id: Option<&str>) -> Result<ConsolidationReport> {
+        let mut report = ConsolidationReport::new(None);
 
-        let memories = memory_manager.get_memories_by_layer(layer);
+        let memories: Vec<Memory> = memory_manager
+            .get_memories_by_layer(layer)
+            .into_iter()
+            .filter(|m| agent_id.map_or(true, |agent| m.metadata.agent_id == agent))
+            .filter(|m| !m.pinned)
+            .cloned()
+            .collect();
         report.memories_processed = memories.len();
 
         if memories.len() < self.consolidation_batch_size {
@@ -89,7 +220,7 @@ impl ConsolidationEngine {
         }
 
         // Group similar memories
-        let memory_groups = self.group_similar_memories(memories).await?;
+        let memory_groups = self.group_similar_memories(&memories).await?;
 
         for group in memory_groups {
             if group.len() > 1 {
@@ -97,16 +228,16 @@ impl ConsolidationEngine {
                 
                 match consolidation_strategy {
                     ConsolidationStrategy::Summarize => {
-                        report.memories_consolidated += self.summarize_memory_group(memory_manager, group).await?;
+                        report.memories_consolidated += self.summarize_memory_group(memory_manager, group, run).await?;
                     },
                     ConsolidationStrategy::Merge => {
-                        report.memories_consolidated += self.merge_memory_group(memory_manager, group).await?;
+                        report.memories_consolidated += self.merge_memory_group(memory_manager, group, run).await?;
                     },
                     ConsolidationStrategy::Archive => {
-                        report.memories_archived += self.archive_memory_group(memory_manager, group).await?;
+                        report.memories_archived += self.archive_memory_group(memory_manager, group, run).await?;
                     },
                     ConsolidationStrategy::Deduplicate => {
-                        report.memories_deleted += self.deduplicate_memory_group(memory_manager, group).await?;
+                        report.memories_deleted += self.deduplicate_memory_group(memory_manager, group, run).await?;
                     },
                     ConsolidationStrategy::Preserve => {
                         // Keep all memories as-is
@@ -118,7 +249,7 @@ impl ConsolidationEngine {
         Ok(report)
     }
 
-    async fn group_similar_memories(&self, memories: Vec<&Memory>) -> Result<Vec<Vec<Memory>>> {
+    async fn group_similar_memories(&self, memories: &[Memory]) -> Result<Vec<Vec<Memory>>> {
         let mut groups: Vec<Vec<Memory>> = Vec::new();
         let mut used_indices = std::collections::HashSet::new();
 
@@ -127,7 +258,7 @@ impl ConsolidationEngine {
                 continue;
             }
 
-            let mut group = vec![(*memory).clone()];
+            let mut group = vec![memory.clone()];
             used_indices.insert(i);
 
             // Find similar memories to group with this one
@@ -138,7 +269,7 @@ impl ConsolidationEngine {
 
                 let similarity = self.calculate_memory_similarity(memory, other_memory);
                 if similarity > self.similarity_threshold {
-                    group.push((*other_memory).clone());
+                    group.push(other_memory.clone());
                     used_indices.insert(j);
                 }
             }
@@ -150,6 +281,11 @@ impl ConsolidationEngine {
     }
 
     fn calculate_memory_similarity(&self, memory1: &Memory, memory2: &Memory) -> f32 {
+        // Memories of different agents are never consolidated together
+        if memory1.metadata.agent_id != memory2.metadata.agent_id {
+            return 0.0;
+        }
+
         let mut similarity = 0.0;
         let mut factors = 0;
 
@@ -200,24 +336,15 @@ impl ConsolidationEngine {
             }
         }
 
-        // Temporal similarity (if created close in time)
-        let time_diff = (memory1.created_at - memory2.created_at).abs();
-        if time_diff.num_hours() < 24 {
-            similarity += 0.3;
-            factors += 1;
+        if factors == 0 {
+            return 0.0;
         }
 
-        // Agent similarity
-        if memory1.metadata.agent_id == memory2.metadata.agent_id {
-            similarity += 0.2;
-            factors += 1;
-        }
+        // Memories created close in time get a small boost
+        let time_diff = (memory1.created_at - memory2.created_at).abs();
+        let temporal_bonus = if time_diff.num_hours() < 24 { 0.1 } else { 0.0 };
 
-        if factors > 0 {
-            similarity / factors as f32
-        } else {
-            0.0
-        }
+        (similarity / factors as f32 + temporal_bonus).min(1.0)
     }
 
     fn determine_consolidation_strategy(&self, group: &[Memory]) -> ConsolidationStrategy {
@@ -316,100 +443,198 @@ impl ConsolidationEngine {
         dp[s1_len][s2_len]
     }
 
-    async fn summarize_memory_group(&self, _memory_manager: &mut MemoryManager, group: Vec<Memory>) -> Result<usize> {
-        // In a real implementation, this would use an LLM to create a summary
-        // For now, we'll create a simple concatenated summary
-        
+    async fn summarize_memory_group(&self, memory_manager: &mut MemoryManager, group: Vec<Memory>, run: &mut ConsolidationRun) -> Result<usize> {
         if group.is_empty() {
             return Ok(0);
         }
 
-        let combined_content = group.iter()
-            .map(|m| &m.content)
-            .collect::<Vec<_>>()
-            .join(". ");
+        let summary_content = self.summarize_group_content(&group).await;
 
-        let summary_content = if combined_content.len() > 200 {
-            format!("{}...", &combined_content[..200])
-        } else {
-            combined_content
-        };
+        // Memories spanning several sessions describe general knowledge rather than one episode
+        let sessions: HashSet<_> = group.iter().filter_map(|m| m.metadata.session_id.as_ref()).collect();
+        let layer = if sessions.len() > 1 { MemoryLayer::Semantic } else { MemoryLayer::LongTerm };
 
-        // Create a new consolidated memory
-        let representative_memory = &group[0];
-        let mut summary_metadata = representative_memory.metadata.clone();
+        let mut summary_metadata = group[0].metadata.clone();
         summary_metadata.source = MemorySource::Consolidation;
+        summary_metadata.session_id = None;
+        summary_metadata.topics = Self::union_topics(&group);
+        summary_metadata.entities = Self::union_entities(&group);
+        summary_metadata.custom_fields.insert(
+            CONSOLIDATED_FROM_FIELD.to_string(),
+            serde_json::json!(group.iter().map(|m| m.id.to_string()).collect::<Vec<_>>()),
+        );
 
-        // Merge topics and entities from all memories
-        let mut all_topics = std::collections::HashSet::new();
-        let mut all_entities = Vec::new();
-        
-        for memory in &group {
-            for topic in &memory.metadata.topics {
-                all_topics.insert(topic.clone());
-            }
-            all_entities.extend(memory.metadata.entities.clone());
-        }
+        let mut summary = Memory::new(summary_content, layer, summary_metadata);
+        summary.importance_score = group.iter().map(|m| m.importance_score).fold(0.0, f32::max);
+        summary.tags = Self::union_tags(&group);
 
-        summary_metadata.topics = all_topics.into_iter().collect();
-        summary_metadata.entities = all_entities;
+        let summary = memory_manager.insert_memory(summary).await?;
+        run.created.push(summary.id);
 
-        let _summary_memory = Memory::new(
-            summary_content,
-            MemoryLayer::LongTerm, // Summaries go to long-term
-            summary_metadata,
-        );
+        let source_ids: Vec<Uuid> = group.iter().map(|m| m.id).collect();
+        let archived = memory_manager.archive_memories(&source_ids, Some(run.id), Some(summary.id)).await?;
+        run.archived.extend(source_ids.iter().copied());
 
-        // In a real implementation, we would:
-        // 1. Store the summary memory
-        // 2. Delete the original memories
-        // 3. Update associations
+        // Provenance: the summary points back at every source
+        for id in source_ids {
+            run.associations.push(memory_manager.associate(summary.id, id, AssociationType::Supporting, 1.0).await?);
+        }
 
-        Ok(group.len() - 1) // Return number of memories consolidated (all but the summary)
+        Ok(archived)
     }
 
-    async fn merge_memory_group(&self, _memory_manager: &mut MemoryManager, group: Vec<Memory>) -> Result<usize> {
-        // Simple merge: keep the most important memory and add notes about others
-        if group.is_empty() {
+    async fn merge_memory_group(&self, memory_manager: &mut MemoryManager, group: Vec<Memory>, run: &mut ConsolidationRun) -> Result<usize> {
+        // Keep the most important memory and fold the others into it
+        if group.len() < 2 {
             return Ok(0);
         }
 
         let best_memory = group.iter()
-            .max_by(|a, b| a.importance_score.partial_cmp(&b.importance_score).unwrap())
-            .unwrap();
+            .max_by(|a, b| a.importance_score.partial_cmp(&b.importance_score).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap()
+            .clone();
+        let others: Vec<&Memory> = group.iter().filter(|m| m.id != best_memory.id).collect();
+        let other_ids: Vec<Uuid> = others.iter().map(|m| m.id).collect();
+
+        run.previous_versions.push(best_memory.clone());
+
+        let mut merged = best_memory.clone();
+        for other in &others {
+            let detail = other.content.trim();
+            if !detail.is_empty() && !merged.content.to_lowercase().contains(&detail.to_lowercase()) {
+                merged.content.push('\n');
+                merged.content.push_str(detail);
+            }
+            merged.access_count += other.access_count;
+            merged.last_accessed = merged.last_accessed.max(other.last_accessed);
+        }
+        merged.importance_score = group.iter().map(|m| m.importance_score).fold(0.0, f32::max);
+        merged.tags = Self::union_tags(&group);
+        merged.metadata.topics = Self::union_topics(&group);
+        merged.metadata.entities = Self::union_entities(&group);
+        merged.metadata.custom_fields.insert(
+            CONSOLIDATED_FROM_FIELD.to_string(),
+            serde_json::json!(other_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>()),
+        );
+        if merged.content != best_memory.content {
+            merged.embedding = memory_manager.embed_text(&merged.content).await?;
+        }
+        memory_manager.replace_memory(merged).await?;
 
-        // In a real implementation, we would update the best memory with information from others
-        // and then delete the redundant memories
+        let archived = memory_manager.archive_memories(&other_ids, Some(run.id), Some(best_memory.id)).await?;
+        run.archived.extend(other_ids.iter().copied());
 
-        Ok(group.len() - 1)
+        for id in other_ids {
+            run.associations.push(memory_manager.associate(best_memory.id, id, AssociationType::Supporting, 1.0).await?);
+        }
+
+        Ok(archived)
     }
 
-    async fn archive_memory_group(&self, _memory_manager: &mut MemoryManager, group: Vec<Memory>) -> Result<usize> {
-        // Move memories to a compressed archive storage
-        // In a real implementation, this would move them to a different storage system
-        
-        Ok(group.len())
+    async fn archive_memory_group(&self, memory_manager: &mut MemoryManager, group: Vec<Memory>, run: &mut ConsolidationRun) -> Result<usize> {
+        let ids: Vec<Uuid> = group.iter().map(|m| m.id).collect();
+        let archived = memory_manager.archive_memories(&ids, Some(run.id), None).await?;
+        run.archived.extend(ids);
+        Ok(archived)
     }
 
-    async fn deduplicate_memory_group(&self, _memory_manager: &mut MemoryManager, group: Vec<Memory>) -> Result<usize> {
-        // Keep only the first memory and delete duplicates
+    async fn deduplicate_memory_group(&self, memory_manager: &mut MemoryManager, group: Vec<Memory>, run: &mut ConsolidationRun) -> Result<usize> {
+        // Keep the most important copy; the rest go to the archive
         if group.len() <= 1 {
             return Ok(0);
         }
 
-        // In a real implementation, we would delete all but the first memory
-        Ok(group.len() - 1)
+        let keep = group.iter()
+            .max_by(|a, b| a.importance_score.partial_cmp(&b.importance_score).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap()
+            .id;
+        let duplicates: Vec<Uuid> = group.iter().map(|m| m.id).filter(|id| *id != keep).collect();
+
+        let archived = memory_manager.archive_memories(&duplicates, Some(run.id), Some(keep)).await?;
+        run.archived.extend(duplicates);
+        Ok(archived)
     }
 
-    async fn rebalance_memory_layers(&self, _memory_manager: &mut MemoryManager) -> Result<()> {
-        // Move memories between layers based on:
-        // - Importance scores
-        // - Access patterns  
-        // - Age
-        // - Layer capacity
+    /// Promote working and short-term memories whose importance or use warrants a more durable layer
+    async fn rebalance_memory_layers(&self, memory_manager: &mut MemoryManager, run: &mut ConsolidationRun, agent_id: Option<&str>) -> Result<usize> {
+        let now = Utc::now();
+        let mut moves = Vec::new();
+
+        for layer in [MemoryLayer::Working, MemoryLayer::ShortTerm] {
+            let memories = memory_manager.get_memories_by_layer(layer)
+                .into_iter()
+                .filter(|m| agent_id.map_or(true, |agent| m.metadata.agent_id == agent));
+            for memory in memories {
+                let mut target = memory_manager.determine_target_layer(memory);
+
+                // Working memory is for the current conversation only
+                let idle_hours = now.signed_duration_since(memory.last_accessed).num_hours();
+                if target == MemoryLayer::Working && layer == MemoryLayer::Working && idle_hours >= WORKING_MEMORY_IDLE_HOURS {
+                    target = MemoryLayer::ShortTerm;
+                }
+
+                // Never demote back into working memory
+                if target != layer && target != MemoryLayer::Working {
+                    moves.push((memory.id, target));
+                }
+            }
+        }
+
+        for (id, target) in &moves {
+            let from = memory_manager.move_to_layer(*id, *target).await?;
+            run.moved.push((*id, from));
+        }
 
-        // This would involve analyzing each memory and potentially moving it to a more appropriate layer
-        Ok(())
+        Ok(moves.len())
+    }
+
+    async fn summarize_group_content(&self, group: &[Memory]) -> String {
+        if let Some(ref summarizer) = self.summarizer {
+            match summarizer.summarize(group).await {
+                Ok(summary) if !summary.trim().is_empty() => return summary.trim().to_string(),
+                Ok(_) => log::warn!("Summarizer returned nothing; using extractive summary"),
+                Err(e) => log::warn!("{}; using extractive summary", e),
+            }
+        }
+
+        // Extractive fallback: the group's content, trimmed to a readable length
+        let combined_content = group.iter()
+            .map(|m| m.content.trim())
+            .collect::<Vec<_>>()
+            .join(". ");
+
+        if combined_content.chars().count() > 200 {
+            format!("{}...", combined_content.chars().take(200).collect::<String>())
+        } else {
+            combined_content
+        }
+    }
+
+    fn union_topics(group: &[Memory]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        group.iter()
+            .flat_map(|m| m.metadata.topics.iter())
+            .filter(|topic| seen.insert((*topic).clone()))
+            .cloned()
+            .collect()
+    }
+
+    fn union_tags(group: &[Memory]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        group.iter()
+            .flat_map(|m| m.tags.iter())
+            .filter(|tag| seen.insert((*tag).clone()))
+            .cloned()
+            .collect()
+    }
+
+    fn union_entities(group: &[Memory]) -> Vec<Entity> {
+        let mut seen = HashSet::new();
+        group.iter()
+            .flat_map(|m| m.metadata.entities.iter())
+            .filter(|entity| seen.insert(entity.name.to_lowercase()))
+            .cloned()
+            .collect()
     }
 
     // Insight generation methods
@@ -438,6 +663,7 @@ impl ConsolidationEngine {
                         supporting_memories: agent_memories.iter().take(3).map(|m| m.id).collect(),
                         created_at: Utc::now(),
                         agent_id: agent_id.clone(),
+                        verification_status: VerificationStatus::Unverified,
                     });
                 }
             }
@@ -503,6 +729,7 @@ impl ConsolidationEngine {
                                 supporting_memories: vec![memory.id],
                                 created_at: Utc::now(),
                                 agent_id: memory.metadata.agent_id.clone(),
+                                verification_status: VerificationStatus::Unverified,
                             });
                         }
                     }
@@ -526,63 +753,296 @@ impl ConsolidationEngine {
         String::new()
     }
 
-    async fn identify_recurring_themes(&self, _memory_manager: &MemoryManager) -> Result<Vec<Insight>> {
+    async fn identify_recurring_themes(&self, memory_manager: &MemoryManager) -> Result<Vec<Insight>> {
         let mut insights = Vec::new();
 
-        // Analyze topics that appear frequently across different conversations
-        // This would involve more sophisticated topic modeling in a real implementation
+        let mut by_agent: HashMap<String, Vec<&Memory>> = HashMap::new();
+        for memory in Self::active_memories(memory_manager) {
+            by_agent.entry(memory.metadata.agent_id.clone()).or_default().push(memory);
+        }
+
+        for (agent_id, memories) in by_agent {
+            // Which memories mention each topic or salient keyword
+            let mut term_memories: HashMap<String, HashSet<Uuid>> = HashMap::new();
+            for memory in &memories {
+                let terms = memory.metadata.topics.iter()
+                    .map(|topic| topic.to_lowercase())
+                    .chain(Self::content_words(&memory.content).into_iter().filter(|word| word.len() >= 5));
+                for term in terms {
+                    term_memories.entry(term).or_default().insert(memory.id);
+                }
+            }
 
-        insights.push(Insight {
-            id: Uuid::new_v4(),
-            insight_type: InsightType::Theme,
-            content: "Recurring theme analysis not yet implemented".to_string(),
-            confidence: 0.3,
-            supporting_memories: vec![],
-            created_at: Utc::now(),
-            agent_id: "system".to_string(),
-        });
+            let sessions: HashMap<Uuid, String> = memories.iter()
+                .map(|memory| (memory.id, Self::conversation_key(memory)))
+                .collect();
+            let span = |ids: &HashSet<Uuid>| ids.iter().map(|id| &sessions[id]).collect::<HashSet<_>>().len();
+
+            let mut recurring: Vec<(String, HashSet<Uuid>)> = term_memories
+                .into_iter()
+                .filter(|(_, ids)| ids.len() >= MIN_THEME_MEMORIES && span(ids) >= MIN_THEME_CONVERSATIONS)
+                .collect();
+            recurring.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+
+            // Terms that keep showing up in the same memories form one theme
+            let mut themes: Vec<(Vec<String>, HashSet<Uuid>)> = Vec::new();
+            for (term, ids) in recurring {
+                let existing = themes.iter_mut().find(|(_, theme_ids)| {
+                    let shared = theme_ids.intersection(&ids).count() as f32;
+                    shared / theme_ids.union(&ids).count() as f32 >= THEME_CLUSTER_OVERLAP
+                });
+                match existing {
+                    Some((terms, theme_ids)) => {
+                        terms.push(term);
+                        theme_ids.extend(ids);
+                    }
+                    None => themes.push((vec![term], ids)),
+                }
+            }
+
+            for (terms, ids) in themes {
+                let conversations = span(&ids);
+                let mut supporting: Vec<&Memory> = memories.iter().copied().filter(|m| ids.contains(&m.id)).collect();
+                supporting.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+                insights.push(Insight {
+                    id: Uuid::new_v4(),
+                    insight_type: InsightType::Theme,
+                    content: format!(
+                        "Recurring theme: {} ({} memories across {} conversations)",
+                        terms.iter().take(3).cloned().collect::<Vec<_>>().join(", "),
+                        ids.len(),
+                        conversations
+                    ),
+                    confidence: (0.4 + 0.1 * conversations as f32).min(0.9),
+                    supporting_memories: supporting.iter().take(10).map(|m| m.id).collect(),
+                    created_at: Utc::now(),
+                    agent_id: agent_id.clone(),
+                    verification_status: VerificationStatus::Unverified,
+                });
+            }
+        }
 
         Ok(insights)
     }
 
-    async fn discover_relationships(&self, _memory_manager: &MemoryManager) -> Result<Vec<Insight>> {
+    /// Find cause/effect pairs; each insight lists the cause first and the effect second
+    async fn discover_relationships(&self, memory_manager: &MemoryManager) -> Result<Vec<Insight>> {
         let mut insights = Vec::new();
 
-        // Discover relationships between entities, topics, and concepts
-        // This would involve graph analysis in a real implementation
-
-        insights.push(Insight {
-            id: Uuid::new_v4(),
-            insight_type: InsightType::Relationship,
-            content: "Relationship discovery not yet implemented".to_string(),
-            confidence: 0.3,
-            supporting_memories: vec![],
-            created_at: Utc::now(),
-            agent_id: "system".to_string(),
-        });
+        let mut memories = Self::active_memories(memory_manager);
+        memories.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        for (index, effect) in memories.iter().enumerate() {
+            let earlier = memories[..index].iter()
+                .filter(|m| m.metadata.agent_id == effect.metadata.agent_id);
+            let content = effect.content.to_lowercase();
+
+            let found = if let Some(reason) = CAUSE_CLAUSE.captures(&content).map(|c| c[1].to_string()) {
+                // "... because X": the cause is the earlier memory that best matches X
+                let reason_words = Self::content_words(&reason);
+                earlier
+                    .map(|cause| (cause, Self::word_overlap(&reason_words, &Self::content_words(&cause.content))))
+                    .filter(|(_, overlap)| *overlap >= MIN_CAUSAL_OVERLAP)
+                    .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                    .map(|(cause, overlap)| (*cause, 0.5 + overlap / 2.0))
+            } else if EFFECT_LEAD.is_match(&content) {
+                // "So ..." follows on from whatever was said just before in the same conversation
+                earlier
+                    .filter(|m| Self::conversation_key(m) == Self::conversation_key(effect))
+                    .filter(|m| effect.created_at - m.created_at <= chrono::Duration::minutes(CAUSAL_FOLLOW_UP_MINUTES))
+                    .last()
+                    .map(|cause| (*cause, 0.6))
+            } else {
+                None
+            };
+
+            if let Some((cause, confidence)) = found {
+                if memory_manager.has_association(cause.id, effect.id, &AssociationType::Causal) {
+                    continue;
+                }
+                insights.push(Insight {
+                    id: Uuid::new_v4(),
+                    insight_type: InsightType::Relationship,
+                    content: format!("\"{}\" led to \"{}\"", cause.content.trim(), effect.content.trim()),
+                    confidence: confidence.min(0.95),
+                    supporting_memories: vec![cause.id, effect.id],
+                    created_at: Utc::now(),
+                    agent_id: effect.metadata.agent_id.clone(),
+                    verification_status: VerificationStatus::Unverified,
+                });
+            }
+        }
 
         Ok(insights)
     }
 
-    async fn detect_contradictions(&self, _memory_manager: &MemoryManager) -> Result<Vec<Insight>> {
+    /// Find memories that can't both be true; each insight lists the older memory first
+    async fn detect_contradictions(&self, memory_manager: &MemoryManager) -> Result<Vec<Insight>> {
         let mut insights = Vec::new();
 
-        // Detect contradictory information in memories
-        // This would require semantic understanding in a real implementation
+        let mut memories = Self::active_memories(memory_manager);
+        memories.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        let mut pairs: Vec<(&Memory, &Memory, String, f32)> = Vec::new();
+
+        // The same fact stated with different values, e.g. "I use vim" then "I switched to Helix"
+        let mut slots: HashMap<(String, String), Vec<(&Memory, String)>> = HashMap::new();
+        for memory in &memories {
+            for (slot, value) in Self::extract_fact_slots(&memory.content) {
+                slots.entry((memory.metadata.agent_id.clone(), slot)).or_default().push((*memory, value));
+            }
+        }
+        for ((_, slot), values) in &slots {
+            let Some((newest, newest_value)) = values.last() else { continue };
+            for (older, value) in &values[..values.len() - 1] {
+                if value != newest_value {
+                    pairs.push((*older, *newest, slot.clone(), 0.7));
+                }
+            }
+        }
+
+        // The same statement with and without a negation
+        let mut statements: HashMap<(String, String), Vec<(&Memory, bool)>> = HashMap::new();
+        for memory in &memories {
+            if let Some((key, negated)) = Self::negation_key(&memory.content) {
+                statements.entry((memory.metadata.agent_id.clone(), key)).or_default().push((*memory, negated));
+            }
+        }
+        for versions in statements.values() {
+            let Some((newest, negated)) = versions.last() else { continue };
+            if let Some((older, _)) = versions.iter().rev().find(|(_, other)| other != negated) {
+                pairs.push((*older, *newest, "negation".to_string(), 0.8));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for (older, newer, reason, confidence) in pairs {
+            let settled = |m: &Memory| m.metadata.verification_status == VerificationStatus::Verified;
+            if older.metadata.verification_status == VerificationStatus::Deprecated
+                || newer.metadata.verification_status == VerificationStatus::Deprecated
+                || (settled(older) && settled(newer))
+                || memory_manager.has_association(older.id, newer.id, &AssociationType::Contradictory)
+                || !seen.insert((older.id, newer.id))
+            {
+                continue;
+            }
 
-        insights.push(Insight {
-            id: Uuid::new_v4(),
-            insight_type: InsightType::Contradiction,
-            content: "Contradiction detection not yet implemented".to_string(),
-            confidence: 0.3,
-            supporting_memories: vec![],
-            created_at: Utc::now(),
-            agent_id: "system".to_string(),
-        });
+            insights.push(Insight {
+                id: Uuid::new_v4(),
+                insight_type: InsightType::Contradiction,
+                content: format!("\"{}\" conflicts with \"{}\" ({})", older.content.trim(), newer.content.trim(), reason),
+                confidence,
+                supporting_memories: vec![older.id, newer.id],
+                created_at: Utc::now(),
+                agent_id: newer.metadata.agent_id.clone(),
+                verification_status: VerificationStatus::Unverified,
+            });
+        }
 
         Ok(insights)
     }
 
+    /// Memories from every loaded layer that haven't been ruled out
+    fn active_memories(memory_manager: &MemoryManager) -> Vec<&Memory> {
+        MemoryLayer::ALL
+            .into_iter()
+            .flat_map(|layer| memory_manager.get_memories_by_layer(layer))
+            .filter(|m| m.metadata.verification_status != VerificationStatus::Deprecated)
+            .collect()
+    }
+
+    /// Session, then conversation, then calendar day
+    fn conversation_key(memory: &Memory) -> String {
+        memory.metadata.session_id.clone()
+            .or_else(|| memory.metadata.conversation_id.clone())
+            .unwrap_or_else(|| memory.created_at.date_naive().to_string())
+    }
+
+    fn content_words(text: &str) -> HashSet<String> {
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .filter(|word| word.len() > 2 && !STOPWORDS.contains(word))
+            .map(|word| word.to_string())
+            .collect()
+    }
+
+    fn word_overlap(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+        a.intersection(b).count() as f32 / a.len().min(b.len()) as f32
+    }
+
+    /// Pull (slot, value) facts out of first-person statements
+    fn extract_fact_slots(content: &str) -> Vec<(String, String)> {
+        let text = content.to_lowercase();
+        if text.split(|c: char| !c.is_alphanumeric() && c != '\'').any(|word| NEGATIONS.contains(&word)) {
+            return Vec::new();
+        }
+
+        let mut slots = Vec::new();
+
+        for captures in PERSONAL_FACT.captures_iter(&text) {
+            let (slot, value) = match (captures.get(1), captures.get(2), captures.get(3), captures.get(4), captures.get(5)) {
+                (Some(noun), Some(value), _, _, _) => (format!("favorite {}", noun.as_str()), value),
+                (_, _, Some(value), _, _) => ("home".to_string(), value),
+                (_, _, _, Some(value), _) => ("employer".to_string(), value),
+                (_, _, _, _, Some(value)) => ("name".to_string(), value),
+                _ => continue,
+            };
+            let value = value.as_str()
+                .split(" and ").next().unwrap_or_default()
+                .split(" but ").next().unwrap_or_default()
+                .split_whitespace().take(3).collect::<Vec<_>>().join(" ");
+            slots.push((slot, value));
+        }
+
+        if let Some(usage) = TOOL_USAGE.find(&text) {
+            for (category, tools) in TOOL_CATEGORIES {
+                let mentioned: Vec<(usize, &str)> = tools.iter()
+                    .filter_map(|tool| {
+                        Regex::new(&format!(r"\b{}\b", regex::escape(tool))).ok()
+                            .and_then(|re| re.find(&text))
+                            .map(|m| (m.start(), *tool))
+                    })
+                    .collect();
+                // "switched from vim to helix": the tool after the last "to" is the current one
+                let current = match mentioned.as_slice() {
+                    [] => None,
+                    [(_, tool)] => Some(*tool),
+                    _ => text.rfind(" to ")
+                        .filter(|to| *to >= usage.start())
+                        .and_then(|to| mentioned.iter().filter(|(start, _)| *start > to).min())
+                        .map(|(_, tool)| *tool),
+                };
+                if let Some(tool) = current {
+                    slots.push((category.to_string(), tool.to_string()));
+                }
+            }
+        }
+
+        slots
+    }
+
+    /// Statement with negations and auxiliaries removed, and whether it was negated
+    fn negation_key(content: &str) -> Option<(String, bool)> {
+        let text = content.to_lowercase();
+        let words: Vec<&str> = text
+            .split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .filter(|word| !word.is_empty())
+            .collect();
+        let negated = words.iter().any(|word| NEGATIONS.contains(word));
+        let key: Vec<&str> = words.into_iter()
+            .filter(|word| !NEGATIONS.contains(word) && !AUXILIARIES.contains(word))
+            .collect();
+
+        if key.len() < 3 || key.len() > 12 {
+            return None;
+        }
+        Some((key.join(" "), negated))
+    }
+
     fn merge_reports(&self, main_report: &mut ConsolidationReport, other_report: ConsolidationReport) {
         main_report.memories_processed += other_report.memories_processed;
         main_report.memories_consolidated += other_report.memories_consolidated;
@@ -590,6 +1050,7 @@ impl ConsolidationEngine {
         main_report.memories_deleted += other_report.memories_deleted;
         main_report.new_insights.extend(other_report.new_insights);
         main_report.space_saved_bytes += other_report.space_saved_bytes;
+        main_report.memories_moved += other_report.memories_moved;
     }
 }
 
@@ -684,10 +1145,208 @@ mod tests {
             create_test_memory("Weather is nice today", "agent1"),
         ];
         
-        let memory_refs: Vec<&Memory> = memories.iter().collect();
-        let groups = engine.group_similar_memories(memory_refs).await.unwrap();
+        let groups = engine.group_similar_memories(&memories).await.unwrap();
         
         // Should group similar memories together
         assert!(groups.len() <= memories.len());
     }
-}
\ No newline at end of file
+
+    struct FixedSummarizer;
+
+    #[async_trait]
+    impl MemorySummarizer for FixedSummarizer {
+        async fn summarize(&self, memories: &[Memory]) -> Result<String> {
+            Ok(format!("Summary of {} project notes", memories.len()))
+        }
+    }
+
+    async fn manager_with_long_notes() -> (MemoryManager, Vec<Memory>) {
+        let mut manager = MemoryManager::new(crate::config::AppConfig::default(), None).await.unwrap();
+        manager.set_consolidation_engine(ConsolidationEngine::new().with_batch_size(2));
+        manager.set_summarizer(Arc::new(FixedSummarizer));
+
+        let note = "Project Atlas notes: the deadline moved and the client wants weekly status reports. ".repeat(8);
+        let mut stored = Vec::new();
+        for suffix in ["Owner is Dana.", "Owner is still Dana."] {
+            let memory = create_test_memory(&format!("{}{}", note, suffix), "agent1");
+            stored.push(manager.insert_memory(memory).await.unwrap());
+        }
+        (manager, stored)
+    }
+
+    #[tokio::test]
+    async fn test_summarize_archives_sources_with_provenance() {
+        let (mut manager, stored) = manager_with_long_notes().await;
+
+        let report = manager.consolidate().await.unwrap();
+        assert_eq!(report.memories_consolidated, 2);
+        assert_eq!(report.new_insights.len(), 1);
+        assert!(report.run_id.is_some());
+        assert!(report.space_saved_bytes > 0);
+
+        let summary = &report.new_insights[0];
+        assert_eq!(summary.content, "Summary of 2 project notes");
+        assert_eq!(summary.layer, MemoryLayer::LongTerm);
+        assert_eq!(summary.metadata.source, MemorySource::Consolidation);
+        assert_eq!(summary.metadata.custom_fields[CONSOLIDATED_FROM_FIELD].as_array().unwrap().len(), 2);
+
+        // Sources are archived, not deleted
+        assert_eq!(manager.get_stats().total_memories, 1);
+        for source in &stored {
+            let entry = manager.get_archived(source.id).unwrap();
+            assert_eq!(entry.replaced_by, Some(summary.id));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_undo_consolidation_restores_sources() {
+        let (mut manager, stored) = manager_with_long_notes().await;
+        let associations_before = manager.associations().count();
+
+        let report = manager.consolidate().await.unwrap();
+        let summary_id = report.new_insights[0].id;
+
+        let restored = manager.undo_consolidation(report.run_id.unwrap()).await.unwrap();
+        assert_eq!(restored, 2);
+        assert!(manager.get_memory(summary_id).is_none());
+        assert!(manager.archived_memories().is_empty());
+        assert!(manager.consolidation_runs().is_empty());
+        assert_eq!(manager.associations().count(), associations_before);
+        for source in &stored {
+            assert_eq!(manager.get_memory(source.id).unwrap().content, source.content);
+        }
+
+        // A merge keeps the surviving memory, which must lose its links to the sources
+        let mut manager = MemoryManager::new(crate::config::AppConfig::default(), None).await.unwrap();
+        manager.set_consolidation_engine(ConsolidationEngine::new().with_batch_size(2));
+        let mut stored = Vec::new();
+        for (content, importance) in [("The Atlas deadline moved to Friday", 0.5), ("The Atlas deadline moved to Friday.", 0.4)] {
+            let mut memory = create_test_memory(content, "agent1");
+            memory.importance_score = importance;
+            memory.access_count = 6;
+            stored.push(manager.insert_memory(memory).await.unwrap());
+        }
+        let associations_before = manager.associations().count();
+
+        let report = manager.consolidate().await.unwrap();
+        assert_eq!(manager.archived_memories().len(), 1);
+        assert!(manager.has_association(stored[0].id, stored[1].id, &AssociationType::Supporting));
+
+        assert_eq!(manager.undo_consolidation(report.run_id.unwrap()).await.unwrap(), 1);
+        assert!(!manager.has_association(stored[0].id, stored[1].id, &AssociationType::Supporting));
+        assert_eq!(manager.associations().count(), associations_before);
+    }
+
+    async fn manager_with(statements: &[(&str, Option<&str>)]) -> (MemoryManager, Vec<Memory>) {
+        let mut manager = MemoryManager::new(crate::config::AppConfig::default(), None).await.unwrap();
+        let mut stored = Vec::new();
+        for (index, (content, session)) in statements.iter().enumerate() {
+            let mut memory = create_test_memory(content, "agent1");
+            memory.metadata.session_id = session.map(str::to_string);
+            memory.metadata.topics.clear();
+            memory.created_at = Utc::now() - chrono::Duration::minutes((statements.len() - index) as i64);
+            stored.push(manager.insert_memory(memory).await.unwrap());
+        }
+        (manager, stored)
+    }
+
+    #[test]
+    fn test_fact_slot_extraction() {
+        assert_eq!(
+            ConsolidationEngine::extract_fact_slots("I use vim for everything"),
+            vec![("editor".to_string(), "vim".to_string())]
+        );
+        assert_eq!(
+            ConsolidationEngine::extract_fact_slots("I switched from vim to Helix"),
+            vec![("editor".to_string(), "helix".to_string())]
+        );
+        assert_eq!(
+            ConsolidationEngine::extract_fact_slots("My favorite color is dark green."),
+            vec![("favorite color".to_string(), "dark green".to_string())]
+        );
+        assert!(ConsolidationEngine::extract_fact_slots("I don't use vim").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tool_switch_is_flagged_and_resolved() {
+        let (mut manager, stored) = manager_with(&[
+            ("I use vim for everything", None),
+            ("I switched to Helix last month", None),
+        ]).await;
+
+        let insights = manager.reflect().await.unwrap();
+        let contradiction = insights.iter()
+            .find(|i| i.insight_type == InsightType::Contradiction)
+            .unwrap();
+        assert_eq!(contradiction.supporting_memories, vec![stored[0].id, stored[1].id]);
+        assert!(manager.has_association(stored[0].id, stored[1].id, &AssociationType::Contradictory));
+
+        let pending = manager.pending_contradictions();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].first.metadata.verification_status, VerificationStatus::Disputed);
+        assert_eq!(pending[0].second.metadata.verification_status, VerificationStatus::Disputed);
+
+        manager.resolve_contradiction(pending[0].association_id, ContradictionResolution::KeepSecond).await.unwrap();
+        assert_eq!(manager.get_memory(stored[0].id).unwrap().metadata.verification_status, VerificationStatus::Deprecated);
+        assert_eq!(manager.get_memory(stored[1].id).unwrap().metadata.verification_status, VerificationStatus::Verified);
+        assert!(manager.pending_contradictions().is_empty());
+
+        // Already settled, so reflecting again raises nothing new
+        let again = manager.reflect().await.unwrap();
+        assert!(again.iter().all(|i| i.insight_type != InsightType::Contradiction));
+    }
+
+    #[tokio::test]
+    async fn test_keep_both_removes_contradiction_link() {
+        let (mut manager, stored) = manager_with(&[
+            ("I like coffee in the morning", None),
+            ("I don't like coffee in the morning", None),
+        ]).await;
+
+        manager.reflect().await.unwrap();
+        let pending = manager.pending_contradictions();
+        assert_eq!(pending.len(), 1);
+
+        manager.resolve_contradiction(pending[0].association_id, ContradictionResolution::KeepBoth).await.unwrap();
+        assert!(!manager.has_association(stored[0].id, stored[1].id, &AssociationType::Contradictory));
+        for memory in &stored {
+            assert_eq!(manager.get_memory(memory.id).unwrap().metadata.verification_status, VerificationStatus::Verified);
+        }
+
+        let again = manager.reflect().await.unwrap();
+        assert!(again.iter().all(|i| i.insight_type != InsightType::Contradiction));
+    }
+
+    #[tokio::test]
+    async fn test_causal_link_from_stated_reason() {
+        let (mut manager, stored) = manager_with(&[
+            ("My laptop battery died during the client demo", None),
+            ("The weather is lovely this week", None),
+            ("I ordered a spare charger because my laptop battery died", None),
+        ]).await;
+
+        let insights = manager.reflect().await.unwrap();
+        let relationship = insights.iter()
+            .find(|i| i.insight_type == InsightType::Relationship)
+            .unwrap();
+        assert_eq!(relationship.supporting_memories, vec![stored[0].id, stored[2].id]);
+        assert!(manager.has_association(stored[0].id, stored[2].id, &AssociationType::Causal));
+    }
+
+    #[tokio::test]
+    async fn test_recurring_theme_across_conversations() {
+        let (mut manager, stored) = manager_with(&[
+            ("Deploying the kubernetes cluster failed again", Some("s1")),
+            ("Notes on the kubernetes upgrade", Some("s2")),
+            ("Debugging kubernetes networking with the team", Some("s3")),
+            ("Dinner plans for Friday", Some("s3")),
+        ]).await;
+
+        let insights = manager.reflect().await.unwrap();
+        let theme = insights.iter()
+            .find(|i| i.insight_type == InsightType::Theme && i.content.contains("kubernetes"))
+            .unwrap();
+        assert!(theme.content.contains("3 memories across 3 conversations"));
+        assert!(!theme.supporting_memories.contains(&stored[3].id));
+    }
+}
@@ -0,0 +1,356 @@
+//! Entity extraction for memories: cheap rules for structured values, plus an
+//! optional model pass for people, projects and organizations.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::memory_types::{Entity, EntityType};
+use crate::llm::reasoning::strip_reasoning;
+use crate::services::ollama::{create_generation_options, OllamaClient, OllamaRequest};
+
+/// Model used for entity recognition unless another is configured
+pub const DEFAULT_ENTITY_MODEL: &str = "llama3.1:8b";
+
+/// `EntityType::Other` labels used by the rule-based extractor
+pub const EMAIL_ENTITY: &str = "email";
+pub const URL_ENTITY: &str = "url";
+pub const PATH_ENTITY: &str = "path";
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap()
+});
+
+static URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bhttps?://[^\s<>()"'`]+"#).unwrap()
+});
+
+static PATH: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:^|[\s("'`])((?:~|\.{1,2})?/[\w.-]+(?:/[\w.-]+)+/?|~/[\w.-]+|[A-Za-z]:\\(?:[\w.-]+\\)*[\w.-]+)"#).unwrap()
+});
+
+static DATE: Lazy<Regex> = Lazy::new(|| {
+    const MONTH: &str = r"(?:jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)";
+    const DAY: &str = r"(?:monday|tuesday|wednesday|thursday|friday|saturday|sunday)";
+    Regex::new(&format!(
+        r"(?i)\b(?:\d{{4}}-\d{{2}}-\d{{2}}|\d{{1,2}}/\d{{1,2}}/\d{{2,4}}|{m}\.? \d{{1,2}}(?:st|nd|rd|th)?(?:,? \d{{4}})?|\d{{1,2}}(?:st|nd|rd|th)? (?:of )?{m}(?:,? \d{{4}})?|(?:next|last|this) (?:{d}|week|month|year)|tomorrow|yesterday)\b",
+        m = MONTH,
+        d = DAY
+    ))
+    .unwrap()
+});
+
+static PROJECT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b[Pp]roject ([A-Z][\w-]*(?: [A-Z][\w-]*)*)").unwrap()
+});
+
+static ORGANIZATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b((?:[A-Z][\w&-]*\s)+(?:Inc|Ltd|LLC|GmbH|Corp|Corporation|Labs|Foundation)\b\.?)").unwrap()
+});
+
+/// Finds people, projects and organizations that rules can't
+#[async_trait]
+pub trait EntityRecognizer: Send + Sync {
+    async fn recognize(&self, text: &str) -> Result<Vec<Entity>>;
+}
+
+/// Recognizer backed by a local Ollama model
+pub struct OllamaEntityRecognizer {
+    client: OllamaClient,
+    model: String,
+}
+
+impl OllamaEntityRecognizer {
+    pub fn new(model: &str) -> Self {
+        Self {
+            client: OllamaClient::new(),
+            model: model.to_string(),
+        }
+    }
+}
+
+impl Default for OllamaEntityRecognizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_ENTITY_MODEL)
+    }
+}
+
+#[derive(Deserialize)]
+struct RecognizedEntity {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[async_trait]
+impl EntityRecognizer for OllamaEntityRecognizer {
+    async fn recognize(&self, text: &str) -> Result<Vec<Entity>> {
+        let prompt = format!(
+            "List the people, projects, organizations, places, products and technologies named in the text below. \
+             Reply with a JSON array only, like [{{\"name\": \"Alice\", \"type\": \"person\"}}]. \
+             Use the types person, project, organization, place, product, technology or event. \
+             Reply with [] if there are none.\n\nText: {}\n\nJSON:",
+            text.trim()
+        );
+
+        let request = OllamaRequest {
+            model: self.model.clone(),
+            prompt,
+            stream: false,
+            options: Some(create_generation_options(Some(0.0), Some(300))),
+        };
+
+        let response = self.client.generate(request).await
+            .map_err(|e| anyhow!("Entity recognition failed: {}", e))?;
+
+        parse_recognized_entities(&strip_reasoning(&response.response), text)
+    }
+}
+
+/// Parse a model's JSON reply, dropping names that don't occur in `text`
+fn parse_recognized_entities(reply: &str, text: &str) -> Result<Vec<Entity>> {
+    let (start, end) = reply.find('[').zip(reply.rfind(']'))
+        .filter(|(start, end)| start < end)
+        .ok_or_else(|| anyhow!("Entity recognizer did not return a JSON array"))?;
+    let recognized: Vec<RecognizedEntity> = serde_json::from_str(&reply[start..=end])?;
+
+    let text_lower = text.to_lowercase();
+    Ok(recognized
+        .into_iter()
+        .filter(|entity| !entity.name.trim().is_empty() && text_lower.contains(&entity.name.trim().to_lowercase()))
+        .map(|entity| {
+            let entity_type = match entity.kind.trim().to_lowercase().as_str() {
+                "person" => EntityType::Person,
+                "project" => EntityType::Project,
+                "organization" | "organisation" | "company" => EntityType::Organization,
+                "place" | "location" => EntityType::Place,
+                "product" => EntityType::Product,
+                "technology" => EntityType::Technology,
+                "event" => EntityType::Event,
+                other => EntityType::Other(other.to_string()),
+            };
+            let name = entity.name.trim().to_string();
+            Entity {
+                name: name.clone(),
+                entity_type,
+                confidence: 0.75,
+                mentions: vec![name],
+            }
+        })
+        .collect())
+}
+
+/// Combines rule-based extraction with an optional recognizer
+#[derive(Default)]
+pub struct EntityExtractor {
+    recognizer: Option<Arc<dyn EntityRecognizer>>,
+}
+
+impl EntityExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_recognizer(&mut self, recognizer: Arc<dyn EntityRecognizer>) {
+        self.recognizer = Some(recognizer);
+    }
+
+    /// Extract entities with rules and, if configured, the recognizer
+    ///
+    /// A failing recognizer is logged and the rule-based entities are still returned.
+    pub async fn extract(&self, text: &str) -> Vec<Entity> {
+        let mut entities = Self::extract_with_rules(text);
+
+        if let Some(ref recognizer) = self.recognizer {
+            match recognizer.recognize(text).await {
+                Ok(recognized) => entities.extend(recognized),
+                Err(e) => log::warn!("Model entity extraction failed, using rules only: {}", e),
+            }
+        }
+
+        merge_entities(entities)
+    }
+
+    /// Dates, emails, URLs, file paths, "Project X" and "Acme Inc" style names
+    pub fn extract_with_rules(text: &str) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        let mut push = |name: &str, entity_type: EntityType, confidence: f32| {
+            entities.push(Entity {
+                name: name.to_string(),
+                entity_type,
+                confidence,
+                mentions: vec![name.to_string()],
+            });
+        };
+
+        let urls: Vec<(usize, usize)> = URL.find_iter(text)
+            .map(|m| (m.start(), m.start() + m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']).len()))
+            .collect();
+        for &(start, end) in &urls {
+            push(&text[start..end], EntityType::Other(URL_ENTITY.to_string()), 0.95);
+        }
+        let in_url = |start: usize| urls.iter().any(|&(s, e)| start >= s && start < e);
+
+        for m in EMAIL.find_iter(text).filter(|m| !in_url(m.start())) {
+            push(m.as_str(), EntityType::Other(EMAIL_ENTITY.to_string()), 0.95);
+        }
+
+        for captures in PATH.captures_iter(text) {
+            let m = captures.get(1).unwrap();
+            if !in_url(m.start()) {
+                push(m.as_str().trim_end_matches('.'), EntityType::Other(PATH_ENTITY.to_string()), 0.85);
+            }
+        }
+
+        for m in DATE.find_iter(text).filter(|m| !in_url(m.start())) {
+            push(m.as_str(), EntityType::Date, 0.9);
+        }
+
+        for captures in PROJECT.captures_iter(text) {
+            push(&captures[1], EntityType::Project, 0.8);
+        }
+
+        for captures in ORGANIZATION.captures_iter(text) {
+            push(captures[1].trim_end_matches('.'), EntityType::Organization, 0.7);
+        }
+
+        merge_entities(entities)
+    }
+}
+
+/// Collapse entities that canonicalize to the same thing, keeping every mention
+pub fn merge_entities(entities: Vec<Entity>) -> Vec<Entity> {
+    let mut merged: Vec<Entity> = Vec::new();
+    for entity in entities {
+        let key = canonical_name(&entity.name, &entity.entity_type);
+        match merged.iter_mut().find(|existing| {
+            existing.entity_type == entity.entity_type && canonical_name(&existing.name, &existing.entity_type) == key
+        }) {
+            Some(existing) => {
+                existing.confidence = existing.confidence.max(entity.confidence);
+                for mention in entity.mentions {
+                    if !existing.mentions.contains(&mention) {
+                        existing.mentions.push(mention);
+                    }
+                }
+            }
+            None => merged.push(entity),
+        }
+    }
+    merged
+}
+
+/// Normalized form used to recognize the same entity across memories
+///
+/// Paths keep their case; everything else is case-insensitive. Projects lose a
+/// leading "project" and organizations their legal suffix, so "Project Atlas" and
+/// "Atlas", or "Acme Inc." and "Acme", are the same node.
+pub fn canonical_name(name: &str, entity_type: &EntityType) -> String {
+    let collapsed = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed.trim_matches(|c: char| matches!(c, '.' | ',' | ';' | ':' | '!' | '?' | '"' | '\''));
+
+    if *entity_type == EntityType::Other(PATH_ENTITY.to_string()) {
+        return trimmed.trim_end_matches(['/', '\\']).to_string();
+    }
+
+    let mut canonical = trimmed.to_lowercase();
+    if *entity_type == EntityType::Other(URL_ENTITY.to_string()) {
+        return canonical.trim_end_matches('/').to_string();
+    }
+
+    if let Some(rest) = canonical.strip_prefix("the ") {
+        canonical = rest.to_string();
+    }
+
+    match entity_type {
+        EntityType::Project => {
+            if let Some(rest) = canonical.strip_prefix("project ") {
+                canonical = rest.to_string();
+            }
+        }
+        EntityType::Organization => {
+            for suffix in [" inc", " ltd", " llc", " gmbh", " corp", " corporation", " co"] {
+                if let Some(rest) = canonical.trim_end_matches('.').trim_end_matches(',').strip_suffix(suffix) {
+                    canonical = rest.trim_end_matches(',').to_string();
+                    break;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names_of(entities: &[Entity], entity_type: EntityType) -> Vec<String> {
+        entities.iter()
+            .filter(|entity| entity.entity_type == entity_type)
+            .map(|entity| entity.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_rule_extraction() {
+        let text = "Email alice@example.com the report in ~/work/reports/q3.md before 2024-05-01, \
+                    see https://example.com/docs/setup. Also check /etc/hosts next Friday.";
+        let entities = EntityExtractor::extract_with_rules(text);
+
+        assert_eq!(names_of(&entities, EntityType::Other(EMAIL_ENTITY.to_string())), vec!["alice@example.com"]);
+        assert_eq!(names_of(&entities, EntityType::Other(URL_ENTITY.to_string())), vec!["https://example.com/docs/setup"]);
+        assert_eq!(names_of(&entities, EntityType::Other(PATH_ENTITY.to_string())), vec!["~/work/reports/q3.md", "/etc/hosts"]);
+        assert_eq!(names_of(&entities, EntityType::Date), vec!["2024-05-01", "next Friday"]);
+    }
+
+    #[test]
+    fn test_project_and_organization_rules() {
+        let entities = EntityExtractor::extract_with_rules("Project Atlas ships for Acme Inc. on March 3rd");
+
+        assert_eq!(names_of(&entities, EntityType::Project), vec!["Atlas"]);
+        assert_eq!(names_of(&entities, EntityType::Organization), vec!["Acme Inc"]);
+        assert_eq!(names_of(&entities, EntityType::Date), vec!["March 3rd"]);
+    }
+
+    #[test]
+    fn test_canonical_names() {
+        assert_eq!(canonical_name("Project  Atlas", &EntityType::Project), "atlas");
+        assert_eq!(canonical_name("Acme, Inc.", &EntityType::Organization), "acme");
+        assert_eq!(canonical_name("The Acme Corp", &EntityType::Organization), "acme");
+        assert_eq!(canonical_name("~/Notes/", &EntityType::Other(PATH_ENTITY.to_string())), "~/Notes");
+        assert_eq!(canonical_name("Alice", &EntityType::Person), "alice");
+    }
+
+    #[test]
+    fn test_parse_recognized_entities_drops_hallucinations() {
+        let reply = r#"Sure! [{"name": "Alice", "type": "person"}, {"name": "Atlas", "type": "project"}, {"name": "Bob", "type": "person"}]"#;
+        let entities = parse_recognized_entities(reply, "Alice is leading Atlas").unwrap();
+
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0].entity_type, EntityType::Person);
+        assert_eq!(entities[1].entity_type, EntityType::Project);
+    }
+
+    struct FailingRecognizer;
+
+    #[async_trait]
+    impl EntityRecognizer for FailingRecognizer {
+        async fn recognize(&self, _text: &str) -> Result<Vec<Entity>> {
+            Err(anyhow!("model unavailable"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recognizer_failure_falls_back_to_rules() {
+        let mut extractor = EntityExtractor::new();
+        extractor.set_recognizer(Arc::new(FailingRecognizer));
+
+        let entities = extractor.extract("Ping bob@example.com tomorrow").await;
+        assert_eq!(entities.len(), 2);
+    }
+}
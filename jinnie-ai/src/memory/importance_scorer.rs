@@ -1,9 +1,58 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use regex::Regex;
 
 use super::memory_types::{Memory, MemorySource, MemoryLayer, Entity, EntityType};
 
+/// Weight for entity types without their own entry, including every `EntityType::Other`
+const OTHER_ENTITY_WEIGHT: f32 = 0.3;
+
+/// Rating implied by pinning a memory
+pub const PINNED_RATING: f32 = 0.9;
+
+/// Rating implied by forgetting a memory
+pub const FORGOTTEN_RATING: f32 = 0.1;
+
+/// Rating implied by a recalled memory being drawn on in a reply; below the threshold
+/// for learning new keywords, so everyday use only reinforces known ones
+pub const USED_RATING: f32 = 0.65;
+
+/// Share of a memory's distinctive words a reply must repeat for the memory to count as used
+const USED_WORD_SHARE: f32 = 0.5;
+
+/// Something the user did that says how much a memory matters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ImportanceFeedback {
+    /// Explicit rating from 0.0 (irrelevant) to 1.0 (essential)
+    Rating(f32),
+    Pinned,
+    Forgotten,
+    /// Recalled for a reply and then drawn on in it
+    Used,
+}
+
+impl ImportanceFeedback {
+    pub fn rating(&self) -> f32 {
+        match self {
+            Self::Rating(rating) => rating.clamp(0.0, 1.0),
+            Self::Pinned => PINNED_RATING,
+            Self::Forgotten => FORGOTTEN_RATING,
+            Self::Used => USED_RATING,
+        }
+    }
+}
+
+/// Keyword weights learned from feedback, stored per user
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LearnedWeights {
+    /// Keywords whose weight differs from the built-in defaults, including new ones
+    pub keywords: HashMap<String, f32>,
+    pub feedback_count: u32,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
 /// System for calculating memory importance scores
 pub struct ImportanceScorer {
     /// Keywords that indicate high importance
@@ -12,6 +61,10 @@ pub struct ImportanceScorer {
     importance_patterns: Vec<ImportancePattern>,
     /// Entity type weights
     entity_weights: HashMap<EntityType, f32>,
+    /// Keyword weights before any learning, to tell what feedback changed
+    default_keywords: HashMap<String, f32>,
+    feedback_count: u32,
+    last_feedback: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -143,20 +196,54 @@ impl ImportanceScorer {
         entity_weights.insert(EntityType::Person, 0.7);
         entity_weights.insert(EntityType::Place, 0.5);
         entity_weights.insert(EntityType::Organization, 0.6);
+        entity_weights.insert(EntityType::Project, 0.6);
         entity_weights.insert(EntityType::Date, 0.8);
         entity_weights.insert(EntityType::Event, 0.7);
         entity_weights.insert(EntityType::Concept, 0.5);
         entity_weights.insert(EntityType::Product, 0.4);
         entity_weights.insert(EntityType::Technology, 0.5);
-        entity_weights.insert(EntityType::Other(_), 0.3);
 
         Self {
+            default_keywords: importance_keywords.clone(),
             importance_keywords,
             importance_patterns,
             entity_weights,
+            feedback_count: 0,
+            last_feedback: None,
+        }
+    }
+
+    /// Scorer with previously learned keyword weights applied on top of the defaults
+    pub fn with_learned_weights(mut self, learned: LearnedWeights) -> Self {
+        self.importance_keywords.extend(learned.keywords);
+        self.feedback_count = learned.feedback_count;
+        self.last_feedback = learned.updated_at;
+        self
+    }
+
+    /// What feedback has changed so far, for persisting
+    pub fn learned_weights(&self) -> LearnedWeights {
+        let keywords = self.importance_keywords
+            .iter()
+            .filter(|(keyword, weight)| {
+                self.default_keywords.get(*keyword).map_or(true, |default| (*default - **weight).abs() > f32::EPSILON)
+            })
+            .map(|(keyword, weight)| (keyword.clone(), *weight))
+            .collect();
+        LearnedWeights {
+            keywords,
+            feedback_count: self.feedback_count,
+            updated_at: self.last_feedback,
         }
     }
 
+    /// Learn from one piece of feedback, returning the memory's new importance
+    pub fn record_feedback(&mut self, memory: &Memory, feedback: ImportanceFeedback) -> f32 {
+        self.feedback_count += 1;
+        self.last_feedback = Some(Utc::now());
+        self.update_importance_with_feedback(memory, feedback.rating())
+    }
+
     /// Calculate importance score for a memory (0.0 to 1.0)
     pub async fn calculate_importance(&self, memory: &Memory) -> Result<f32> {
         let mut score = 0.0;
@@ -212,6 +299,32 @@ impl ImportanceScorer {
         new_score.clamp(0.0, 1.0)
     }
 
+    /// Trends across `memories` together with what feedback has taught the scorer
+    pub fn importance_report(&self, memories: &[Memory]) -> ImportanceReport {
+        let mut strengthened = Vec::new();
+        let mut weakened = Vec::new();
+        let mut learned_keywords = Vec::new();
+        for (keyword, weight) in self.learned_weights().keywords {
+            match self.default_keywords.get(&keyword) {
+                Some(default) if weight > *default => strengthened.push((keyword, weight - default)),
+                Some(default) => weakened.push((keyword, weight - default)),
+                None => learned_keywords.push((keyword, weight)),
+            }
+        }
+        strengthened.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        weakened.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        learned_keywords.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        ImportanceReport {
+            trends: self.analyze_importance_trends(memories),
+            feedback_count: self.feedback_count,
+            last_feedback: self.last_feedback,
+            strengthened,
+            weakened,
+            learned_keywords,
+        }
+    }
+
     /// Analyze importance trends for an agent
     pub fn analyze_importance_trends(&self, memories: &[Memory]) -> ImportanceTrends {
         let mut trends = ImportanceTrends {
@@ -259,6 +372,19 @@ impl ImportanceScorer {
             source_scores.push(memory.importance_score);
         }
 
+        // Average importance per day the memories were made
+        let mut by_day: BTreeMap<chrono::NaiveDate, Vec<f32>> = BTreeMap::new();
+        for memory in memories {
+            by_day.entry(memory.created_at.date_naive()).or_default().push(memory.importance_score);
+        }
+        trends.importance_over_time = by_day
+            .into_iter()
+            .filter_map(|(day, scores)| {
+                let start = day.and_hms_opt(0, 0, 0)?.and_utc();
+                Some((start, scores.iter().sum::<f32>() / scores.len() as f32))
+            })
+            .collect();
+
         trends
     }
 
@@ -323,9 +449,11 @@ impl ImportanceScorer {
         let mut entity_score = 0.0;
         
         for entity in entities {
-            if let Some(&weight) = self.entity_weights.get(&entity.entity_type) {
-                entity_score += weight * entity.confidence;
-            }
+            let weight = self.entity_weights
+                .get(&entity.entity_type)
+                .copied()
+                .unwrap_or(OTHER_ENTITY_WEIGHT);
+            entity_score += weight * entity.confidence;
         }
         
         // Normalize by number of entities to prevent bias
@@ -392,8 +520,40 @@ impl ImportanceScorer {
     }
 }
 
+/// Whether a reply drew on a recalled memory: it repeats at least half of the memory's
+/// distinctive words
+pub fn memory_was_used(memory_content: &str, reply: &str) -> bool {
+    let words = |text: &str| -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.len() > 3)
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let memory_words = words(memory_content);
+    if memory_words.is_empty() {
+        return false;
+    }
+    let reply_words = words(reply);
+    let repeated = memory_words.intersection(&reply_words).count();
+    repeated as f32 / memory_words.len() as f32 >= USED_WORD_SHARE
+}
+
+/// What the assistant has learned to care about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportanceReport {
+    pub trends: ImportanceTrends,
+    pub feedback_count: u32,
+    pub last_feedback: Option<DateTime<Utc>>,
+    /// Built-in keywords feedback raised, by how much, largest first
+    pub strengthened: Vec<(String, f32)>,
+    /// Built-in keywords feedback lowered, by how much, largest drop first
+    pub weakened: Vec<(String, f32)>,
+    /// Keywords picked up from highly rated memories, with their weight
+    pub learned_keywords: Vec<(String, f32)>,
+}
+
 /// Analysis of importance trends for an agent
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportanceTrends {
     pub average_importance: f32,
     pub high_importance_ratio: f32,
@@ -506,5 +666,34 @@ mod tests {
         let trends = scorer.analyze_importance_trends(&memories);
         assert!(trends.average_importance > 0.5);
         assert!(trends.high_importance_ratio > 0.0);
+        assert_eq!(trends.importance_over_time.len(), 1);
+    }
+
+    #[test]
+    fn test_learned_weights_roundtrip() {
+        let mut scorer = ImportanceScorer::new();
+        scorer.record_feedback(&create_test_memory("The deadline for the grant"), ImportanceFeedback::Pinned);
+        scorer.record_feedback(&create_test_memory("Meeting notes"), ImportanceFeedback::Forgotten);
+
+        let learned = scorer.learned_weights();
+        assert_eq!(learned.feedback_count, 2);
+        assert!((learned.keywords["deadline"] - 0.84).abs() < 1e-6);
+        assert!((learned.keywords["meeting"] - 0.56).abs() < 1e-6);
+        assert_eq!(learned.keywords["grant"], 0.3);
+        assert!(!learned.keywords.contains_key("urgent"));
+
+        let restored = ImportanceScorer::new().with_learned_weights(learned.clone());
+        assert_eq!(restored.learned_weights(), learned);
+
+        let report = restored.importance_report(&[]);
+        assert_eq!(report.strengthened[0].0, "deadline");
+        assert_eq!(report.weakened[0].0, "meeting");
+        assert!(report.learned_keywords.iter().any(|(keyword, _)| keyword == "grant"));
+    }
+
+    #[test]
+    fn test_memory_use_detection() {
+        assert!(memory_was_used("Ana lives in Lisbon", "Since Ana is in Lisbon, try the pastelaria near her."));
+        assert!(!memory_was_used("Ana lives in Lisbon", "You could try the new bakery downtown."));
     }
 }
\ No newline at end of file
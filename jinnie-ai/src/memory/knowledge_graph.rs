@@ -0,0 +1,424 @@
+//! Personal knowledge graph: entities canonicalized across memories, linked by
+//! the memories that mention them together.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::entities::canonical_name;
+use super::memory_types::{Entity, EntityType, Memory};
+
+static WORKS_AT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:works|worked|is working|works full-time) (?:at|for)$|^(?:joined|is at)$").unwrap()
+});
+
+static WORKS_ON: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:works|worked|is working|is) on(?: the)?$").unwrap()
+});
+
+static LEADS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:leads|is leading|manages|runs|owns|heads)(?: the)?$").unwrap()
+});
+
+/// An entity and every memory that mentions it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityNode {
+    pub id: Uuid,
+    pub name: String,
+    pub entity_type: EntityType,
+    /// Other spellings seen for this entity
+    pub aliases: Vec<String>,
+    pub memories: Vec<Uuid>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum RelationKind {
+    /// Named in the same memory
+    MentionedWith,
+    /// Person works at an organization
+    WorksAt,
+    /// Person works on a project
+    WorksOn,
+    /// Leads, manages or owns the target
+    Leads,
+}
+
+/// Edge between two entities, backed by the memories it was seen in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityRelation {
+    pub source: Uuid,
+    pub target: Uuid,
+    pub kind: RelationKind,
+    pub memories: Vec<Uuid>,
+}
+
+impl EntityRelation {
+    /// Number of memories supporting the relation
+    pub fn weight(&self) -> usize {
+        self.memories.len()
+    }
+}
+
+/// Answer to "who/what is related to X"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedEntity {
+    pub entity: EntityNode,
+    pub relation: RelationKind,
+    /// Whether `entity` is the target of the relation (e.g. the organization in WorksAt)
+    pub outgoing: bool,
+    pub memories: Vec<Uuid>,
+}
+
+/// Graph of entity nodes and relations built from memory metadata
+#[derive(Debug, Clone, Default)]
+pub struct KnowledgeGraph {
+    nodes: HashMap<Uuid, EntityNode>,
+    index: HashMap<(EntityType, String), Uuid>,
+    relations: HashMap<(Uuid, Uuid, RelationKind), EntityRelation>,
+}
+
+impl KnowledgeGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_memories<'a>(memories: impl IntoIterator<Item = &'a Memory>) -> Self {
+        let mut graph = Self::new();
+        for memory in memories {
+            graph.add_memory(memory);
+        }
+        graph
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn relation_count(&self) -> usize {
+        self.relations.len()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &EntityNode> {
+        self.nodes.values()
+    }
+
+    pub fn relations(&self) -> impl Iterator<Item = &EntityRelation> {
+        self.relations.values()
+    }
+
+    /// Add a memory's entities and the relations between them
+    ///
+    /// Dates are left out; they relate to everything and say little about it.
+    pub fn add_memory(&mut self, memory: &Memory) {
+        let entities: Vec<&Entity> = memory.metadata.entities.iter()
+            .filter(|entity| entity.entity_type != EntityType::Date)
+            .collect();
+
+        let mut mentioned: Vec<(Uuid, &Entity)> = Vec::new();
+        for entity in entities {
+            let id = self.resolve_node(entity, memory);
+            if !mentioned.iter().any(|(existing, _)| *existing == id) {
+                mentioned.push((id, entity));
+            }
+        }
+
+        let content = memory.content.to_lowercase();
+        for (i, (a, entity_a)) in mentioned.iter().enumerate() {
+            for (b, entity_b) in &mentioned[i + 1..] {
+                let (source, target, kind) = match Self::relation_between(&content, entity_a, entity_b) {
+                    Some(kind) => (*a, *b, kind),
+                    None => match Self::relation_between(&content, entity_b, entity_a) {
+                        Some(kind) => (*b, *a, kind),
+                        None => (*a.min(b), *a.max(b), RelationKind::MentionedWith),
+                    },
+                };
+
+                let relation = self.relations
+                    .entry((source, target, kind))
+                    .or_insert_with(|| EntityRelation { source, target, kind, memories: Vec::new() });
+                if !relation.memories.contains(&memory.id) {
+                    relation.memories.push(memory.id);
+                }
+            }
+        }
+    }
+
+    /// Drop a memory's contribution, removing nodes and relations it alone supported
+    pub fn remove_memory(&mut self, memory_id: Uuid) {
+        self.relations.retain(|_, relation| {
+            relation.memories.retain(|id| *id != memory_id);
+            !relation.memories.is_empty()
+        });
+
+        let mut emptied = Vec::new();
+        for node in self.nodes.values_mut() {
+            node.memories.retain(|id| *id != memory_id);
+            if node.memories.is_empty() {
+                emptied.push(node.id);
+            }
+        }
+        for id in emptied {
+            self.nodes.remove(&id);
+            self.index.retain(|_, node_id| *node_id != id);
+        }
+    }
+
+    /// Find an entity by name or alias; the best-attested match wins
+    pub fn find(&self, name: &str) -> Option<&EntityNode> {
+        let lowered = name.trim().to_lowercase();
+        self.nodes.values()
+            .filter(|node| {
+                self.index.get(&(node.entity_type.clone(), canonical_name(name, &node.entity_type))) == Some(&node.id)
+                    || node.aliases.iter().any(|alias| alias.to_lowercase() == lowered)
+            })
+            .max_by_key(|node| node.memories.len())
+    }
+
+    /// Memories mentioning the entity, e.g. "everything about Project Atlas"
+    pub fn memories_about(&self, name: &str) -> Vec<Uuid> {
+        self.find(name).map(|node| node.memories.clone()).unwrap_or_default()
+    }
+
+    /// Entities linked to `name`, optionally only of one type, strongest first
+    pub fn related(&self, name: &str, entity_type: Option<&EntityType>) -> Vec<RelatedEntity> {
+        let Some(node) = self.find(name) else { return Vec::new() };
+
+        let mut related: Vec<RelatedEntity> = self.relations.values()
+            .filter_map(|relation| {
+                let (other, outgoing) = if relation.source == node.id {
+                    (relation.target, true)
+                } else if relation.target == node.id {
+                    (relation.source, false)
+                } else {
+                    return None;
+                };
+                let entity = self.nodes.get(&other)?;
+                if entity_type.is_some_and(|wanted| *wanted != entity.entity_type) {
+                    return None;
+                }
+                Some(RelatedEntity {
+                    entity: entity.clone(),
+                    relation: relation.kind,
+                    outgoing: outgoing && relation.kind != RelationKind::MentionedWith,
+                    memories: relation.memories.clone(),
+                })
+            })
+            .collect();
+
+        related.sort_by(|a, b| {
+            b.memories.len().cmp(&a.memories.len())
+                .then_with(|| (a.relation == RelationKind::MentionedWith).cmp(&(b.relation == RelationKind::MentionedWith)))
+                .then_with(|| a.entity.name.cmp(&b.entity.name))
+        });
+        related
+    }
+
+    /// Node for `entity`, creating or merging as needed
+    ///
+    /// A lone first name joins the one person whose full name starts with it, and a
+    /// full name absorbs a node that only had the first name.
+    fn resolve_node(&mut self, entity: &Entity, memory: &Memory) -> Uuid {
+        let key = (entity.entity_type.clone(), canonical_name(&entity.name, &entity.entity_type));
+
+        let id = match self.index.get(&key) {
+            Some(id) => *id,
+            None => {
+                let alias_of = (entity.entity_type == EntityType::Person)
+                    .then(|| self.person_alias(&key.1))
+                    .flatten();
+                match alias_of {
+                    Some(id) => {
+                        self.index.insert(key.clone(), id);
+                        id
+                    }
+                    None => {
+                        let id = Uuid::new_v4();
+                        self.nodes.insert(id, EntityNode {
+                            id,
+                            name: entity.name.clone(),
+                            entity_type: entity.entity_type.clone(),
+                            aliases: Vec::new(),
+                            memories: Vec::new(),
+                            first_seen: memory.created_at,
+                            last_seen: memory.created_at,
+                        });
+                        self.index.insert(key, id);
+                        id
+                    }
+                }
+            }
+        };
+
+        let node = self.nodes.get_mut(&id).unwrap();
+        // Prefer the fuller spelling as the display name
+        if entity.name.split_whitespace().count() > node.name.split_whitespace().count() {
+            let previous = std::mem::replace(&mut node.name, entity.name.clone());
+            node.aliases.push(previous);
+        }
+        for spelling in std::iter::once(&entity.name).chain(entity.mentions.iter()) {
+            if *spelling != node.name && !node.aliases.contains(spelling) {
+                node.aliases.push(spelling.clone());
+            }
+        }
+        if !node.memories.contains(&memory.id) {
+            node.memories.push(memory.id);
+        }
+        node.first_seen = node.first_seen.min(memory.created_at);
+        node.last_seen = node.last_seen.max(memory.created_at);
+        id
+    }
+
+    /// The single existing person node that `canonical` is a shorter or longer form of
+    fn person_alias(&self, canonical: &str) -> Option<Uuid> {
+        let first_name = canonical.split(' ').next().unwrap_or_default();
+        let is_full_name = canonical.contains(' ');
+
+        let mut candidates = self.index.iter()
+            .filter(|((entity_type, name), _)| {
+                *entity_type == EntityType::Person
+                    && if is_full_name {
+                        name == first_name
+                    } else {
+                        name.split(' ').next() == Some(first_name) && name.contains(' ')
+                    }
+            })
+            .map(|(_, id)| *id)
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+
+        match candidates.as_slice() {
+            [id] => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Typed relation when the text reads "<from> works at <to>" and similar
+    fn relation_between(content: &str, from: &Entity, to: &Entity) -> Option<RelationKind> {
+        let from_end = Self::mention_span(content, from)?.1;
+        let to_start = Self::mention_span(content, to)?.0;
+        if to_start < from_end {
+            return None;
+        }
+
+        let between = content[from_end..to_start].trim();
+        let between = between.strip_suffix(" project").unwrap_or(between);
+        match (&from.entity_type, &to.entity_type) {
+            (EntityType::Person, EntityType::Organization) if WORKS_AT.is_match(between) => Some(RelationKind::WorksAt),
+            (EntityType::Person, EntityType::Project) if WORKS_ON.is_match(between) => Some(RelationKind::WorksOn),
+            (EntityType::Person, _) if LEADS.is_match(between) => Some(RelationKind::Leads),
+            _ => None,
+        }
+    }
+
+    fn mention_span(content: &str, entity: &Entity) -> Option<(usize, usize)> {
+        std::iter::once(&entity.name)
+            .chain(entity.mentions.iter())
+            .filter_map(|mention| {
+                let mention = mention.to_lowercase();
+                content.find(&mention).map(|start| (start, start + mention.len()))
+            })
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::memory_types::*;
+
+    fn entity(name: &str, entity_type: EntityType) -> Entity {
+        Entity {
+            name: name.to_string(),
+            entity_type,
+            confidence: 0.8,
+            mentions: vec![name.to_string()],
+        }
+    }
+
+    fn memory_with(content: &str, entities: Vec<Entity>) -> Memory {
+        let metadata = MemoryMetadata {
+            source: MemorySource::UserInput,
+            agent_id: "agent1".to_string(),
+            conversation_id: None,
+            session_id: None,
+            topics: vec![],
+            entities,
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Unverified,
+            custom_fields: std::collections::HashMap::new(),
+        };
+        Memory::new(content.to_string(), MemoryLayer::Working, metadata)
+    }
+
+    fn sample_graph() -> (KnowledgeGraph, Vec<Memory>) {
+        let memories = vec![
+            memory_with(
+                "Alice Chen works at Acme Inc",
+                vec![entity("Alice Chen", EntityType::Person), entity("Acme Inc", EntityType::Organization)],
+            ),
+            memory_with(
+                "Alice is leading Project Atlas with Bob",
+                vec![entity("Alice", EntityType::Person), entity("Atlas", EntityType::Project), entity("Bob", EntityType::Person)],
+            ),
+            memory_with(
+                "Atlas demo moved to 2024-06-01",
+                vec![entity("Atlas", EntityType::Project), entity("2024-06-01", EntityType::Date)],
+            ),
+        ];
+        (KnowledgeGraph::from_memories(&memories), memories)
+    }
+
+    #[test]
+    fn test_entities_are_canonicalized_across_memories() {
+        let (graph, memories) = sample_graph();
+
+        // Alice/Alice Chen and the two Atlas mentions collapse; dates are skipped
+        assert_eq!(graph.node_count(), 4);
+        let alice = graph.find("alice").unwrap();
+        assert_eq!(alice.name, "Alice Chen");
+        assert_eq!(alice.memories, vec![memories[0].id, memories[1].id]);
+        assert_eq!(graph.find("Acme").unwrap().name, "Acme Inc");
+    }
+
+    #[test]
+    fn test_everything_about_project() {
+        let (graph, memories) = sample_graph();
+
+        assert_eq!(graph.memories_about("Project Atlas"), vec![memories[1].id, memories[2].id]);
+        assert!(graph.memories_about("Project Zeus").is_empty());
+    }
+
+    #[test]
+    fn test_who_is_related_to_alice() {
+        let (graph, _) = sample_graph();
+
+        let people = graph.related("Alice", Some(&EntityType::Person));
+        assert_eq!(people.len(), 1);
+        assert_eq!(people[0].entity.name, "Bob");
+        assert_eq!(people[0].relation, RelationKind::MentionedWith);
+
+        let all = graph.related("Alice", None);
+        let employer = all.iter().find(|r| r.entity.name == "Acme Inc").unwrap();
+        assert_eq!(employer.relation, RelationKind::WorksAt);
+        assert!(employer.outgoing);
+        let project = all.iter().find(|r| r.entity.name == "Atlas").unwrap();
+        assert_eq!(project.relation, RelationKind::Leads);
+    }
+
+    #[test]
+    fn test_remove_memory_drops_unsupported_nodes() {
+        let (mut graph, memories) = sample_graph();
+
+        graph.remove_memory(memories[0].id);
+        assert!(graph.find("Acme").is_none());
+        assert_eq!(graph.find("Alice").unwrap().memories, vec![memories[1].id]);
+        assert!(graph.related("Alice", Some(&EntityType::Organization)).is_empty());
+    }
+}
@@ -0,0 +1,472 @@
+//! Background upkeep for the memory hierarchy: per-agent consolidation and
+//! pruning, reflection, and importance decay on the configured cadence.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::memory_manager::MemoryManager;
+use super::memory_types::{ConsolidationReport, MemoryLayer, PruningReport, PruningStrategy};
+use crate::config::app_config::MemoryConfig;
+
+/// Number of maintenance reports kept in history
+pub const MAINTENANCE_HISTORY_LIMIT: usize = 50;
+
+/// What started a maintenance run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum MaintenanceTrigger {
+    Scheduled,
+    Manual,
+}
+
+/// Work done for one agent during a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentMaintenance {
+    pub agent_id: String,
+    /// `None` when the agent was under the consolidation threshold
+    pub consolidation: Option<ConsolidationReport>,
+    pub pruning: Option<PruningReport>,
+}
+
+/// Outcome of one maintenance run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub id: Uuid,
+    pub trigger: MaintenanceTrigger,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub agents: Vec<AgentMaintenance>,
+    pub insights_generated: usize,
+    pub memories_decayed: usize,
+    /// Set when resource pressure stopped the run before it finished
+    pub skipped_reason: Option<String>,
+    pub cancelled: bool,
+    pub errors: Vec<String>,
+}
+
+impl MaintenanceReport {
+    fn new(trigger: MaintenanceTrigger) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            trigger,
+            started_at: now,
+            finished_at: now,
+            agents: Vec::new(),
+            insights_generated: 0,
+            memories_decayed: 0,
+            skipped_reason: None,
+            cancelled: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Whether every step ran without errors, cancellation or skipping
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty() && !self.cancelled && self.skipped_reason.is_none()
+    }
+}
+
+/// What a maintenance run does and how often
+#[derive(Debug, Clone)]
+pub struct MaintenancePolicy {
+    pub interval: Duration,
+    /// Working and short-term memories an agent needs before it is consolidated
+    pub consolidation_threshold: usize,
+    /// Importance kept per idle day, e.g. 0.95
+    pub decay_rate: f32,
+    pub pruning_strategy: PruningStrategy,
+    pub reflect: bool,
+}
+
+impl MaintenancePolicy {
+    pub fn from_config(config: &MemoryConfig) -> Self {
+        Self {
+            interval: Duration::from_secs(config.consolidation_interval.max(1)),
+            consolidation_threshold: config.consolidation_threshold,
+            decay_rate: config.importance_decay_rate,
+            pruning_strategy: PruningStrategy::LRU,
+            reflect: true,
+        }
+    }
+}
+
+/// Reports whether the machine is too busy for background work
+#[async_trait]
+pub trait PressureCheck: Send + Sync {
+    /// Reason to hold off, or `None` when there is room to run
+    async fn pressure(&self) -> Option<String>;
+}
+
+#[cfg(feature = "system")]
+#[async_trait]
+impl PressureCheck for crate::platform::ResourceMonitor {
+    async fn pressure(&self) -> Option<String> {
+        use crate::platform::PerformanceLevel;
+
+        let metrics = self.get_current_metrics().await;
+        if metrics.memory_total_mb == 0 {
+            // No sample yet
+            return None;
+        }
+        match metrics.performance_level {
+            PerformanceLevel::Poor | PerformanceLevel::Critical => Some(format!(
+                "system under load ({:.0}% CPU, {} MB free)",
+                metrics.cpu_usage_percent, metrics.memory_available_mb
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Pressure check backed by a running resource monitor, if the `system` feature is on
+pub async fn system_pressure_check() -> Option<Arc<dyn PressureCheck>> {
+    #[cfg(feature = "system")]
+    {
+        let monitor = crate::platform::ResourceMonitor::new();
+        match monitor.start_monitoring().await {
+            Ok(()) => return Some(Arc::new(monitor)),
+            Err(e) => log::warn!("Memory maintenance will run without resource checks: {}", e),
+        }
+    }
+    None
+}
+
+struct SchedulerInner {
+    manager: Arc<Mutex<MemoryManager>>,
+    policy: MaintenancePolicy,
+    pressure: std::sync::RwLock<Option<Arc<dyn PressureCheck>>>,
+    history: Mutex<VecDeque<MaintenanceReport>>,
+    /// Held for the length of a run so scheduled and manual runs never overlap
+    run_lock: Mutex<()>,
+    last_decay: Mutex<Option<DateTime<Utc>>>,
+    cancel: watch::Sender<bool>,
+    task: std::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Runs memory maintenance in the background and on demand
+#[derive(Clone)]
+pub struct MaintenanceScheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+impl MaintenanceScheduler {
+    pub fn new(manager: Arc<Mutex<MemoryManager>>, policy: MaintenancePolicy) -> Self {
+        let (cancel, _) = watch::channel(false);
+        Self {
+            inner: Arc::new(SchedulerInner {
+                manager,
+                policy,
+                pressure: std::sync::RwLock::new(None),
+                history: Mutex::new(VecDeque::new()),
+                run_lock: Mutex::new(()),
+                last_decay: Mutex::new(None),
+                cancel,
+                task: std::sync::Mutex::new(None),
+            }),
+        }
+    }
+
+    pub fn policy(&self) -> &MaintenancePolicy {
+        &self.inner.policy
+    }
+
+    /// Defer runs while `check` reports the system is busy
+    pub fn set_pressure_check(&self, check: Arc<dyn PressureCheck>) {
+        *self.inner.pressure.write().unwrap() = Some(check);
+    }
+
+    /// Start the periodic task; does nothing if it is already running
+    pub fn start(&self) {
+        let mut task = self.inner.task.lock().unwrap();
+        if task.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return;
+        }
+
+        self.inner.cancel.send_replace(false);
+        let mut cancelled = self.inner.cancel.subscribe();
+        let scheduler = self.clone();
+        let interval = self.inner.policy.interval;
+
+        log::info!("Memory maintenance every {}s", interval.as_secs());
+        *task = Some(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = cancelled.changed() => {}
+                }
+                if *cancelled.borrow() {
+                    break;
+                }
+
+                let report = scheduler.run(MaintenanceTrigger::Scheduled).await;
+                log::debug!("Scheduled memory maintenance finished: {} agents, {} errors", report.agents.len(), report.errors.len());
+            }
+            log::info!("Memory maintenance stopped");
+        }));
+    }
+
+    /// Stop the periodic task, cancelling any run in progress at its next step
+    pub async fn stop(&self) {
+        self.inner.cancel.send_replace(true);
+        let handle = self.inner.task.lock().unwrap().take();
+        if let Some(handle) = handle {
+            let _ = handle.await;
+        }
+        // Let manual runs proceed again
+        self.inner.cancel.send_replace(false);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.inner.task.lock().unwrap().as_ref().is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Run maintenance now, waiting for any run already in progress first
+    pub async fn run_now(&self) -> MaintenanceReport {
+        self.run(MaintenanceTrigger::Manual).await
+    }
+
+    /// Past reports, most recent first
+    pub async fn history(&self) -> Vec<MaintenanceReport> {
+        self.inner.history.lock().await.iter().rev().cloned().collect()
+    }
+
+    pub async fn last_report(&self) -> Option<MaintenanceReport> {
+        self.inner.history.lock().await.back().cloned()
+    }
+
+    async fn run(&self, trigger: MaintenanceTrigger) -> MaintenanceReport {
+        let _running = self.inner.run_lock.lock().await;
+        let policy = &self.inner.policy;
+        let mut report = MaintenanceReport::new(trigger);
+
+        let agents = self.inner.manager.lock().await.agent_ids();
+        let agents = match agents {
+            Ok(agents) => agents,
+            Err(e) => {
+                report.errors.push(format!("Listing agents failed: {}", e));
+                Vec::new()
+            }
+        };
+
+        for agent_id in agents {
+            if self.should_stop(&mut report).await {
+                break;
+            }
+
+            // The manager lock is released between agents so chat isn't held up
+            let mut manager = self.inner.manager.lock().await;
+            let mut work = AgentMaintenance {
+                agent_id: agent_id.clone(),
+                consolidation: None,
+                pruning: None,
+            };
+
+            let pending = match manager.agent_memory_count(&agent_id, &[MemoryLayer::Working, MemoryLayer::ShortTerm]) {
+                Ok(pending) => pending,
+                Err(e) => {
+                    report.errors.push(format!("Counting {}'s memories failed: {}", agent_id, e));
+                    0
+                }
+            };
+            if pending >= policy.consolidation_threshold {
+                match manager.consolidate_agent(&agent_id).await {
+                    Ok(consolidation) => work.consolidation = Some(consolidation),
+                    Err(e) => report.errors.push(format!("Consolidating {} failed: {}", agent_id, e)),
+                }
+            }
+
+            match manager.prune_agent(&agent_id, policy.pruning_strategy.clone()).await {
+                Ok(pruning) => work.pruning = Some(pruning),
+                Err(e) => report.errors.push(format!("Pruning {} failed: {}", agent_id, e)),
+            }
+
+            report.agents.push(work);
+        }
+
+        if policy.reflect && !self.should_stop(&mut report).await {
+            let mut manager = self.inner.manager.lock().await;
+            match manager.reflect().await {
+                Ok(insights) => report.insights_generated = insights.len(),
+                Err(e) => report.errors.push(format!("Reflection failed: {}", e)),
+            }
+        }
+
+        if !self.should_stop(&mut report).await {
+            let mut last_decay = self.inner.last_decay.lock().await;
+            let now = Utc::now();
+            let elapsed = last_decay
+                .map(|at| now.signed_duration_since(at))
+                .unwrap_or_else(|| chrono::Duration::from_std(policy.interval).unwrap_or_else(|_| chrono::Duration::hours(1)));
+
+            let mut manager = self.inner.manager.lock().await;
+            match manager.apply_importance_decay(policy.decay_rate, elapsed) {
+                Ok(decayed) => {
+                    report.memories_decayed = decayed;
+                    *last_decay = Some(now);
+                }
+                Err(e) => report.errors.push(format!("Importance decay failed: {}", e)),
+            }
+        }
+
+        report.finished_at = Utc::now();
+        for error in &report.errors {
+            log::warn!("Memory maintenance: {}", error);
+        }
+
+        let mut history = self.inner.history.lock().await;
+        history.push_back(report.clone());
+        while history.len() > MAINTENANCE_HISTORY_LIMIT {
+            history.pop_front();
+        }
+
+        report
+    }
+
+    /// Record cancellation or resource pressure on `report`; true if the run should end
+    async fn should_stop(&self, report: &mut MaintenanceReport) -> bool {
+        if report.cancelled || report.skipped_reason.is_some() {
+            return true;
+        }
+        if *self.inner.cancel.borrow() {
+            report.cancelled = true;
+            return true;
+        }
+        let pressure = self.inner.pressure.read().unwrap().clone();
+        if let Some(pressure) = pressure {
+            if let Some(reason) = pressure.pressure().await {
+                log::info!("Deferring memory maintenance: {}", reason);
+                report.skipped_reason = Some(reason);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::memory::memory_types::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn metadata(agent_id: &str) -> MemoryMetadata {
+        MemoryMetadata {
+            source: MemorySource::UserInput,
+            agent_id: agent_id.to_string(),
+            conversation_id: None,
+            session_id: None,
+            topics: vec![],
+            entities: vec![],
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Unverified,
+            custom_fields: HashMap::new(),
+        }
+    }
+
+    fn policy(interval: Duration) -> MaintenancePolicy {
+        MaintenancePolicy {
+            interval,
+            consolidation_threshold: 1,
+            decay_rate: 0.5,
+            pruning_strategy: PruningStrategy::LRU,
+            reflect: false,
+        }
+    }
+
+    async fn manager_with_stale_memory() -> (Arc<Mutex<MemoryManager>>, Memory, Memory) {
+        let mut manager = MemoryManager::new(AppConfig::default(), None).await.unwrap();
+
+        let mut stale = Memory::new("Old scratch note".to_string(), MemoryLayer::ShortTerm, metadata("agent1"));
+        stale.importance_score = 0.2;
+        stale.last_accessed = Utc::now() - chrono::Duration::days(60);
+        let stale = manager.insert_memory(stale).await.unwrap();
+
+        let mut fresh = Memory::new("Yesterday's plan".to_string(), MemoryLayer::ShortTerm, metadata("agent2"));
+        fresh.importance_score = 0.4;
+        fresh.last_accessed = Utc::now() - chrono::Duration::days(1);
+        let fresh = manager.insert_memory(fresh).await.unwrap();
+
+        (Arc::new(Mutex::new(manager)), stale, fresh)
+    }
+
+    #[tokio::test]
+    async fn test_run_now_prunes_decays_and_records_history() {
+        let (manager, stale, fresh) = manager_with_stale_memory().await;
+        let scheduler = MaintenanceScheduler::new(manager.clone(), policy(Duration::from_secs(86_400)));
+
+        let report = scheduler.run_now().await;
+        assert!(report.is_complete());
+        assert_eq!(report.trigger, MaintenanceTrigger::Manual);
+        assert_eq!(report.agents.len(), 2);
+        let agent1 = report.agents.iter().find(|a| a.agent_id == "agent1").unwrap();
+        assert_eq!(agent1.pruning.as_ref().unwrap().memories_removed, 1);
+        assert!(agent1.consolidation.is_some());
+        assert_eq!(report.memories_decayed, 1);
+
+        let manager = manager.lock().await;
+        assert!(manager.get_memory(stale.id).is_none());
+        assert!(manager.archived_memories().iter().any(|entry| entry.memory.id == stale.id));
+        // About one idle day at half retention
+        let decayed = manager.get_memory(fresh.id).unwrap().importance_score;
+        assert!((decayed - 0.2).abs() < 0.01);
+        drop(manager);
+
+        assert_eq!(scheduler.history().await.len(), 1);
+        assert_eq!(scheduler.last_report().await.unwrap().id, report.id);
+    }
+
+    struct Busy(AtomicBool);
+
+    #[async_trait]
+    impl PressureCheck for Busy {
+        async fn pressure(&self) -> Option<String> {
+            self.0.load(Ordering::SeqCst).then(|| "busy".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resource_pressure_defers_work() {
+        let (manager, stale, _) = manager_with_stale_memory().await;
+        let busy = Arc::new(Busy(AtomicBool::new(true)));
+        let scheduler = MaintenanceScheduler::new(manager.clone(), policy(Duration::from_secs(86_400)));
+        scheduler.set_pressure_check(busy.clone());
+
+        let report = scheduler.run_now().await;
+        assert_eq!(report.skipped_reason.as_deref(), Some("busy"));
+        assert!(report.agents.is_empty());
+        assert!(manager.lock().await.get_memory(stale.id).is_some());
+
+        busy.0.store(false, Ordering::SeqCst);
+        assert!(scheduler.run_now().await.is_complete());
+        assert!(manager.lock().await.get_memory(stale.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_background_task_runs_until_stopped() {
+        let (manager, _, _) = manager_with_stale_memory().await;
+        let scheduler = MaintenanceScheduler::new(manager, policy(Duration::from_millis(20)));
+
+        scheduler.start();
+        assert!(scheduler.is_running());
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        scheduler.stop().await;
+        assert!(!scheduler.is_running());
+
+        let runs = scheduler.history().await.len();
+        assert!(runs >= 1);
+        assert!(scheduler.history().await.iter().all(|r| r.trigger == MaintenanceTrigger::Scheduled));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(scheduler.history().await.len(), runs);
+    }
+}
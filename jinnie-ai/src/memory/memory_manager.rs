@@ -1,15 +1,42 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
 use super::memory_types::*;
-use super::importance_scorer::ImportanceScorer;
-use super::consolidation::ConsolidationEngine;
-use super::retrieval::MemoryRetrieval;
-use crate::vector::VectorStore;
+use super::importance_scorer::{ImportanceFeedback, ImportanceReport, ImportanceScorer};
+use super::consolidation::{ConsolidationEngine, MemorySummarizer};
+use super::entities::{merge_entities, EntityExtractor};
+use super::knowledge_graph::{KnowledgeGraph, RelatedEntity};
+use crate::privacy::{RedactionReport, Redactor};
+use super::retrieval::{AssociativeResult, MemoryRetrieval, SearchResult};
+use super::verification::{self, VerificationAnswer, VerificationRequest, VerificationSubject};
+use super::persistence::{MemoryStore, MemoryWrite};
+use crate::vector::{CollectionSchema, VectorPoint, VectorStore};
 use crate::config::AppConfig;
 
+/// Layers pruning and decay apply to; long-term, semantic and reflective memories are kept
+const PRUNABLE_LAYERS: [MemoryLayer; 3] = [MemoryLayer::Working, MemoryLayer::ShortTerm, MemoryLayer::Episodic];
+
+/// Days without access after which LRU pruning drops an unimportant memory
+const PRUNE_IDLE_DAYS: i64 = 30;
+
+/// Memories at least this important survive LRU, age and frequency pruning
+const PRUNE_KEEP_IMPORTANCE: f32 = 0.5;
+
+/// Importance below which `LowestImportance` pruning drops a memory
+const PRUNE_IMPORTANCE_FLOOR: f32 = 0.1;
+
+/// Never-accessed memories younger than this survive `LowFrequency` pruning
+const LOW_FREQUENCY_MIN_AGE_DAYS: i64 = 7;
+
+/// `memory_strength` below which `CustomScore` pruning drops a memory
+const PRUNE_STRENGTH_FLOOR: f32 = 0.15;
+
+/// Points per vector store write when indexing memories that were missing from it
+const INDEX_BATCH_SIZE: usize = 256;
+
 /// Core memory management system implementing MemGPT-style hierarchical memory
 pub struct MemoryManager {
     /// Memory storage organized by layer
@@ -23,7 +50,25 @@ pub struct MemoryManager {
     /// Memory retrieval system
     retrieval_engine: MemoryRetrieval,
     /// Vector store for semantic search
-    vector_store: Option<VectorStore>,
+    vector_store: Option<Arc<VectorStore>>,
+    /// Durable storage; `None` keeps everything in memory only
+    store: Option<Arc<MemoryStore>>,
+    /// Layers whose memories have been read from `store`
+    loaded_layers: HashSet<MemoryLayer>,
+    /// Row counts of layers that have not been loaded yet
+    persisted_counts: HashMap<MemoryLayer, usize>,
+    /// Insights produced by reflection
+    insights: Vec<Insight>,
+    /// Memories taken out of the active layers by consolidation
+    archive: HashMap<Uuid, ArchivedMemory>,
+    /// Journals of consolidation runs that can still be undone
+    consolidation_runs: Vec<ConsolidationRun>,
+    /// Entity graph over all layers, built on first query
+    knowledge_graph: Option<KnowledgeGraph>,
+    /// Personal data redaction applied before anything is stored
+    redactor: Redactor,
+    /// Everything redacted since startup
+    redaction_report: RedactionReport,
     /// Configuration
     config: AppConfig,
     /// Statistics
@@ -40,20 +85,22 @@ pub struct MemoryStats {
 }
 
 impl MemoryManager {
-    pub async fn new(config: AppConfig, vector_store: Option<VectorStore>) -> Result<Self> {
+    pub async fn new(config: AppConfig, vector_store: Option<Arc<VectorStore>>) -> Result<Self> {
         let importance_scorer = ImportanceScorer::new();
         let consolidation_engine = ConsolidationEngine::new();
-        let retrieval_engine = MemoryRetrieval::new();
+        let mut retrieval_engine = MemoryRetrieval::new()
+            .with_scopes(config.memory.scopes.clone())
+            .with_spreading(config.memory.spreading)
+            .with_ranking(config.memory.ranking)
+            .with_hybrid(config.vector.hybrid);
+        if config.memory.ranking.cross_encoder {
+            if let Some(ref store) = vector_store {
+                retrieval_engine = retrieval_engine.with_cross_encoder(store.clone());
+            }
+        }
 
         let mut memories_by_layer = HashMap::new();
-        for layer in [
-            MemoryLayer::Working,
-            MemoryLayer::ShortTerm,
-            MemoryLayer::LongTerm,
-            MemoryLayer::Episodic,
-            MemoryLayer::Semantic,
-            MemoryLayer::Reflective,
-        ] {
+        for layer in MemoryLayer::ALL {
             memories_by_layer.insert(layer, HashMap::new());
         }
 
@@ -64,6 +111,15 @@ impl MemoryManager {
             consolidation_engine,
             retrieval_engine,
             vector_store,
+            store: None,
+            loaded_layers: MemoryLayer::ALL.into_iter().collect(),
+            persisted_counts: HashMap::new(),
+            insights: Vec::new(),
+            archive: HashMap::new(),
+            consolidation_runs: Vec::new(),
+            knowledge_graph: None,
+            redactor: Redactor::from_config(&config.privacy),
+            redaction_report: RedactionReport::default(),
             config,
             stats: MemoryStats {
                 total_memories: 0,
@@ -75,17 +131,90 @@ impl MemoryManager {
         })
     }
 
-    /// Store a new memory
+    /// Create a manager backed by `store`; layers are read lazily on first use
+    pub async fn with_store(config: AppConfig, vector_store: Option<Arc<VectorStore>>, store: MemoryStore) -> Result<Self> {
+        let mut manager = Self::new(config, vector_store).await?;
+
+        for association in store.load_associations()? {
+            manager.associations.insert(association.id, association);
+        }
+        manager.insights = store.load_insights()?;
+        for entry in store.load_archive()? {
+            manager.archive.insert(entry.memory.id, entry);
+        }
+        manager.consolidation_runs = store.load_consolidation_runs()?;
+        if let Some(learned) = store.load_learned_weights(&manager.config.memory.user_id)? {
+            manager.importance_scorer = ImportanceScorer::new().with_learned_weights(learned);
+        }
+        manager.persisted_counts = store.layer_counts()?;
+        manager.loaded_layers.clear();
+        manager.store = Some(Arc::new(store));
+
+        manager.update_stats();
+        Ok(manager)
+    }
+
+    /// Whether changes are written to durable storage
+    pub fn is_persistent(&self) -> bool {
+        self.store.is_some()
+    }
+
+    /// Read `layer` from storage if it has not been loaded yet
+    pub fn ensure_layer_loaded(&mut self, layer: MemoryLayer) -> Result<()> {
+        if self.loaded_layers.contains(&layer) {
+            return Ok(());
+        }
+
+        if let Some(ref store) = self.store {
+            let memories = store.load_layer(layer)?;
+            log::debug!("Loaded {} {} memories from storage", memories.len(), layer.as_str());
+
+            let layer_memories = self.memories_by_layer.entry(layer).or_default();
+            for memory in memories {
+                // Anything already in memory is newer than what is on disk
+                layer_memories.entry(memory.id).or_insert(memory);
+            }
+        }
+
+        self.loaded_layers.insert(layer);
+        self.persisted_counts.remove(&layer);
+        Ok(())
+    }
+
+    /// Read every layer from storage
+    pub fn load_all_layers(&mut self) -> Result<()> {
+        for layer in MemoryLayer::ALL {
+            self.ensure_layer_loaded(layer)?;
+        }
+        Ok(())
+    }
+
+    /// Store a new memory, private to the agent in `metadata`
     pub async fn store(&mut self, content: String, metadata: MemoryMetadata) -> Result<Memory> {
+        self.store_in_scope(content, metadata, MemoryScope::Agent).await
+    }
+
+    /// Store a new memory in `scope`, if the agent in `metadata` may write there
+    pub async fn store_in_scope(&mut self, content: String, metadata: MemoryMetadata, scope: MemoryScope) -> Result<Memory> {
+        if !self.retrieval_engine.scopes().can_write(&metadata.agent_id, &scope) {
+            return Err(anyhow::anyhow!("Agent {} may not write to the {} scope", metadata.agent_id, scope.as_key()));
+        }
+        self.ensure_layer_loaded(MemoryLayer::Working)?;
+
+        // Redact personal data before it reaches entities, embeddings or disk
+        let mut metadata = metadata;
+        let content = self.redact(content, &mut metadata)?;
+
         // Create the memory
         let mut memory = Memory::new(content.clone(), MemoryLayer::Working, metadata.clone());
+        memory.scope = scope;
+        
+        // Extract entities and topics; importance depends on them
+        self.extract_metadata(&mut memory).await?;
         
         // Calculate importance score
         memory.importance_score = self.importance_scorer.calculate_importance(&memory).await?;
         
-        // Extract entities and topics
-        self.extract_metadata(&mut memory).await?;
-        
         // Generate embedding if vector store is available
         if let Some(ref vector_store) = self.vector_store {
             memory.embedding = Some(vector_store.generate_embedding(&content).await?);
@@ -96,11 +225,15 @@ impl MemoryManager {
         memory.layer = target_layer;
         
         // Store the memory
+        self.ensure_layer_loaded(target_layer)?;
         let memory_id = memory.id;
+        self.persist(vec![MemoryWrite::UpsertMemory(memory.clone())])?;
         self.memories_by_layer
             .get_mut(&target_layer)
             .unwrap()
             .insert(memory_id, memory.clone());
+        self.graph_add(&memory);
+        self.index_memory(&memory).await;
         
         // Update statistics
         self.update_stats();
@@ -114,20 +247,103 @@ impl MemoryManager {
         Ok(memory)
     }
 
-    /// Retrieve memories based on query
-    pub async fn retrieve(&self, query: MemoryQuery) -> Result<Vec<Memory>> {
+    /// Retrieve memories based on query, recording an access on each result
+    pub async fn retrieve(&mut self, query: MemoryQuery) -> Result<Vec<Memory>> {
+        self.load_query_layers(&query)?;
+        let results = self.retrieval_engine.search(self, query).await?;
+        self.record_access(results.iter().map(|memory| memory.id))?;
+        Ok(results)
+    }
+
+    /// Look memories up for display without counting it as an access
+    pub async fn browse(&mut self, query: MemoryQuery) -> Result<Vec<Memory>> {
+        self.load_query_layers(&query)?;
         self.retrieval_engine.search(self, query).await
     }
 
+    /// Retrieve memories with relevance scores, recording an access on each result
+    pub async fn search_with_scores(&mut self, query: MemoryQuery) -> Result<Vec<SearchResult>> {
+        self.load_query_layers(&query)?;
+        let limit = query.limit.unwrap_or(10);
+        let mut results = self.retrieval_engine.search_with_scores(self, query).await?;
+        results.truncate(limit);
+        self.record_access(results.iter().map(|result| result.memory.id))?;
+        Ok(results)
+    }
+
+    /// Retrieve the best matches plus memories linked to them, each with the path that led there
+    pub async fn search_associative(&mut self, query: MemoryQuery) -> Result<Vec<AssociativeResult>> {
+        self.load_all_layers()?;
+        let results = self.retrieval_engine.search_associative(self, query).await?;
+        self.record_access(results.iter().map(|result| result.memory.id))?;
+        Ok(results)
+    }
+
+    /// The vector store memories are embedded with and indexed in, if any
+    pub(crate) fn vector_store(&self) -> Option<&Arc<VectorStore>> {
+        self.vector_store.as_ref()
+    }
+
+    /// Embed and index memories missing from the vector store, such as ones stored before
+    /// it was set up or dropped when the embedding model changed; returns how many were added
+    pub async fn index_missing_memories(&mut self) -> Result<usize> {
+        let Some(vector_store) = self.vector_store.clone() else { return Ok(0) };
+        self.load_all_layers()?;
+
+        let collection = CollectionSchema::memory_collection().name;
+        let missing: Vec<Memory> = self.memories_by_layer
+            .values()
+            .flat_map(|memories| memories.values())
+            .filter(|memory| memory.embedding.is_none() || !vector_store.is_indexed(&collection, memory.id))
+            .cloned()
+            .collect();
+
+        let mut embedded = Vec::new();
+        let mut points = Vec::with_capacity(missing.len());
+        for mut memory in missing.iter().cloned() {
+            // A stored embedding may come from a model the index no longer uses
+            let embedding = vector_store.generate_embedding(&memory.content).await?;
+            if memory.embedding.as_ref() != Some(&embedding) {
+                memory.embedding = Some(embedding.clone());
+                self.memories_by_layer.get_mut(&memory.layer).unwrap().insert(memory.id, memory.clone());
+                embedded.push(MemoryWrite::UpsertMemory(memory.clone()));
+            }
+            points.push(VectorPoint { id: memory.id, vector: embedding, payload: memory_payload(&memory) });
+        }
+        if !embedded.is_empty() {
+            self.persist(embedded)?;
+        }
+
+        // Upserted in batches: one store write per batch rather than per memory
+        while !points.is_empty() {
+            let batch: Vec<VectorPoint> = points.drain(..points.len().min(INDEX_BATCH_SIZE)).collect();
+            vector_store.upsert_points(&collection, batch).await?;
+        }
+        if !missing.is_empty() {
+            log::info!("Indexed {} memories in the vector store", missing.len());
+        }
+        Ok(missing.len())
+    }
+
+    /// Embed text with the same model used for stored memories, if one is configured
+    pub async fn embed_text(&self, text: &str) -> Result<Option<Vec<f32>>> {
+        match self.vector_store {
+            Some(ref vector_store) => Ok(Some(vector_store.generate_embedding(text).await?)),
+            None => Ok(None),
+        }
+    }
+
     /// Update an existing memory
     pub async fn update(&mut self, id: Uuid, updates: MemoryUpdate) -> Result<Memory> {
         // Find the memory
-        let (layer, mut memory) = self.find_memory_mut(id)
+        let layer = self.locate(id)?
             .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id))?;
+        let mut memory = self.memories_by_layer[&layer][&id].clone();
         
         // Apply updates
         if let Some(content) = updates.content {
-            memory.content = content;
+            memory.content = self.redact(content, &mut memory.metadata)?;
+            memory.embedding = self.embed_text(&memory.content).await?;
             // Recalculate importance if content changed
             memory.importance_score = self.importance_scorer.calculate_importance(&memory).await?;
         }
@@ -149,47 +365,187 @@ impl MemoryManager {
                 memory.metadata.custom_fields.insert(key, value);
             }
         }
-        
-        let updated_memory = memory.clone();
+
+        if let Some(pinned) = updates.pinned {
+            memory.pinned = pinned;
+        }
         
         // Check if memory should move to a different layer
         let new_layer = self.determine_target_layer(&memory);
+        memory.layer = new_layer;
+        self.persist(vec![MemoryWrite::UpsertMemory(memory.clone())])?;
+
+        self.memories_by_layer.get_mut(&layer).unwrap().insert(id, memory.clone());
+        self.graph_remove(id);
+        self.graph_add(&memory);
         if new_layer != layer {
             self.move_memory_to_layer(id, layer, new_layer)?;
         }
+        self.index_memory(&memory).await;
         
         self.update_stats();
-        Ok(updated_memory)
+        Ok(memory)
     }
 
     /// Delete a memory
     pub async fn forget(&mut self, id: Uuid) -> Result<()> {
         // Find and remove the memory
-        let layer = self.find_memory_layer(id)
+        let layer = self.locate(id)?
             .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id))?;
         
+        self.persist(vec![MemoryWrite::DeleteMemory(id)])?;
         self.memories_by_layer
             .get_mut(&layer)
             .unwrap()
             .remove(&id);
+        self.graph_remove(id);
         
         // Remove associated associations
         self.associations.retain(|_, assoc| {
             assoc.memory_a != id && assoc.memory_b != id
         });
         
-        // Remove from vector store if available
-        if let Some(ref vector_store) = self.vector_store {
-            vector_store.delete_embedding(id).await?;
-        }
+        self.unindex_memories(&[id]).await;
         
         self.update_stats();
         Ok(())
     }
 
-    /// Consolidate memories to manage capacity and create insights
+    /// What has been redacted from stored memories since startup
+    pub fn redaction_report(&self) -> &RedactionReport {
+        &self.redaction_report
+    }
+
+    /// Redact `content`, noting what was removed in the memory's metadata
+    fn redact(&mut self, content: String, metadata: &mut MemoryMetadata) -> Result<String> {
+        let redacted = self.redactor.redact(&content);
+        if !redacted.report.is_empty() {
+            log::debug!("Redacted from memory: {}", redacted.report.summary());
+            metadata.custom_fields.insert("redactions".to_string(), serde_json::to_value(&redacted.report)?);
+            self.redaction_report.merge(&redacted.report);
+        }
+        Ok(redacted.text)
+    }
+
+    /// Delete several memories; ids that no longer exist are skipped
+    pub async fn forget_many(&mut self, ids: &[Uuid]) -> Result<usize> {
+        let mut forgotten = 0;
+        for id in ids {
+            if self.locate(*id)?.is_some() {
+                self.forget(*id).await?;
+                forgotten += 1;
+            }
+        }
+        Ok(forgotten)
+    }
+
+    /// Pin or unpin memories, returning how many changed
+    pub fn set_pinned(&mut self, ids: &[Uuid], pinned: bool) -> Result<usize> {
+        let mut writes = Vec::new();
+        for id in ids {
+            let Some(layer) = self.locate(*id)? else { continue };
+            let memory = self.memories_by_layer.get_mut(&layer).unwrap().get_mut(id).unwrap();
+            if memory.pinned != pinned {
+                memory.pinned = pinned;
+                writes.push(MemoryWrite::UpsertMemory(memory.clone()));
+            }
+        }
+
+        let changed = writes.len();
+        self.persist(writes)?;
+        Ok(changed)
+    }
+
+    /// Learn from what the user did with a memory and persist what was learned.
+    ///
+    /// The memory's importance is updated unless it is being forgotten. Returns `None` if
+    /// the memory doesn't exist.
+    pub fn record_feedback(&mut self, id: Uuid, feedback: ImportanceFeedback) -> Result<Option<Memory>> {
+        let Some(layer) = self.locate(id)? else { return Ok(None) };
+        let mut memory = self.memories_by_layer[&layer][&id].clone();
+        let importance = self.importance_scorer.record_feedback(&memory, feedback);
+
+        let mut writes = vec![MemoryWrite::UpsertLearnedWeights {
+            user_id: self.config.memory.user_id.clone(),
+            weights: self.importance_scorer.learned_weights(),
+        }];
+        if feedback != ImportanceFeedback::Forgotten {
+            memory.importance_score = importance;
+            self.memories_by_layer.get_mut(&layer).unwrap().insert(id, memory.clone());
+            writes.push(MemoryWrite::UpsertMemory(memory.clone()));
+        }
+        self.persist(writes)?;
+
+        self.update_stats();
+        Ok(Some(memory))
+    }
+
+    /// Importance trends over the memories `agent_id` can see (all memories without one),
+    /// and what feedback has taught the scorer
+    pub fn importance_report(&mut self, agent_id: Option<&str>) -> Result<ImportanceReport> {
+        self.load_all_layers()?;
+        let scopes = self.retrieval_engine.scopes();
+        let memories: Vec<Memory> = self.memories_by_layer
+            .values()
+            .flat_map(|memories| memories.values())
+            .filter(|memory| agent_id.map_or(true, |agent_id| scopes.can_read(agent_id, memory)))
+            .cloned()
+            .collect();
+        Ok(self.importance_scorer.importance_report(&memories))
+    }
+
+    /// Every association between memories
+    pub fn associations(&self) -> impl Iterator<Item = &MemoryAssociation> {
+        self.associations.values()
+    }
+
+    /// Associations touching a memory
+    pub fn associations_of(&self, id: Uuid) -> Vec<MemoryAssociation> {
+        self.associations
+            .values()
+            .filter(|association| association.memory_a == id || association.memory_b == id)
+            .cloned()
+            .collect()
+    }
+
+    /// Use a model to summarize memory groups during consolidation
+    pub fn set_summarizer(&mut self, summarizer: Arc<dyn MemorySummarizer>) {
+        self.consolidation_engine.set_summarizer(summarizer);
+    }
+
+    /// Replace the consolidation engine (e.g. to tune its batch size)
+    pub fn set_consolidation_engine(&mut self, engine: ConsolidationEngine) {
+        self.consolidation_engine = engine;
+    }
+
+    /// Consolidate memories to manage capacity; the returned `run_id` can be undone
     pub async fn consolidate(&mut self) -> Result<ConsolidationReport> {
-        let report = self.consolidation_engine.consolidate_memories(self).await?;
+        self.run_consolidation(None).await
+    }
+
+    /// Consolidate one agent's memories, leaving other agents untouched
+    pub async fn consolidate_agent(&mut self, agent_id: &str) -> Result<ConsolidationReport> {
+        self.run_consolidation(Some(agent_id)).await
+    }
+
+    async fn run_consolidation(&mut self, agent_id: Option<&str>) -> Result<ConsolidationReport> {
+        self.load_all_layers()?;
+        let engine = std::mem::take(&mut self.consolidation_engine);
+        let mut run = ConsolidationRun::new();
+        let report = engine.consolidate_memories(self, &mut run, agent_id).await;
+        self.consolidation_engine = engine;
+
+        // Kept even when the run failed part way, so what it did change can be undone
+        let run_id = if run.is_empty() {
+            None
+        } else {
+            self.persist(vec![MemoryWrite::UpsertConsolidationRun(run.clone())])?;
+            let id = run.id;
+            self.consolidation_runs.push(run);
+            Some(id)
+        };
+        let mut report = report?;
+        report.run_id = run_id;
         
         self.stats.last_consolidation = Some(Utc::now());
         self.update_stats();
@@ -197,13 +553,466 @@ impl MemoryManager {
         Ok(report)
     }
 
+    /// Revert a consolidation run: summaries are removed and their sources restored.
+    ///
+    /// Returns the number of memories brought back from the archive. Memories that later
+    /// runs or edits have already removed are skipped.
+    pub async fn undo_consolidation(&mut self, run_id: Uuid) -> Result<usize> {
+        let index = self.consolidation_runs.iter()
+            .position(|run| run.id == run_id)
+            .ok_or_else(|| anyhow::anyhow!("Consolidation run not found: {}", run_id))?;
+        let run = self.consolidation_runs[index].clone();
+        self.load_all_layers()?;
+
+        // Undo in reverse order of how the run applied its changes
+        for (id, from) in run.moved.iter().rev() {
+            if self.find_memory_layer(*id).is_some() {
+                self.move_to_layer(*id, *from).await?;
+            }
+        }
+
+        for previous in &run.previous_versions {
+            if self.find_memory_layer(previous.id).is_some() {
+                self.replace_memory(previous.clone()).await?;
+            }
+        }
+
+        for id in &run.created {
+            if self.find_memory_layer(*id).is_some() {
+                self.forget(*id).await?;
+            }
+        }
+
+        // Merged memories survive the undo, so their links to the sources must go explicitly
+        let links: Vec<Uuid> = run.associations.iter()
+            .copied()
+            .filter(|id| self.associations.contains_key(id))
+            .collect();
+        if !links.is_empty() {
+            self.persist(links.iter().map(|id| MemoryWrite::DeleteAssociation(*id)).collect())?;
+            for id in links {
+                self.associations.remove(&id);
+            }
+        }
+
+        let mut restored = 0;
+        for id in &run.archived {
+            if self.archive.get(id).map(|entry| entry.run_id == Some(run_id)).unwrap_or(false) {
+                self.restore_archived(*id).await?;
+                restored += 1;
+            }
+        }
+
+        self.persist(vec![MemoryWrite::DeleteConsolidationRun(run_id)])?;
+        self.consolidation_runs.remove(index);
+        self.update_stats();
+        Ok(restored)
+    }
+
+    /// Consolidation runs that can still be undone, oldest first
+    pub fn consolidation_runs(&self) -> &[ConsolidationRun] {
+        &self.consolidation_runs
+    }
+
+    /// Add a fully-formed memory (such as a consolidation summary) to its layer
+    pub async fn insert_memory(&mut self, mut memory: Memory) -> Result<Memory> {
+        if memory.embedding.is_none() {
+            memory.embedding = self.embed_text(&memory.content).await?;
+        }
+
+        let layer = memory.layer;
+        self.ensure_layer_loaded(layer)?;
+        self.persist(vec![MemoryWrite::UpsertMemory(memory.clone())])?;
+        self.memories_by_layer.get_mut(&layer).unwrap().insert(memory.id, memory.clone());
+        self.graph_add(&memory);
+        self.index_memory(&memory).await;
+
+        self.update_stats();
+        Ok(memory)
+    }
+
+    /// Overwrite an existing memory as given, moving it if its layer changed
+    pub async fn replace_memory(&mut self, memory: Memory) -> Result<()> {
+        let current = self.locate(memory.id)?
+            .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", memory.id))?;
+        self.ensure_layer_loaded(memory.layer)?;
+        self.persist(vec![MemoryWrite::UpsertMemory(memory.clone())])?;
+
+        self.memories_by_layer.get_mut(&current).unwrap().remove(&memory.id);
+        self.graph_remove(memory.id);
+        self.graph_add(&memory);
+        self.index_memory(&memory).await;
+        self.memories_by_layer.get_mut(&memory.layer).unwrap().insert(memory.id, memory);
+
+        self.update_stats();
+        Ok(())
+    }
+
+    /// Move a memory to another layer, returning the layer it came from
+    pub async fn move_to_layer(&mut self, id: Uuid, layer: MemoryLayer) -> Result<MemoryLayer> {
+        let from = self.locate(id)?
+            .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id))?;
+        if from != layer {
+            let mut memory = self.memories_by_layer[&from][&id].clone();
+            memory.layer = layer;
+            self.replace_memory(memory).await?;
+        }
+        Ok(from)
+    }
+
+    /// Move a memory to another scope on behalf of `agent_id`.
+    ///
+    /// The agent must be able to read the memory and write to the target scope. Facts shared
+    /// beyond one agent are kept in LongTerm, so they aren't pruned as scratch notes.
+    pub async fn promote(&mut self, id: Uuid, scope: MemoryScope, agent_id: &str) -> Result<Memory> {
+        let layer = self.locate(id)?
+            .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id))?;
+        let mut memory = self.memories_by_layer[&layer][&id].clone();
+
+        let scopes = self.retrieval_engine.scopes();
+        if !scopes.can_read(agent_id, &memory) {
+            return Err(anyhow::anyhow!("Agent {} cannot see memory {}", agent_id, id));
+        }
+        // Taking a fact out of a shared scope needs write access to that scope
+        if !scopes.can_write(agent_id, &scope) || !scopes.can_write(agent_id, &memory.scope) {
+            return Err(anyhow::anyhow!(
+                "Agent {} may not move memories from the {} scope to the {} scope",
+                agent_id, memory.scope.as_key(), scope.as_key()
+            ));
+        }
+        if memory.scope == scope {
+            return Ok(memory);
+        }
+
+        if scope == MemoryScope::Agent {
+            // Back to private: it belongs to whoever took it back
+            memory.metadata.agent_id = agent_id.to_string();
+        } else if matches!(memory.layer, MemoryLayer::Working | MemoryLayer::ShortTerm) {
+            self.ensure_layer_loaded(MemoryLayer::LongTerm)?;
+            memory.layer = MemoryLayer::LongTerm;
+        }
+        log::info!("Memory {} moved from {} to {} by {}", id, memory.scope.as_key(), scope.as_key(), agent_id);
+        memory.scope = scope;
+
+        self.replace_memory(memory.clone()).await?;
+        Ok(memory)
+    }
+
+    /// Take memories out of the active layers and into the archive
+    pub async fn archive_memories(&mut self, ids: &[Uuid], run_id: Option<Uuid>, replaced_by: Option<Uuid>) -> Result<usize> {
+        let mut entries = Vec::new();
+        for id in ids {
+            if let Some(layer) = self.locate(*id)? {
+                entries.push(ArchivedMemory {
+                    memory: self.memories_by_layer[&layer][id].clone(),
+                    archived_at: Utc::now(),
+                    run_id,
+                    replaced_by,
+                });
+            }
+        }
+
+        self.persist(entries.iter().cloned().map(MemoryWrite::ArchiveMemory).collect())?;
+        for entry in &entries {
+            self.memories_by_layer.get_mut(&entry.memory.layer).unwrap().remove(&entry.memory.id);
+            self.graph_remove(entry.memory.id);
+        }
+        let archived_ids: Vec<Uuid> = entries.iter().map(|entry| entry.memory.id).collect();
+        self.unindex_memories(&archived_ids).await;
+
+        let archived = entries.len();
+        for entry in entries {
+            self.archive.insert(entry.memory.id, entry);
+        }
+
+        self.update_stats();
+        Ok(archived)
+    }
+
+    /// Bring an archived memory back into the layer it was archived from
+    pub async fn restore_archived(&mut self, id: Uuid) -> Result<Memory> {
+        let entry = self.archive.get(&id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Archived memory not found: {}", id))?;
+        let memory = entry.memory;
+
+        self.ensure_layer_loaded(memory.layer)?;
+        self.persist(vec![
+            MemoryWrite::UpsertMemory(memory.clone()),
+            MemoryWrite::DeleteArchived(id),
+        ])?;
+
+        self.archive.remove(&id);
+        self.memories_by_layer.get_mut(&memory.layer).unwrap().insert(id, memory.clone());
+        self.graph_add(&memory);
+        self.index_memory(&memory).await;
+
+        self.update_stats();
+        Ok(memory)
+    }
+
+    /// Archived memories, most recently archived first
+    pub fn archived_memories(&self) -> Vec<&ArchivedMemory> {
+        let mut entries: Vec<&ArchivedMemory> = self.archive.values().collect();
+        entries.sort_by(|a, b| b.archived_at.cmp(&a.archived_at));
+        entries
+    }
+
+    /// Look up an archived memory
+    pub fn get_archived(&self, id: Uuid) -> Option<&ArchivedMemory> {
+        self.archive.get(&id)
+    }
+
+    /// Look up a memory in the loaded layers
+    pub fn get_memory(&self, id: Uuid) -> Option<&Memory> {
+        self.memories_by_layer.values().find_map(|memories| memories.get(&id))
+    }
+
     /// Generate reflective insights from memories
+    ///
+    /// Contradictions and causal relationships are also linked as associations, and
+    /// both sides of a contradiction are marked disputed until the user resolves it.
     pub async fn reflect(&mut self) -> Result<Vec<Insight>> {
-        self.consolidation_engine.generate_insights(self).await
+        self.load_all_layers()?;
+        let generated = self.consolidation_engine.generate_insights(self).await?;
+        let insights: Vec<Insight> = generated
+            .into_iter()
+            .filter(|insight| !self.has_insight(insight))
+            .collect();
+
+        self.persist(insights.iter().cloned().map(MemoryWrite::UpsertInsight).collect())?;
+        self.insights.extend(insights.iter().cloned());
+
+        for insight in &insights {
+            let &[first, second] = &insight.supporting_memories[..] else { continue };
+            match insight.insight_type {
+                InsightType::Contradiction => {
+                    self.associate_with_notes(first, second, AssociationType::Contradictory, insight.confidence, Some(insight.content.clone())).await?;
+                    self.set_verification_status(&[first, second], VerificationStatus::Disputed)?;
+                }
+                InsightType::Relationship => {
+                    self.associate_with_notes(first, second, AssociationType::Causal, insight.confidence, Some(insight.content.clone())).await?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(insights)
+    }
+
+    /// Insights produced by earlier reflections
+    pub fn get_insights(&self) -> &[Insight] {
+        &self.insights
     }
 
-    /// Create an association between two memories
-    pub async fn associate(&mut self, memory1: Uuid, memory2: Uuid, association_type: AssociationType, strength: f32) -> Result<()> {
+    /// Contradictions that still have a disputed side
+    pub fn pending_contradictions(&self) -> Vec<Contradiction> {
+        let mut pending: Vec<Contradiction> = self.associations
+            .values()
+            .filter(|association| association.association_type == AssociationType::Contradictory)
+            .filter_map(|association| {
+                let first = self.get_memory(association.memory_a)?;
+                let second = self.get_memory(association.memory_b)?;
+                let disputed = |m: &Memory| m.metadata.verification_status == VerificationStatus::Disputed;
+                (disputed(first) || disputed(second)).then(|| Contradiction {
+                    association_id: association.id,
+                    first: first.clone(),
+                    second: second.clone(),
+                    explanation: association.notes.clone(),
+                })
+            })
+            .collect();
+        pending.sort_by(|a, b| a.second.created_at.cmp(&b.second.created_at));
+        pending
+    }
+
+    /// Settle a contradiction by verifying or deprecating its two memories
+    pub async fn resolve_contradiction(&mut self, association_id: Uuid, resolution: ContradictionResolution) -> Result<()> {
+        let association = self.associations
+            .get(&association_id)
+            .filter(|association| association.association_type == AssociationType::Contradictory)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Contradiction not found: {}", association_id))?;
+        let (first, second) = (association.memory_a, association.memory_b);
+
+        match resolution {
+            ContradictionResolution::KeepFirst => {
+                self.set_verification_status(&[first], VerificationStatus::Verified)?;
+                self.set_verification_status(&[second], VerificationStatus::Deprecated)?;
+            }
+            ContradictionResolution::KeepSecond => {
+                self.set_verification_status(&[first], VerificationStatus::Deprecated)?;
+                self.set_verification_status(&[second], VerificationStatus::Verified)?;
+            }
+            ContradictionResolution::KeepBoth => {
+                // Not a real conflict, so the link goes too
+                self.set_verification_status(&[first, second], VerificationStatus::Verified)?;
+                self.persist(vec![MemoryWrite::DeleteAssociation(association_id)])?;
+                self.associations.remove(&association_id);
+            }
+            ContradictionResolution::DiscardBoth => {
+                self.set_verification_status(&[first, second], VerificationStatus::Deprecated)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Inferred facts and preference insights waiting on the user, oldest first
+    pub fn pending_verifications(&mut self) -> Result<Vec<VerificationRequest>> {
+        self.load_all_layers()?;
+
+        let memories = self.memories_by_layer
+            .values()
+            .flat_map(|memories| memories.values())
+            .filter(|memory| verification::needs_verification(memory))
+            .map(VerificationRequest::for_memory);
+
+        // Reflection finds the same preference in several memories; ask about it once
+        let mut asked = HashSet::new();
+        let insights = self.insights
+            .iter()
+            .filter(|insight| insight.insight_type == InsightType::Preference)
+            .filter(|insight| insight.verification_status == VerificationStatus::Unverified)
+            .map(VerificationRequest::for_insight)
+            .filter(|request| asked.insert(request.statement.to_lowercase()));
+
+        let mut pending: Vec<VerificationRequest> = memories.chain(insights).collect();
+        pending.sort_by_key(|request| request.created_at);
+        Ok(pending)
+    }
+
+    /// Apply the user's answer, returning the memory that now holds the fact.
+    ///
+    /// Confirmed and corrected facts are verified, gain importance and join the user profile.
+    /// Rejected facts are deprecated so retrieval leaves them out, and lose importance.
+    pub async fn answer_verification(&mut self, subject: VerificationSubject, answer: VerificationAnswer) -> Result<Option<Memory>> {
+        match subject {
+            VerificationSubject::Memory(id) => self.answer_memory_verification(id, answer).await.map(Some),
+            VerificationSubject::Insight(id) => self.answer_insight_verification(id, answer).await,
+        }
+    }
+
+    async fn answer_memory_verification(&mut self, id: Uuid, answer: VerificationAnswer) -> Result<Memory> {
+        let layer = self.locate(id)?
+            .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id))?;
+        let mut memory = self.memories_by_layer[&layer][&id].clone();
+
+        if let VerificationAnswer::Correct(content) = &answer {
+            memory.content = self.redact(content.clone(), &mut memory.metadata)?;
+            memory.embedding = self.embed_text(&memory.content).await?;
+        }
+        match answer {
+            VerificationAnswer::Reject => {
+                memory.metadata.verification_status = VerificationStatus::Deprecated;
+                memory.importance_score *= verification::REJECTED_IMPORTANCE_FACTOR;
+                memory.tags.retain(|tag| tag != verification::PROFILE_TAG);
+            }
+            VerificationAnswer::Confirm | VerificationAnswer::Correct(_) => {
+                memory.metadata.verification_status = VerificationStatus::Verified;
+                memory.importance_score = (memory.importance_score + verification::CONFIRMED_IMPORTANCE_BOOST).min(1.0);
+                if !memory.tags.iter().any(|tag| tag == verification::PROFILE_TAG) {
+                    memory.tags.push(verification::PROFILE_TAG.to_string());
+                }
+            }
+        }
+
+        self.replace_memory(memory.clone()).await?;
+        Ok(memory)
+    }
+
+    async fn answer_insight_verification(&mut self, id: Uuid, answer: VerificationAnswer) -> Result<Option<Memory>> {
+        let insight = self.insights
+            .iter()
+            .find(|insight| insight.id == id && insight.insight_type == InsightType::Preference)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Preference insight not found: {}", id))?;
+        let statement = verification::insight_statement(&insight);
+
+        // One answer settles every copy of the same preference
+        let status = match answer {
+            VerificationAnswer::Reject => VerificationStatus::Deprecated,
+            _ => VerificationStatus::Verified,
+        };
+        let mut writes = Vec::new();
+        for other in self.insights.iter_mut() {
+            if other.insight_type == InsightType::Preference
+                && verification::insight_statement(other).eq_ignore_ascii_case(&statement)
+            {
+                other.verification_status = status.clone();
+                writes.push(MemoryWrite::UpsertInsight(other.clone()));
+            }
+        }
+        self.persist(writes)?;
+
+        let content = match answer {
+            VerificationAnswer::Reject => return Ok(None),
+            VerificationAnswer::Confirm => statement,
+            VerificationAnswer::Correct(content) => content,
+        };
+
+        let mut metadata = MemoryMetadata {
+            source: MemorySource::SystemInsight,
+            agent_id: insight.agent_id.clone(),
+            conversation_id: None,
+            session_id: None,
+            topics: Vec::new(),
+            entities: Vec::new(),
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Verified,
+            custom_fields: HashMap::from([(verification::INSIGHT_FIELD.to_string(), serde_json::json!(insight.id))]),
+        };
+        let content = self.redact(content, &mut metadata)?;
+
+        // The user vouched for it, so every agent may rely on it
+        let mut memory = Memory::new(content, MemoryLayer::LongTerm, metadata);
+        memory.scope = MemoryScope::Global;
+        memory.importance_score = (insight.confidence + verification::CONFIRMED_IMPORTANCE_BOOST).min(1.0);
+        memory.associations = insight.supporting_memories.clone();
+        memory.tags.push(verification::PROFILE_TAG.to_string());
+        self.insert_memory(memory).await.map(Some)
+    }
+
+    /// Verified facts about the user that `agent_id` can see, most important first
+    pub fn user_profile(&self, agent_id: &str) -> Result<Vec<Memory>> {
+        let scopes = self.retrieval_engine.scopes();
+        // Asked on every chat message, so the store is queried rather than every layer loaded
+        let candidates: Vec<Memory> = match self.store {
+            Some(ref store) => store.load_profile()?
+                .into_iter()
+                .map(|memory| self.get_memory(memory.id).cloned().unwrap_or(memory))
+                .collect(),
+            None => self.memories_by_layer
+                .values()
+                .flat_map(|memories| memories.values())
+                .cloned()
+                .collect(),
+        };
+        let mut profile: Vec<Memory> = candidates
+            .into_iter()
+            .filter(|memory| verification::is_profile_fact(memory) && scopes.can_read(agent_id, memory))
+            .collect();
+        profile.sort_by(|a, b| b.importance_score.total_cmp(&a.importance_score));
+        Ok(profile)
+    }
+
+    /// Whether two memories are already linked with this association type, in either direction
+    pub fn has_association(&self, memory1: Uuid, memory2: Uuid, association_type: &AssociationType) -> bool {
+        self.associations.values().any(|association| {
+            association.association_type == *association_type
+                && ((association.memory_a == memory1 && association.memory_b == memory2)
+                    || (association.memory_a == memory2 && association.memory_b == memory1))
+        })
+    }
+
+    /// Create an association between two memories, returning its id
+    pub async fn associate(&mut self, memory1: Uuid, memory2: Uuid, association_type: AssociationType, strength: f32) -> Result<Uuid> {
+        self.associate_with_notes(memory1, memory2, association_type, strength, None).await
+    }
+
+    /// Create an association between two memories, recording why they are linked
+    pub async fn associate_with_notes(&mut self, memory1: Uuid, memory2: Uuid, association_type: AssociationType, strength: f32, notes: Option<String>) -> Result<Uuid> {
         let association = MemoryAssociation {
             id: Uuid::new_v4(),
             memory_a: memory1,
@@ -211,75 +1020,149 @@ impl MemoryManager {
             association_type,
             strength: strength.clamp(0.0, 1.0),
             created_at: Utc::now(),
-            notes: None,
+            notes,
         };
         
-        self.associations.insert(association.id, association);
-        
-        // Update memory association lists
-        if let Some((_, memory)) = self.find_memory_mut(memory1) {
-            if !memory.associations.contains(&memory2) {
-                memory.associations.push(memory2);
-            }
-        }
-        
-        if let Some((_, memory)) = self.find_memory_mut(memory2) {
-            if !memory.associations.contains(&memory1) {
-                memory.associations.push(memory1);
+        // Update memory association lists; the edge and both endpoints are written together
+        let mut writes = vec![MemoryWrite::UpsertAssociation(association.clone())];
+        for (id, other) in [(memory1, memory2), (memory2, memory1)] {
+            self.locate(id)?;
+            if let Some((_, memory)) = self.find_memory_mut(id) {
+                if !memory.associations.contains(&other) {
+                    memory.associations.push(other);
+                    writes.push(MemoryWrite::UpsertMemory(memory.clone()));
+                }
             }
         }
+
+        self.persist(writes)?;
+        let id = association.id;
+        self.associations.insert(id, association);
         
-        Ok(())
+        Ok(id)
     }
 
     /// Prune memories based on strategy
+    ///
+    /// Only working, short-term and episodic memories are candidates, and memories the
+    /// user has verified or pinned are never pruned. Pruned memories go to the archive,
+    /// where they can still be restored.
     pub async fn prune(&mut self, strategy: PruningStrategy) -> Result<PruningReport> {
+        self.prune_scoped(None, strategy).await
+    }
+
+    /// Prune one agent's memories
+    pub async fn prune_agent(&mut self, agent_id: &str, strategy: PruningStrategy) -> Result<PruningReport> {
+        self.prune_scoped(Some(agent_id), strategy).await
+    }
+
+    async fn prune_scoped(&mut self, agent_id: Option<&str>, strategy: PruningStrategy) -> Result<PruningReport> {
         let start_time = std::time::Instant::now();
-        let initial_count = self.total_memory_count();
-        
-        let mut removed_count = 0;
-        let mut space_freed = 0;
-        
-        match strategy {
-            PruningStrategy::LRU => {
-                removed_count = self.prune_lru().await?;
-            },
-            PruningStrategy::LowestImportance => {
-                removed_count = self.prune_by_importance().await?;
-            },
-            PruningStrategy::AgeThreshold(threshold) => {
-                removed_count = self.prune_by_age(threshold).await?;
-            },
-            PruningStrategy::LowFrequency => {
-                removed_count = self.prune_by_frequency().await?;
-            },
-            PruningStrategy::CustomScore => {
-                removed_count = self.prune_by_custom_score().await?;
-            },
-        }
-        
-        // Estimate space freed (rough calculation)
-        space_freed = removed_count * 1024; // Assume 1KB per memory on average
-        
-        let processing_time = start_time.elapsed().as_millis() as u64;
-        
-        self.update_stats();
+        self.load_prunable_layers()?;
+
+        let now = Utc::now();
+        let doomed: Vec<(Uuid, usize)> = PRUNABLE_LAYERS
+            .into_iter()
+            .flat_map(|layer| self.get_memories_by_layer(layer))
+            .filter(|m| agent_id.map_or(true, |agent| m.metadata.agent_id == agent))
+            .filter(|m| m.metadata.verification_status != VerificationStatus::Verified && !m.pinned)
+            .filter(|m| Self::should_prune(m, &strategy, now))
+            .map(|m| (m.id, m.content.len()))
+            .collect();
+
+        let ids: Vec<Uuid> = doomed.iter().map(|(id, _)| *id).collect();
+        self.archive_memories(&ids, None, None).await?;
         
         Ok(PruningReport {
-            memories_removed: removed_count,
-            space_freed_bytes: space_freed,
-            processing_time_ms: processing_time,
+            memories_removed: doomed.len(),
+            space_freed_bytes: doomed.iter().map(|(_, bytes)| bytes).sum(),
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
             retention_criteria: format!("{:?}", strategy),
         })
     }
 
-    /// Get all memories for an agent
+    /// Fade the importance of memories left unused during the last `elapsed`
+    ///
+    /// Importance is multiplied by `rate` per idle day, so a memory accessed halfway
+    /// through the window only decays for the idle half. Durable layers, verified and
+    /// pinned memories keep their importance. Returns the number of memories changed.
+    pub fn apply_importance_decay(&mut self, rate: f32, elapsed: chrono::Duration) -> Result<usize> {
+        self.load_prunable_layers()?;
+
+        let now = Utc::now();
+        let mut writes = Vec::new();
+        for layer in PRUNABLE_LAYERS {
+            for memory in self.memories_by_layer.get_mut(&layer).unwrap().values_mut() {
+                if memory.metadata.verification_status == VerificationStatus::Verified || memory.pinned {
+                    continue;
+                }
+
+                let idle = now.signed_duration_since(memory.last_accessed).min(elapsed);
+                let idle_days = idle.num_seconds() as f32 / 86_400.0;
+                if idle_days <= 0.0 {
+                    continue;
+                }
+
+                let decayed = memory.importance_score * rate.powf(idle_days);
+                if memory.importance_score - decayed > f32::EPSILON {
+                    memory.importance_score = decayed;
+                    writes.push(MemoryWrite::UpsertMemory(memory.clone()));
+                }
+            }
+        }
+
+        let changed = writes.len();
+        self.persist(writes)?;
+        self.update_stats();
+        Ok(changed)
+    }
+
+    /// Agents with at least one memory, across all layers
+    pub fn agent_ids(&self) -> Result<Vec<String>> {
+        let mut agents: std::collections::BTreeSet<String> = self.memories_by_layer
+            .values()
+            .flat_map(|memories| memories.values())
+            .map(|memory| memory.metadata.agent_id.clone())
+            .collect();
+
+        // Layers still on disk are asked for their agents without being loaded
+        let unloaded: Vec<MemoryLayer> = MemoryLayer::ALL
+            .into_iter()
+            .filter(|layer| !self.loaded_layers.contains(layer))
+            .collect();
+        if let (Some(store), false) = (&self.store, unloaded.is_empty()) {
+            agents.extend(store.agent_ids(&unloaded)?);
+        }
+        Ok(agents.into_iter().collect())
+    }
+
+    /// Read the layers pruning and decay work on; durable layers stay on disk
+    fn load_prunable_layers(&mut self) -> Result<()> {
+        for layer in PRUNABLE_LAYERS {
+            self.ensure_layer_loaded(layer)?;
+        }
+        Ok(())
+    }
+
+    /// Number of an agent's memories in the given layers
+    pub fn agent_memory_count(&mut self, agent_id: &str, layers: &[MemoryLayer]) -> Result<usize> {
+        for layer in layers {
+            self.ensure_layer_loaded(*layer)?;
+        }
+        Ok(layers.iter()
+            .flat_map(|layer| self.get_memories_by_layer(*layer))
+            .filter(|memory| memory.metadata.agent_id == agent_id)
+            .count())
+    }
+
+    /// Get all memories an agent can see from the loaded layers: its own plus shared ones
     pub async fn get_agent_memories(&self, agent_id: &str) -> Result<Vec<Memory>> {
+        let scopes = self.retrieval_engine.scopes();
         let mut memories = Vec::new();
         
         for layer_memories in self.memories_by_layer.values() {
             for memory in layer_memories.values() {
-                if memory.metadata.agent_id == agent_id {
+                if scopes.can_read(agent_id, memory) {
                     memories.push(memory.clone());
                 }
             }
@@ -306,35 +1189,193 @@ impl MemoryManager {
 
     /// Clear all memories for an agent
     pub async fn clear_agent_memories(&mut self, agent_id: &str) -> Result<usize> {
-        let mut removed_count = 0;
-        
+        self.load_all_layers()?;
+
+        let removed: HashSet<Uuid> = self.memories_by_layer
+            .values()
+            .flat_map(|memories| memories.values())
+            // Shared memories outlive the agent that stored them
+            .filter(|memory| memory.metadata.agent_id == agent_id && !memory.scope.is_shared())
+            .map(|memory| memory.id)
+            .collect();
+
+        self.persist(removed.iter().map(|id| MemoryWrite::DeleteMemory(*id)).collect())?;
+
         for layer_memories in self.memories_by_layer.values_mut() {
-            layer_memories.retain(|_, memory| {
-                if memory.metadata.agent_id == agent_id {
-                    removed_count += 1;
-                    false
-                } else {
-                    true
-                }
-            });
+            layer_memories.retain(|id, _| !removed.contains(id));
         }
+        for id in &removed {
+            self.graph_remove(*id);
+        }
+        self.unindex_memories(&removed.iter().copied().collect::<Vec<_>>()).await;
         
         // Remove associations for deleted memories
         self.associations.retain(|_, assoc| {
-            // Check if either memory in the association belonged to the agent
-            !self.association_involves_agent(&assoc, agent_id)
+            !removed.contains(&assoc.memory_a) && !removed.contains(&assoc.memory_b)
         });
         
         self.update_stats();
-        Ok(removed_count)
+        Ok(removed.len())
+    }
+
+    /// Merge entities found after a memory was stored, e.g. by a model in the background.
+    ///
+    /// Returns false when the memory is gone or already had them all.
+    pub async fn add_entities(&mut self, id: Uuid, entities: Vec<Entity>) -> Result<bool> {
+        let Some(layer) = self.locate(id)? else { return Ok(false) };
+        let mut memory = self.memories_by_layer[&layer][&id].clone();
+
+        let before = memory.metadata.entities.len();
+        let mut merged = std::mem::take(&mut memory.metadata.entities);
+        merged.extend(entities);
+        memory.metadata.entities = merge_entities(merged);
+        if memory.metadata.entities.len() == before {
+            return Ok(false);
+        }
+
+        self.replace_memory(memory).await?;
+        Ok(true)
+    }
+
+    /// Entity graph over every layer, loading all layers the first time
+    pub fn knowledge_graph(&mut self) -> Result<&KnowledgeGraph> {
+        if self.knowledge_graph.is_none() {
+            self.load_all_layers()?;
+            let graph = KnowledgeGraph::from_memories(self.memories_by_layer.values().flat_map(|memories| memories.values()));
+            log::debug!("Built knowledge graph with {} entities and {} relations", graph.node_count(), graph.relation_count());
+            self.knowledge_graph = Some(graph);
+        }
+        Ok(self.knowledge_graph.as_ref().unwrap())
+    }
+
+    /// Every memory mentioning an entity, newest first
+    pub fn memories_about(&mut self, entity_name: &str) -> Result<Vec<Memory>> {
+        let ids = self.knowledge_graph()?.memories_about(entity_name);
+        let mut memories: Vec<Memory> = ids.into_iter()
+            .filter_map(|id| self.get_memory(id).cloned())
+            .collect();
+        memories.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(memories)
+    }
+
+    /// Entities related to `entity_name`, optionally restricted to one type
+    pub fn related_entities(&mut self, entity_name: &str, entity_type: Option<EntityType>) -> Result<Vec<RelatedEntity>> {
+        Ok(self.knowledge_graph()?.related(entity_name, entity_type.as_ref()))
     }
 
     // Private helper methods
 
+    fn graph_add(&mut self, memory: &Memory) {
+        if let Some(ref mut graph) = self.knowledge_graph {
+            graph.add_memory(memory);
+        }
+    }
+
+    fn graph_remove(&mut self, id: Uuid) {
+        if let Some(ref mut graph) = self.knowledge_graph {
+            graph.remove_memory(id);
+        }
+    }
+
+    /// Write a memory's embedding to the vector store; the index can be rebuilt, so failures only warn
+    async fn index_memory(&self, memory: &Memory) {
+        let (Some(vector_store), Some(embedding)) = (&self.vector_store, &memory.embedding) else { return };
+        let point = VectorPoint {
+            id: memory.id,
+            vector: embedding.clone(),
+            payload: memory_payload(memory),
+        };
+        if let Err(e) = vector_store.upsert_points(&CollectionSchema::memory_collection().name, vec![point]).await {
+            log::warn!("Failed to index memory {} in the vector store: {}", memory.id, e);
+        }
+    }
+
+    async fn unindex_memories(&self, ids: &[Uuid]) {
+        let Some(ref vector_store) = self.vector_store else { return };
+        for id in ids {
+            if let Err(e) = vector_store.delete_embedding(*id).await {
+                log::warn!("Failed to remove memory {} from the vector store: {}", id, e);
+            }
+        }
+    }
+
+    fn has_insight(&self, insight: &Insight) -> bool {
+        self.insights.iter().any(|existing| {
+            existing.insight_type == insight.insight_type
+                && existing.agent_id == insight.agent_id
+                && existing.content == insight.content
+                && existing.supporting_memories == insight.supporting_memories
+        })
+    }
+
+    fn set_verification_status(&mut self, ids: &[Uuid], status: VerificationStatus) -> Result<()> {
+        let mut writes = Vec::new();
+        for id in ids {
+            self.locate(*id)?;
+            if let Some((_, memory)) = self.find_memory_mut(*id) {
+                if memory.metadata.verification_status != status {
+                    memory.metadata.verification_status = status.clone();
+                    writes.push(MemoryWrite::UpsertMemory(memory.clone()));
+                }
+            }
+        }
+        self.persist(writes)
+    }
+
+    /// Write changes to storage in a single transaction
+    fn persist(&self, writes: Vec<MemoryWrite>) -> Result<()> {
+        match self.store {
+            Some(ref store) => store.apply(&writes),
+            None => Ok(()),
+        }
+    }
+
+    fn load_query_layers(&mut self, query: &MemoryQuery) -> Result<()> {
+        let layers = query.layers.clone().unwrap_or_else(|| MemoryLayer::ALL.to_vec());
+        for layer in layers {
+            self.ensure_layer_loaded(layer)?;
+        }
+        Ok(())
+    }
+
+    fn record_access(&mut self, ids: impl Iterator<Item = Uuid>) -> Result<()> {
+        let mut writes = Vec::new();
+        for id in ids {
+            if let Some((_, memory)) = self.find_memory_mut(id) {
+                memory.access();
+                writes.push(MemoryWrite::RecordAccess {
+                    id,
+                    access_count: memory.access_count,
+                    last_accessed: memory.last_accessed,
+                });
+            }
+        }
+        self.persist(writes)
+    }
+
+    /// Find the layer holding `id`, loading only the layer that contains it
+    fn locate(&mut self, id: Uuid) -> Result<Option<MemoryLayer>> {
+        if let Some(layer) = self.find_memory_layer(id) {
+            return Ok(Some(layer));
+        }
+
+        let unloaded: Vec<MemoryLayer> = MemoryLayer::ALL
+            .into_iter()
+            .filter(|layer| !self.loaded_layers.contains(layer))
+            .collect();
+        for layer in unloaded {
+            self.ensure_layer_loaded(layer)?;
+            if self.memories_by_layer[&layer].contains_key(&id) {
+                return Ok(Some(layer));
+            }
+        }
+        Ok(None)
+    }
+
     fn find_memory_mut(&mut self, id: Uuid) -> Option<(MemoryLayer, &mut Memory)> {
         for (layer, memories) in self.memories_by_layer.iter_mut() {
             if let Some(memory) = memories.get_mut(&id) {
-                return Some(*layer, memory);
+                return Some((*layer, memory));
             }
         }
         None
@@ -350,11 +1391,13 @@ impl MemoryManager {
     }
 
     fn move_memory_to_layer(&mut self, id: Uuid, from_layer: MemoryLayer, to_layer: MemoryLayer) -> Result<()> {
-        let memory = self.memories_by_layer
+        self.ensure_layer_loaded(to_layer)?;
+        let mut memory = self.memories_by_layer
             .get_mut(&from_layer)
             .unwrap()
             .remove(&id)
             .ok_or_else(|| anyhow::anyhow!("Memory not found in source layer"))?;
+        memory.layer = to_layer;
         
         self.memories_by_layer
             .get_mut(&to_layer)
@@ -370,7 +1413,7 @@ impl MemoryManager {
         MemoryLayer::Working
     }
 
-    fn determine_target_layer(&self, memory: &Memory) -> MemoryLayer {
+    pub(crate) fn determine_target_layer(&self, memory: &Memory) -> MemoryLayer {
         let strength = memory.memory_strength();
         let importance = memory.importance_score;
         
@@ -407,6 +1450,7 @@ impl MemoryManager {
         let layer_memories = self.memories_by_layer.get(&layer).unwrap();
         let mut memory_scores: Vec<(Uuid, f32)> = layer_memories
             .iter()
+            .filter(|(_, memory)| !memory.pinned)
             .map(|(id, memory)| (*id, memory.memory_strength()))
             .collect();
         
@@ -424,7 +1468,6 @@ impl MemoryManager {
     async fn extract_metadata(&mut self, memory: &mut Memory) -> Result<()> {
         // Simple keyword-based topic extraction
         let content_lower = memory.content.to_lowercase();
-        let mut topics = Vec::new();
         
         // Basic topic keywords - in a real implementation, use NLP
         let topic_keywords = [
@@ -434,29 +1477,18 @@ impl MemoryManager {
         ];
         
         for (topic, keywords) in topic_keywords.iter() {
-            if keywords.iter().any(|keyword| content_lower.contains(keyword)) {
-                topics.push(topic.to_string());
-            }
-        }
-        
-        memory.metadata.topics = topics;
-        
-        // Extract simple entities (capitalized words)
-        let words: Vec<&str> = memory.content.split_whitespace().collect();
-        let mut entities = Vec::new();
-        
-        for word in words {
-            if word.chars().next().unwrap_or_default().is_uppercase() && word.len() > 2 {
-                entities.push(Entity {
-                    name: word.to_string(),
-                    entity_type: EntityType::Other("detected".to_string()),
-                    confidence: 0.7,
-                    mentions: vec![word.to_string()],
-                });
+            if keywords.iter().any(|keyword| content_lower.contains(keyword))
+                && !memory.metadata.topics.iter().any(|existing| existing == topic)
+            {
+                memory.metadata.topics.push(topic.to_string());
             }
         }
         
-        memory.metadata.entities = entities;
+        // Entities supplied by the caller are kept alongside extracted ones
+        let mut entities = std::mem::take(&mut memory.metadata.entities);
+        // Model recognition is slow, so the coordinator adds its entities afterwards
+        entities.extend(EntityExtractor::extract_with_rules(&memory.content));
+        memory.metadata.entities = merge_entities(entities);
         
         Ok(())
     }
@@ -518,15 +1550,24 @@ impl MemoryManager {
     }
 
     fn total_memory_count(&self) -> usize {
-        self.memories_by_layer.values().map(|m| m.len()).sum()
+        MemoryLayer::ALL.iter().map(|layer| self.layer_count(*layer)).sum()
+    }
+
+    /// Memories in a layer, using the stored count for layers not yet loaded
+    fn layer_count(&self, layer: MemoryLayer) -> usize {
+        if self.loaded_layers.contains(&layer) {
+            self.memories_by_layer.get(&layer).map(|m| m.len()).unwrap_or(0)
+        } else {
+            self.persisted_counts.get(&layer).copied().unwrap_or(0)
+        }
     }
 
     fn update_stats(&mut self) {
         self.stats.total_memories = self.total_memory_count();
         
         self.stats.memories_by_layer.clear();
-        for (layer, memories) in &self.memories_by_layer {
-            self.stats.memories_by_layer.insert(*layer, memories.len());
+        for layer in MemoryLayer::ALL {
+            self.stats.memories_by_layer.insert(layer, self.layer_count(layer));
         }
         
         self.stats.total_associations = self.associations.len();
@@ -543,43 +1584,51 @@ impl MemoryManager {
         }
     }
 
-    async fn prune_lru(&mut self) -> Result<usize> {
-        // Implementation for LRU pruning
-        // Find least recently used memories and remove them
-        // This is a simplified version
-        Ok(0)
-    }
-
-    async fn prune_by_importance(&mut self) -> Result<usize> {
-        // Remove memories with lowest importance scores
-        Ok(0)
-    }
-
-    async fn prune_by_age(&mut self, _threshold: chrono::Duration) -> Result<usize> {
-        // Remove memories older than threshold
-        Ok(0)
-    }
-
-    async fn prune_by_frequency(&mut self) -> Result<usize> {
-        // Remove memories with low access frequency
-        Ok(0)
-    }
+    fn should_prune(memory: &Memory, strategy: &PruningStrategy, now: DateTime<Utc>) -> bool {
+        let idle = now.signed_duration_since(memory.last_accessed);
+        let age = now.signed_duration_since(memory.created_at);
 
-    async fn prune_by_custom_score(&mut self) -> Result<usize> {
-        // Custom scoring algorithm for pruning
-        Ok(0)
+        match strategy {
+            PruningStrategy::LRU => {
+                idle >= chrono::Duration::days(PRUNE_IDLE_DAYS) && memory.importance_score < PRUNE_KEEP_IMPORTANCE
+            }
+            PruningStrategy::LowestImportance => memory.importance_score < PRUNE_IMPORTANCE_FLOOR,
+            PruningStrategy::AgeThreshold(threshold) => {
+                age >= *threshold && memory.importance_score < PRUNE_KEEP_IMPORTANCE
+            }
+            PruningStrategy::LowFrequency => {
+                memory.access_count == 0
+                    && age >= chrono::Duration::days(LOW_FREQUENCY_MIN_AGE_DAYS)
+                    && memory.importance_score < PRUNE_KEEP_IMPORTANCE
+            }
+            PruningStrategy::CustomScore => {
+                age >= chrono::Duration::days(1) && memory.memory_strength() < PRUNE_STRENGTH_FLOOR
+            }
+        }
     }
+}
 
-    fn association_involves_agent(&self, _association: &MemoryAssociation, _agent_id: &str) -> bool {
-        // Check if association involves memories from the specified agent
-        // This would require looking up the memories by ID
-        false
-    }
+/// Payload stored with a memory's point; access counts change too often to be worth indexing
+fn memory_payload(memory: &Memory) -> HashMap<String, serde_json::Value> {
+    HashMap::from([
+        ("memory_id".to_string(), serde_json::json!(memory.id.to_string())),
+        ("content".to_string(), serde_json::json!(memory.content)),
+        ("agent_id".to_string(), serde_json::json!(memory.metadata.agent_id)),
+        ("conversation_id".to_string(), serde_json::json!(memory.metadata.conversation_id)),
+        ("session_id".to_string(), serde_json::json!(memory.metadata.session_id)),
+        ("layer".to_string(), serde_json::json!(memory.layer.as_str())),
+        ("scope".to_string(), serde_json::json!(memory.scope.as_key())),
+        ("importance_score".to_string(), serde_json::json!(memory.importance_score)),
+        ("created_at".to_string(), serde_json::json!(memory.created_at.to_rfc3339())),
+        ("topics".to_string(), serde_json::json!(memory.metadata.topics)),
+        ("tags".to_string(), serde_json::json!(memory.tags)),
+    ])
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::entities::EntityRecognizer;
     use crate::config::AppConfig;
 
     fn create_test_metadata(agent_id: &str) -> MemoryMetadata {
@@ -630,4 +1679,401 @@ mod tests {
         assert!(!retrieved.is_empty());
         assert_eq!(retrieved[0].id, stored_memory.id);
     }
-}
\ No newline at end of file
+
+    /// Config for an embedded vector index with hash embeddings in `dir`
+    fn embedded_vector_config(dir: &std::path::Path) -> AppConfig {
+        let mut config = AppConfig::with_data_dir(dir.to_path_buf());
+        config.vector.index_backend = "embedded".to_string();
+        config.vector.embedding_backend = "hash".to_string();
+        config
+    }
+
+    #[tokio::test]
+    async fn test_stored_memories_are_searchable_in_the_vector_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = embedded_vector_config(dir.path());
+        let vector_store = Arc::new(VectorStore::new(config.clone()).await.unwrap());
+        vector_store.initialize().await.unwrap();
+        let mut manager = MemoryManager::new(config, Some(vector_store.clone())).await.unwrap();
+
+        let report = manager.store("The quarterly report is due on Friday".to_string(), create_test_metadata("test-agent")).await.unwrap();
+        manager.store("Lunch with Sam at the noodle place".to_string(), create_test_metadata("test-agent")).await.unwrap();
+
+        let collection = CollectionSchema::memory_collection().name;
+        let embedding = manager.embed_text("The quarterly report is due on Friday").await.unwrap().unwrap();
+        let results = vector_store
+            .search(crate::vector::SearchQuery::new(embedding, collection.clone()).with_limit(1))
+            .await
+            .unwrap();
+        assert_eq!(results[0].id, report.id);
+        assert_eq!(results[0].payload["agent_id"], "test-agent");
+
+        // Archiving and forgetting take the point out; restoring puts it back
+        manager.archive_memories(&[report.id], None, None).await.unwrap();
+        assert!(vector_store.get_point(&collection, report.id).await.unwrap().is_none());
+        manager.restore_archived(report.id).await.unwrap();
+        assert!(vector_store.get_point(&collection, report.id).await.unwrap().is_some());
+        manager.forget(report.id).await.unwrap();
+        assert!(vector_store.get_point(&collection, report.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_weights_decide_between_keyword_and_semantic_matches() {
+        use crate::memory::RankingConfig;
+        use crate::vector::{FusionMethod, HybridWeights};
+
+        // (vector weight, fusion, whether the keyword match comes first)
+        let cases = [
+            (0.9, FusionMethod::Weighted, false),
+            (0.1, FusionMethod::Weighted, true),
+            // Ranked in both lists beats first in one under RRF, whatever the similarity
+            (0.9, FusionMethod::Rrf, true),
+        ];
+        for (vector_weight, fusion, keyword_first) in cases {
+            let dir = tempfile::tempdir().unwrap();
+            let mut config = embedded_vector_config(dir.path());
+            config.vector.hybrid = HybridWeights { vector_weight, text_weight: 1.0 - vector_weight, fusion, ..Default::default() };
+            config.memory.ranking = RankingConfig {
+                importance_weight: 0.0,
+                recency_weight: 0.0,
+                frequency_weight: 0.0,
+                relevance_weight: 1.0,
+                ..Default::default()
+            };
+            let vector_store = Arc::new(VectorStore::new(config.clone()).await.unwrap());
+            vector_store.initialize().await.unwrap();
+            let mut manager = MemoryManager::new(config, Some(vector_store)).await.unwrap();
+
+            let keyword = manager.store("Tabs for indentation, never spaces".to_string(), create_test_metadata("test-agent")).await.unwrap();
+            let semantic = manager.store("Editor preferences were set up last week".to_string(), create_test_metadata("test-agent")).await.unwrap();
+
+            let mut query = MemoryQuery::new();
+            query.text_query = Some("tabs indentation".to_string());
+            query.semantic_query = semantic.embedding.clone();
+            let results = manager.search_with_scores(query).await.unwrap();
+
+            let expected = if keyword_first { keyword.id } else { semantic.id };
+            assert_eq!(results[0].memory.id, expected, "{:?} at vector weight {}", fusion, vector_weight);
+            assert!(results[0].match_reasons.iter().any(|reason| reason.starts_with("Hybrid relevance")));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_other_agents_keyword_matches_do_not_crowd_out_hybrid_recall() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = embedded_vector_config(dir.path());
+        let vector_store = Arc::new(VectorStore::new(config.clone()).await.unwrap());
+        vector_store.initialize().await.unwrap();
+        let mut manager = MemoryManager::new(config, Some(vector_store)).await.unwrap();
+
+        let own = manager.store("Tabs for indentation".to_string(), create_test_metadata("test-agent")).await.unwrap();
+        for i in 0..70 {
+            let content = format!("Tabs tabs indentation indentation, note {}", i);
+            manager.store(content, create_test_metadata("other-agent")).await.unwrap();
+        }
+
+        let mut query = MemoryQuery::new().with_agent("test-agent".to_string());
+        query.text_query = Some("tabs indentation".to_string());
+        query.semantic_query = own.embedding.clone();
+        let results = manager.search_with_scores(query).await.unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].memory.id, own.id);
+        let fused = results[0].match_reasons.iter().find(|reason| reason.starts_with("Hybrid relevance")).unwrap();
+        assert_ne!(fused, "Hybrid relevance: 0.00");
+    }
+
+    #[tokio::test]
+    async fn test_memories_are_reindexed_when_the_embedding_model_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = embedded_vector_config(dir.path());
+        let collection = CollectionSchema::memory_collection().name;
+        let memory = {
+            let vector_store = Arc::new(VectorStore::new(config.clone()).await.unwrap());
+            vector_store.initialize().await.unwrap();
+            let mut manager = MemoryManager::new(config.clone(), Some(vector_store)).await.unwrap();
+            manager.store("The quarterly report is due on Friday".to_string(), create_test_metadata("test-agent")).await.unwrap()
+        };
+
+        // As if the collection had been filled by another model
+        let models_file = config.data_dir_path().join("vector_models.json");
+        let mut models: HashMap<String, String> = serde_json::from_slice(&std::fs::read(&models_file).unwrap()).unwrap();
+        models.insert(collection.clone(), "ollama:other-model".to_string());
+        std::fs::write(&models_file, serde_json::to_vec(&models).unwrap()).unwrap();
+
+        let vector_store = Arc::new(VectorStore::new(config.clone()).await.unwrap());
+        vector_store.initialize().await.unwrap();
+        assert!(vector_store.get_point(&collection, memory.id).await.unwrap().is_none());
+
+        let mut manager = MemoryManager::new(config, Some(vector_store.clone())).await.unwrap();
+        assert_eq!(manager.index_missing_memories().await.unwrap(), 1);
+        assert!(vector_store.get_point(&collection, memory.id).await.unwrap().is_some());
+        assert_eq!(manager.index_missing_memories().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_leaves_durable_layers_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig::default();
+        {
+            let store = MemoryStore::open(dir.path()).unwrap();
+            let mut manager = MemoryManager::with_store(config.clone(), None, store).await.unwrap();
+            manager.store("Scratch note".to_string(), create_test_metadata("agent1")).await.unwrap();
+            let fact = Memory::new("Prefers tea".to_string(), MemoryLayer::Semantic, create_test_metadata("agent2"));
+            manager.insert_memory(fact).await.unwrap();
+        }
+
+        let store = MemoryStore::open(dir.path()).unwrap();
+        let mut manager = MemoryManager::with_store(config, None, store).await.unwrap();
+        assert_eq!(manager.agent_ids().unwrap(), vec!["agent1".to_string(), "agent2".to_string()]);
+        manager.prune(PruningStrategy::LRU).await.unwrap();
+        manager.apply_importance_decay(0.9, chrono::Duration::days(1)).unwrap();
+        assert!(!manager.loaded_layers.contains(&MemoryLayer::Semantic));
+    }
+
+    #[tokio::test]
+    async fn test_memories_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig::default();
+
+        let (first, second) = {
+            let store = MemoryStore::open(dir.path()).unwrap();
+            let mut manager = MemoryManager::with_store(config.clone(), None, store).await.unwrap();
+            let first = manager.store("Project deadline is Friday".to_string(), create_test_metadata("agent1")).await.unwrap();
+            let second = manager.store("Dinner with family".to_string(), create_test_metadata("agent1")).await.unwrap();
+            manager.associate(first.id, second.id, AssociationType::Temporal, 0.6).await.unwrap();
+            manager.retrieve(MemoryQuery::new().with_agent("agent1".to_string())).await.unwrap();
+            (first, second)
+        };
+
+        let store = MemoryStore::open(dir.path()).unwrap();
+        let mut manager = MemoryManager::with_store(config, None, store).await.unwrap();
+
+        // Counts are known before any layer is read
+        assert!(manager.loaded_layers.is_empty());
+        assert_eq!(manager.get_stats().total_memories, 2);
+        assert_eq!(manager.get_stats().total_associations, 1);
+
+        let retrieved = manager.retrieve(MemoryQuery::new().with_agent("agent1".to_string())).await.unwrap();
+        let ids: HashSet<Uuid> = retrieved.iter().map(|m| m.id).collect();
+        assert!(ids.contains(&first.id) && ids.contains(&second.id));
+
+        let reloaded = retrieved.iter().find(|m| m.id == first.id).unwrap();
+        assert!(reloaded.associations.contains(&second.id));
+        assert_eq!(reloaded.access_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_forget_is_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig::default();
+
+        let id = {
+            let store = MemoryStore::open(dir.path()).unwrap();
+            let mut manager = MemoryManager::with_store(config.clone(), None, store).await.unwrap();
+            let memory = manager.store("Temporary note".to_string(), create_test_metadata("agent1")).await.unwrap();
+            manager.forget(memory.id).await.unwrap();
+            memory.id
+        };
+
+        let store = MemoryStore::open(dir.path()).unwrap();
+        let mut manager = MemoryManager::with_store(config, None, store).await.unwrap();
+        assert!(manager.locate(id).unwrap().is_none());
+        assert_eq!(manager.get_stats().total_memories, 0);
+    }
+
+    #[tokio::test]
+    async fn test_learned_importance_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig::default();
+
+        {
+            let store = MemoryStore::open(dir.path()).unwrap();
+            let mut manager = MemoryManager::with_store(config.clone(), None, store).await.unwrap();
+            let memory = manager.store("Grant deadline moved to June".to_string(), create_test_metadata("agent1")).await.unwrap();
+            let rated = manager.record_feedback(memory.id, ImportanceFeedback::Rating(1.0)).unwrap().unwrap();
+            assert!(rated.importance_score > memory.importance_score);
+            assert!(manager.record_feedback(Uuid::new_v4(), ImportanceFeedback::Pinned).unwrap().is_none());
+        }
+
+        let store = MemoryStore::open(dir.path()).unwrap();
+        let mut manager = MemoryManager::with_store(config, None, store).await.unwrap();
+        let report = manager.importance_report(Some("agent1")).unwrap();
+        assert_eq!(report.feedback_count, 1);
+        assert_eq!(report.strengthened[0].0, "deadline");
+        assert!(report.learned_keywords.iter().any(|(keyword, _)| keyword == "grant"));
+        assert_eq!(report.trends.importance_by_source[&MemorySource::UserInput].len(), 1);
+    }
+
+    struct NameRecognizer;
+
+    #[async_trait::async_trait]
+    impl EntityRecognizer for NameRecognizer {
+        async fn recognize(&self, text: &str) -> Result<Vec<Entity>> {
+            Ok([("Alice", EntityType::Person), ("Bob", EntityType::Person), ("Atlas", EntityType::Project)].into_iter()
+                .filter(|(name, _)| text.contains(name))
+                .map(|(name, entity_type)| Entity {
+                    name: name.to_string(),
+                    entity_type,
+                    confidence: 0.75,
+                    mentions: vec![name.to_string()],
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stored_memories_feed_knowledge_graph() {
+        let mut manager = MemoryManager::new(AppConfig::default(), None).await.unwrap();
+
+        let kickoff = manager.store("Alice and Bob kick off Project Atlas on 2024-03-04".to_string(), create_test_metadata("agent1")).await.unwrap();
+        assert!(kickoff.metadata.entities.iter().any(|e| e.entity_type == EntityType::Date));
+        let recognized = NameRecognizer.recognize(&kickoff.content).await.unwrap();
+        assert!(manager.add_entities(kickoff.id, recognized.clone()).await.unwrap());
+        assert!(!manager.add_entities(kickoff.id, recognized).await.unwrap());
+
+        // Built lazily, then kept current as memories come and go
+        assert_eq!(manager.memories_about("Project Atlas").unwrap().len(), 1);
+        let review = manager.store("Atlas review notes from Alice".to_string(), create_test_metadata("agent1")).await.unwrap();
+        manager.add_entities(review.id, NameRecognizer.recognize(&review.content).await.unwrap()).await.unwrap();
+        let about = manager.memories_about("atlas").unwrap();
+        assert_eq!(about.iter().map(|m| m.id).collect::<Vec<_>>(), vec![review.id, kickoff.id]);
+
+        let people = manager.related_entities("Alice", Some(EntityType::Person)).unwrap();
+        assert_eq!(people.len(), 1);
+        assert_eq!(people[0].entity.name, "Bob");
+
+        manager.forget(kickoff.id).await.unwrap();
+        assert!(manager.related_entities("Alice", Some(EntityType::Person)).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_memories_are_not_pruned() {
+        let mut manager = MemoryManager::new(AppConfig::default(), None).await.unwrap();
+        let mut ids = Vec::new();
+        for content in ["Parking spot is B12", "Wifi password is on the fridge", "Lunch was fine"] {
+            let memory = manager.store(content.to_string(), create_test_metadata("agent1")).await.unwrap();
+            let low_importance = MemoryUpdate {
+                content: None,
+                importance_score: Some(0.05),
+                tags: None,
+                associations: None,
+                metadata_updates: None,
+                pinned: None,
+            };
+            manager.update(memory.id, low_importance).await.unwrap();
+            ids.push(memory.id);
+        }
+
+        assert_eq!(manager.set_pinned(&ids[..2], true).unwrap(), 2);
+        assert_eq!(manager.set_pinned(&ids[..1], true).unwrap(), 0);
+
+        let report = manager.prune(PruningStrategy::LowestImportance).await.unwrap();
+        assert_eq!(report.memories_removed, 1);
+        assert!(manager.locate(ids[2]).unwrap().is_none());
+        // Pruned into the archive rather than deleted
+        assert!(manager.archived_memories().iter().any(|entry| entry.memory.id == ids[2]));
+
+        assert_eq!(manager.forget_many(&ids).await.unwrap(), 2);
+        assert_eq!(manager.get_stats().total_memories, 0);
+    }
+
+    #[tokio::test]
+    async fn test_shared_memories_reach_other_agents() {
+        let mut config = AppConfig::default();
+        config.memory.scopes.permissions.insert("guest".to_string(), crate::memory::ScopePermissions {
+            write_global: false,
+            ..Default::default()
+        });
+        let mut manager = MemoryManager::new(config, None).await.unwrap();
+
+        let fact = manager.store("The user prefers dark mode".to_string(), create_test_metadata("coder")).await.unwrap();
+        let scratch = manager.store("Halfway through the refactor".to_string(), create_test_metadata("coder")).await.unwrap();
+        assert!(manager.retrieve(MemoryQuery::new().with_agent("writer".to_string())).await.unwrap().is_empty());
+
+        assert!(manager.promote(fact.id, MemoryScope::Global, "writer").await.is_err());
+        assert!(manager.store_in_scope("Nope".to_string(), create_test_metadata("guest"), MemoryScope::Global).await.is_err());
+
+        let promoted = manager.promote(fact.id, MemoryScope::Global, "coder").await.unwrap();
+        assert_eq!(promoted.layer, MemoryLayer::LongTerm);
+
+        let recalled = manager.retrieve(MemoryQuery::new().with_agent("writer".to_string())).await.unwrap();
+        assert_eq!(recalled.iter().map(|m| m.id).collect::<Vec<_>>(), vec![fact.id]);
+
+        // Clearing an agent leaves what it shared
+        assert_eq!(manager.clear_agent_memories("coder").await.unwrap(), 1);
+        assert!(manager.locate(scratch.id).unwrap().is_none());
+        assert!(manager.locate(fact.id).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_verified_facts_feed_the_profile() {
+        let mut manager = MemoryManager::new(AppConfig::default(), None).await.unwrap();
+        manager.store("I prefer TypeScript over JavaScript".to_string(), create_test_metadata("agent1")).await.unwrap();
+        let mut inferred_metadata = create_test_metadata("agent1");
+        inferred_metadata.source = MemorySource::Reflection;
+        let inferred = manager.store("The user works night shifts".to_string(), inferred_metadata).await.unwrap();
+
+        manager.reflect().await.unwrap();
+        let pending = manager.pending_verifications().unwrap();
+        let preference = pending.iter()
+            .find(|request| matches!(request.subject, VerificationSubject::Insight(_)))
+            .unwrap();
+        assert_eq!(preference.question, "Is it right that you prefer TypeScript over JavaScript?");
+        assert!(pending.iter().any(|request| request.subject == VerificationSubject::Memory(inferred.id)));
+        assert!(manager.user_profile("agent1").unwrap().is_empty());
+
+        let confirmed = manager.answer_verification(preference.subject, VerificationAnswer::Confirm).await.unwrap().unwrap();
+        let rejected = manager.answer_verification(VerificationSubject::Memory(inferred.id), VerificationAnswer::Reject).await.unwrap().unwrap();
+        assert!(rejected.importance_score < inferred.importance_score);
+        assert!(manager.pending_verifications().unwrap().is_empty());
+
+        // Every agent gets the confirmed preference; nobody recalls the rejected fact
+        let profile = manager.user_profile("agent2").unwrap();
+        assert_eq!(profile.iter().map(|m| m.id).collect::<Vec<_>>(), vec![confirmed.id]);
+        let recalled = manager.retrieve(MemoryQuery::new().with_agent("agent1".to_string())).await.unwrap();
+        assert!(recalled.iter().all(|m| m.id != inferred.id));
+    }
+
+    #[tokio::test]
+    async fn test_associative_search_follows_links() {
+        let mut manager = MemoryManager::new(AppConfig::default(), None).await.unwrap();
+        let train = manager.store("Missed the train to Hamburg".to_string(), create_test_metadata("agent1")).await.unwrap();
+        let meeting = manager.store("Client meeting moved to Friday".to_string(), create_test_metadata("agent1")).await.unwrap();
+        let invoice = manager.store("Invoice sent to the client".to_string(), create_test_metadata("agent1")).await.unwrap();
+        manager.associate(train.id, meeting.id, AssociationType::Causal, 0.9).await.unwrap();
+        manager.associate(meeting.id, invoice.id, AssociationType::Supporting, 0.8).await.unwrap();
+
+        let query = MemoryQuery::new().with_agent("agent1".to_string()).with_text("train".to_string());
+        let plain = manager.retrieve(query.clone()).await.unwrap();
+        assert_eq!(plain.iter().map(|m| m.id).collect::<Vec<_>>(), vec![train.id]);
+
+        let results = manager.search_associative(query.clone()).await.unwrap();
+        let ids: Vec<Uuid> = results.iter().map(|r| r.memory.id).collect();
+        assert_eq!(ids, vec![train.id, meeting.id, invoice.id]);
+        assert_eq!(results[0].explanation, "Direct match");
+        assert_eq!(results[1].explanation, "Linked to \"Missed the train to Hamburg\" by causal 0.81");
+        assert_eq!(results[2].path.len(), 2);
+
+        // Archived memories are left out, and so is anything only reachable through them
+        manager.archive_memories(&[meeting.id], None, None).await.unwrap();
+        let results = manager.search_associative(query).await.unwrap();
+        assert_eq!(results.iter().map(|r| r.memory.id).collect::<Vec<_>>(), vec![train.id]);
+    }
+
+    #[tokio::test]
+    async fn test_personal_data_is_redacted_before_storing() {
+        let mut config = AppConfig::default();
+        config.privacy.pii_policies.insert(crate::privacy::PiiKind::Phone, crate::privacy::RedactionPolicy::Drop);
+        let mut manager = MemoryManager::new(config, None).await.unwrap();
+
+        let memory = manager
+            .store("Reach me at jane@example.com or 555-123-4567".to_string(), create_test_metadata("agent1"))
+            .await
+            .unwrap();
+
+        assert_eq!(memory.content, "Reach me at [EMAIL] or ");
+        assert!(memory.metadata.custom_fields.contains_key("redactions"));
+        assert_eq!(manager.redaction_report().summary(), "1 email address masked, 1 phone number dropped");
+    }
+}
@@ -21,6 +21,33 @@ pub enum MemoryLayer {
 }
 
 impl MemoryLayer {
+    /// All layers, from most to least volatile
+    pub const ALL: [MemoryLayer; 6] = [
+        MemoryLayer::Working,
+        MemoryLayer::ShortTerm,
+        MemoryLayer::LongTerm,
+        MemoryLayer::Episodic,
+        MemoryLayer::Semantic,
+        MemoryLayer::Reflective,
+    ];
+
+    /// Stable name used in storage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemoryLayer::Working => "working",
+            MemoryLayer::ShortTerm => "short_term",
+            MemoryLayer::LongTerm => "long_term",
+            MemoryLayer::Episodic => "episodic",
+            MemoryLayer::Semantic => "semantic",
+            MemoryLayer::Reflective => "reflective",
+        }
+    }
+
+    /// Parse a name produced by `as_str`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|layer| layer.as_str() == name)
+    }
+
     /// Get the typical capacity for each memory layer
     pub fn typical_capacity(&self) -> usize {
         match self {
@@ -46,6 +73,45 @@ impl MemoryLayer {
     }
 }
 
+/// Who a memory is shared with
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryScope {
+    /// Private to the agent that stored it
+    #[default]
+    Agent,
+    /// Shared with every member of a team
+    Team(String),
+    /// The user profile: visible to every agent
+    Global,
+}
+
+impl MemoryScope {
+    /// Stable name used in storage, e.g. `team:research`
+    pub fn as_key(&self) -> String {
+        match self {
+            MemoryScope::Agent => "agent".to_string(),
+            MemoryScope::Team(team) => format!("team:{}", team),
+            MemoryScope::Global => "global".to_string(),
+        }
+    }
+
+    /// Parse a name produced by `as_key`
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "agent" => Some(MemoryScope::Agent),
+            "global" => Some(MemoryScope::Global),
+            _ => key.strip_prefix("team:")
+                .filter(|team| !team.is_empty())
+                .map(|team| MemoryScope::Team(team.to_string())),
+        }
+    }
+
+    pub fn is_shared(&self) -> bool {
+        !matches!(self, MemoryScope::Agent)
+    }
+}
+
 /// Core memory structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
@@ -60,6 +126,12 @@ pub struct Memory {
     pub created_at: DateTime<Utc>,
     pub associations: Vec<Uuid>,
     pub tags: Vec<String>,
+    /// Pinned by the user; never pruned or decayed
+    #[serde(default)]
+    pub pinned: bool,
+    /// Who can see the memory; `metadata.agent_id` stays the agent that stored it
+    #[serde(default)]
+    pub scope: MemoryScope,
 }
 
 impl Memory {
@@ -76,6 +148,8 @@ impl Memory {
             created_at: Utc::now(),
             associations: Vec::new(),
             tags: Vec::new(),
+            pinned: false,
+            scope: MemoryScope::Agent,
         }
     }
 
@@ -88,6 +162,12 @@ impl Memory {
         (-age_hours / 24.0).exp()
     }
 
+    /// Recency that halves every `half_life_hours`
+    pub fn recency_with_half_life(&self, half_life_hours: f32) -> f32 {
+        let age_hours = Utc::now().signed_duration_since(self.created_at).num_minutes().max(0) as f32 / 60.0;
+        0.5f32.powf(age_hours / half_life_hours.max(f32::EPSILON))
+    }
+
     /// Calculate frequency score based on access count
     pub fn frequency_score(&self) -> f32 {
         // Logarithmic scaling to prevent runaway frequency scores
@@ -157,11 +237,12 @@ pub struct Entity {
     pub mentions: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum EntityType {
     Person,
     Place,
     Organization,
+    Project,
     Date,
     Event,
     Concept,
@@ -188,8 +269,9 @@ pub struct ContextWindow {
 }
 
 /// Verification status for memory accuracy
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub enum VerificationStatus {
+    #[default]
     Unverified,
     Verified,
     Disputed,
@@ -209,6 +291,9 @@ pub struct MemoryQuery {
     pub entities: Option<Vec<String>>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    /// Also match facts the user rejected; only the inspector wants these
+    #[serde(default)]
+    pub include_rejected: bool,
 }
 
 impl MemoryQuery {
@@ -224,6 +309,7 @@ impl MemoryQuery {
             entities: None,
             limit: Some(10),
             offset: None,
+            include_rejected: false,
         }
     }
 
@@ -262,6 +348,8 @@ pub struct MemoryUpdate {
     pub tags: Option<Vec<String>>,
     pub associations: Option<Vec<Uuid>>,
     pub metadata_updates: Option<HashMap<String, serde_json::Value>>,
+    #[serde(default)]
+    pub pinned: Option<bool>,
 }
 
 /// Memory consolidation strategies
@@ -283,12 +371,89 @@ pub enum ConsolidationStrategy {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsolidationReport {
     pub memories_processed: usize,
+    /// Originals folded into a summary or merged memory
     pub memories_consolidated: usize,
+    /// Originals archived without a replacement
     pub memories_archived: usize,
+    /// Duplicates dropped (still recoverable from the archive)
     pub memories_deleted: usize,
+    /// Summary memories created by this run
     pub new_insights: Vec<Memory>,
     pub processing_time_ms: u64,
     pub space_saved_bytes: usize,
+    #[serde(default)]
+    pub memories_moved: usize,
+    #[serde(default)]
+    pub run_id: Option<Uuid>, // Pass to `MemoryManager::undo_consolidation`
+}
+
+impl ConsolidationReport {
+    /// Empty report for a run
+    pub fn new(run_id: Option<Uuid>) -> Self {
+        Self {
+            memories_processed: 0,
+            memories_consolidated: 0,
+            memories_archived: 0,
+            memories_deleted: 0,
+            new_insights: Vec::new(),
+            processing_time_ms: 0,
+            space_saved_bytes: 0,
+            memories_moved: 0,
+            run_id,
+        }
+    }
+}
+
+/// A memory taken out of the active layers; kept so it can be restored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedMemory {
+    pub memory: Memory,
+    pub archived_at: DateTime<Utc>,
+    pub run_id: Option<Uuid>,
+    pub replaced_by: Option<Uuid>,
+}
+
+/// Everything a consolidation run changed, so the run can be undone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidationRun {
+    pub id: Uuid,
+    pub started_at: DateTime<Utc>,
+    /// Summary memories created
+    pub created: Vec<Uuid>,
+    /// Originals moved to the archive
+    pub archived: Vec<Uuid>,
+    /// Memories as they were before being rewritten by a merge
+    pub previous_versions: Vec<Memory>,
+    /// Memories moved between layers, with the layer they came from
+    pub moved: Vec<(Uuid, MemoryLayer)>,
+    /// Associations linking summaries and merged memories to their sources
+    #[serde(default)]
+    pub associations: Vec<Uuid>,
+}
+
+impl ConsolidationRun {
+    pub fn new() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            started_at: Utc::now(),
+            created: Vec::new(),
+            archived: Vec::new(),
+            previous_versions: Vec::new(),
+            moved: Vec::new(),
+            associations: Vec::new(),
+        }
+    }
+
+    /// Whether the run changed anything
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.archived.is_empty() && self.previous_versions.is_empty() && self.moved.is_empty()
+    }
+}
+
+impl Default for ConsolidationRun {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Memory pruning strategies
@@ -325,6 +490,9 @@ pub struct Insight {
     pub supporting_memories: Vec<Uuid>,
     pub created_at: DateTime<Utc>,
     pub agent_id: String,
+    /// Set once the user confirms or rejects a `Preference` insight
+    #[serde(default)]
+    pub verification_status: VerificationStatus,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -376,6 +544,30 @@ pub struct MemoryAssociation {
     pub notes: Option<String>,
 }
 
+/// Two memories flagged as contradicting each other, awaiting the user's call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contradiction {
+    pub association_id: Uuid,
+    /// The older statement
+    pub first: Memory,
+    /// The newer statement
+    pub second: Memory,
+    pub explanation: Option<String>,
+}
+
+/// How the user settled a contradiction
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ContradictionResolution {
+    /// The older statement is right; the newer one is deprecated
+    KeepFirst,
+    /// The newer statement is right; the older one is deprecated
+    KeepSecond,
+    /// Both hold, they only looked contradictory
+    KeepBoth,
+    /// Neither is true any more
+    DiscardBoth,
+}
+
 impl Default for MemoryQuery {
     fn default() -> Self {
         Self::new()
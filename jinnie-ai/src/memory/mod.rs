@@ -13,19 +13,38 @@ pub mod memory_manager;
 pub mod importance_scorer;
 pub mod consolidation;
 pub mod retrieval;
+pub mod activation;
+pub mod ranking;
+pub mod persistence;
+pub mod scopes;
+pub mod temporal;
+pub mod verification;
+pub mod entities;
+pub mod knowledge_graph;
+pub mod maintenance;
 
 // Re-export commonly used types and structs
 pub use memory_types::{
     Memory, MemoryLayer, MemoryMetadata, MemorySource, MemoryQuery, MemoryUpdate,
     ConsolidationStrategy, ConsolidationReport, PruningStrategy, PruningReport,
     Insight, InsightType, Entity, EntityType, Sentiment, VerificationStatus,
-    AssociationType, MemoryAssociation, DateRange, ContextWindow
+    AssociationType, MemoryAssociation, DateRange, ContextWindow,
+    ArchivedMemory, ConsolidationRun, Contradiction, ContradictionResolution, MemoryScope
 };
 
 pub use memory_manager::{MemoryManager, MemoryStats};
-pub use importance_scorer::{ImportanceScorer, ImportanceTrends};
-pub use consolidation::ConsolidationEngine;
-pub use retrieval::{MemoryRetrieval, SearchResult};
+pub use importance_scorer::{ImportanceFeedback, ImportanceReport, ImportanceScorer, ImportanceTrends, LearnedWeights};
+pub use consolidation::{ConsolidationEngine, MemorySummarizer, OllamaSummarizer};
+pub use retrieval::{AssociativeResult, MemoryRetrieval, SearchResult};
+pub use activation::{ActivationStep, EdgeWeights, SpreadingConfig};
+pub use ranking::{CrossEncoder, LayerWeights, RankingConfig};
+pub use persistence::{MemoryStore, MemoryWrite};
+pub use entities::{EntityExtractor, EntityRecognizer, OllamaEntityRecognizer};
+pub use knowledge_graph::{EntityNode, EntityRelation, KnowledgeGraph, RelatedEntity, RelationKind};
+pub use scopes::{ScopeConfig, ScopePermissions, ScopeWeights};
+pub use temporal::{DateParser, TemporalMatch, UserTimezone};
+pub use verification::{VerificationAnswer, VerificationRequest, VerificationSubject};
+pub use maintenance::{AgentMaintenance, MaintenancePolicy, MaintenanceReport, MaintenanceScheduler, MaintenanceTrigger, PressureCheck};
 
 use anyhow::Result;
 use std::sync::Arc;
@@ -41,42 +60,365 @@ pub struct MemoryCoordinator {
     importance_scorer: Arc<ImportanceScorer>,
     consolidation_engine: Arc<ConsolidationEngine>,
     retrieval_engine: Arc<MemoryRetrieval>,
+    maintenance: MaintenanceScheduler,
+    date_parser: DateParser,
+    /// Model pass for entities rules miss, run after a memory is stored
+    entity_recognizer: Option<Arc<dyn EntityRecognizer>>,
 }
 
 impl MemoryCoordinator {
-    /// Create a new memory coordinator
+    /// Create a new memory coordinator, reopening persisted memories when enabled
     pub async fn new(config: AppConfig, vector_store: Option<Arc<VectorStore>>) -> Result<Self> {
-        let memory_manager = Arc::new(Mutex::new(
-            MemoryManager::new(config.clone(), vector_store.clone()).await?
-        ));
+        let mut manager = if config.memory.persistence_enabled {
+            let store = MemoryStore::open(&config.data_dir_path())?;
+            log::info!("Memory database: {}", store.path().display());
+            MemoryManager::with_store(config.clone(), vector_store, store).await?
+        } else {
+            MemoryManager::new(config.clone(), vector_store).await?
+        };
+        if let Err(e) = manager.index_missing_memories().await {
+            log::warn!("Failed to index existing memories in the vector store: {}", e);
+        }
+        manager.set_summarizer(Arc::new(OllamaSummarizer::default()));
+        let entity_recognizer: Option<Arc<dyn EntityRecognizer>> = if config.memory.entity_recognition_enabled {
+            Some(Arc::new(OllamaEntityRecognizer::default()))
+        } else {
+            None
+        };
+        let memory_manager = Arc::new(Mutex::new(manager));
+        let maintenance = MaintenanceScheduler::new(memory_manager.clone(), MaintenancePolicy::from_config(&config.memory));
 
         let importance_scorer = Arc::new(ImportanceScorer::new());
         let consolidation_engine = Arc::new(ConsolidationEngine::new());
-        let retrieval_engine = Arc::new(MemoryRetrieval::new(vector_store));
+        let retrieval_engine = Arc::new(MemoryRetrieval::new());
 
         Ok(Self {
             memory_manager,
             importance_scorer,
             consolidation_engine,
             retrieval_engine,
+            maintenance,
+            date_parser: DateParser::from_config(&config),
+            entity_recognizer,
         })
     }
+
+    /// Use `recognizer` for people, projects and organizations in new memories
+    pub fn set_entity_recognizer(&mut self, recognizer: Arc<dyn EntityRecognizer>) {
+        self.entity_recognizer = Some(recognizer);
+    }
+
+    /// Run the entity model over new memories in the background and merge what it finds.
+    ///
+    /// The model can take seconds per memory, so it runs without the manager lock; the
+    /// lock is only taken to add the entities.
+    fn recognize_entities_later(&self, memories: &[Memory]) -> Option<tokio::task::JoinHandle<()>> {
+        let recognizer = self.entity_recognizer.clone()?;
+        let manager = self.memory_manager.clone();
+        let pending: Vec<(uuid::Uuid, String)> = memories.iter().map(|memory| (memory.id, memory.content.clone())).collect();
+
+        Some(tokio::spawn(async move {
+            for (id, content) in pending {
+                let entities = match recognizer.recognize(&content).await {
+                    Ok(entities) if !entities.is_empty() => entities,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        log::warn!("Model entity extraction failed for memory {}: {}", id, e);
+                        continue;
+                    }
+                };
+                if let Err(e) = manager.lock().await.add_entities(id, entities).await {
+                    log::warn!("Failed to add recognized entities to memory {}: {}", id, e);
+                }
+            }
+        }))
+    }
     
-    /// Search memories
+    /// Search memories; a date phrase like "last Tuesday" narrows by when they were made
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
-        self.retrieval_engine.search(query, limit).await
+        let mut memory_query = MemoryQuery::new();
+        memory_query.text_query = self.apply_date_phrase(query, &mut memory_query);
+        memory_query.limit = Some(limit);
+
+        let mut manager = self.memory_manager.lock().await;
+        manager.retrieve(memory_query).await
     }
     
     /// Add a new memory
-    pub async fn add_memory(&self, memory: Memory) -> Result<()> {
-        let mut manager = self.memory_manager.lock().await;
-        manager.add_memory(memory).await
+    pub async fn add_memory(&self, content: String, metadata: MemoryMetadata) -> Result<Memory> {
+        let memory = self.memory_manager.lock().await.store(content, metadata).await?;
+        self.recognize_entities_later(std::slice::from_ref(&memory));
+        Ok(memory)
     }
     
+    /// Memories relevant to `query` for one agent, best first
+    pub async fn recall(&self, agent_id: &str, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let mut manager = self.memory_manager.lock().await;
+
+        let mut memory_query = MemoryQuery::new().with_agent(agent_id.to_string());
+        memory_query.text_query = self.apply_date_phrase(query, &mut memory_query);
+        let to_embed = memory_query.text_query.clone().unwrap_or_else(|| query.to_string());
+        memory_query.semantic_query = match manager.embed_text(&to_embed).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                log::warn!("Falling back to text-only memory recall: {}", e);
+                None
+            }
+        };
+        memory_query.limit = Some(limit);
+
+        manager.search_with_scores(memory_query).await
+    }
+
+    /// Memories relevant to `query` plus those linked to them by associations, with the reason
+    /// each linked one came up
+    pub async fn recall_associated(&self, agent_id: &str, query: &str, limit: usize) -> Result<Vec<AssociativeResult>> {
+        let mut manager = self.memory_manager.lock().await;
+
+        let mut memory_query = MemoryQuery::new().with_agent(agent_id.to_string());
+        memory_query.text_query = self.apply_date_phrase(query, &mut memory_query);
+        let to_embed = memory_query.text_query.clone().unwrap_or_else(|| query.to_string());
+        memory_query.semantic_query = manager.embed_text(&to_embed).await.unwrap_or_else(|e| {
+            log::warn!("Falling back to text-only associative recall: {}", e);
+            None
+        });
+        memory_query.limit = Some(limit);
+
+        manager.search_associative(memory_query).await
+    }
+
+    /// Set the query's date range from a date phrase in `text`, returning the text left to match
+    fn apply_date_phrase(&self, text: &str, memory_query: &mut MemoryQuery) -> Option<String> {
+        let Some(found) = self.date_parser.parse(text) else {
+            return Some(text.to_string());
+        };
+        log::debug!("Reading \"{}\" as {} .. {}", found.phrase, found.range.start, found.range.end);
+        memory_query.date_range = Some(found.range);
+        (!found.remainder.is_empty()).then_some(found.remainder)
+    }
+
+    /// Store both sides of a chat exchange as memories
+    pub async fn remember_exchange(
+        &self,
+        agent_id: &str,
+        session_id: &str,
+        user_message: &str,
+        agent_reply: &str,
+    ) -> Result<Vec<Memory>> {
+        let mut manager = self.memory_manager.lock().await;
+        let mut stored = Vec::new();
+
+        for (content, source) in [
+            (user_message, MemorySource::UserInput),
+            (agent_reply, MemorySource::AgentResponse),
+        ] {
+            if content.trim().is_empty() {
+                continue;
+            }
+
+            let metadata = MemoryMetadata {
+                source,
+                agent_id: agent_id.to_string(),
+                conversation_id: Some(agent_id.to_string()),
+                session_id: Some(session_id.to_string()),
+                topics: Vec::new(),
+                entities: Vec::new(),
+                sentiment: None,
+                context_window: None,
+                verification_status: VerificationStatus::Unverified,
+                custom_fields: std::collections::HashMap::new(),
+            };
+            stored.push(manager.store(content.to_string(), metadata).await?);
+        }
+        drop(manager);
+
+        self.recognize_entities_later(&stored);
+        Ok(stored)
+    }
+
+    /// Run a consolidation pass over all layers
+    pub async fn consolidate(&self) -> Result<ConsolidationReport> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.consolidate().await
+    }
+
+    /// Revert a consolidation run by the `run_id` from its report
+    pub async fn undo_consolidation(&self, run_id: uuid::Uuid) -> Result<usize> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.undo_consolidation(run_id).await
+    }
+
+    /// Reflect over all memories, flagging contradictions and linking causes
+    pub async fn reflect(&self) -> Result<Vec<Insight>> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.reflect().await
+    }
+
+    /// Contradictions waiting for the user to pick a side
+    pub async fn pending_contradictions(&self) -> Vec<Contradiction> {
+        let manager = self.memory_manager.lock().await;
+        manager.pending_contradictions()
+    }
+
+    /// Apply the user's answer to a contradiction
+    pub async fn resolve_contradiction(&self, association_id: uuid::Uuid, resolution: ContradictionResolution) -> Result<()> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.resolve_contradiction(association_id, resolution).await
+    }
+
+    /// Everything remembered about an entity, e.g. "Project Atlas"
+    pub async fn memories_about(&self, entity_name: &str) -> Result<Vec<Memory>> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.memories_about(entity_name)
+    }
+
+    /// Entities linked to `entity_name`; pass `EntityType::Person` for "who is related to Alice"
+    pub async fn related_entities(&self, entity_name: &str, entity_type: Option<EntityType>) -> Result<Vec<RelatedEntity>> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.related_entities(entity_name, entity_type)
+    }
+
+    /// Store a fact told to `agent_id` in `scope`, so other agents can recall it
+    pub async fn remember_fact(&self, agent_id: &str, content: String, scope: MemoryScope) -> Result<Memory> {
+        let metadata = MemoryMetadata {
+            source: MemorySource::UserInput,
+            agent_id: agent_id.to_string(),
+            conversation_id: None,
+            session_id: None,
+            topics: Vec::new(),
+            entities: Vec::new(),
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Unverified,
+            custom_fields: std::collections::HashMap::new(),
+        };
+        let memory = self.memory_manager.lock().await.store_in_scope(content, metadata, scope).await?;
+        self.recognize_entities_later(std::slice::from_ref(&memory));
+        Ok(memory)
+    }
+
+    /// Move a memory to another scope on behalf of `agent_id`
+    pub async fn promote_memory(&self, id: uuid::Uuid, scope: MemoryScope, agent_id: &str) -> Result<Memory> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.promote(id, scope, agent_id).await
+    }
+
+    /// Inferred facts waiting for the user to confirm, reject or correct
+    pub async fn pending_verifications(&self) -> Result<Vec<VerificationRequest>> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.pending_verifications()
+    }
+
+    /// Apply the user's answer to a verification request
+    pub async fn answer_verification(&self, subject: VerificationSubject, answer: VerificationAnswer) -> Result<Option<Memory>> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.answer_verification(subject, answer).await
+    }
+
+    /// Verified facts about the user that `agent_id` can see
+    pub async fn user_profile(&self, agent_id: &str) -> Result<Vec<Memory>> {
+        let manager = self.memory_manager.lock().await;
+        manager.user_profile(agent_id)
+    }
+
+    /// Memories matching `query` for the inspector; does not count as an access
+    pub async fn browse(&self, query: MemoryQuery) -> Result<Vec<Memory>> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.browse(query).await
+    }
+
+    /// Apply a user edit to one memory
+    pub async fn update_memory(&self, id: uuid::Uuid, updates: MemoryUpdate) -> Result<Memory> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.update(id, updates).await
+    }
+
+    /// Pin or unpin memories so maintenance leaves them alone; pinning teaches the scorer
+    pub async fn set_pinned(&self, ids: &[uuid::Uuid], pinned: bool) -> Result<usize> {
+        let mut manager = self.memory_manager.lock().await;
+        if pinned {
+            for id in ids {
+                manager.record_feedback(*id, ImportanceFeedback::Pinned)?;
+            }
+        }
+        manager.set_pinned(ids, pinned)
+    }
+
+    /// Forget memories the user no longer wants kept, teaching the scorer they didn't matter
+    pub async fn forget_memories(&self, ids: &[uuid::Uuid]) -> Result<usize> {
+        let mut manager = self.memory_manager.lock().await;
+        for id in ids {
+            manager.record_feedback(*id, ImportanceFeedback::Forgotten)?;
+        }
+        manager.forget_many(ids).await
+    }
+
+    /// Record the user's rating of a memory, from 0.0 (irrelevant) to 1.0 (essential)
+    pub async fn rate_memory(&self, id: uuid::Uuid, rating: f32) -> Result<Memory> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.record_feedback(id, ImportanceFeedback::Rating(rating))?
+            .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id))
+    }
+
+    /// Note that recalled memories were drawn on in a reply
+    pub async fn record_memory_use(&self, ids: &[uuid::Uuid]) -> Result<()> {
+        let mut manager = self.memory_manager.lock().await;
+        for id in ids {
+            manager.record_feedback(*id, ImportanceFeedback::Used)?;
+        }
+        Ok(())
+    }
+
+    /// What the assistant has learned to treat as important
+    pub async fn importance_report(&self, agent_id: Option<&str>) -> Result<ImportanceReport> {
+        let mut manager = self.memory_manager.lock().await;
+        manager.importance_report(agent_id)
+    }
+
+    /// Associations touching a memory
+    pub async fn associations_of(&self, id: uuid::Uuid) -> Vec<MemoryAssociation> {
+        let manager = self.memory_manager.lock().await;
+        manager.associations_of(id)
+    }
+
+    /// Personal data redacted from memories since startup
+    pub async fn redaction_report(&self) -> crate::privacy::RedactionReport {
+        let manager = self.memory_manager.lock().await;
+        manager.redaction_report().clone()
+    }
+
+    /// Start periodic maintenance, deferring runs while the system is busy
+    pub async fn start_maintenance(&self) {
+        if let Some(check) = maintenance::system_pressure_check().await {
+            self.maintenance.set_pressure_check(check);
+        }
+        self.maintenance.start();
+    }
+
+    /// Stop periodic maintenance, cancelling a run in progress
+    pub async fn stop_maintenance(&self) {
+        self.maintenance.stop().await;
+    }
+
+    /// Run consolidation, pruning, reflection and decay right away
+    pub async fn run_maintenance_now(&self) -> MaintenanceReport {
+        self.maintenance.run_now().await
+    }
+
+    /// Past maintenance runs, most recent first
+    pub async fn maintenance_history(&self) -> Vec<MaintenanceReport> {
+        self.maintenance.history().await
+    }
+
     /// Get memory statistics
     pub async fn get_stats(&self) -> Result<MemoryStats> {
         let manager = self.memory_manager.lock().await;
-        Ok(manager.get_stats())
+        Ok(manager.get_stats().clone())
+    }
+
+    /// Shared handle to the underlying memory manager
+    pub fn memory_manager(&self) -> Arc<Mutex<MemoryManager>> {
+        self.memory_manager.clone()
     }
 }
 
@@ -99,4 +441,100 @@ pub async fn initialize_memory_system(state: &AppState) -> Result<()> {
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_coordinator_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = AppConfig::default();
+        config.paths.data_dir = dir.path().to_string_lossy().to_string();
+        config.memory.entity_recognition_enabled = false;
+
+        let metadata = MemoryMetadata {
+            source: MemorySource::UserInput,
+            agent_id: "agent1".to_string(),
+            conversation_id: None,
+            session_id: None,
+            topics: vec![],
+            entities: vec![],
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Unverified,
+            custom_fields: HashMap::new(),
+        };
+
+        let coordinator = MemoryCoordinator::new(config.clone(), None).await.unwrap();
+        let stored = coordinator.add_memory("My favourite editor is Helix".to_string(), metadata).await.unwrap();
+        drop(coordinator);
+
+        let coordinator = MemoryCoordinator::new(config, None).await.unwrap();
+        assert_eq!(coordinator.get_stats().await.unwrap().total_memories, 1);
+
+        let results = coordinator.search("favourite editor", 5).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, stored.id);
+    }
+
+    #[tokio::test]
+    async fn test_exchanges_are_recalled_per_agent() {
+        let mut config = AppConfig::default();
+        config.memory.persistence_enabled = false;
+        config.memory.entity_recognition_enabled = false;
+        let coordinator = MemoryCoordinator::new(config, None).await.unwrap();
+
+        let stored = coordinator
+            .remember_exchange("agent1", "session1", "My dog is called Biscuit", "Biscuit is a lovely name!")
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].metadata.source, MemorySource::UserInput);
+        assert_eq!(stored[1].metadata.source, MemorySource::AgentResponse);
+        assert_eq!(stored[0].metadata.session_id.as_deref(), Some("session1"));
+
+        let recalled = coordinator.recall("agent1", "what is my dog called", 5).await.unwrap();
+        assert!(recalled.iter().any(|r| r.memory.id == stored[0].id));
+
+        let other_agent = coordinator.recall("agent2", "what is my dog called", 5).await.unwrap();
+        assert!(other_agent.is_empty());
+    }
+
+    struct SlowRecognizer;
+
+    #[async_trait::async_trait]
+    impl EntityRecognizer for SlowRecognizer {
+        async fn recognize(&self, text: &str) -> Result<Vec<Entity>> {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            Ok(text.contains("Biscuit").then(|| Entity {
+                name: "Biscuit".to_string(),
+                entity_type: EntityType::Other("pet".to_string()),
+                confidence: 0.8,
+                mentions: vec!["Biscuit".to_string()],
+            }).into_iter().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entities_are_recognized_after_the_store() {
+        let mut config = AppConfig::default();
+        config.memory.persistence_enabled = false;
+        config.memory.entity_recognition_enabled = false;
+        let mut coordinator = MemoryCoordinator::new(config, None).await.unwrap();
+        coordinator.set_entity_recognizer(Arc::new(SlowRecognizer));
+
+        let stored = coordinator
+            .remember_exchange("agent1", "session1", "My dog is called Biscuit", "")
+            .await
+            .unwrap();
+        assert!(!stored[0].metadata.entities.iter().any(|entity| entity.name == "Biscuit"));
+
+        // The store returned without waiting for the model; the entity arrives afterwards
+        coordinator.recognize_entities_later(&stored).unwrap().await.unwrap();
+        let manager = coordinator.memory_manager.lock().await;
+        let memory = manager.get_memory(stored[0].id).unwrap();
+        assert!(memory.metadata.entities.iter().any(|entity| entity.name == "Biscuit"));
+    }
+}
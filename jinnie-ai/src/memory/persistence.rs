@@ -0,0 +1,920 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use super::importance_scorer::LearnedWeights;
+use super::memory_types::{ArchivedMemory, ConsolidationRun, Insight, Memory, MemoryAssociation, MemoryLayer, MemoryScope};
+use super::verification::is_profile_fact;
+use crate::storage::encryption::{active_cipher, DataCipher};
+use crate::utils::error::LocalMindError;
+use crate::vector::embedding_cache::{decode_vector, encode_vector};
+
+/// File name of the memory database inside the data directory
+pub const MEMORY_DB_FILE: &str = "memory.sqlite";
+
+/// Schema migrations, applied in order; `PRAGMA user_version` records how many ran
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE memories (
+         id TEXT PRIMARY KEY,
+         layer TEXT NOT NULL,
+         agent_id TEXT NOT NULL,
+         content TEXT NOT NULL,
+         metadata TEXT NOT NULL,
+         importance REAL NOT NULL,
+         access_count INTEGER NOT NULL DEFAULT 0,
+         last_accessed TEXT NOT NULL,
+         created_at TEXT NOT NULL,
+         tags TEXT NOT NULL,
+         associations TEXT NOT NULL
+     );
+     CREATE INDEX idx_memories_layer ON memories(layer);
+     CREATE INDEX idx_memories_agent ON memories(agent_id);
+
+     CREATE TABLE memory_embeddings (
+         memory_id TEXT PRIMARY KEY REFERENCES memories(id) ON DELETE CASCADE,
+         dimension INTEGER NOT NULL,
+         vector BLOB NOT NULL
+     );
+
+     CREATE TABLE memory_associations (
+         id TEXT PRIMARY KEY,
+         memory_a TEXT NOT NULL,
+         memory_b TEXT NOT NULL,
+         association_type TEXT NOT NULL,
+         strength REAL NOT NULL,
+         created_at TEXT NOT NULL,
+         notes TEXT
+     );
+     CREATE INDEX idx_associations_a ON memory_associations(memory_a);
+     CREATE INDEX idx_associations_b ON memory_associations(memory_b);
+
+     CREATE TABLE memory_insights (
+         id TEXT PRIMARY KEY,
+         agent_id TEXT NOT NULL,
+         insight_type TEXT NOT NULL,
+         content TEXT NOT NULL,
+         confidence REAL NOT NULL,
+         supporting_memories TEXT NOT NULL,
+         created_at TEXT NOT NULL
+     );",
+    // 2: archive for consolidated memories and the journal used to undo runs
+    "CREATE TABLE memory_archive (
+         id TEXT PRIMARY KEY,
+         memory TEXT NOT NULL,
+         archived_at TEXT NOT NULL,
+         run_id TEXT,
+         replaced_by TEXT
+     );
+     CREATE INDEX idx_archive_run ON memory_archive(run_id);
+
+     CREATE TABLE consolidation_runs (
+         id TEXT PRIMARY KEY,
+         started_at TEXT NOT NULL,
+         journal TEXT NOT NULL
+     );",
+    // 3: memories the user pinned from the inspector
+    "ALTER TABLE memories ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;",
+    // 4: memories shared with a team or with every agent
+    "ALTER TABLE memories ADD COLUMN scope TEXT NOT NULL DEFAULT 'agent';
+     CREATE INDEX idx_memories_scope ON memories(scope);",
+    // 5: whether the user confirmed or rejected an insight
+    "ALTER TABLE memory_insights ADD COLUMN verification_status TEXT NOT NULL DEFAULT '\"Unverified\"';",
+    // 6: importance keyword weights learned from each user's feedback
+    "CREATE TABLE learned_weights (
+         user_id TEXT PRIMARY KEY,
+         weights TEXT NOT NULL,
+         updated_at TEXT NOT NULL
+     );",
+    // 7: verified profile facts, found without decrypting every memory; NULL until classified
+    "ALTER TABLE memories ADD COLUMN profile INTEGER;
+     CREATE INDEX idx_memories_profile ON memories(profile);",
+];
+
+/// A single change to persisted memory state
+#[derive(Debug, Clone)]
+pub enum MemoryWrite {
+    /// Insert or replace a memory (and its embedding)
+    UpsertMemory(Memory),
+    /// Remove a memory along with its embedding and associations
+    DeleteMemory(Uuid),
+    /// Record updated access statistics
+    RecordAccess { id: Uuid, access_count: u32, last_accessed: DateTime<Utc> },
+    /// Insert or replace an association
+    UpsertAssociation(MemoryAssociation),
+    /// Remove an association
+    DeleteAssociation(Uuid),
+    /// Insert or replace an insight
+    UpsertInsight(Insight),
+    /// Move a memory out of the active layers into the archive, keeping its associations
+    ArchiveMemory(ArchivedMemory),
+    /// Drop an archive entry (the memory itself is written back separately)
+    DeleteArchived(Uuid),
+    /// Record the journal of a consolidation run
+    UpsertConsolidationRun(ConsolidationRun),
+    /// Forget a consolidation run once it has been undone
+    DeleteConsolidationRun(Uuid),
+    /// Replace a user's learned importance weights
+    UpsertLearnedWeights { user_id: String, weights: LearnedWeights },
+}
+
+/// SQLite-backed storage for the memory hierarchy.
+///
+/// When a cipher is available, memory content, metadata, tags, embeddings, association notes,
+/// insights, the archive and run journals are encrypted; ids, layers, agent ids and scores stay
+/// in clear so they can be indexed.
+pub struct MemoryStore {
+    conn: Mutex<Connection>,
+    path: PathBuf,
+    cipher: Option<Arc<DataCipher>>,
+}
+
+impl MemoryStore {
+    /// Open (or create) `memory.sqlite` in `dir` and bring its schema up to date
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(MEMORY_DB_FILE);
+
+        let conn = Connection::open(&path)
+            .map_err(|e| anyhow!("Failed to open memory database {}: {}", path.display(), e))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             PRAGMA foreign_keys = ON;",
+        )?;
+
+        let mut store = Self { conn: Mutex::new(conn), path, cipher: None };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// Use `cipher` instead of the globally unlocked one
+    pub fn with_cipher(mut self, cipher: Arc<DataCipher>) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Path of the memory database
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of migrations applied to the database
+    pub fn schema_version(&self) -> Result<usize> {
+        let conn = self.lock()?;
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        Ok(version as usize)
+    }
+
+    /// Memory counts per layer, without loading the memories themselves
+    pub fn layer_counts(&self) -> Result<HashMap<MemoryLayer, usize>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare("SELECT layer, COUNT(*) FROM memories GROUP BY layer")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+
+        let mut counts = HashMap::new();
+        for row in rows {
+            let (name, count) = row?;
+            if let Some(layer) = MemoryLayer::from_name(&name) {
+                counts.insert(layer, count as usize);
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Agents owning memories in `layers`, without loading the memories themselves
+    pub fn agent_ids(&self, layers: &[MemoryLayer]) -> Result<Vec<String>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare("SELECT DISTINCT agent_id FROM memories WHERE layer = ?1")?;
+
+        let mut agents = Vec::new();
+        for layer in layers {
+            for agent in stmt.query_map(params![layer.as_str()], |row| row.get::<_, String>(0))? {
+                agents.push(agent?);
+            }
+        }
+        Ok(agents)
+    }
+
+    /// Load every memory stored in `layer`, embeddings included
+    pub fn load_layer(&self, layer: MemoryLayer) -> Result<Vec<Memory>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT m.id, m.content, m.metadata, m.importance, m.access_count, m.last_accessed,
+                    m.created_at, m.tags, m.associations, e.vector, m.pinned, m.scope
+             FROM memories m
+             LEFT JOIN memory_embeddings e ON e.memory_id = m.id
+             WHERE m.layer = ?1",
+        )?;
+
+        let codec = self.codec();
+        let rows = stmt.query_map(params![layer.as_str()], |row| read_memory_row(row, layer, &codec))?;
+        let mut memories = Vec::new();
+        for row in rows {
+            memories.push(row??);
+        }
+        Ok(memories)
+    }
+
+    /// Load the memories making up the user profile, from every layer.
+    ///
+    /// Rows written before the profile column existed are classified on the first call.
+    pub fn load_profile(&self) -> Result<Vec<Memory>> {
+        let conn = self.lock()?;
+        let codec = self.codec();
+        let mut candidates = Vec::new();
+        {
+            let mut stmt = conn.prepare(
+                "SELECT m.id, m.content, m.metadata, m.importance, m.access_count, m.last_accessed,
+                        m.created_at, m.tags, m.associations, e.vector, m.pinned, m.scope, m.layer, m.profile
+                 FROM memories m
+                 LEFT JOIN memory_embeddings e ON e.memory_id = m.id
+                 WHERE m.profile IS NULL OR m.profile = 1",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                let layer: String = row.get(12)?;
+                let classified: Option<bool> = row.get(13)?;
+                let layer = MemoryLayer::from_name(&layer).unwrap_or(MemoryLayer::Working);
+                Ok((read_memory_row(row, layer, &codec)?, classified.is_some()))
+            })?;
+            for row in rows {
+                let (memory, classified) = row?;
+                candidates.push((memory?, classified));
+            }
+        }
+
+        let unclassified: Vec<(String, bool)> = candidates.iter()
+            .filter(|(_, classified)| !classified)
+            .map(|(memory, _)| (memory.id.to_string(), is_profile_fact(memory)))
+            .collect();
+        for (id, profile) in &unclassified {
+            conn.execute("UPDATE memories SET profile = ?2 WHERE id = ?1", params![id, profile])?;
+        }
+
+        Ok(candidates.into_iter()
+            .map(|(memory, _)| memory)
+            .filter(is_profile_fact)
+            .collect())
+    }
+
+    /// Load all associations
+    pub fn load_associations(&self) -> Result<Vec<MemoryAssociation>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, memory_a, memory_b, association_type, strength, created_at, notes
+             FROM memory_associations",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, f64>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, Option<String>>(6)?,
+            ))
+        })?;
+
+        let codec = self.codec();
+        let mut associations = Vec::new();
+        for row in rows {
+            let (id, memory_a, memory_b, association_type, strength, created_at, notes) = row?;
+            associations.push(MemoryAssociation {
+                id: parse_uuid(&id)?,
+                memory_a: parse_uuid(&memory_a)?,
+                memory_b: parse_uuid(&memory_b)?,
+                association_type: serde_json::from_str(&association_type)?,
+                strength: strength as f32,
+                created_at: parse_time(&created_at)?,
+                notes: notes.as_deref().map(|notes| codec.open_text(notes)).transpose()?,
+            });
+        }
+        Ok(associations)
+    }
+
+    /// Load all insights, oldest first
+    pub fn load_insights(&self) -> Result<Vec<Insight>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, agent_id, insight_type, content, confidence, supporting_memories, created_at,
+                    verification_status
+             FROM memory_insights ORDER BY created_at",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, f64>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        })?;
+
+        let codec = self.codec();
+        let mut insights = Vec::new();
+        for row in rows {
+            let (id, agent_id, insight_type, content, confidence, supporting, created_at, verification_status) = row?;
+            insights.push(Insight {
+                id: parse_uuid(&id)?,
+                insight_type: serde_json::from_str(&insight_type)?,
+                content: codec.open_text(&content)?,
+                confidence: confidence as f32,
+                supporting_memories: serde_json::from_str(&supporting)?,
+                created_at: parse_time(&created_at)?,
+                agent_id,
+                verification_status: serde_json::from_str(&verification_status)?,
+            });
+        }
+        Ok(insights)
+    }
+
+    /// Load the archive of memories removed by consolidation
+    pub fn load_archive(&self) -> Result<Vec<ArchivedMemory>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare("SELECT memory, archived_at, run_id, replaced_by FROM memory_archive")?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+
+        let codec = self.codec();
+        let mut archive = Vec::new();
+        for row in rows {
+            let (memory, archived_at, run_id, replaced_by) = row?;
+            archive.push(ArchivedMemory {
+                memory: serde_json::from_str(&codec.open_text(&memory)?)?,
+                archived_at: parse_time(&archived_at)?,
+                run_id: run_id.as_deref().map(parse_uuid).transpose()?,
+                replaced_by: replaced_by.as_deref().map(parse_uuid).transpose()?,
+            });
+        }
+        Ok(archive)
+    }
+
+    /// Load consolidation run journals, oldest first
+    pub fn load_consolidation_runs(&self) -> Result<Vec<ConsolidationRun>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare("SELECT journal FROM consolidation_runs ORDER BY started_at")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let codec = self.codec();
+        let mut runs = Vec::new();
+        for row in rows {
+            runs.push(serde_json::from_str(&codec.open_text(&row?)?)?);
+        }
+        Ok(runs)
+    }
+
+    /// Load the importance weights learned for `user_id`, if any
+    pub fn load_learned_weights(&self, user_id: &str) -> Result<Option<LearnedWeights>> {
+        let conn = self.lock()?;
+        let sealed: Option<String> = conn
+            .query_row("SELECT weights FROM learned_weights WHERE user_id = ?1", params![user_id], |row| row.get(0))
+            .optional()?;
+        sealed
+            .map(|sealed| -> Result<LearnedWeights> { Ok(serde_json::from_str(&self.codec().open_text(&sealed)?)?) })
+            .transpose()
+    }
+
+    fn load_all_learned_weights(&self) -> Result<Vec<(String, LearnedWeights)>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare("SELECT user_id, weights FROM learned_weights")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+        let codec = self.codec();
+        let mut weights = Vec::new();
+        for row in rows {
+            let (user_id, sealed) = row?;
+            weights.push((user_id, serde_json::from_str(&codec.open_text(&sealed)?)?));
+        }
+        Ok(weights)
+    }
+
+    /// Apply a set of writes atomically: either all of them land or none do
+    pub fn apply(&self, writes: &[MemoryWrite]) -> Result<()> {
+        if writes.is_empty() {
+            return Ok(());
+        }
+
+        let codec = self.codec();
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        for write in writes {
+            apply_write(&tx, write, &codec)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Rewrite every row with the current cipher, e.g. after enabling encryption or rotating keys
+    pub fn reseal(&self) -> Result<()> {
+        let mut writes = Vec::new();
+        for layer in MemoryLayer::ALL {
+            writes.extend(self.load_layer(layer)?.into_iter().map(MemoryWrite::UpsertMemory));
+        }
+        writes.extend(self.load_associations()?.into_iter().map(MemoryWrite::UpsertAssociation));
+        writes.extend(self.load_insights()?.into_iter().map(MemoryWrite::UpsertInsight));
+        writes.extend(self.load_consolidation_runs()?.into_iter().map(MemoryWrite::UpsertConsolidationRun));
+        writes.extend(self.load_all_learned_weights()?.into_iter()
+            .map(|(user_id, weights)| MemoryWrite::UpsertLearnedWeights { user_id, weights }));
+
+        // Archiving deletes the active row, so archive entries are rewritten directly
+        let archive = self.load_archive()?;
+        self.apply(&writes)?;
+
+        let codec = self.codec();
+        let conn = self.lock()?;
+        for entry in &archive {
+            conn.execute(
+                "UPDATE memory_archive SET memory = ?2 WHERE id = ?1",
+                params![entry.memory.id.to_string(), codec.seal_text(&serde_json::to_string(&entry.memory)?)?],
+            )?;
+        }
+
+        // Don't leave the old plaintext or ciphertext behind in free pages or the WAL
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        log::info!("Re-encrypted memory database ({} rows)", writes.len() + archive.len());
+        Ok(())
+    }
+
+    fn codec(&self) -> Codec {
+        Codec(self.cipher.clone().or_else(active_cipher))
+    }
+
+    fn migrate(&mut self) -> Result<()> {
+        let conn = self.conn.get_mut().map_err(|_| anyhow!("Memory database lock poisoned"))?;
+        let current: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let current = current as usize;
+
+        if current > MIGRATIONS.len() {
+            return Err(anyhow!(
+                "Memory database {} has schema version {}, newer than this build supports ({})",
+                self.path.display(), current, MIGRATIONS.len()
+            ));
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", (index + 1) as i64)?;
+            tx.commit()?;
+            log::info!("Applied memory schema migration {}", index + 1);
+        }
+
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|_| anyhow!("Memory database lock poisoned"))
+    }
+}
+
+fn apply_write(tx: &Transaction<'_>, write: &MemoryWrite, codec: &Codec) -> Result<()> {
+    match write {
+        MemoryWrite::UpsertMemory(memory) => {
+            let id = memory.id.to_string();
+            tx.execute(
+                "INSERT INTO memories
+                     (id, layer, agent_id, content, metadata, importance, access_count,
+                      last_accessed, created_at, tags, associations, pinned, scope, profile)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+                 ON CONFLICT(id) DO UPDATE SET
+                     layer = excluded.layer, agent_id = excluded.agent_id, content = excluded.content,
+                     metadata = excluded.metadata, importance = excluded.importance,
+                     access_count = excluded.access_count, last_accessed = excluded.last_accessed,
+                     tags = excluded.tags, associations = excluded.associations,
+                     pinned = excluded.pinned, scope = excluded.scope, profile = excluded.profile",
+                params![
+                    id,
+                    memory.layer.as_str(),
+                    memory.metadata.agent_id,
+                    codec.seal_text(&memory.content)?,
+                    codec.seal_text(&serde_json::to_string(&memory.metadata)?)?,
+                    memory.importance_score as f64,
+                    memory.access_count as i64,
+                    memory.last_accessed.to_rfc3339(),
+                    memory.created_at.to_rfc3339(),
+                    codec.seal_text(&serde_json::to_string(&memory.tags)?)?,
+                    serde_json::to_string(&memory.associations)?,
+                    memory.pinned,
+                    memory.scope.as_key(),
+                    is_profile_fact(memory),
+                ],
+            )?;
+
+            match memory.embedding {
+                Some(ref embedding) => {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO memory_embeddings (memory_id, dimension, vector) VALUES (?1, ?2, ?3)",
+                        params![id, embedding.len() as i64, codec.seal_bytes(encode_vector(embedding))?],
+                    )?;
+                }
+                None => {
+                    tx.execute("DELETE FROM memory_embeddings WHERE memory_id = ?1", params![id])?;
+                }
+            }
+        }
+        MemoryWrite::DeleteMemory(id) => {
+            let id = id.to_string();
+            tx.execute("DELETE FROM memories WHERE id = ?1", params![id])?;
+            tx.execute(
+                "DELETE FROM memory_associations WHERE memory_a = ?1 OR memory_b = ?1",
+                params![id],
+            )?;
+        }
+        MemoryWrite::RecordAccess { id, access_count, last_accessed } => {
+            tx.execute(
+                "UPDATE memories SET access_count = ?2, last_accessed = ?3 WHERE id = ?1",
+                params![id.to_string(), *access_count as i64, last_accessed.to_rfc3339()],
+            )?;
+        }
+        MemoryWrite::UpsertAssociation(association) => {
+            tx.execute(
+                "INSERT OR REPLACE INTO memory_associations
+                     (id, memory_a, memory_b, association_type, strength, created_at, notes)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    association.id.to_string(),
+                    association.memory_a.to_string(),
+                    association.memory_b.to_string(),
+                    serde_json::to_string(&association.association_type)?,
+                    association.strength as f64,
+                    association.created_at.to_rfc3339(),
+                    association.notes.as_deref().map(|notes| codec.seal_text(notes)).transpose()?,
+                ],
+            )?;
+        }
+        MemoryWrite::DeleteAssociation(id) => {
+            tx.execute("DELETE FROM memory_associations WHERE id = ?1", params![id.to_string()])?;
+        }
+        MemoryWrite::UpsertInsight(insight) => {
+            tx.execute(
+                "INSERT OR REPLACE INTO memory_insights
+                     (id, agent_id, insight_type, content, confidence, supporting_memories, created_at,
+                      verification_status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    insight.id.to_string(),
+                    insight.agent_id,
+                    serde_json::to_string(&insight.insight_type)?,
+                    codec.seal_text(&insight.content)?,
+                    insight.confidence as f64,
+                    serde_json::to_string(&insight.supporting_memories)?,
+                    insight.created_at.to_rfc3339(),
+                    serde_json::to_string(&insight.verification_status)?,
+                ],
+            )?;
+        }
+        MemoryWrite::ArchiveMemory(entry) => {
+            let id = entry.memory.id.to_string();
+            tx.execute(
+                "INSERT OR REPLACE INTO memory_archive (id, memory, archived_at, run_id, replaced_by)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    id,
+                    codec.seal_text(&serde_json::to_string(&entry.memory)?)?,
+                    entry.archived_at.to_rfc3339(),
+                    entry.run_id.map(|run| run.to_string()),
+                    entry.replaced_by.map(|replacement| replacement.to_string()),
+                ],
+            )?;
+            tx.execute("DELETE FROM memories WHERE id = ?1", params![id])?;
+        }
+        MemoryWrite::DeleteArchived(id) => {
+            tx.execute("DELETE FROM memory_archive WHERE id = ?1", params![id.to_string()])?;
+        }
+        MemoryWrite::UpsertConsolidationRun(run) => {
+            tx.execute(
+                "INSERT OR REPLACE INTO consolidation_runs (id, started_at, journal) VALUES (?1, ?2, ?3)",
+                params![run.id.to_string(), run.started_at.to_rfc3339(), codec.seal_text(&serde_json::to_string(run)?)?],
+            )?;
+        }
+        MemoryWrite::DeleteConsolidationRun(id) => {
+            tx.execute("DELETE FROM consolidation_runs WHERE id = ?1", params![id.to_string()])?;
+        }
+        MemoryWrite::UpsertLearnedWeights { user_id, weights } => {
+            // Learned keywords come from memory content, so they are sealed like it
+            tx.execute(
+                "INSERT OR REPLACE INTO learned_weights (user_id, weights, updated_at) VALUES (?1, ?2, ?3)",
+                params![user_id, codec.seal_text(&serde_json::to_string(weights)?)?, Utc::now().to_rfc3339()],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Row reader for `load_layer`; the outer result is rusqlite's, the inner one our decoding
+fn read_memory_row(row: &Row<'_>, layer: MemoryLayer, codec: &Codec) -> rusqlite::Result<Result<Memory>> {
+    let id: String = row.get(0)?;
+    let content: String = row.get(1)?;
+    let metadata: String = row.get(2)?;
+    let importance: f64 = row.get(3)?;
+    let access_count: i64 = row.get(4)?;
+    let last_accessed: String = row.get(5)?;
+    let created_at: String = row.get(6)?;
+    let tags: String = row.get(7)?;
+    let associations: String = row.get(8)?;
+    let vector: Option<Vec<u8>> = row.get(9)?;
+    let pinned: bool = row.get(10)?;
+    let scope: String = row.get(11)?;
+
+    Ok((|| {
+        Ok(Memory {
+            id: parse_uuid(&id)?,
+            layer,
+            content: codec.open_text(&content)?,
+            embedding: vector.map(|vector| codec.open_bytes(vector)).transpose()?.as_deref().and_then(decode_vector),
+            metadata: serde_json::from_str(&codec.open_text(&metadata)?)?,
+            importance_score: importance as f32,
+            access_count: access_count.max(0) as u32,
+            last_accessed: parse_time(&last_accessed)?,
+            created_at: parse_time(&created_at)?,
+            associations: serde_json::from_str(&associations)?,
+            tags: serde_json::from_str(&codec.open_text(&tags)?)?,
+            pinned,
+            scope: MemoryScope::from_key(&scope)
+                .ok_or_else(|| anyhow!("Invalid scope '{}' in memory database", scope))?,
+        })
+    })())
+}
+
+/// Encrypts values on the way in and decrypts them on the way out; without a cipher values are
+/// written in clear, and reading an encrypted value fails with a locked error
+struct Codec(Option<Arc<DataCipher>>);
+
+impl Codec {
+    fn seal_text(&self, text: &str) -> Result<String> {
+        match self.0 {
+            Some(ref cipher) => Ok(cipher.seal_text(text)?),
+            None => Ok(text.to_string()),
+        }
+    }
+
+    fn open_text(&self, text: &str) -> Result<String> {
+        match self.0 {
+            Some(ref cipher) => Ok(cipher.open_text(text)?),
+            None if DataCipher::is_sealed_text(text) => Err(locked().into()),
+            None => Ok(text.to_string()),
+        }
+    }
+
+    fn seal_bytes(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        match self.0 {
+            Some(ref cipher) => Ok(cipher.encrypt(&bytes)?),
+            None => Ok(bytes),
+        }
+    }
+
+    fn open_bytes(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        if !DataCipher::is_encrypted(&bytes) {
+            return Ok(bytes);
+        }
+        match self.0 {
+            Some(ref cipher) => Ok(cipher.decrypt(&bytes)?),
+            None => Err(locked().into()),
+        }
+    }
+}
+
+fn locked() -> LocalMindError {
+    LocalMindError::Locked("the memory database is encrypted; unlock with your passphrase first".to_string())
+}
+
+fn parse_uuid(value: &str) -> Result<Uuid> {
+    Uuid::parse_str(value).map_err(|e| anyhow!("Invalid id '{}' in memory database: {}", value, e))
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| anyhow!("Invalid timestamp '{}' in memory database: {}", value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::memory_types::*;
+
+    fn test_memory(content: &str, layer: MemoryLayer) -> Memory {
+        let metadata = MemoryMetadata {
+            source: MemorySource::UserInput,
+            agent_id: "agent1".to_string(),
+            conversation_id: None,
+            session_id: Some("session".to_string()),
+            topics: vec!["test".to_string()],
+            entities: vec![],
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Unverified,
+            custom_fields: HashMap::new(),
+        };
+        let mut memory = Memory::new(content.to_string(), layer, metadata);
+        memory.embedding = Some(vec![0.5, -0.25, 1.0]);
+        memory
+    }
+
+    #[test]
+    fn test_migrations_run_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(dir.path()).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+        drop(store);
+
+        // Reopening must not try to recreate tables
+        let store = MemoryStore::open(dir.path()).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn test_profile_facts_are_found_without_loading_layers() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(dir.path()).unwrap();
+
+        let mut fact = test_memory("Prefers tea over coffee", MemoryLayer::Semantic);
+        fact.metadata.verification_status = VerificationStatus::Verified;
+        fact.tags.push(crate::memory::verification::PROFILE_TAG.to_string());
+        let mut unverified = test_memory("Might like jazz", MemoryLayer::LongTerm);
+        unverified.tags.push(crate::memory::verification::PROFILE_TAG.to_string());
+        let plain = test_memory("Lunch was fine", MemoryLayer::Working);
+        store.apply(&[
+            MemoryWrite::UpsertMemory(fact.clone()),
+            MemoryWrite::UpsertMemory(unverified),
+            MemoryWrite::UpsertMemory(plain),
+        ]).unwrap();
+        let ids = |memories: Vec<Memory>| memories.into_iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(ids(store.load_profile().unwrap()), vec![fact.id]);
+
+        // Rows from before the profile column are classified on first use
+        store.lock().unwrap().execute("UPDATE memories SET profile = NULL", []).unwrap();
+        assert_eq!(ids(store.load_profile().unwrap()), vec![fact.id]);
+        let unclassified: i64 = store.lock().unwrap()
+            .query_row("SELECT COUNT(*) FROM memories WHERE profile IS NULL", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(unclassified, 0);
+    }
+
+    #[test]
+    fn test_memory_roundtrip_by_layer() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(dir.path()).unwrap();
+
+        let working = test_memory("working item", MemoryLayer::Working);
+        let mut long_term = test_memory("long term item", MemoryLayer::LongTerm);
+        long_term.pinned = true;
+        long_term.scope = MemoryScope::Team("research".to_string());
+        store.apply(&[
+            MemoryWrite::UpsertMemory(working.clone()),
+            MemoryWrite::UpsertMemory(long_term.clone()),
+            MemoryWrite::RecordAccess { id: working.id, access_count: 7, last_accessed: Utc::now() },
+        ]).unwrap();
+
+        let counts = store.layer_counts().unwrap();
+        assert_eq!(counts.get(&MemoryLayer::Working), Some(&1));
+        assert_eq!(counts.get(&MemoryLayer::LongTerm), Some(&1));
+
+        let loaded = store.load_layer(MemoryLayer::Working).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, working.id);
+        assert_eq!(loaded[0].access_count, 7);
+        assert_eq!(loaded[0].embedding, Some(vec![0.5, -0.25, 1.0]));
+        assert_eq!(loaded[0].metadata.session_id.as_deref(), Some("session"));
+        assert!(!loaded[0].pinned);
+        assert_eq!(loaded[0].scope, MemoryScope::Agent);
+        let long_term = &store.load_layer(MemoryLayer::LongTerm).unwrap()[0];
+        assert!(long_term.pinned);
+        assert_eq!(long_term.scope, MemoryScope::Team("research".to_string()));
+    }
+
+    #[test]
+    fn test_delete_removes_associations() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(dir.path()).unwrap();
+
+        let a = test_memory("a", MemoryLayer::Working);
+        let b = test_memory("b", MemoryLayer::Working);
+        let association = MemoryAssociation {
+            id: Uuid::new_v4(),
+            memory_a: a.id,
+            memory_b: b.id,
+            association_type: AssociationType::UserDefined("sibling".to_string()),
+            strength: 0.8,
+            created_at: Utc::now(),
+            notes: None,
+        };
+        store.apply(&[
+            MemoryWrite::UpsertMemory(a.clone()),
+            MemoryWrite::UpsertMemory(b),
+            MemoryWrite::UpsertAssociation(association),
+        ]).unwrap();
+        assert_eq!(store.load_associations().unwrap()[0].association_type, AssociationType::UserDefined("sibling".to_string()));
+
+        store.apply(&[MemoryWrite::DeleteMemory(a.id)]).unwrap();
+        assert!(store.load_associations().unwrap().is_empty());
+        assert_eq!(store.load_layer(MemoryLayer::Working).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_failed_batch_is_rolled_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(dir.path()).unwrap();
+
+        let memory = test_memory("kept out", MemoryLayer::Working);
+        // Make the second write of the batch fail
+        {
+            let conn = store.lock().unwrap();
+            conn.execute_batch("CREATE TRIGGER fail_insights BEFORE INSERT ON memory_insights BEGIN SELECT RAISE(ABORT, 'boom'); END;").unwrap();
+        }
+        let insight = Insight {
+            id: Uuid::new_v4(),
+            insight_type: InsightType::Theme,
+            content: "theme".to_string(),
+            confidence: 0.5,
+            supporting_memories: vec![memory.id],
+            created_at: Utc::now(),
+            agent_id: "agent1".to_string(),
+            verification_status: VerificationStatus::Unverified,
+        };
+
+        let result = store.apply(&[MemoryWrite::UpsertMemory(memory), MemoryWrite::UpsertInsight(insight)]);
+        assert!(result.is_err());
+        assert!(store.load_layer(MemoryLayer::Working).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(dir.path()).unwrap();
+
+        let memory = test_memory("old detail", MemoryLayer::ShortTerm);
+        let mut run = ConsolidationRun::new();
+        run.archived.push(memory.id);
+
+        store.apply(&[MemoryWrite::UpsertMemory(memory.clone())]).unwrap();
+        store.apply(&[
+            MemoryWrite::ArchiveMemory(ArchivedMemory {
+                memory: memory.clone(),
+                archived_at: Utc::now(),
+                run_id: Some(run.id),
+                replaced_by: None,
+            }),
+            MemoryWrite::UpsertConsolidationRun(run.clone()),
+        ]).unwrap();
+
+        assert!(store.load_layer(MemoryLayer::ShortTerm).unwrap().is_empty());
+        let archive = store.load_archive().unwrap();
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive[0].memory.id, memory.id);
+        assert_eq!(archive[0].run_id, Some(run.id));
+        assert_eq!(store.load_consolidation_runs().unwrap()[0].archived, vec![memory.id]);
+    }
+
+    #[test]
+    fn test_encrypted_store_hides_content() {
+        use crate::storage::encryption::{KdfParams, Keyring};
+
+        let dir = tempfile::tempdir().unwrap();
+        let (_, cipher) = Keyring::create_with_params("secret", KdfParams::with_costs(64, 1, 1)).unwrap();
+        let cipher = Arc::new(cipher);
+
+        // Written in clear first, then encrypted in place
+        let plain = MemoryStore::open(dir.path()).unwrap();
+        let memory = test_memory("my bank PIN hint is the cat's name", MemoryLayer::LongTerm);
+        plain.apply(&[MemoryWrite::UpsertMemory(memory.clone())]).unwrap();
+        drop(plain);
+
+        let store = MemoryStore::open(dir.path()).unwrap().with_cipher(cipher.clone());
+        assert_eq!(store.load_layer(MemoryLayer::LongTerm).unwrap()[0].content, memory.content);
+        store.reseal().unwrap();
+
+        let raw: String = store.lock().unwrap()
+            .query_row("SELECT content FROM memories", [], |row| row.get(0))
+            .unwrap();
+        assert!(!raw.contains("PIN"));
+
+        let loaded = store.load_layer(MemoryLayer::LongTerm).unwrap();
+        assert_eq!(loaded[0].content, memory.content);
+        assert_eq!(loaded[0].embedding, memory.embedding);
+        assert_eq!(loaded[0].metadata.topics, vec!["test".to_string()]);
+        drop(store);
+
+        // Without the key the data stays locked
+        let locked = MemoryStore::open(dir.path()).unwrap();
+        let error = locked.load_layer(MemoryLayer::LongTerm).unwrap_err();
+        assert!(matches!(error.downcast_ref::<LocalMindError>(), Some(LocalMindError::Locked(_))));
+    }
+}
@@ -0,0 +1,258 @@
+//! Final ordering of retrieval results: configurable score weights, an optional cross-encoder
+//! pass, and maximal marginal relevance so near-duplicates don't fill the top of the list.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::memory_types::{Memory, MemoryLayer};
+use super::retrieval::SearchResult;
+
+/// Half-life used when nothing else is configured
+pub const DEFAULT_RECENCY_HALF_LIFE_HOURS: f32 = 24.0;
+
+/// Score added for each layer; working memory is the most relevant to the current context
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayerWeights {
+    pub working: f32,
+    pub short_term: f32,
+    pub long_term: f32,
+    pub episodic: f32,
+    pub semantic: f32,
+    pub reflective: f32,
+}
+
+impl Default for LayerWeights {
+    fn default() -> Self {
+        Self {
+            working: 0.1,
+            short_term: 0.08,
+            long_term: 0.06,
+            episodic: 0.04,
+            semantic: 0.05,
+            reflective: 0.07,
+        }
+    }
+}
+
+impl LayerWeights {
+    pub fn weight(&self, layer: MemoryLayer) -> f32 {
+        match layer {
+            MemoryLayer::Working => self.working,
+            MemoryLayer::ShortTerm => self.short_term,
+            MemoryLayer::LongTerm => self.long_term,
+            MemoryLayer::Episodic => self.episodic,
+            MemoryLayer::Semantic => self.semantic,
+            MemoryLayer::Reflective => self.reflective,
+        }
+    }
+}
+
+/// Weights for scoring and reranking retrieved memories
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    pub importance_weight: f32,
+    pub recency_weight: f32,
+    pub frequency_weight: f32,
+    /// Weight of relevance: the hybrid score when a query has text and an embedding, else the better of the two
+    pub relevance_weight: f32,
+    pub recency_half_life_hours: f32,
+    pub layer_weights: LayerWeights,
+    /// MMR trade-off: 1.0 ranks by relevance alone, lower values favour variety
+    pub mmr_lambda: f32,
+    /// How many of the best results MMR reorders
+    pub mmr_candidates: usize,
+    /// Rescore the best results with the embedding backend's cross-encoder, when it has one
+    pub cross_encoder: bool,
+    /// Share of the final score given to the cross-encoder
+    pub cross_encoder_weight: f32,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            importance_weight: 0.3,
+            recency_weight: 0.2,
+            frequency_weight: 0.1,
+            relevance_weight: 0.4,
+            recency_half_life_hours: DEFAULT_RECENCY_HALF_LIFE_HOURS,
+            layer_weights: LayerWeights::default(),
+            mmr_lambda: 0.7,
+            mmr_candidates: 50,
+            cross_encoder: false,
+            cross_encoder_weight: 0.5,
+        }
+    }
+}
+
+/// Scores query/document pairs jointly, which is slower but sharper than comparing embeddings
+#[async_trait]
+pub trait CrossEncoder: Send + Sync {
+    /// One score in 0..=1 per document, or `None` if no cross-encoder is available
+    async fn score(&self, query: &str, documents: &[String]) -> Result<Option<Vec<f32>>>;
+}
+
+/// Blend cross-encoder scores into the best `candidates` results and re-sort them
+pub async fn apply_cross_encoder(
+    cross_encoder: &dyn CrossEncoder,
+    query: &str,
+    results: &mut [SearchResult],
+    config: &RankingConfig,
+) -> Result<()> {
+    let head = config.mmr_candidates.min(results.len());
+    let documents: Vec<String> = results[..head].iter().map(|result| result.memory.content.clone()).collect();
+    let Some(scores) = cross_encoder.score(query, &documents).await? else {
+        return Ok(());
+    };
+    if scores.len() != head {
+        return Err(anyhow::anyhow!("Cross-encoder returned {} scores for {} documents", scores.len(), head));
+    }
+
+    let weight = config.cross_encoder_weight.clamp(0.0, 1.0);
+    for (result, score) in results[..head].iter_mut().zip(scores) {
+        result.relevance_score = (1.0 - weight) * result.relevance_score + weight * score.clamp(0.0, 1.0);
+        result.match_reasons.push(format!("Cross-encoder: {:.2}", score));
+    }
+    results[..head].sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+    Ok(())
+}
+
+/// Reorder the best `mmr_candidates` results by maximal marginal relevance.
+///
+/// Each pick maximises `lambda * relevance - (1 - lambda) * similarity to what's already picked`,
+/// using embeddings where both memories have one and word overlap otherwise.
+pub fn mmr_rerank(mut results: Vec<SearchResult>, config: &RankingConfig) -> Vec<SearchResult> {
+    let lambda = config.mmr_lambda.clamp(0.0, 1.0);
+    let head = config.mmr_candidates.min(results.len());
+    if lambda >= 1.0 || head < 2 {
+        return results;
+    }
+
+    let tail = results.split_off(head);
+    let mut remaining = results;
+    let mut picked: Vec<SearchResult> = Vec::with_capacity(head);
+    while !remaining.is_empty() {
+        let (index, redundancy) = remaining
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| {
+                let redundancy = picked.iter()
+                    .map(|chosen| similarity(&candidate.memory, &chosen.memory))
+                    .fold(0.0f32, f32::max);
+                (index, redundancy, lambda * candidate.relevance_score - (1.0 - lambda) * redundancy)
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(index, redundancy, _)| (index, redundancy))
+            .expect("remaining is not empty");
+
+        let mut next = remaining.remove(index);
+        if redundancy >= 0.5 {
+            next.match_reasons.push(format!("Similar to a higher result: {:.2}", redundancy));
+        }
+        picked.push(next);
+    }
+
+    picked.extend(tail);
+    picked
+}
+
+/// How alike two memories are, 0..=1
+fn similarity(a: &Memory, b: &Memory) -> f32 {
+    match (&a.embedding, &b.embedding) {
+        (Some(x), Some(y)) if x.len() == y.len() => cosine(x, y).max(0.0),
+        _ => word_overlap(&a.content, &b.content),
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+fn word_overlap(a: &str, b: &str) -> f32 {
+    let words = |text: &str| -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.len() > 2)
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::memory_types::{MemoryMetadata, MemorySource, VerificationStatus};
+    use std::collections::HashMap;
+
+    fn result(content: &str, embedding: Vec<f32>, score: f32) -> SearchResult {
+        let metadata = MemoryMetadata {
+            source: MemorySource::UserInput,
+            agent_id: "agent1".to_string(),
+            conversation_id: None,
+            session_id: None,
+            topics: vec![],
+            entities: vec![],
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Unverified,
+            custom_fields: HashMap::new(),
+        };
+        let mut memory = Memory::new(content.to_string(), MemoryLayer::Working, metadata);
+        memory.embedding = Some(embedding);
+        SearchResult { memory, relevance_score: score, match_reasons: vec![] }
+    }
+
+    fn contents(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.memory.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_mmr_pushes_near_duplicates_down() {
+        let results = vec![
+            result("standup at 9", vec![1.0, 0.0], 0.9),
+            result("standup at 9am", vec![0.99, 0.05], 0.88),
+            result("dentist on Monday", vec![0.0, 1.0], 0.7),
+        ];
+
+        let reranked = mmr_rerank(results.clone(), &RankingConfig::default());
+        assert_eq!(contents(&reranked), vec!["standup at 9", "dentist on Monday", "standup at 9am"]);
+        assert!(reranked[2].match_reasons[0].starts_with("Similar to a higher result"));
+
+        let relevance_only = RankingConfig { mmr_lambda: 1.0, ..RankingConfig::default() };
+        assert_eq!(contents(&mmr_rerank(results, &relevance_only)), vec!["standup at 9", "standup at 9am", "dentist on Monday"]);
+    }
+
+    struct PrefersDentist;
+
+    #[async_trait]
+    impl CrossEncoder for PrefersDentist {
+        async fn score(&self, _query: &str, documents: &[String]) -> Result<Option<Vec<f32>>> {
+            Ok(Some(documents.iter().map(|d| if d.contains("dentist") { 1.0 } else { 0.0 }).collect()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cross_encoder_scores_are_blended() {
+        let mut results = vec![
+            result("standup at 9", vec![1.0, 0.0], 0.9),
+            result("dentist on Monday", vec![0.0, 1.0], 0.7),
+        ];
+        apply_cross_encoder(&PrefersDentist, "appointments", &mut results, &RankingConfig::default()).await.unwrap();
+        assert_eq!(contents(&results), vec!["dentist on Monday", "standup at 9"]);
+        assert!((results[0].relevance_score - 0.85).abs() < 1e-6);
+    }
+}
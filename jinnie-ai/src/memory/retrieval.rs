@@ -1,8 +1,23 @@
 use anyhow::Result;
 use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
 
-use super::memory_types::{Memory, MemoryQuery, MemoryLayer, MemorySource};
+use super::activation::{self, ActivationStep, SpreadingConfig};
+use super::memory_types::{AssociationType, Memory, MemoryQuery, MemoryLayer, MemoryScope, MemorySource, VerificationStatus};
 use super::memory_manager::MemoryManager;
+use super::ranking::{self, CrossEncoder, RankingConfig};
+use super::scopes::ScopeConfig;
+use crate::vector::search_engine::{fuse_scores, FilterCondition, SearchFilter};
+use crate::vector::text_index::{tokenize, Bm25Index};
+use crate::vector::{CollectionSchema, HybridWeights, SearchQuery};
+
+/// Minimum cosine similarity for a memory to match a semantic query
+const MIN_SEMANTIC_SIMILARITY: f32 = 0.35;
+
+/// Fewest matches hybrid search through the vector store fetches per ranking, so
+/// reranking still has room when a query asks for only a few results
+const MIN_FUSED_CANDIDATES: usize = 20;
 
 /// Memory retrieval and search engine
 pub struct MemoryRetrieval {
@@ -10,6 +25,16 @@ pub struct MemoryRetrieval {
     search_cache: HashMap<String, Vec<Memory>>,
     /// Maximum cache size
     max_cache_size: usize,
+    /// Decides which shared memories an agent sees and how they are weighted
+    scopes: ScopeConfig,
+    /// How associative search follows association edges
+    spreading: SpreadingConfig,
+    /// Score weights and reranking
+    ranking: RankingConfig,
+    /// Rescores the best results when `ranking.cross_encoder` is on
+    cross_encoder: Option<Arc<dyn CrossEncoder>>,
+    /// How text and embedding matches combine when a query has both
+    hybrid: HybridWeights,
 }
 
 #[derive(Debug, Clone)]
@@ -19,14 +44,59 @@ pub struct SearchResult {
     pub match_reasons: Vec<String>,
 }
 
+/// A memory found by associative search
+#[derive(Debug, Clone)]
+pub struct AssociativeResult {
+    pub memory: Memory,
+    pub activation: f32,
+    /// Edges followed from a direct match; empty for the direct matches themselves
+    pub path: Vec<ActivationStep>,
+    /// e.g. `Linked to "Missed the train" by causal 0.90 → supporting 0.40`
+    pub explanation: String,
+}
+
 impl MemoryRetrieval {
     pub fn new() -> Self {
         Self {
             search_cache: HashMap::new(),
             max_cache_size: 100,
+            scopes: ScopeConfig::default(),
+            spreading: SpreadingConfig::default(),
+            ranking: RankingConfig::default(),
+            cross_encoder: None,
+            hybrid: HybridWeights::default(),
         }
     }
 
+    pub fn with_scopes(mut self, scopes: ScopeConfig) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    pub fn with_spreading(mut self, spreading: SpreadingConfig) -> Self {
+        self.spreading = spreading;
+        self
+    }
+
+    pub fn with_ranking(mut self, ranking: RankingConfig) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    pub fn with_hybrid(mut self, hybrid: HybridWeights) -> Self {
+        self.hybrid = hybrid;
+        self
+    }
+
+    pub fn with_cross_encoder(mut self, cross_encoder: Arc<dyn CrossEncoder>) -> Self {
+        self.cross_encoder = Some(cross_encoder);
+        self
+    }
+
+    pub fn scopes(&self) -> &ScopeConfig {
+        &self.scopes
+    }
+
     /// Search memories based on query parameters
     pub async fn search(&self, memory_manager: &MemoryManager, query: MemoryQuery) -> Result<Vec<Memory>> {
         // Generate cache key
@@ -38,31 +108,81 @@ impl MemoryRetrieval {
         }
 
         // Perform actual search
-        let results = self.perform_search(memory_manager, &query).await?;
+        let results: Vec<Memory> = self.perform_search(memory_manager, &query).await?
+            .into_iter()
+            .map(|result| result.memory)
+            .collect();
         
         // Note: In a real implementation, we'd update the cache here
         // For now, we'll just return the results
         Ok(self.apply_pagination(results, &query))
     }
 
-    /// Search memories with detailed scoring
+    /// Search memories with detailed scoring, in final ranked order
     pub async fn search_with_scores(&self, memory_manager: &MemoryManager, query: MemoryQuery) -> Result<Vec<SearchResult>> {
-        let memories = self.perform_search(memory_manager, &query).await?;
-        let mut scored_results = Vec::new();
-
-        for memory in memories {
-            let (score, reasons) = self.calculate_relevance_score(&memory, &query);
-            scored_results.push(SearchResult {
-                memory,
-                relevance_score: score,
-                match_reasons: reasons,
-            });
-        }
+        self.perform_search(memory_manager, &query).await
+    }
 
-        // Sort by relevance score (highest first)
-        scored_results.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
+    /// Seed from the best direct matches, then spread activation along association edges.
+    ///
+    /// This brings up memories that don't match the query themselves but are linked to ones
+    /// that do. Archived memories are not in the layers, so paths never pass through them.
+    pub async fn search_associative(&self, memory_manager: &MemoryManager, query: MemoryQuery) -> Result<Vec<AssociativeResult>> {
+        let limit = query.limit.unwrap_or(10);
+        let seeds: Vec<(uuid::Uuid, f32)> = self.search_with_scores(memory_manager, query.clone()).await?
+            .into_iter()
+            .take(self.spreading.seeds)
+            .map(|result| (result.memory.id, result.relevance_score))
+            .collect();
+
+        let reachable = |id| memory_manager.get_memory(id).is_some_and(|memory| self.is_visible(memory, &query));
+        let activations = activation::spread(&seeds, memory_manager.associations(), reachable, &self.spreading);
 
-        Ok(scored_results)
+        let mut results: Vec<AssociativeResult> = activations
+            .into_iter()
+            .filter_map(|activation| {
+                let memory = memory_manager.get_memory(activation.id)?.clone();
+                let explanation = Self::explain_path(memory_manager, &activation.path);
+                Some(AssociativeResult {
+                    memory,
+                    activation: activation.activation,
+                    path: activation.path,
+                    explanation,
+                })
+            })
+            .collect();
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    fn explain_path(memory_manager: &MemoryManager, path: &[ActivationStep]) -> String {
+        let Some(first) = path.first() else {
+            return "Direct match".to_string();
+        };
+        let seed = memory_manager.get_memory(first.from)
+            .map(|memory| Self::snippet(&memory.content))
+            .unwrap_or_default();
+        let hops: Vec<String> = path.iter()
+            .map(|step| format!("{} {:.2}", Self::association_label(&step.association_type), step.weight))
+            .collect();
+        format!("Linked to \"{}\" by {}", seed, hops.join(" → "))
+    }
+
+    fn association_label(association_type: &AssociationType) -> String {
+        match association_type {
+            AssociationType::TopicalRelation => "topic".to_string(),
+            AssociationType::UserDefined(name) => name.clone(),
+            other => format!("{:?}", other).to_lowercase(),
+        }
+    }
+
+    fn snippet(content: &str) -> String {
+        const MAX_CHARS: usize = 40;
+        let content = content.trim();
+        if content.chars().count() <= MAX_CHARS {
+            return content.to_string();
+        }
+        format!("{}…", content.chars().take(MAX_CHARS).collect::<String>().trim_end())
     }
 
     /// Find similar memories to a given memory
@@ -242,7 +362,7 @@ impl MemoryRetrieval {
 
     // Private helper methods
 
-    async fn perform_search(&self, memory_manager: &MemoryManager, query: &MemoryQuery) -> Result<Vec<Memory>> {
+    async fn perform_search(&self, memory_manager: &MemoryManager, query: &MemoryQuery) -> Result<Vec<SearchResult>> {
         let mut candidates = Vec::new();
 
         // Determine which layers to search
@@ -265,6 +385,11 @@ impl MemoryRetrieval {
             candidates.extend(layer_memories.into_iter().cloned());
         }
 
+        // Term statistics come from every searched memory, not just the matches
+        let text_index = query.text_query
+            .as_ref()
+            .map(|_| Bm25Index::from_texts(candidates.iter().map(|memory| (memory.id, memory.content.as_str()))));
+
         // Apply filters
         let mut filtered_memories = Vec::new();
         
@@ -274,26 +399,136 @@ impl MemoryRetrieval {
             }
         }
 
+        // BM25 scaled by the best match, so the top text hit gets full text relevance
+        let text_scores = match (&text_index, &query.text_query) {
+            (Some(index), Some(text_query)) => {
+                let scores = index.scores(text_query);
+                let best = filtered_memories
+                    .iter()
+                    .filter_map(|memory| scores.get(&memory.id))
+                    .fold(0.0f32, |best, score| best.max(*score));
+                Some((scores, best))
+            }
+            _ => None,
+        };
+
+        // With both a text and an embedding, relevance is the two fused per `hybrid`
+        let fused = match (&query.text_query, &query.semantic_query) {
+            (Some(text_query), Some(embedding)) => Some(
+                self.fused_relevance(memory_manager, query, text_query, embedding, &filtered_memories, text_index.as_ref())
+                    .await?,
+            ),
+            _ => None,
+        };
+
         // Score and sort results
-        let mut scored_memories: Vec<(Memory, f32)> = filtered_memories
+        let mut results: Vec<SearchResult> = filtered_memories
             .into_iter()
             .map(|memory| {
-                let (score, _) = self.calculate_relevance_score(&memory, query);
-                (memory, score)
+                let fused_score = fused.as_ref().map(|fused| fused.get(&memory.id).copied().unwrap_or(0.0));
+                let (relevance_score, match_reasons) = match &text_scores {
+                    Some((scores, best)) => {
+                        let text_score = scores.get(&memory.id).map_or(0.0, |score| score / best);
+                        self.score_with_text_relevance(&memory, query, text_score, fused_score)
+                    }
+                    None => self.calculate_relevance_score(&memory, query),
+                };
+                SearchResult { memory, relevance_score, match_reasons }
             })
             .collect();
+        results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+
+        // A missing or failing cross-encoder leaves the first-pass order alone
+        if let (true, Some(cross_encoder), Some(text_query)) =
+            (self.ranking.cross_encoder, &self.cross_encoder, &query.text_query)
+        {
+            if let Err(e) = ranking::apply_cross_encoder(cross_encoder.as_ref(), text_query, &mut results, &self.ranking).await {
+                log::warn!("Cross-encoder reranking failed: {}", e);
+            }
+        }
 
-        scored_memories.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        Ok(ranking::mmr_rerank(results, &self.ranking))
+    }
+
+    /// Vector and BM25 matches fused per `hybrid`: through the vector store's hybrid search
+    /// when there is one, otherwise over the candidate memories themselves. Through the
+    /// store only the top matches get a fused score; the rest count as 0.
+    async fn fused_relevance(
+        &self,
+        memory_manager: &MemoryManager,
+        query: &MemoryQuery,
+        text_query: &str,
+        embedding: &[f32],
+        memories: &[Memory],
+        text_index: Option<&Bm25Index<Uuid>>,
+    ) -> Result<HashMap<Uuid, f32>> {
+        if let Some(vector_store) = memory_manager.vector_store() {
+            let wanted = query.offset.unwrap_or(0) + query.limit.unwrap_or(10);
+            let search = SearchQuery::new(embedding.to_vec(), CollectionSchema::memory_collection().name)
+                .with_limit(wanted.max(MIN_FUSED_CANDIDATES))
+                .with_filter(self.candidate_filter(query));
+            let results = vector_store.hybrid_search(search, Some(text_query.to_string()), self.hybrid).await?;
+            return Ok(results.into_iter().map(|result| (result.id, result.score)).collect());
+        }
 
-        Ok(scored_memories.into_iter().map(|(memory, _)| memory).collect())
+        let vector: Vec<(Uuid, f32)> = memories
+            .iter()
+            .filter_map(|memory| memory.embedding.as_ref().map(|e| (memory.id, self.cosine_similarity(embedding, e))))
+            .collect();
+        let text: Vec<(Uuid, f32)> = match text_index {
+            Some(index) => {
+                let scores = index.scores(text_query);
+                memories.iter().filter_map(|memory| scores.get(&memory.id).map(|score| (memory.id, *score))).collect()
+            }
+            None => Vec::new(),
+        };
+        Ok(fuse_scores(&vector, &text, &self.hybrid).into_iter().collect())
     }
 
-    fn matches_query(&self, memory: &Memory, query: &MemoryQuery) -> bool {
-        // Agent filter
+    /// Payload filter narrowing the vector store to the query's layers and the scopes its
+    /// agent may read. It is looser than `matches_query`, which still decides.
+    fn candidate_filter(&self, query: &MemoryQuery) -> SearchFilter {
+        let mut filter = SearchFilter::new();
+        if let Some(ref layers) = query.layers {
+            let layers = layers.iter().map(|layer| layer.as_str().into()).collect();
+            filter = filter.must(FilterCondition::in_values("layer".to_string(), layers));
+        }
+
         if let Some(ref agent_id) = query.agent_id {
-            if memory.metadata.agent_id != *agent_id {
-                return false;
+            let permissions = self.scopes.permissions_for(agent_id);
+            filter = filter.should(FilterCondition::equals("agent_id".to_string(), agent_id.as_str().into()));
+            if permissions.read_global {
+                filter = filter.should(FilterCondition::equals("scope".to_string(), MemoryScope::Global.as_key().into()));
             }
+            if permissions.read_team {
+                let teams = self.scopes
+                    .teams_of(agent_id)
+                    .into_iter()
+                    .map(|team| MemoryScope::Team(team.to_string()).as_key().into())
+                    .collect();
+                filter = filter.should(FilterCondition::in_values("scope".to_string(), teams));
+            }
+        }
+        filter
+    }
+
+    /// Whether the query may see this memory at all, whatever its content
+    fn is_visible(&self, memory: &Memory, query: &MemoryQuery) -> bool {
+        // Facts the user said are wrong stay out of recall
+        if memory.metadata.verification_status == VerificationStatus::Deprecated && !query.include_rejected {
+            return false;
+        }
+
+        // Agent filter: the agent's own memories plus shared ones it may read
+        match query.agent_id {
+            Some(ref agent_id) => self.scopes.can_read(agent_id, memory),
+            None => true,
+        }
+    }
+
+    fn matches_query(&self, memory: &Memory, query: &MemoryQuery) -> bool {
+        if !self.is_visible(memory, query) {
+            return false;
         }
 
         // Importance threshold filter
@@ -328,11 +563,15 @@ impl MemoryRetrieval {
             }
         }
 
-        // Text query filter
-        if let Some(ref text_query) = query.text_query {
-            if !self.matches_text_query(memory, text_query) {
-                return false;
-            }
+        // Text and semantic queries: matching either one is enough
+        let text_match = query.text_query
+            .as_ref()
+            .map(|text_query| self.matches_text_query(memory, text_query));
+        let semantic_match = self.semantic_similarity(memory, query)
+            .map(|similarity| similarity >= MIN_SEMANTIC_SIMILARITY);
+
+        if text_match.is_some() || semantic_match.is_some() {
+            return text_match.unwrap_or(false) || semantic_match.unwrap_or(false);
         }
 
         true
@@ -358,75 +597,94 @@ impl MemoryRetrieval {
     }
 
     fn calculate_relevance_score(&self, memory: &Memory, query: &MemoryQuery) -> (f32, Vec<String>) {
+        let text_score = query.text_query
+            .as_ref()
+            .map(|text_query| self.calculate_text_relevance_score(memory, text_query))
+            .unwrap_or(0.0);
+        self.score_with_text_relevance(memory, query, text_score, None)
+    }
+
+    fn score_with_text_relevance(&self, memory: &Memory, query: &MemoryQuery, text_score: f32, fused_score: Option<f32>) -> (f32, Vec<String>) {
         let mut score = 0.0;
         let mut reasons = Vec::new();
 
+        let weights = &self.ranking;
+
         // Base importance score
-        score += memory.importance_score * 0.3;
+        score += memory.importance_score * weights.importance_weight;
         reasons.push(format!("Importance: {:.2}", memory.importance_score));
 
         // Recency score
-        let recency = memory.recency_score();
-        score += recency * 0.2;
+        let recency = memory.recency_with_half_life(weights.recency_half_life_hours);
+        score += recency * weights.recency_weight;
         reasons.push(format!("Recency: {:.2}", recency));
 
         // Access frequency score
         let frequency = memory.frequency_score();
-        score += frequency * 0.1;
+        score += frequency * weights.frequency_weight;
         if frequency > 0.0 {
             reasons.push(format!("Access frequency: {:.2}", frequency));
         }
 
         // Text relevance score
-        if let Some(ref text_query) = query.text_query {
-            let text_score = self.calculate_text_relevance_score(memory, text_query);
-            score += text_score * 0.4;
-            if text_score > 0.0 {
-                reasons.push(format!("Text relevance: {:.2}", text_score));
-            }
+        if text_score > 0.0 {
+            reasons.push(format!("Text relevance: {:.2}", text_score));
         }
 
-        // Layer relevance (working and short-term memories are more relevant for current context)
-        let layer_score = match memory.layer {
-            MemoryLayer::Working => 0.1,
-            MemoryLayer::ShortTerm => 0.08,
-            MemoryLayer::LongTerm => 0.06,
-            MemoryLayer::Episodic => 0.04,
-            MemoryLayer::Semantic => 0.05,
-            MemoryLayer::Reflective => 0.07,
+        let semantic_score = self.semantic_similarity(memory, query).unwrap_or(0.0).max(0.0);
+        if semantic_score > 0.0 {
+            reasons.push(format!("Semantic similarity: {:.2}", semantic_score));
+        }
+
+        // A hybrid score already weighs text against semantic relevance; on their own they
+        // compete for the same weight
+        let relevance = match fused_score {
+            Some(fused_score) => {
+                reasons.push(format!("Hybrid relevance: {:.2}", fused_score));
+                fused_score
+            }
+            None => text_score.max(semantic_score),
         };
-        score += layer_score;
+        score += relevance * weights.relevance_weight;
+
+        // Layer relevance (working and short-term memories are more relevant for current context)
+        score += weights.layer_weights.weight(memory.layer);
+
+        // Shared memories are merged in with their scope's weight
+        if let Some(ref agent_id) = query.agent_id {
+            let weight = self.scopes.weight(agent_id, memory).unwrap_or(0.0);
+            score *= weight;
+            if memory.scope.is_shared() {
+                reasons.push(format!("Scope {}: x{:.2}", memory.scope.as_key(), weight));
+            }
+        }
 
         (score.min(1.0), reasons)
     }
 
-    fn calculate_text_relevance_score(&self, memory: &Memory, text_query: &str) -> f32 {
-        let content_lower = memory.content.to_lowercase();
-        let query_lower = text_query.to_lowercase();
-        let query_words: Vec<&str> = query_lower.split_whitespace().collect();
+    /// Cosine similarity to the query embedding, when both sides have one
+    fn semantic_similarity(&self, memory: &Memory, query: &MemoryQuery) -> Option<f32> {
+        match (&query.semantic_query, &memory.embedding) {
+            (Some(query_embedding), Some(embedding)) => Some(self.cosine_similarity(query_embedding, embedding)),
+            _ => None,
+        }
+    }
 
-        if query_words.is_empty() {
+    /// Text relevance of one memory on its own: 1.0 for the exact phrase, otherwise the
+    /// share of query terms it contains. Searches use BM25 across all memories instead.
+    fn calculate_text_relevance_score(&self, memory: &Memory, text_query: &str) -> f32 {
+        let query_terms: std::collections::HashSet<String> = tokenize(text_query).into_iter().collect();
+        if query_terms.is_empty() {
             return 0.0;
         }
 
         // Exact phrase match gets highest score
-        if content_lower.contains(&query_lower) {
+        if memory.content.to_lowercase().contains(&text_query.to_lowercase()) {
             return 1.0;
         }
 
-        // Calculate word overlap score
-        let content_words: Vec<&str> = content_lower.split_whitespace().collect();
-        let content_word_set: std::collections::HashSet<_> = content_words.iter().collect();
-        let query_word_set: std::collections::HashSet<_> = query_words.iter().collect();
-
-        let intersection = content_word_set.intersection(&query_word_set).count();
-        let union = content_word_set.union(&query_word_set).count();
-
-        if union == 0 {
-            0.0
-        } else {
-            intersection as f32 / union as f32
-        }
+        let content_terms: std::collections::HashSet<String> = tokenize(&memory.content).into_iter().collect();
+        query_terms.intersection(&content_terms).count() as f32 / query_terms.len() as f32
     }
 
     fn calculate_similarity(&self, memory1: &Memory, memory2: &Memory) -> f32 {
@@ -638,6 +896,47 @@ mod tests {
         assert!(!retrieval.matches_query(&memory, &query));
     }
 
+    #[test]
+    fn test_semantic_query_matching() {
+        let retrieval = MemoryRetrieval::new();
+        let mut memory = create_test_memory("Prefers tabs over spaces", "agent1", 0.5);
+        memory.embedding = Some(vec![1.0, 0.0, 0.0]);
+
+        let mut query = MemoryQuery::new();
+        query.text_query = Some("indentation style".to_string());
+        query.semantic_query = Some(vec![0.9, 0.1, 0.0]);
+        assert!(retrieval.matches_query(&memory, &query));
+
+        query.semantic_query = Some(vec![0.0, 1.0, 0.0]);
+        assert!(!retrieval.matches_query(&memory, &query));
+
+        let (score, reasons) = retrieval.calculate_relevance_score(&memory, &MemoryQuery {
+            semantic_query: Some(vec![1.0, 0.0, 0.0]),
+            ..MemoryQuery::new()
+        });
+        assert!(score > 0.4);
+        assert!(reasons.iter().any(|r| r.starts_with("Semantic similarity")));
+    }
+
+    #[test]
+    fn test_shared_scopes_are_merged_with_weights() {
+        let retrieval = MemoryRetrieval::new();
+        let own = create_test_memory("Works in Berlin", "agent1", 0.5);
+        let mut profile = create_test_memory("Works in Berlin", "agent2", 0.5);
+        profile.scope = MemoryScope::Global;
+        let other = create_test_memory("Works in Berlin", "agent2", 0.5);
+
+        let query = MemoryQuery::new().with_agent("agent1".to_string());
+        assert!(retrieval.matches_query(&own, &query));
+        assert!(retrieval.matches_query(&profile, &query));
+        assert!(!retrieval.matches_query(&other, &query));
+
+        let (own_score, _) = retrieval.calculate_relevance_score(&own, &query);
+        let (profile_score, reasons) = retrieval.calculate_relevance_score(&profile, &query);
+        assert!((profile_score - own_score * 0.9).abs() < 1e-4);
+        assert!(reasons.iter().any(|r| r.starts_with("Scope global")));
+    }
+
     #[test]
     fn test_pagination() {
         let retrieval = MemoryRetrieval::new();
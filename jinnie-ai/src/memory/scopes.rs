@@ -0,0 +1,170 @@
+//! Memory scopes: which agents can see and write shared memories, and how much
+//! shared memories count for at retrieval compared with an agent's own.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::memory_types::{Memory, MemoryScope};
+
+/// What an agent may do with shared scopes; its private scope is always readable and writable
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScopePermissions {
+    pub read_global: bool,
+    pub write_global: bool,
+    pub read_team: bool,
+    pub write_team: bool,
+}
+
+impl Default for ScopePermissions {
+    fn default() -> Self {
+        Self {
+            read_global: true,
+            write_global: true,
+            read_team: true,
+            write_team: true,
+        }
+    }
+}
+
+/// Retrieval weight per scope, multiplied into relevance scores
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScopeWeights {
+    pub agent: f32,
+    pub team: f32,
+    pub global: f32,
+}
+
+impl Default for ScopeWeights {
+    fn default() -> Self {
+        Self {
+            agent: 1.0,
+            team: 0.8,
+            global: 0.9,
+        }
+    }
+}
+
+/// Teams, per-agent permissions and retrieval weights
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScopeConfig {
+    /// Team name -> member agent ids
+    pub teams: HashMap<String, Vec<String>>,
+    /// Agents not listed here get `default_permissions`
+    pub permissions: HashMap<String, ScopePermissions>,
+    pub default_permissions: ScopePermissions,
+    pub weights: ScopeWeights,
+}
+
+impl ScopeConfig {
+    pub fn permissions_for(&self, agent_id: &str) -> ScopePermissions {
+        self.permissions.get(agent_id).copied().unwrap_or(self.default_permissions)
+    }
+
+    pub fn is_member(&self, agent_id: &str, team: &str) -> bool {
+        self.teams.get(team).is_some_and(|members| members.iter().any(|member| member == agent_id))
+    }
+
+    /// Teams `agent_id` belongs to, sorted
+    pub fn teams_of(&self, agent_id: &str) -> Vec<&str> {
+        let mut teams: Vec<&str> = self.teams.iter()
+            .filter(|(_, members)| members.iter().any(|member| member == agent_id))
+            .map(|(team, _)| team.as_str())
+            .collect();
+        teams.sort_unstable();
+        teams
+    }
+
+    /// Whether `agent_id` may see `memory`
+    pub fn can_read(&self, agent_id: &str, memory: &Memory) -> bool {
+        let permissions = self.permissions_for(agent_id);
+        match memory.scope {
+            MemoryScope::Agent => memory.metadata.agent_id == agent_id,
+            MemoryScope::Team(ref team) => permissions.read_team && self.is_member(agent_id, team),
+            MemoryScope::Global => permissions.read_global,
+        }
+    }
+
+    /// Whether `agent_id` may store memories in, or promote memories to, `scope`
+    pub fn can_write(&self, agent_id: &str, scope: &MemoryScope) -> bool {
+        let permissions = self.permissions_for(agent_id);
+        match scope {
+            MemoryScope::Agent => true,
+            MemoryScope::Team(team) => permissions.write_team && self.is_member(agent_id, team),
+            MemoryScope::Global => permissions.write_global,
+        }
+    }
+
+    /// Retrieval weight of `memory` for `agent_id`, or `None` if the agent cannot see it
+    pub fn weight(&self, agent_id: &str, memory: &Memory) -> Option<f32> {
+        if !self.can_read(agent_id, memory) {
+            return None;
+        }
+        Some(match memory.scope {
+            MemoryScope::Agent => self.weights.agent,
+            MemoryScope::Team(_) => self.weights.team,
+            MemoryScope::Global => self.weights.global,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::memory_types::*;
+
+    fn memory(agent_id: &str, scope: MemoryScope) -> Memory {
+        let metadata = MemoryMetadata {
+            source: MemorySource::UserInput,
+            agent_id: agent_id.to_string(),
+            conversation_id: None,
+            session_id: None,
+            topics: vec![],
+            entities: vec![],
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Unverified,
+            custom_fields: HashMap::new(),
+        };
+        let mut memory = Memory::new("fact".to_string(), MemoryLayer::LongTerm, metadata);
+        memory.scope = scope;
+        memory
+    }
+
+    #[test]
+    fn test_visibility_and_permissions() {
+        let mut config = ScopeConfig::default();
+        config.teams.insert("research".to_string(), vec!["coder".to_string(), "writer".to_string()]);
+        config.permissions.insert("guest".to_string(), ScopePermissions {
+            read_global: false,
+            write_global: false,
+            ..ScopePermissions::default()
+        });
+
+        let private = memory("coder", MemoryScope::Agent);
+        let team = memory("coder", MemoryScope::Team("research".to_string()));
+        let global = memory("coder", MemoryScope::Global);
+
+        assert_eq!(config.weight("coder", &private), Some(1.0));
+        assert_eq!(config.weight("writer", &private), None);
+        assert_eq!(config.weight("writer", &team), Some(0.8));
+        assert_eq!(config.weight("planner", &team), None);
+        assert_eq!(config.weight("planner", &global), Some(0.9));
+        assert_eq!(config.weight("guest", &global), None);
+
+        assert!(config.can_write("writer", &MemoryScope::Team("research".to_string())));
+        assert!(!config.can_write("planner", &MemoryScope::Team("research".to_string())));
+        assert!(!config.can_write("guest", &MemoryScope::Global));
+        assert_eq!(config.teams_of("writer"), vec!["research"]);
+    }
+
+    #[test]
+    fn test_scope_keys_roundtrip() {
+        for scope in [MemoryScope::Agent, MemoryScope::Global, MemoryScope::Team("ops".to_string())] {
+            assert_eq!(MemoryScope::from_key(&scope.as_key()), Some(scope));
+        }
+        assert_eq!(MemoryScope::from_key("team:"), None);
+    }
+}
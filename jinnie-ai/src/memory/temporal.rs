@@ -0,0 +1,506 @@
+//! Natural-language date expressions ("last Tuesday", "two weeks ago", "in March")
+//! resolved to a `DateRange` in the user's timezone.
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+use super::memory_types::DateRange;
+use crate::config::AppConfig;
+
+/// Wall-clock span in the user's timezone, end exclusive
+type Span = (NaiveDateTime, NaiveDateTime);
+
+/// The timezone date expressions are read in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UserTimezone {
+    Local,
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl UserTimezone {
+    /// Parse the `ui.timezone` setting, falling back to the system timezone
+    pub fn from_setting(setting: &str) -> Self {
+        let setting = setting.trim();
+        if setting.is_empty() || setting.eq_ignore_ascii_case("local") {
+            return Self::Local;
+        }
+        if let Some(offset) = parse_offset(setting) {
+            return Self::Fixed(offset);
+        }
+        match setting.parse::<Tz>() {
+            Ok(tz) => Self::Named(tz),
+            Err(_) => {
+                log::warn!("Unknown timezone '{}', using the system timezone", setting);
+                Self::Local
+            }
+        }
+    }
+}
+
+/// "UTC", "+02:00", "-0530" or "UTC+2"
+fn parse_offset(setting: &str) -> Option<FixedOffset> {
+    let rest = setting.strip_prefix("UTC").or_else(|| setting.strip_prefix("GMT")).unwrap_or(setting);
+    if rest.is_empty() {
+        return FixedOffset::east_opt(0);
+    }
+    let sign = match rest.as_bytes()[0] {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits = &rest[1..];
+    let (hours, minutes) = match digits.split_once(':') {
+        Some(parts) => parts,
+        None if digits.len() > 2 => digits.split_at(digits.len() - 2),
+        None => (digits, "0"),
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// A date expression found in a query
+#[derive(Debug, Clone)]
+pub struct TemporalMatch {
+    pub range: DateRange,
+    /// The phrase as written, e.g. "last Tuesday"
+    pub phrase: String,
+    /// The query with the phrase taken out, for text and semantic matching
+    pub remainder: String,
+}
+
+/// Finds date expressions in free text
+#[derive(Debug, Clone, Copy)]
+pub struct DateParser {
+    timezone: UserTimezone,
+}
+
+impl DateParser {
+    pub fn new(timezone: UserTimezone) -> Self {
+        Self { timezone }
+    }
+
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(UserTimezone::from_setting(&config.ui.timezone))
+    }
+
+    /// The first date expression in `text`, relative to now
+    pub fn parse(&self, text: &str) -> Option<TemporalMatch> {
+        self.parse_at(text, Utc::now())
+    }
+
+    /// The first date expression in `text`, relative to `now`
+    pub fn parse_at(&self, text: &str, now: DateTime<Utc>) -> Option<TemporalMatch> {
+        match self.timezone {
+            UserTimezone::Local => find(text, now.with_timezone(&Local)),
+            UserTimezone::Named(tz) => find(text, now.with_timezone(&tz)),
+            UserTimezone::Fixed(offset) => find(text, now.with_timezone(&offset)),
+        }
+    }
+}
+
+struct Rule {
+    pattern: Regex,
+    resolve: fn(&Captures, NaiveDateTime) -> Option<Span>,
+}
+
+const NUMBER: &str = r"(?P<n>\d{1,3}|an?|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve|(?:a\s+)?couple(?:\s+of)?|(?:a\s+)?few)";
+const UNIT: &str = r"(?P<unit>day|week|month|year)s?";
+// Full names only: "sat" or "wed" on their own are usually something else
+const WEEKDAY: &str = r"monday|tuesday|wednesday|thursday|friday|saturday|sunday";
+const MONTH: &str = r"jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?";
+const PART: &str = r"(?:\s+(?P<part>morning|afternoon|evening|night))?";
+
+fn rule(pattern: &str, resolve: fn(&Captures, NaiveDateTime) -> Option<Span>) -> Rule {
+    // A leading preposition belongs to the phrase so it leaves the remainder too
+    let pattern = format!(r"(?i)\b(?:(?:on|in|during|from|over)\s+)?(?:{})\b", pattern);
+    Rule {
+        pattern: Regex::new(&pattern).expect("valid date pattern"),
+        resolve,
+    }
+}
+
+static RULES: Lazy<Vec<Rule>> = Lazy::new(|| vec![
+    rule(&format!(r"{}\s+{}\s+ago", NUMBER, UNIT), units_ago),
+    rule(&format!(r"(?:the\s+)?(?:last|previous|past)\s+{}\s+{}", NUMBER, UNIT), trailing_units),
+    rule(&format!(r"(?:the\s+)?past\s+{}", UNIT), trailing_units),
+    rule(&format!(r"(?P<day>today|yesterday|(?:the\s+)?day\s+before\s+yesterday|(?:last\s+)?(?:{})){}", WEEKDAY, PART), day_with_part),
+    rule(r"this\s+(?P<part>morning|afternoon|evening)|tonight|last\s+night", part_of_today),
+    rule(r"(?P<which>this|last|previous)\s+(?P<unit>week|weekend|month|year)", calendar_period),
+    rule(r"(?:the\s+)?(?P<unit>week|month|year)\s+before\s+last", period_before_last),
+    rule(&format!(r"(?P<month>{})\s+(?P<day>\d{{1,2}})(?:st|nd|rd|th)?(?:,?\s+(?P<year>\d{{4}}))?", MONTH), day_of_month),
+    rule(&format!(r"(?:the\s+)?(?P<day>\d{{1,2}})(?:st|nd|rd|th)?\s+(?:of\s+)?(?P<month>{})(?:,?\s+(?P<year>\d{{4}}))?", MONTH), day_of_month),
+    rule(r"(?P<year>\d{4})-(?P<month>\d{1,2})-(?P<day>\d{1,2})", iso_date),
+    rule(&format!(r"(?P<month>{})\s+(?P<year>\d{{4}})", MONTH), month_of_year),
+    // A bare month name is too ambiguous ("may"); it needs a preposition or qualifier
+    rule(&format!(r"(?P<which>in|during|last|this)\s+(?P<month>{})", MONTH), named_month),
+    rule(r"(?:in|during)\s+(?P<year>(?:19|20)\d{2})", whole_year),
+]);
+
+static SINCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bsince\s+$").unwrap());
+
+fn find<Zone: TimeZone>(text: &str, now: DateTime<Zone>) -> Option<TemporalMatch> {
+    let local_now = now.naive_local();
+
+    // Leftmost phrase wins, then the longest one starting there
+    let mut best: Option<(usize, usize, Span)> = None;
+    for rule in RULES.iter() {
+        let found = rule.pattern.captures_iter(text).find_map(|captures| {
+            let whole = captures.get(0)?;
+            (rule.resolve)(&captures, local_now).map(|span| (whole.start(), whole.end(), span))
+        });
+        if let Some((start, end, span)) = found {
+            let better = match best {
+                None => true,
+                Some((best_start, best_end, _)) => start < best_start || (start == best_start && end > best_end),
+            };
+            if better {
+                best = Some((start, end, span));
+            }
+        }
+    }
+
+    let (mut start, end, (from, mut to)) = best?;
+    if let Some(since) = SINCE.find(&text[..start]) {
+        start = since.start();
+        to = local_now;
+    }
+
+    let timezone = now.timezone();
+    let remainder = format!("{} {}", &text[..start], &text[end..])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(TemporalMatch {
+        range: DateRange {
+            start: to_utc(&timezone, from),
+            end: to_utc(&timezone, to) - Duration::nanoseconds(1),
+        },
+        phrase: text[start..end].trim().to_string(),
+        remainder,
+    })
+}
+
+fn to_utc<Zone: TimeZone>(timezone: &Zone, local: NaiveDateTime) -> DateTime<Utc> {
+    // A wall-clock time skipped by a DST change doesn't exist; the hour after it does
+    timezone.from_local_datetime(&local).earliest()
+        .or_else(|| timezone.from_local_datetime(&(local + Duration::hours(1))).earliest())
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+}
+
+fn number(captures: &Captures) -> Option<u32> {
+    let word = captures.name("n")?.as_str().to_lowercase();
+    let word = word.split_whitespace().last()?;
+    Some(match word {
+        "a" | "an" | "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" => 12,
+        "couple" | "of" => 2,
+        "few" => 3,
+        digits => digits.parse().ok()?,
+    })
+}
+
+fn unit(captures: &Captures) -> String {
+    captures.name("unit").map(|m| m.as_str().to_lowercase()).unwrap_or_default()
+}
+
+fn midnight(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0).expect("midnight exists")
+}
+
+fn whole_days(date: NaiveDate, days: i64) -> Span {
+    (midnight(date), midnight(date) + Duration::days(days))
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn month_span(year: i32, month: u32) -> Option<Span> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    Some((midnight(first), midnight(first.checked_add_months(Months::new(1))?)))
+}
+
+fn year_span(year: i32) -> Option<Span> {
+    Some((midnight(NaiveDate::from_ymd_opt(year, 1, 1)?), midnight(NaiveDate::from_ymd_opt(year + 1, 1, 1)?)))
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"]
+        .iter()
+        .position(|prefix| name.starts_with(prefix))
+        .map(|index| index as u32 + 1)
+}
+
+fn weekday_number(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+        .iter()
+        .position(|prefix| name.starts_with(prefix))
+        .map(|index| index as u32)
+}
+
+/// The calendar day, week, month or year `n` units back: "two weeks ago"
+fn units_ago(captures: &Captures, now: NaiveDateTime) -> Option<Span> {
+    let n = number(captures)?;
+    let today = now.date();
+    match unit(captures).as_str() {
+        "day" => Some(whole_days(today - Duration::days(n as i64), 1)),
+        "week" => Some(whole_days(week_start(today) - Duration::weeks(n as i64), 7)),
+        "month" => {
+            let month = today.with_day(1)?.checked_sub_months(Months::new(n))?;
+            month_span(month.year(), month.month())
+        }
+        "year" => year_span(today.year() - n as i32),
+        _ => None,
+    }
+}
+
+/// A rolling window ending now: "the past 3 days", "last two weeks"
+fn trailing_units(captures: &Captures, now: NaiveDateTime) -> Option<Span> {
+    let n = if captures.name("n").is_some() { number(captures)? } else { 1 };
+    let from = match unit(captures).as_str() {
+        "day" => now - Duration::days(n as i64),
+        "week" => now - Duration::weeks(n as i64),
+        "month" => now.checked_sub_months(Months::new(n))?,
+        "year" => now.checked_sub_months(Months::new(n * 12))?,
+        _ => return None,
+    };
+    Some((from, now))
+}
+
+/// Wall-clock hours of a part of the day; night runs into the next morning
+fn part_of_day(date: NaiveDate, part: &str) -> Span {
+    let (from, to) = match part {
+        "morning" => (5, 12),
+        "afternoon" => (12, 17),
+        "evening" => (17, 21),
+        _ => (21, 29),
+    };
+    (midnight(date) + Duration::hours(from), midnight(date) + Duration::hours(to))
+}
+
+/// "yesterday afternoon", "last Tuesday", "friday evening"
+fn day_with_part(captures: &Captures, now: NaiveDateTime) -> Option<Span> {
+    let today = now.date();
+    let day = captures.name("day")?.as_str().to_lowercase();
+    let date = if day == "today" {
+        today
+    } else if day == "yesterday" {
+        today - Duration::days(1)
+    } else if day.ends_with("before yesterday") {
+        today - Duration::days(2)
+    } else {
+        let (strictly_past, name) = match day.strip_prefix("last") {
+            Some(name) => (true, name.trim()),
+            None => (false, day.as_str()),
+        };
+        let target = weekday_number(name)?;
+        let mut back = (today.weekday().num_days_from_monday() + 7 - target) % 7;
+        if back == 0 && strictly_past {
+            back = 7;
+        }
+        today - Duration::days(back as i64)
+    };
+
+    Some(match captures.name("part") {
+        Some(part) => part_of_day(date, &part.as_str().to_lowercase()),
+        None => whole_days(date, 1),
+    })
+}
+
+/// "this morning", "tonight", "last night"
+fn part_of_today(captures: &Captures, now: NaiveDateTime) -> Option<Span> {
+    let today = now.date();
+    let phrase = captures.get(0)?.as_str().to_lowercase();
+    Some(match captures.name("part") {
+        Some(part) => part_of_day(today, &part.as_str().to_lowercase()),
+        None if phrase.ends_with("tonight") => part_of_day(today, "night"),
+        None => part_of_day(today - Duration::days(1), "night"),
+    })
+}
+
+/// "this week", "last month", "last weekend"; weeks start on Monday
+fn calendar_period(captures: &Captures, now: NaiveDateTime) -> Option<Span> {
+    let back = match captures.name("which")?.as_str().to_lowercase().as_str() {
+        "this" => 0,
+        _ => 1,
+    };
+    calendar_period_back(&unit(captures), now.date(), back)
+}
+
+/// "the week before last"
+fn period_before_last(captures: &Captures, now: NaiveDateTime) -> Option<Span> {
+    calendar_period_back(&unit(captures), now.date(), 2)
+}
+
+fn calendar_period_back(unit: &str, today: NaiveDate, back: u32) -> Option<Span> {
+    match unit {
+        "week" => Some(whole_days(week_start(today) - Duration::weeks(back as i64), 7)),
+        "weekend" => Some(whole_days(week_start(today) + Duration::days(5) - Duration::weeks(back as i64), 2)),
+        "month" => {
+            let month = today.with_day(1)?.checked_sub_months(Months::new(back))?;
+            month_span(month.year(), month.month())
+        }
+        "year" => year_span(today.year() - back as i32),
+        _ => None,
+    }
+}
+
+/// "March 5th", "5 March 2024"; without a year, the latest one not in the future
+fn day_of_month(captures: &Captures, now: NaiveDateTime) -> Option<Span> {
+    let month = month_number(captures.name("month")?.as_str())?;
+    let day: u32 = captures.name("day")?.as_str().parse().ok()?;
+    let today = now.date();
+    let date = match captures.name("year") {
+        Some(year) => NaiveDate::from_ymd_opt(year.as_str().parse().ok()?, month, day)?,
+        None => {
+            let this_year = NaiveDate::from_ymd_opt(today.year(), month, day);
+            match this_year {
+                Some(date) if date <= today => date,
+                _ => NaiveDate::from_ymd_opt(today.year() - 1, month, day)?,
+            }
+        }
+    };
+    Some(whole_days(date, 1))
+}
+
+/// "2024-03-05"
+fn iso_date(captures: &Captures, _now: NaiveDateTime) -> Option<Span> {
+    let date = NaiveDate::from_ymd_opt(
+        captures.name("year")?.as_str().parse().ok()?,
+        captures.name("month")?.as_str().parse().ok()?,
+        captures.name("day")?.as_str().parse().ok()?,
+    )?;
+    Some(whole_days(date, 1))
+}
+
+/// "March 2024"
+fn month_of_year(captures: &Captures, _now: NaiveDateTime) -> Option<Span> {
+    month_span(captures.name("year")?.as_str().parse().ok()?, month_number(captures.name("month")?.as_str())?)
+}
+
+/// "in March" is the latest March that has begun; "last March" the latest one that has ended
+fn named_month(captures: &Captures, now: NaiveDateTime) -> Option<Span> {
+    let month = month_number(captures.name("month")?.as_str())?;
+    let today = now.date();
+    let year = match captures.name("which")?.as_str().to_lowercase().as_str() {
+        "this" => today.year(),
+        "last" if month >= today.month() => today.year() - 1,
+        "last" => today.year(),
+        _ if month > today.month() => today.year() - 1,
+        _ => today.year(),
+    };
+    month_span(year, month)
+}
+
+/// "in 2023"
+fn whole_year(captures: &Captures, _now: NaiveDateTime) -> Option<Span> {
+    year_span(captures.name("year")?.as_str().parse().ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Thursday 2024-03-14 15:30 UTC, which is 16:30 in Berlin
+    fn fixed_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 14, 15, 30, 0).unwrap()
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    fn parse(text: &str) -> TemporalMatch {
+        DateParser::new(UserTimezone::Fixed(FixedOffset::east_opt(0).unwrap()))
+            .parse_at(text, fixed_now())
+            .unwrap_or_else(|| panic!("no date in {:?}", text))
+    }
+
+    fn assert_range(text: &str, start: DateTime<Utc>, end: DateTime<Utc>) {
+        let found = parse(text);
+        assert_eq!(found.range.start, start, "start of {:?}", text);
+        assert_eq!(found.range.end + Duration::nanoseconds(1), end, "end of {:?}", text);
+    }
+
+    #[test]
+    fn test_relative_days_and_weekdays() {
+        assert_range("yesterday", utc(2024, 3, 13, 0), utc(2024, 3, 14, 0));
+        assert_range("yesterday afternoon", utc(2024, 3, 13, 12), utc(2024, 3, 13, 17));
+        assert_range("last night", utc(2024, 3, 13, 21), utc(2024, 3, 14, 5));
+        assert_range("what did we discuss last Tuesday", utc(2024, 3, 12, 0), utc(2024, 3, 13, 0));
+        assert_range("last Thursday", utc(2024, 3, 7, 0), utc(2024, 3, 8, 0));
+        assert_range("on thursday", utc(2024, 3, 14, 0), utc(2024, 3, 15, 0));
+        assert_range("3 days ago", utc(2024, 3, 11, 0), utc(2024, 3, 12, 0));
+    }
+
+    #[test]
+    fn test_weeks_months_and_years() {
+        assert_range("two weeks ago", utc(2024, 2, 26, 0), utc(2024, 3, 4, 0));
+        assert_range("last week", utc(2024, 3, 4, 0), utc(2024, 3, 11, 0));
+        assert_range("last weekend", utc(2024, 3, 9, 0), utc(2024, 3, 11, 0));
+        assert_range("the past 3 days", utc(2024, 3, 11, 15) + Duration::minutes(30), fixed_now());
+        assert_range("a month ago", utc(2024, 2, 1, 0), utc(2024, 3, 1, 0));
+        assert_range("in March", utc(2024, 3, 1, 0), utc(2024, 4, 1, 0));
+        assert_range("in June", utc(2023, 6, 1, 0), utc(2023, 7, 1, 0));
+        assert_range("last March", utc(2023, 3, 1, 0), utc(2023, 4, 1, 0));
+        assert_range("during 2022", utc(2022, 1, 1, 0), utc(2023, 1, 1, 0));
+    }
+
+    #[test]
+    fn test_absolute_dates() {
+        assert_range("March 5th", utc(2024, 3, 5, 0), utc(2024, 3, 6, 0));
+        assert_range("on 20 December", utc(2023, 12, 20, 0), utc(2023, 12, 21, 0));
+        assert_range("jan 2 2023", utc(2023, 1, 2, 0), utc(2023, 1, 3, 0));
+        assert_range("2024-02-29", utc(2024, 2, 29, 0), utc(2024, 3, 1, 0));
+        assert_range("since last week", utc(2024, 3, 4, 0), fixed_now());
+    }
+
+    #[test]
+    fn test_remainder_and_no_match() {
+        let found = parse("what did we discuss last Tuesday about the budget");
+        assert_eq!(found.phrase, "last Tuesday");
+        assert_eq!(found.remainder, "what did we discuss about the budget");
+
+        assert_eq!(parse("notes from yesterday").remainder, "notes");
+
+        let parser = DateParser::new(UserTimezone::Local);
+        assert!(parser.parse_at("you may want a second opinion", fixed_now()).is_none());
+        assert!(parser.parse_at("deploy the service", fixed_now()).is_none());
+    }
+
+    #[test]
+    fn test_resolves_in_user_timezone() {
+        // 16:30 on Thursday in Berlin; "yesterday" is Wednesday there, an hour ahead of UTC
+        let berlin = DateParser::new(UserTimezone::from_setting("Europe/Berlin"));
+        let found = berlin.parse_at("yesterday", fixed_now()).unwrap();
+        assert_eq!(found.range.start, utc(2024, 3, 12, 23));
+
+        // Late evening in New York is already the next day in UTC
+        let late = Utc.with_ymd_and_hms(2024, 3, 15, 2, 0, 0).unwrap();
+        let new_york = DateParser::new(UserTimezone::from_setting("-04:00"));
+        assert_eq!(new_york.parse_at("today", late).unwrap().range.start, utc(2024, 3, 14, 4));
+
+        assert_eq!(UserTimezone::from_setting("UTC+5:30"), UserTimezone::Fixed(FixedOffset::east_opt(19800).unwrap()));
+        assert_eq!(UserTimezone::from_setting("local"), UserTimezone::Local);
+    }
+}
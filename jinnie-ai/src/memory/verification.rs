@@ -0,0 +1,168 @@
+//! Facts the system worked out on its own, queued for the user to confirm, reject or correct
+//! before they are trusted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::memory_types::{Insight, Memory, MemorySource, VerificationStatus};
+
+/// Tag on memories that describe the user; verified ones make up the profile prompt
+pub const PROFILE_TAG: &str = "profile";
+
+/// Whether `memory` belongs in the user profile: verified and tagged `PROFILE_TAG`
+pub fn is_profile_fact(memory: &Memory) -> bool {
+    memory.metadata.verification_status == VerificationStatus::Verified
+        && memory.tags.iter().any(|tag| tag == PROFILE_TAG)
+}
+
+/// Custom field linking a memory to the insight it was confirmed from
+pub const INSIGHT_FIELD: &str = "insight_id";
+
+/// Importance added when the user confirms or corrects a fact
+pub const CONFIRMED_IMPORTANCE_BOOST: f32 = 0.2;
+
+/// Importance kept by a rejected fact, so maintenance clears it out
+pub const REJECTED_IMPORTANCE_FACTOR: f32 = 0.2;
+
+/// What is being asked about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationSubject {
+    /// A memory the system inferred itself
+    Memory(Uuid),
+    /// A `Preference` insight from reflection
+    Insight(Uuid),
+}
+
+/// A fact awaiting the user's confirmation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationRequest {
+    pub subject: VerificationSubject,
+    pub agent_id: String,
+    pub statement: String,
+    /// e.g. "Is it right that you prefer TypeScript over JavaScript?"
+    pub question: String,
+    pub confidence: f32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The user's answer to a verification request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum VerificationAnswer {
+    Confirm,
+    Reject,
+    /// Not quite: this is what's true instead
+    Correct(String),
+}
+
+/// Inferred memories still waiting on the user
+pub fn needs_verification(memory: &Memory) -> bool {
+    matches!(memory.metadata.source, MemorySource::Reflection | MemorySource::SystemInsight)
+        && memory.metadata.verification_status == VerificationStatus::Unverified
+}
+
+impl VerificationRequest {
+    pub fn for_memory(memory: &Memory) -> Self {
+        Self {
+            subject: VerificationSubject::Memory(memory.id),
+            agent_id: memory.metadata.agent_id.clone(),
+            statement: memory.content.clone(),
+            question: question_for(&memory.content),
+            confidence: memory.importance_score,
+            created_at: memory.created_at,
+        }
+    }
+
+    pub fn for_insight(insight: &Insight) -> Self {
+        let statement = insight_statement(insight);
+        Self {
+            subject: VerificationSubject::Insight(insight.id),
+            agent_id: insight.agent_id.clone(),
+            question: question_for(&statement),
+            statement,
+            confidence: insight.confidence,
+            created_at: insight.created_at,
+        }
+    }
+}
+
+/// The fact an insight asserts, without the reflection's label
+pub fn insight_statement(insight: &Insight) -> String {
+    let content = insight.content.trim();
+    content
+        .split_once(": ")
+        .filter(|(label, _)| label.starts_with("Detected"))
+        .map_or(content, |(_, statement)| statement.trim())
+        .to_string()
+}
+
+/// Turn a statement in the user's words into a yes/no question put back to them
+pub fn question_for(statement: &str) -> String {
+    let statement = statement.trim().trim_end_matches(['.', '!', '?']);
+    let words: Vec<String> = statement
+        .split_whitespace()
+        .enumerate()
+        .map(|(index, word)| {
+            let (core, trailing) = word.split_at(word.trim_end_matches([',', ';', ':']).len());
+            let swapped = match core.to_lowercase().as_str() {
+                "i" => Some("you"),
+                "i'm" => Some("you're"),
+                "i've" => Some("you've"),
+                "i'd" => Some("you'd"),
+                "i'll" => Some("you'll"),
+                "me" => Some("you"),
+                "my" => Some("your"),
+                "mine" => Some("yours"),
+                "myself" => Some("yourself"),
+                "am" => Some("are"),
+                _ => None,
+            };
+            match swapped {
+                Some(replacement) => format!("{}{}", replacement, trailing),
+                // Lowercase a sentence-initial word unless it looks like a name or acronym
+                None if index == 0 && core.chars().skip(1).all(|c| !c.is_uppercase()) => {
+                    format!("{}{}", core.to_lowercase(), trailing)
+                }
+                None => word.to_string(),
+            }
+        })
+        .collect();
+    format!("Is it right that {}?", words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::memory_types::InsightType;
+
+    #[test]
+    fn test_questions_are_put_back_to_the_user() {
+        assert_eq!(
+            question_for("I prefer TypeScript over JavaScript."),
+            "Is it right that you prefer TypeScript over JavaScript?"
+        );
+        assert_eq!(
+            question_for("My team, and I, am moving to Rust"),
+            "Is it right that your team, and you, are moving to Rust?"
+        );
+        assert_eq!(question_for("The user works remotely"), "Is it right that the user works remotely?");
+        assert_eq!(question_for("NASA is a client"), "Is it right that NASA is a client?");
+    }
+
+    #[test]
+    fn test_insight_statement_drops_label() {
+        let insight = Insight {
+            id: Uuid::new_v4(),
+            insight_type: InsightType::Preference,
+            content: "Detected preference: I prefer tea over coffee".to_string(),
+            confidence: 0.6,
+            supporting_memories: vec![],
+            created_at: Utc::now(),
+            agent_id: "agent1".to_string(),
+            verification_status: VerificationStatus::Unverified,
+        };
+        let request = VerificationRequest::for_insight(&insight);
+        assert_eq!(request.statement, "I prefer tea over coffee");
+        assert_eq!(request.question, "Is it right that you prefer tea over coffee?");
+    }
+}
@@ -0,0 +1,69 @@
+//! Log wrapper that redacts personal data before records reach the real logger
+
+use log::{Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+
+use super::redaction::Redactor;
+
+static LOG_REDACTOR: Lazy<RwLock<Redactor>> = Lazy::new(|| RwLock::new(Redactor::default()));
+
+/// Use `redactor` for log output from now on, e.g. once the privacy settings are loaded
+pub fn set_log_redactor(redactor: Redactor) {
+    if let Ok(mut current) = LOG_REDACTOR.write() {
+        *current = redactor;
+    }
+}
+
+/// Passes records to `inner` with personal data redacted from the message
+pub struct RedactingLogger<L> {
+    inner: L,
+}
+
+impl<L: Log> RedactingLogger<L> {
+    pub fn new(inner: L) -> Self {
+        Self { inner }
+    }
+
+    /// Install as the global logger
+    pub fn install(self, max_level: log::LevelFilter) -> Result<(), log::SetLoggerError>
+    where
+        L: 'static,
+    {
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+impl<L: Log> Log for RedactingLogger<L> {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = record.args().to_string();
+        let redacted = match LOG_REDACTOR.read() {
+            Ok(redactor) => redactor.redact(&message).text,
+            Err(_) => message,
+        };
+
+        self.inner.log(
+            &Record::builder()
+                .metadata(record.metadata().clone())
+                .args(format_args!("{}", redacted))
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .build(),
+        );
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
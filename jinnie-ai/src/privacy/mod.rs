@@ -0,0 +1,14 @@
+//! Privacy safeguards for data the assistant keeps, exports or expires
+
+pub mod logging;
+pub mod redaction;
+pub mod retention;
+
+pub use logging::{set_log_redactor, RedactingLogger};
+pub use redaction::{
+    detect_pii, PiiKind, PiiMatch, Redacted, RedactionEntry, RedactionPolicy, RedactionReport, Redactor,
+};
+pub use retention::{
+    start_retention, RetentionAction, RetentionAudit, RetentionEngine, RetentionItem, RetentionKind,
+    RetentionPolicy, RetentionReport,
+};
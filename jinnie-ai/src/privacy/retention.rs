@@ -0,0 +1,684 @@
+//! Retention: removes data older than `privacy.auto_cleanup_days`.
+//!
+//! Covers chat messages, Working/ShortTerm memories, chat sessions, log files and caches.
+//! Pinned memories and the LongTerm layer are never touched. Every run, including dry runs,
+//! appends an audit entry to `retention_audit.jsonl` in the data directory.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::llm::SessionManager;
+use crate::memory::{MemoryLayer, MemoryManager};
+use crate::types::{AppState, Message};
+use crate::vector::EmbeddingCache;
+
+/// File the audit trail is appended to, inside the data directory
+pub const RETENTION_AUDIT_FILE: &str = "retention_audit.jsonl";
+
+/// How often the background task enforces retention
+pub const RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Memory layers retention applies to; everything else is exempt
+const EXPIRING_LAYERS: [MemoryLayer; 2] = [MemoryLayer::Working, MemoryLayer::ShortTerm];
+
+/// What happens to expired messages and memories
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Keep a copy out of the way: memories go to the memory archive, messages to `archive/`
+    #[default]
+    Archive,
+    Delete,
+}
+
+/// Kind of data a retention item belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionKind {
+    Message,
+    Memory,
+    Session,
+    Log,
+    Cache,
+}
+
+/// One piece of data past its retention period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionItem {
+    pub kind: RetentionKind,
+    /// Message, memory or session id, file path, or cache name
+    pub id: String,
+    pub agent_id: Option<String>,
+    /// When the item was created or, for files and caches, last used
+    pub timestamp: DateTime<Utc>,
+    /// Number of entries the item stands for (cache rows); 1 otherwise
+    pub count: usize,
+}
+
+/// Outcome of a retention run; for a dry run, `items` is what would be removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub id: Uuid,
+    pub run_at: DateTime<Utc>,
+    pub dry_run: bool,
+    pub action: RetentionAction,
+    pub items: Vec<RetentionItem>,
+    /// Expired memories kept because they are pinned
+    pub exempt_pinned: usize,
+    pub errors: Vec<String>,
+}
+
+impl RetentionReport {
+    /// Items per kind, counting cache rows individually
+    pub fn counts(&self) -> BTreeMap<RetentionKind, usize> {
+        let mut counts = BTreeMap::new();
+        for item in &self.items {
+            *counts.entry(item.kind).or_insert(0) += item.count;
+        }
+        counts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn audit_entry(&self) -> RetentionAudit {
+        RetentionAudit {
+            id: self.id,
+            run_at: self.run_at,
+            dry_run: self.dry_run,
+            action: self.action,
+            removed: self.counts(),
+            exempt_pinned: self.exempt_pinned,
+            errors: self.errors.clone(),
+        }
+    }
+}
+
+/// Audit trail entry; counts only, so the trail itself holds no personal data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionAudit {
+    pub id: Uuid,
+    pub run_at: DateTime<Utc>,
+    pub dry_run: bool,
+    pub action: RetentionAction,
+    pub removed: BTreeMap<RetentionKind, usize>,
+    pub exempt_pinned: usize,
+    pub errors: Vec<String>,
+}
+
+/// Retention periods, globally and per agent
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    /// `None` keeps data forever
+    pub default_days: Option<u32>,
+    /// Per-agent days; 0 keeps that agent's data forever
+    pub overrides: HashMap<String, u32>,
+    pub action: RetentionAction,
+}
+
+impl RetentionPolicy {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            default_days: config.privacy.auto_cleanup_days,
+            overrides: config.privacy.retention_overrides.clone(),
+            action: config.privacy.retention_action,
+        }
+    }
+
+    /// Whether any data can expire at all
+    pub fn is_active(&self) -> bool {
+        self.default_days.is_some_and(|days| days > 0) || self.overrides.values().any(|days| *days > 0)
+    }
+
+    /// Data older than this is expired; `None` keeps it forever
+    pub fn cutoff(&self, agent_id: Option<&str>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let days = agent_id
+            .and_then(|agent_id| self.overrides.get(agent_id).copied())
+            .or(self.default_days)?;
+        (days > 0).then(|| now - Duration::days(days as i64))
+    }
+}
+
+/// Finds and removes expired data
+pub struct RetentionEngine {
+    policy: RetentionPolicy,
+    data_dir: PathBuf,
+    logs_dir: PathBuf,
+    cache_dir: PathBuf,
+    sessions: Option<Arc<Mutex<SessionManager>>>,
+    embedding_cache: Option<Arc<EmbeddingCache>>,
+}
+
+impl RetentionEngine {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            policy: RetentionPolicy::from_config(config),
+            data_dir: config.data_dir_path(),
+            logs_dir: config.logs_dir_path(),
+            cache_dir: config.cache_dir_path(),
+            sessions: None,
+            embedding_cache: None,
+        }
+    }
+
+    /// Engine for the running app, with the embedding cache attached when there is one
+    pub fn for_state(state: &AppState) -> Self {
+        let engine = Self::new(&state.config);
+        match state.vector_store.as_ref().and_then(|store| store.embedding_engine().cache()) {
+            Some(cache) => engine.with_embedding_cache(cache),
+            None => engine,
+        }
+    }
+
+    /// Also expire chat sessions held by `sessions`
+    pub fn with_sessions(mut self, sessions: Arc<Mutex<SessionManager>>) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    /// Also expire embeddings not used within the retention period
+    pub fn with_embedding_cache(mut self, cache: Arc<EmbeddingCache>) -> Self {
+        self.embedding_cache = Some(cache);
+        self
+    }
+
+    pub fn policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    /// List what would be removed without changing anything
+    pub async fn preview(&self, state: &AppState) -> Result<RetentionReport> {
+        self.run(state, true).await
+    }
+
+    /// Remove (or archive) everything past its retention period
+    pub async fn enforce(&self, state: &AppState) -> Result<RetentionReport> {
+        self.run(state, false).await
+    }
+
+    /// Past audit entries, oldest first
+    pub fn audit_trail(&self) -> Result<Vec<RetentionAudit>> {
+        let path = self.audit_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        std::fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    async fn run(&self, state: &AppState, dry_run: bool) -> Result<RetentionReport> {
+        let now = Utc::now();
+        let mut report = RetentionReport {
+            id: Uuid::new_v4(),
+            run_at: now,
+            dry_run,
+            action: self.policy.action,
+            items: Vec::new(),
+            exempt_pinned: 0,
+            errors: Vec::new(),
+        };
+
+        if self.policy.is_active() {
+            self.messages(state, now, &mut report).await;
+            self.memories(state, now, &mut report).await;
+            self.sessions(now, &mut report).await;
+            self.files(RetentionKind::Log, &self.logs_dir, now, &mut report);
+            self.files(RetentionKind::Cache, &self.cache_dir, now, &mut report);
+            self.embeddings(now, &mut report);
+        }
+
+        for error in &report.errors {
+            log::warn!("Retention: {}", error);
+        }
+        if !report.is_empty() {
+            log::info!(
+                "Retention {}: {} item(s) {}",
+                if dry_run { "preview" } else { "run" },
+                report.items.len(),
+                if dry_run { "would be removed" } else { "removed" },
+            );
+        }
+
+        self.append_audit(&report.audit_entry())?;
+        Ok(report)
+    }
+
+    async fn messages(&self, state: &AppState, now: DateTime<Utc>, report: &mut RetentionReport) {
+        let mut messages = state.messages.lock().await;
+        let expired = expired_messages(&self.policy, &messages, now);
+        if expired.is_empty() {
+            return;
+        }
+
+        report.items.extend(expired.iter().map(|message| RetentionItem {
+            kind: RetentionKind::Message,
+            id: message.id.clone(),
+            agent_id: Some(message.agent_id.clone()),
+            timestamp: message_time(message).unwrap_or(now),
+            count: 1,
+        }));
+        if report.dry_run {
+            return;
+        }
+
+        if self.policy.action == RetentionAction::Archive {
+            if let Err(e) = self.archive_messages(&expired) {
+                // Without the archive copy the messages must stay
+                report.errors.push(format!("Archiving messages failed: {}", e));
+                return;
+            }
+        }
+
+        let expired_ids: std::collections::HashSet<&str> = expired.iter().map(|message| message.id.as_str()).collect();
+        for agent_messages in messages.values_mut() {
+            agent_messages.retain(|message| !expired_ids.contains(message.id.as_str()));
+        }
+        messages.retain(|_, agent_messages| !agent_messages.is_empty());
+
+        if let Err(e) = crate::storage::MessageStorage::save(&messages).await {
+            report.errors.push(format!("Saving messages failed: {}", e));
+        }
+    }
+
+    async fn memories(&self, state: &AppState, now: DateTime<Utc>, report: &mut RetentionReport) {
+        let Some(ref memory_system) = state.memory_system else { return };
+        let manager = memory_system.memory_manager();
+        let mut manager = manager.lock().await;
+
+        let (expired, exempt) = match expired_memories(&self.policy, &mut manager, now) {
+            Ok(found) => found,
+            Err(e) => {
+                report.errors.push(format!("Listing memories failed: {}", e));
+                return;
+            }
+        };
+        report.exempt_pinned += exempt;
+        report.items.extend(expired.iter().cloned());
+        if report.dry_run || expired.is_empty() {
+            return;
+        }
+
+        let ids: Vec<Uuid> = expired.iter().filter_map(|item| Uuid::parse_str(&item.id).ok()).collect();
+        let result = match self.policy.action {
+            RetentionAction::Archive => manager.archive_memories(&ids, None, None).await,
+            RetentionAction::Delete => manager.forget_many(&ids).await,
+        };
+        if let Err(e) = result {
+            report.errors.push(format!("Removing memories failed: {}", e));
+        }
+    }
+
+    async fn sessions(&self, now: DateTime<Utc>, report: &mut RetentionReport) {
+        let Some(ref sessions) = self.sessions else { return };
+        let mut sessions = sessions.lock().await;
+
+        let expired: Vec<RetentionItem> = sessions.sessions()
+            .filter(|session| {
+                self.policy.cutoff(Some(&session.agent_id), now)
+                    .is_some_and(|cutoff| session.last_activity < cutoff)
+            })
+            .map(|session| RetentionItem {
+                kind: RetentionKind::Session,
+                id: session.session_id.clone(),
+                agent_id: Some(session.agent_id.clone()),
+                timestamp: session.last_activity,
+                count: 1,
+            })
+            .collect();
+
+        if !report.dry_run {
+            for item in &expired {
+                if let Err(e) = sessions.end_session(&item.id).await {
+                    report.errors.push(format!("Ending session {} failed: {}", item.id, e));
+                }
+            }
+        }
+        report.items.extend(expired);
+    }
+
+    /// Files under `dir` not modified within the default retention period
+    fn files(&self, kind: RetentionKind, dir: &Path, now: DateTime<Utc>, report: &mut RetentionReport) {
+        let Some(cutoff) = self.policy.cutoff(None, now) else { return };
+        if !dir.is_dir() {
+            return;
+        }
+
+        let mut expired = Vec::new();
+        if let Err(e) = collect_stale_files(dir, cutoff, &mut expired) {
+            report.errors.push(format!("Scanning {} failed: {}", dir.display(), e));
+        }
+
+        for (path, modified) in expired {
+            if !report.dry_run {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    report.errors.push(format!("Removing {} failed: {}", path.display(), e));
+                    continue;
+                }
+            }
+            report.items.push(RetentionItem {
+                kind,
+                id: path.display().to_string(),
+                agent_id: None,
+                timestamp: modified,
+                count: 1,
+            });
+        }
+    }
+
+    fn embeddings(&self, now: DateTime<Utc>, report: &mut RetentionReport) {
+        let (Some(cache), Some(cutoff)) = (&self.embedding_cache, self.policy.cutoff(None, now)) else { return };
+
+        let result = if report.dry_run {
+            cache.count_unused_since(cutoff)
+        } else {
+            cache.remove_unused_since(cutoff)
+        };
+        match result {
+            Ok(0) => {}
+            Ok(count) => report.items.push(RetentionItem {
+                kind: RetentionKind::Cache,
+                id: format!("embedding cache ({})", cache.model_id()),
+                agent_id: None,
+                timestamp: cutoff,
+                count,
+            }),
+            Err(e) => report.errors.push(format!("Expiring cached embeddings failed: {}", e)),
+        }
+    }
+
+    /// Merge `expired` into `archive/messages.json`, encrypted like the live messages file
+    fn archive_messages(&self, expired: &[Message]) -> Result<()> {
+        use crate::storage::encryption::{read_to_string, write_file};
+
+        let dir = self.data_dir.join("archive");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("messages.json");
+
+        let mut archive: HashMap<String, Vec<Message>> = if path.exists() {
+            serde_json::from_str(&read_to_string(&path)?)?
+        } else {
+            HashMap::new()
+        };
+        for message in expired {
+            archive.entry(message.agent_id.clone()).or_default().push(message.clone());
+        }
+
+        write_file(&path, serde_json::to_string_pretty(&archive)?.as_bytes())?;
+        Ok(())
+    }
+
+    fn audit_path(&self) -> PathBuf {
+        self.data_dir.join(RETENTION_AUDIT_FILE)
+    }
+
+    fn append_audit(&self, entry: &RetentionAudit) -> Result<()> {
+        std::fs::create_dir_all(&self.data_dir)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.audit_path())?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+/// Enforce retention now and then once a day in the background
+pub fn start_retention(state: &AppState) {
+    let engine = RetentionEngine::for_state(state);
+    if !engine.policy().is_active() {
+        log::info!("Retention disabled: data is kept until removed by hand");
+        return;
+    }
+
+    let state = state.clone();
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = engine.enforce(&state).await {
+                log::warn!("Retention run failed: {}", e);
+            }
+            tokio::time::sleep(RETENTION_INTERVAL).await;
+        }
+    });
+}
+
+/// Messages older than their agent's retention period
+fn expired_messages(
+    policy: &RetentionPolicy,
+    messages: &HashMap<String, Vec<Message>>,
+    now: DateTime<Utc>,
+) -> Vec<Message> {
+    messages.iter()
+        .filter_map(|(agent_id, agent_messages)| Some((policy.cutoff(Some(agent_id), now)?, agent_messages)))
+        .flat_map(|(cutoff, agent_messages)| {
+            agent_messages.iter().filter(move |message| message_time(message).is_some_and(|at| at < cutoff))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Expired Working/ShortTerm memories, and how many expired ones are kept because they are pinned
+fn expired_memories(
+    policy: &RetentionPolicy,
+    manager: &mut MemoryManager,
+    now: DateTime<Utc>,
+) -> Result<(Vec<RetentionItem>, usize)> {
+    let mut expired = Vec::new();
+    let mut exempt = 0;
+
+    for layer in EXPIRING_LAYERS {
+        manager.ensure_layer_loaded(layer)?;
+        for memory in manager.get_memories_by_layer(layer) {
+            let agent_id = &memory.metadata.agent_id;
+            let Some(cutoff) = policy.cutoff(Some(agent_id), now) else { continue };
+            if memory.created_at >= cutoff {
+                continue;
+            }
+            if memory.pinned {
+                exempt += 1;
+                continue;
+            }
+
+            expired.push(RetentionItem {
+                kind: RetentionKind::Memory,
+                id: memory.id.to_string(),
+                agent_id: Some(agent_id.clone()),
+                timestamp: memory.created_at,
+                count: 1,
+            });
+        }
+    }
+
+    Ok((expired, exempt))
+}
+
+/// Messages keep their timestamp as an RFC 3339 string; unparseable ones never expire
+fn message_time(message: &Message) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&message.timestamp)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+fn collect_stale_files(dir: &Path, cutoff: DateTime<Utc>, found: &mut Vec<(PathBuf, DateTime<Utc>)>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let path = entry.path();
+
+        if metadata.is_dir() {
+            collect_stale_files(&path, cutoff, found)?;
+        } else if is_live_database(&path) {
+            // The embedding cache is open; its entries are expired row by row
+            continue;
+        } else {
+            let modified: DateTime<Utc> = metadata.modified()?.into();
+            if modified < cutoff {
+                found.push((path, modified));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_live_database(path: &Path) -> bool {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    name.ends_with(".sqlite") || name.ends_with(".sqlite-wal") || name.ends_with(".sqlite-shm")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::memory_types::*;
+    use crate::memory::MemoryCoordinator;
+    use std::time::SystemTime;
+
+    fn metadata(agent_id: &str) -> MemoryMetadata {
+        MemoryMetadata {
+            source: MemorySource::UserInput,
+            agent_id: agent_id.to_string(),
+            conversation_id: None,
+            session_id: None,
+            topics: vec![],
+            entities: vec![],
+            sentiment: None,
+            context_window: None,
+            verification_status: VerificationStatus::Unverified,
+            custom_fields: HashMap::new(),
+        }
+    }
+
+    fn config(dir: &Path) -> AppConfig {
+        let mut config = AppConfig::with_data_dir(dir.to_path_buf());
+        config.memory.entity_recognition_enabled = false;
+        config.privacy.auto_cleanup_days = Some(30);
+        config.privacy.retention_overrides.insert("keeper".to_string(), 0);
+        config
+    }
+
+    /// Memories for every retention case, keyed by what should happen to them
+    async fn state_with_memories(config: &AppConfig) -> (AppState, HashMap<&'static str, Uuid>) {
+        let coordinator = MemoryCoordinator::new(config.clone(), None).await.unwrap();
+        let mut ids = HashMap::new();
+        {
+            let manager = coordinator.memory_manager();
+            let mut manager = manager.lock().await;
+            let old = Utc::now() - Duration::days(45);
+
+            let cases = [
+                ("expired", MemoryLayer::Working, "agent1", false, old),
+                ("pinned", MemoryLayer::ShortTerm, "agent1", true, old),
+                ("long_term", MemoryLayer::LongTerm, "agent1", false, old),
+                ("override", MemoryLayer::Working, "keeper", false, old),
+                ("fresh", MemoryLayer::ShortTerm, "agent1", false, Utc::now()),
+            ];
+            for (name, layer, agent_id, pinned, created_at) in cases {
+                let mut memory = Memory::new(format!("{} note", name), layer, metadata(agent_id));
+                memory.pinned = pinned;
+                memory.created_at = created_at;
+                ids.insert(name, manager.insert_memory(memory).await.unwrap().id);
+            }
+        }
+        (AppState::new(config.clone()).with_memory_system(coordinator), ids)
+    }
+
+    fn stale_log(config: &AppConfig) -> PathBuf {
+        std::fs::create_dir_all(config.logs_dir_path()).unwrap();
+        let path = config.logs_dir_path().join("old.log");
+        std::fs::write(&path, "log line").unwrap();
+        let old = SystemTime::now() - std::time::Duration::from_secs(60 * 86_400);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_cutoff_uses_agent_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = config(dir.path());
+        config.privacy.retention_overrides.insert("chatty".to_string(), 7);
+        let policy = RetentionPolicy::from_config(&config);
+        let now = Utc::now();
+
+        assert_eq!(policy.cutoff(Some("agent1"), now), Some(now - Duration::days(30)));
+        assert_eq!(policy.cutoff(Some("chatty"), now), Some(now - Duration::days(7)));
+        assert_eq!(policy.cutoff(Some("keeper"), now), None);
+
+        let mut old = Message::new_user_message("hello".to_string(), "chatty".to_string());
+        old.timestamp = (now - Duration::days(10)).to_rfc3339();
+        let mut kept = old.clone();
+        kept.agent_id = "agent1".to_string();
+        let messages = HashMap::from([
+            ("chatty".to_string(), vec![old.clone()]),
+            ("agent1".to_string(), vec![kept]),
+        ]);
+
+        let expired = expired_messages(&policy, &messages, now);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, old.id);
+    }
+
+    #[tokio::test]
+    async fn test_preview_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+        let (state, ids) = state_with_memories(&config).await;
+        let log = stale_log(&config);
+        let engine = RetentionEngine::new(&config);
+
+        let report = engine.preview(&state).await.unwrap();
+        assert!(report.dry_run);
+        assert!(report.errors.is_empty());
+        assert_eq!(report.exempt_pinned, 1);
+        let memory_items: Vec<&str> = report.items.iter()
+            .filter(|item| item.kind == RetentionKind::Memory)
+            .map(|item| item.id.as_str())
+            .collect();
+        assert_eq!(memory_items, vec![ids["expired"].to_string().as_str()]);
+        assert_eq!(report.counts().get(&RetentionKind::Log), Some(&1));
+
+        assert!(log.exists());
+        let manager = state.memory_system.as_ref().unwrap().memory_manager();
+        assert!(manager.lock().await.get_memory(ids["expired"]).is_some());
+
+        let trail = engine.audit_trail().unwrap();
+        assert_eq!(trail.len(), 1);
+        assert!(trail[0].dry_run);
+    }
+
+    #[tokio::test]
+    async fn test_enforce_archives_expired_memories() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+        let (state, ids) = state_with_memories(&config).await;
+        let log = stale_log(&config);
+        let engine = RetentionEngine::new(&config);
+
+        let report = engine.enforce(&state).await.unwrap();
+        assert!(!report.dry_run);
+        assert!(report.errors.is_empty());
+        assert!(!log.exists());
+
+        let manager = state.memory_system.as_ref().unwrap().memory_manager();
+        let manager = manager.lock().await;
+        assert!(manager.get_memory(ids["expired"]).is_none());
+        assert!(manager.archived_memories().iter().any(|entry| entry.memory.id == ids["expired"]));
+        for kept in ["pinned", "long_term", "override", "fresh"] {
+            assert!(manager.get_memory(ids[kept]).is_some(), "{} should be kept", kept);
+        }
+        drop(manager);
+
+        let trail = engine.audit_trail().unwrap();
+        assert_eq!(trail.len(), 1);
+        assert_eq!(trail[0].removed.get(&RetentionKind::Memory), Some(&1));
+    }
+}
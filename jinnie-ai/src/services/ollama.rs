@@ -1,4 +1,5 @@
 use crate::utils::error::{LocalMindError, Result};
+use crate::llm::reasoning::{ReasoningStreamSplitter, StreamChunk};
 use serde::{Deserialize, Serialize};
 
 /// Ollama service configuration
@@ -63,6 +64,8 @@ pub struct OllamaResponse {
     pub model: String,
     pub created_at: String,
     pub response: String,
+    #[serde(default)]
+    pub thinking: Option<String>, // Set by Ollama when the model's thinking is returned separately
     pub done: bool,
     pub context: Option<Vec<u32>>,
     pub total_duration: Option<u64>,
@@ -160,6 +163,91 @@ impl OllamaClient {
         Ok(ollama_response)
     }
 
+    /// Generate with streaming, routing thinking tokens and answer tokens separately.
+    ///
+    /// Returns the final response with `response` holding only the answer and
+    /// `thinking` holding the collected reasoning trace.
+    pub async fn generate_stream<F>(&self, mut request: OllamaRequest, mut on_chunk: F) -> Result<OllamaResponse>
+    where
+        F: FnMut(StreamChunk) -> Result<()>,
+    {
+        use futures::StreamExt;
+
+        let url = format!("{}/api/generate", self.config.base_url);
+        request.stream = true;
+
+        let response = self.client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| LocalMindError::Network(format!("Failed to send request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(LocalMindError::ExternalService(format!(
+                "Ollama generation failed: {}",
+                response.status()
+            )));
+        }
+
+        let mut splitter = ReasoningStreamSplitter::new();
+        let mut answer = String::new();
+        let mut thinking = String::new();
+        let mut pending = Vec::new();
+        let mut last: Option<OllamaResponse> = None;
+
+        let mut route = |chunk: StreamChunk, answer: &mut String, thinking: &mut String| -> Result<()> {
+            match &chunk {
+                StreamChunk::Thinking(t) => thinking.push_str(t),
+                StreamChunk::Answer(a) => answer.push_str(a),
+            }
+            on_chunk(chunk)
+        };
+
+        let mut stream = response.bytes_stream();
+        while let Some(bytes) = stream.next().await {
+            let bytes = bytes
+                .map_err(|e| LocalMindError::Network(format!("Failed to read stream: {}", e)))?;
+            pending.extend_from_slice(&bytes);
+
+            // Ollama streams newline-delimited JSON objects
+            while let Some(newline) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                if line.iter().all(|b| b.is_ascii_whitespace()) {
+                    continue;
+                }
+
+                let part: OllamaResponse = serde_json::from_slice(&line)
+                    .map_err(|e| LocalMindError::Network(format!("Failed to parse stream chunk: {}", e)))?;
+
+                if let Some(t) = part.thinking.as_ref().filter(|t| !t.is_empty()) {
+                    route(StreamChunk::Thinking(t.clone()), &mut answer, &mut thinking)?;
+                }
+                for chunk in splitter.push(&part.response) {
+                    route(chunk, &mut answer, &mut thinking)?;
+                }
+
+                let done = part.done;
+                last = Some(part);
+                if done {
+                    break;
+                }
+            }
+        }
+
+        for chunk in splitter.finish() {
+            route(chunk, &mut answer, &mut thinking)?;
+        }
+
+        let mut final_response = last.ok_or_else(|| {
+            LocalMindError::ExternalService("Ollama stream ended without a response".to_string())
+        })?;
+        final_response.response = answer.trim().to_string();
+        final_response.thinking = Some(thinking.trim().to_string()).filter(|t| !t.is_empty());
+
+        Ok(final_response)
+    }
+
     /// Check if a specific model is available
     pub async fn has_model(&self, model_name: &str) -> Result<bool> {
         let models = self.list_models().await?;
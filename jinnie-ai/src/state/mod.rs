@@ -15,37 +15,39 @@ pub async fn initialize_app_state(config: AppConfig) -> Result<AppState> {
     // Load persisted data
     AppStateManager::initialize_data(&mut state).await?;
     
-    // Initialize memory system if feature is enabled
-    #[cfg(feature = "basic-ai")]
+    // Initialize vector store; without Qdrant it runs on the embedded index
     {
-        use crate::memory::MemoryCoordinator;
-        match MemoryCoordinator::new(config.clone(), state.vector_store.clone()).await {
-            Ok(memory_system) => {
-                state = state.with_memory_system(memory_system);
-                log::info!("Memory system initialized");
+        use crate::vector::VectorStore;
+        match VectorStore::new(config.clone()).await {
+            Ok(vector_store) => {
+                vector_store.initialize().await?;
+                state = state.with_vector_store(vector_store);
+                log::info!("Vector store initialized");
             }
             Err(e) => {
-                log::warn!("Failed to initialize memory system: {}", e);
+                log::warn!("Failed to initialize vector store: {}", e);
             }
         }
     }
     
-    // Initialize vector store if feature is enabled
-    #[cfg(feature = "vector-db")]
+    // Memory needs the vector store for embeddings, so it comes second
     {
-        use crate::vector::VectorStore;
-        match VectorStore::new(config).await {
-            Ok(vector_store) => {
-                vector_store.initialize().await?;
-                state = state.with_vector_store(vector_store);
-                log::info!("Vector store initialized");
+        use crate::memory::MemoryCoordinator;
+        match MemoryCoordinator::new(config.clone(), state.vector_store.clone()).await {
+            Ok(memory_system) => {
+                memory_system.start_maintenance().await;
+                state = state.with_memory_system(memory_system);
+                log::info!("Memory system initialized");
             }
             Err(e) => {
-                log::warn!("Failed to initialize vector store: {}", e);
+                log::warn!("Failed to initialize memory system: {}", e);
             }
         }
     }
     
+    // Expire old data once everything that holds it is loaded
+    crate::privacy::start_retention(&state);
+    
     log::info!("Application state initialized successfully");
     Ok(state)
 }
\ No newline at end of file
@@ -0,0 +1,740 @@
+//! At-rest encryption for everything in the data directory.
+//!
+//! Data is encrypted with random 32-byte data keys (XChaCha20-Poly1305). The data keys are
+//! wrapped with a key derived from the user's passphrase (Argon2id) and kept in `keyring.json`,
+//! so changing the passphrase only rewraps the keys, while rotating adds a new data key and
+//! re-encrypts stored data with it at the next start.
+//!
+//! Covered: the JSON stores, backups (including their vector snapshots) and retention
+//! archives, the memory database, the embedded vector index (vectors and payloads) and cached
+//! embedding vectors. Not covered: index structure such as HNSW graph links and ids, embedding
+//! cache keys (blake3 hashes, never the text), and anything kept by an external Qdrant server,
+//! which has its own storage.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::config::AppConfig;
+use crate::utils::error::{LocalMindError, Result};
+use super::paths::ensure_data_dir;
+
+/// File name of the keyring inside the data directory
+pub const KEYRING_FILE: &str = "keyring.json";
+
+/// Environment variable consulted for the passphrase before prompting
+pub const PASSPHRASE_ENV: &str = "JINNIE_PASSPHRASE";
+
+/// Leading bytes of every encrypted file or blob
+const MAGIC: &[u8; 4] = b"JNC1";
+/// Prefix of encrypted values stored in text columns
+const TEXT_PREFIX: &str = "jnc1:";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + 4 + NONCE_LEN;
+const KEYRING_VERSION: u32 = 1;
+const PASSPHRASE_ATTEMPTS: usize = 3;
+
+static ACTIVE_CIPHER: Lazy<RwLock<Option<Arc<DataCipher>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Install the cipher used by storage; `None` leaves new data in plaintext
+pub fn set_active_cipher(cipher: Option<Arc<DataCipher>>) {
+    if let Ok(mut active) = ACTIVE_CIPHER.write() {
+        *active = cipher;
+    }
+}
+
+/// The cipher installed by the unlock step, if encryption is enabled
+pub fn active_cipher() -> Option<Arc<DataCipher>> {
+    ACTIVE_CIPHER.read().ok().and_then(|active| active.clone())
+}
+
+/// A data key, wiped from memory when dropped
+struct DataKey([u8; KEY_LEN]);
+
+impl DataKey {
+    fn generate() -> Self {
+        let mut key = [0u8; KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    fn aead(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+}
+
+impl Drop for DataKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Unlocked data keys: encrypts with the current key and decrypts with any known key
+pub struct DataCipher {
+    keys: HashMap<u32, DataKey>,
+    current: u32,
+}
+
+impl DataCipher {
+    /// Id of the key new data is encrypted with
+    pub fn current_key_id(&self) -> u32 {
+        self.current
+    }
+
+    /// Whether `data` carries the encrypted envelope header
+    pub fn is_encrypted(data: &[u8]) -> bool {
+        data.len() >= HEADER_LEN && data.starts_with(MAGIC)
+    }
+
+    /// Encrypt `plaintext` into `MAGIC | key id | nonce | ciphertext`; the header is authenticated
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let key = self.keys.get(&self.current)
+            .ok_or_else(|| LocalMindError::Encryption(format!("Data key {} is not unlocked", self.current)))?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let mut output = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+        output.extend_from_slice(MAGIC);
+        output.extend_from_slice(&self.current.to_le_bytes());
+        output.extend_from_slice(&nonce);
+
+        let ciphertext = key.aead()
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad: &output[..HEADER_LEN] })
+            .map_err(|_| LocalMindError::Encryption("Failed to encrypt data".to_string()))?;
+        output.extend_from_slice(&ciphertext);
+        Ok(output)
+    }
+
+    /// Decrypt an envelope produced by `encrypt`
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if !Self::is_encrypted(data) {
+            return Err(LocalMindError::Encryption("Data is not encrypted".to_string()));
+        }
+
+        let key_id = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        let key = self.keys.get(&key_id).ok_or_else(|| {
+            LocalMindError::Encryption(format!("Data was encrypted with key {}, which is not in the keyring", key_id))
+        })?;
+
+        let (header, ciphertext) = data.split_at(HEADER_LEN);
+        key.aead()
+            .decrypt(XNonce::from_slice(&header[8..]), Payload { msg: ciphertext, aad: header })
+            .map_err(|_| LocalMindError::Encryption("Encrypted data is corrupted or was tampered with".to_string()))
+    }
+
+    /// Encrypt a text value for storage in a text column
+    pub fn seal_text(&self, text: &str) -> Result<String> {
+        Ok(format!("{}{}", TEXT_PREFIX, to_hex(&self.encrypt(text.as_bytes())?)))
+    }
+
+    /// Whether a text value was produced by `seal_text`
+    pub fn is_sealed_text(text: &str) -> bool {
+        text.starts_with(TEXT_PREFIX)
+    }
+
+    /// Decrypt a value produced by `seal_text`; plaintext values pass through unchanged
+    pub fn open_text(&self, text: &str) -> Result<String> {
+        match text.strip_prefix(TEXT_PREFIX) {
+            Some(hex) => {
+                let bytes = from_hex(hex)
+                    .ok_or_else(|| LocalMindError::Encryption("Malformed encrypted value".to_string()))?;
+                String::from_utf8(self.decrypt(&bytes)?)
+                    .map_err(|e| LocalMindError::Encryption(format!("Decrypted value is not UTF-8: {}", e)))
+            }
+            None => Ok(text.to_string()),
+        }
+    }
+}
+
+/// Argon2id cost parameters and salt for deriving the key-wrapping key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Hex-encoded salt
+    pub salt: String,
+}
+
+impl KdfParams {
+    /// OWASP-recommended Argon2id costs with a fresh salt
+    pub fn recommended() -> Self {
+        Self::with_costs(64 * 1024, 3, 1)
+    }
+
+    /// Custom costs with a fresh salt
+    pub fn with_costs(memory_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self { memory_kib, iterations, parallelism, salt: to_hex(&salt) }
+    }
+
+    fn derive(&self, passphrase: &str) -> Result<DataKey> {
+        let salt = from_hex(&self.salt)
+            .ok_or_else(|| LocalMindError::Encryption("Malformed salt in keyring".to_string()))?;
+        let params = argon2::Params::new(self.memory_kib, self.iterations, self.parallelism, Some(KEY_LEN))
+            .map_err(|e| LocalMindError::Encryption(format!("Invalid key derivation parameters: {}", e)))?;
+
+        let mut key = [0u8; KEY_LEN];
+        argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| LocalMindError::Encryption(format!("Key derivation failed: {}", e)))?;
+        Ok(DataKey(key))
+    }
+}
+
+/// A data key encrypted with the passphrase-derived key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedKey {
+    pub id: u32,
+    pub created_at: DateTime<Utc>,
+    /// Hex-encoded nonce followed by the encrypted key
+    wrapped: String,
+}
+
+impl WrappedKey {
+    fn wrap(id: u32, key: &DataKey, kek: &DataKey) -> Result<Self> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = kek.aead()
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: &key.0, aad: &id.to_le_bytes() })
+            .map_err(|_| LocalMindError::Encryption("Failed to wrap data key".to_string()))?;
+
+        let mut wrapped = nonce.to_vec();
+        wrapped.extend_from_slice(&ciphertext);
+        Ok(Self { id, created_at: Utc::now(), wrapped: to_hex(&wrapped) })
+    }
+
+    /// Fails with `WrongPassphrase` when `kek` was derived from a different passphrase
+    fn unwrap(&self, kek: &DataKey) -> Result<DataKey> {
+        let wrapped = from_hex(&self.wrapped)
+            .filter(|bytes| bytes.len() > NONCE_LEN)
+            .ok_or_else(|| LocalMindError::Encryption(format!("Malformed key {} in keyring", self.id)))?;
+        let (nonce, ciphertext) = wrapped.split_at(NONCE_LEN);
+
+        let plaintext = Zeroizing::new(
+            kek.aead()
+                .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: &self.id.to_le_bytes() })
+                .map_err(|_| LocalMindError::WrongPassphrase)?,
+        );
+        let key: [u8; KEY_LEN] = plaintext.as_slice().try_into()
+            .map_err(|_| LocalMindError::Encryption(format!("Key {} in keyring has the wrong length", self.id)))?;
+        Ok(DataKey(key))
+    }
+}
+
+/// The wrapped data keys and the parameters needed to unwrap them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keyring {
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub current_key: u32,
+    pub keys: Vec<WrappedKey>,
+}
+
+impl Keyring {
+    /// Create a keyring with one fresh data key
+    pub fn create(passphrase: &str) -> Result<(Self, DataCipher)> {
+        Self::create_with_params(passphrase, KdfParams::recommended())
+    }
+
+    /// Create a keyring using the given key derivation parameters
+    pub fn create_with_params(passphrase: &str, kdf: KdfParams) -> Result<(Self, DataCipher)> {
+        if passphrase.is_empty() {
+            return Err(LocalMindError::validation_failed("passphrase", "must not be empty"));
+        }
+
+        let kek = kdf.derive(passphrase)?;
+        let key = DataKey::generate();
+        let keyring = Self {
+            version: KEYRING_VERSION,
+            kdf,
+            current_key: 1,
+            keys: vec![WrappedKey::wrap(1, &key, &kek)?],
+        };
+
+        let cipher = DataCipher { keys: HashMap::from([(1, key)]), current: 1 };
+        Ok((keyring, cipher))
+    }
+
+    /// Load the keyring at `path`, if one exists
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = fs::read_to_string(path)
+            .map_err(|_| LocalMindError::storage_read_failed(&path.display().to_string()))?;
+        let keyring: Self = serde_json::from_str(&json)
+            .map_err(|e| LocalMindError::Encryption(format!("Failed to parse keyring {}: {}", path.display(), e)))?;
+
+        if keyring.version > KEYRING_VERSION {
+            return Err(LocalMindError::Encryption(format!(
+                "Keyring version {} is newer than this build supports ({})", keyring.version, KEYRING_VERSION
+            )));
+        }
+        Ok(Some(keyring))
+    }
+
+    /// Write the keyring atomically, so a crash never leaves it half written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| LocalMindError::Serialization(format!("Failed to serialize keyring: {}", e)))?;
+        write_atomic(path, json.as_bytes())
+    }
+
+    /// Unwrap every data key with the passphrase
+    pub fn unlock(&self, passphrase: &str) -> Result<DataCipher> {
+        let kek = self.kdf.derive(passphrase)?;
+        let mut keys = HashMap::new();
+        for wrapped in &self.keys {
+            keys.insert(wrapped.id, wrapped.unwrap(&kek)?);
+        }
+
+        if !keys.contains_key(&self.current_key) {
+            return Err(LocalMindError::Encryption(format!("Current key {} is missing from the keyring", self.current_key)));
+        }
+        Ok(DataCipher { keys, current: self.current_key })
+    }
+
+    /// Rewrap the data keys under a new passphrase; stored data is untouched
+    pub fn change_passphrase(&mut self, old: &str, new: &str) -> Result<()> {
+        if new.is_empty() {
+            return Err(LocalMindError::validation_failed("passphrase", "must not be empty"));
+        }
+
+        let cipher = self.unlock(old)?;
+        let kdf = KdfParams::with_costs(self.kdf.memory_kib, self.kdf.iterations, self.kdf.parallelism);
+        let kek = kdf.derive(new)?;
+
+        let mut keys = Vec::with_capacity(self.keys.len());
+        for wrapped in &self.keys {
+            let mut rewrapped = WrappedKey::wrap(wrapped.id, &cipher.keys[&wrapped.id], &kek)?;
+            rewrapped.created_at = wrapped.created_at;
+            keys.push(rewrapped);
+        }
+
+        self.kdf = kdf;
+        self.keys = keys;
+        Ok(())
+    }
+
+    /// Add a fresh data key and make it current; older keys stay readable until retired
+    pub fn add_key(&mut self, passphrase: &str) -> Result<DataCipher> {
+        let mut cipher = self.unlock(passphrase)?;
+        let kek = self.kdf.derive(passphrase)?;
+
+        let id = self.keys.iter().map(|key| key.id).max().unwrap_or(0) + 1;
+        let key = DataKey::generate();
+        self.keys.push(WrappedKey::wrap(id, &key, &kek)?);
+        self.current_key = id;
+
+        cipher.keys.insert(id, key);
+        cipher.current = id;
+        Ok(cipher)
+    }
+
+    /// Drop every key except the current one; only safe once all data is re-encrypted
+    pub fn retire_old_keys(&mut self) -> usize {
+        let before = self.keys.len();
+        let current = self.current_key;
+        self.keys.retain(|key| key.id == current);
+        before - self.keys.len()
+    }
+}
+
+/// Path of the keyring file
+pub fn get_keyring_file_path() -> Result<PathBuf> {
+    Ok(ensure_data_dir()?.join(KEYRING_FILE))
+}
+
+/// Write a data file, encrypting it when a cipher is active
+pub fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+    match active_cipher() {
+        Some(cipher) => write_atomic(path, &cipher.encrypt(contents)?),
+        None => write_atomic(path, contents),
+    }
+}
+
+/// Read a data file written by `write_file`; plaintext files from before encryption still load
+pub fn read_file(path: &Path) -> Result<Vec<u8>> {
+    let data = fs::read(path)
+        .map_err(|_| LocalMindError::storage_read_failed(&path.display().to_string()))?;
+
+    if !DataCipher::is_encrypted(&data) {
+        return Ok(data);
+    }
+    match active_cipher() {
+        Some(cipher) => cipher.decrypt(&data),
+        None => Err(LocalMindError::Locked(format!("{} is encrypted; unlock with your passphrase first", path.display()))),
+    }
+}
+
+/// Encrypt `bytes` when a cipher is active, for data stored other than through `write_file`
+pub fn seal_bytes(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    match active_cipher() {
+        Some(cipher) => cipher.encrypt(&bytes),
+        None => Ok(bytes),
+    }
+}
+
+/// Reverse `seal_bytes`; data written before encryption passes through
+pub fn open_bytes(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if !DataCipher::is_encrypted(&bytes) {
+        return Ok(bytes);
+    }
+    match active_cipher() {
+        Some(cipher) => cipher.decrypt(&bytes),
+        None => Err(LocalMindError::Locked("stored data is encrypted; unlock with your passphrase first".to_string())),
+    }
+}
+
+/// `read_file` for UTF-8 content
+pub fn read_to_string(path: &Path) -> Result<String> {
+    String::from_utf8(read_file(path)?)
+        .map_err(|e| LocalMindError::Storage(format!("{} is not valid UTF-8: {}", path.display(), e)))
+}
+
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.tmp", file_name));
+
+    fs::write(&tmp, contents)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|_| LocalMindError::storage_write_failed(&path.display().to_string()))
+}
+
+/// Unlock (or set up) encryption before any stored data is read.
+///
+/// With `privacy.memory_encryption` on and no keyring yet, a keyring is created and existing
+/// plaintext data is encrypted in place. An existing keyring is always unlocked, even if the
+/// flag was turned off later, since the data cannot be read otherwise. A rotation started in
+/// an earlier session is finished here.
+///
+/// `passphrase` comes from the unlock screen; without it the passphrase is read from
+/// the environment or a terminal prompt.
+pub async fn unlock_at_startup(config: &AppConfig, passphrase: Option<&str>) -> Result<()> {
+    let path = get_keyring_file_path()?;
+
+    match Keyring::load(&path)? {
+        Some(keyring) => {
+            if !config.privacy.memory_encryption {
+                log::warn!("privacy.memory_encryption is off, but the data directory is encrypted; unlocking it anyway");
+            }
+
+            let cipher = match passphrase {
+                Some(passphrase) => keyring.unlock(passphrase)?,
+                None => unlock_with_prompt(&keyring)?,
+            };
+            log::info!("🔐 Unlocked encrypted storage (key {})", cipher.current_key_id());
+            if keyring.keys.len() > 1 {
+                finish_rotation(config, keyring, cipher, &path).await?;
+            } else {
+                set_active_cipher(Some(Arc::new(cipher)));
+            }
+        }
+        None if config.privacy.memory_encryption => {
+            let passphrase = match passphrase {
+                Some(passphrase) => Zeroizing::new(passphrase.to_string()),
+                None => read_new_passphrase()?,
+            };
+            let (keyring, cipher) = Keyring::create(&passphrase)?;
+            keyring.save(&path)?;
+            set_active_cipher(Some(Arc::new(cipher)));
+
+            reencrypt_all(config).await?;
+            log::info!("🔐 Encryption set up; existing data is now encrypted");
+        }
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Change the passphrase protecting the keyring
+pub fn change_passphrase(old: &str, new: &str) -> Result<()> {
+    let path = get_keyring_file_path()?;
+    let mut keyring = Keyring::load(&path)?
+        .ok_or_else(|| LocalMindError::Configuration("Encryption is not set up".to_string()))?;
+
+    keyring.change_passphrase(old, new)?;
+    keyring.save(&path)
+}
+
+/// Switch to a new data key for new writes. Data already stored is re-encrypted with it
+/// at the next start, before any store opens, and the old keys are retired then.
+pub fn rotate_data_key(passphrase: &str) -> Result<u32> {
+    let path = get_keyring_file_path()?;
+    let mut keyring = Keyring::load(&path)?
+        .ok_or_else(|| LocalMindError::Configuration("Encryption is not set up".to_string()))?;
+
+    let cipher = keyring.add_key(passphrase)?;
+    keyring.save(&path)?;
+    set_active_cipher(Some(Arc::new(cipher)));
+
+    log::info!("🔐 Added data key {}; stored data is re-encrypted at the next start", keyring.current_key);
+    Ok(keyring.current_key)
+}
+
+/// Re-encrypt all stored data with the current key, then retire the older ones. Runs at
+/// startup only, since it rewrites the stores underneath anything that has them open.
+async fn finish_rotation(config: &AppConfig, mut keyring: Keyring, cipher: DataCipher, path: &Path) -> Result<()> {
+    set_active_cipher(Some(Arc::new(cipher)));
+    reencrypt_all(config).await?;
+
+    // A crash before this point keeps the old keys, so the next start simply repeats the work
+    let retired = keyring.retire_old_keys();
+    keyring.save(path)?;
+
+    log::info!("🔐 Re-encrypted stored data with key {} and retired {} old key(s)", keyring.current_key, retired);
+    Ok(())
+}
+
+/// Rewrite every stored file and the memory database with the active cipher
+async fn reencrypt_all(config: &AppConfig) -> Result<()> {
+    use super::file_storage::{AgentStorage, DocumentStorage, MessageStorage};
+    use super::paths::{get_agents_file_path, get_documents_file_path, get_exports_dir, get_messages_file_path};
+    use crate::memory::persistence::{MemoryStore, MEMORY_DB_FILE};
+
+    if get_agents_file_path()?.exists() {
+        AgentStorage::save(&AgentStorage::load().await?).await?;
+    }
+    if get_messages_file_path()?.exists() {
+        MessageStorage::save(&MessageStorage::load().await?).await?;
+    }
+    if get_documents_file_path()?.exists() {
+        DocumentStorage::save(&DocumentStorage::load().await?).await?;
+    }
+
+    let memory_dir = config.data_dir_path();
+    if memory_dir.join(MEMORY_DB_FILE).exists() {
+        MemoryStore::open(&memory_dir)
+            .and_then(|store| store.reseal())
+            .map_err(|e| LocalMindError::Storage(format!("Failed to re-encrypt memory database: {}", e)))?;
+    }
+
+    // Backups and retention archives must stay readable once the old keys are retired
+    let resealed = reseal_files(&get_exports_dir()?)? + reseal_files(&config.data_dir_path().join("archive"))?;
+    if resealed > 0 {
+        log::info!("Re-encrypted {} backup and archive files", resealed);
+    }
+
+    crate::vector::EmbeddedVectorStore::reseal(&config.data_dir_path().join(crate::vector::EMBEDDED_INDEX_DIR))
+        .map_err(|e| LocalMindError::Storage(format!("Failed to re-encrypt vector index: {}", e)))?;
+    crate::vector::EmbeddingCache::reseal(&config.cache_dir_path().join("embeddings"))
+        .map_err(|e| LocalMindError::Storage(format!("Failed to re-encrypt embedding cache: {}", e)))?;
+    Ok(())
+}
+
+/// Re-encrypt every encrypted file under `dir`; plaintext files, such as exports meant
+/// to be shared, are left alone
+fn reseal_files(dir: &Path) -> Result<usize> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut resealed = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            resealed += reseal_files(&path)?;
+        } else if DataCipher::is_encrypted(&fs::read(&path)?) {
+            write_file(&path, &read_file(&path)?)?;
+            resealed += 1;
+        }
+    }
+    Ok(resealed)
+}
+
+fn unlock_with_prompt(keyring: &Keyring) -> Result<DataCipher> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        let passphrase = Zeroizing::new(passphrase);
+        return keyring.unlock(&passphrase);
+    }
+    ensure_interactive(false)?;
+
+    for attempt in 1..=PASSPHRASE_ATTEMPTS {
+        let passphrase = prompt("🔐 Passphrase: ")?;
+        match keyring.unlock(&passphrase) {
+            Err(LocalMindError::WrongPassphrase) if attempt < PASSPHRASE_ATTEMPTS => {
+                eprintln!("Wrong passphrase, try again.");
+            }
+            result => return result,
+        }
+    }
+    Err(LocalMindError::WrongPassphrase)
+}
+
+fn read_new_passphrase() -> Result<Zeroizing<String>> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(Zeroizing::new(passphrase));
+    }
+    ensure_interactive(true)?;
+
+    eprintln!("Encryption is enabled. Choose a passphrase to protect your data; it cannot be recovered if lost.");
+    loop {
+        let passphrase = prompt("🔐 New passphrase: ")?;
+        if passphrase.is_empty() {
+            eprintln!("The passphrase must not be empty.");
+            continue;
+        }
+        if *prompt("🔐 Repeat passphrase: ")? == *passphrase {
+            return Ok(passphrase);
+        }
+        eprintln!("Passphrases do not match, try again.");
+    }
+}
+
+/// Without a terminal the caller has to ask for the passphrase itself, e.g. on the unlock screen
+fn ensure_interactive(setup: bool) -> Result<()> {
+    if std::io::stdin().is_terminal() {
+        Ok(())
+    } else {
+        Err(LocalMindError::PassphraseRequired { setup })
+    }
+}
+
+fn prompt(message: &str) -> Result<Zeroizing<String>> {
+    rpassword::prompt_password(message)
+        .map(Zeroizing::new)
+        .map_err(|e| LocalMindError::Encryption(format!("Failed to read passphrase: {}", e)))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap Argon2 costs so tests stay fast
+    fn test_params() -> KdfParams {
+        KdfParams::with_costs(64, 1, 1)
+    }
+
+    #[test]
+    fn test_roundtrip_and_text_values() {
+        let (_, cipher) = Keyring::create_with_params("correct horse", test_params()).unwrap();
+
+        let sealed = cipher.encrypt(b"agents and messages").unwrap();
+        assert!(DataCipher::is_encrypted(&sealed));
+        assert!(!sealed.windows(6).any(|window| window == b"agents"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), b"agents and messages");
+
+        let text = cipher.seal_text("likes hiking").unwrap();
+        assert!(DataCipher::is_sealed_text(&text));
+        assert_eq!(cipher.open_text(&text).unwrap(), "likes hiking");
+        assert_eq!(cipher.open_text("written before encryption").unwrap(), "written before encryption");
+
+        // Flipping a ciphertext byte must be detected
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(cipher.decrypt(&tampered), Err(LocalMindError::Encryption(_))));
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(KEYRING_FILE);
+        let (keyring, _) = Keyring::create_with_params("correct horse", test_params()).unwrap();
+        keyring.save(&path).unwrap();
+
+        let keyring = Keyring::load(&path).unwrap().unwrap();
+        assert!(matches!(keyring.unlock("battery staple"), Err(LocalMindError::WrongPassphrase)));
+        assert!(keyring.unlock("correct horse").is_ok());
+    }
+
+    #[test]
+    fn test_change_passphrase_keeps_data_readable() {
+        let (mut keyring, cipher) = Keyring::create_with_params("old secret", test_params()).unwrap();
+        let sealed = cipher.encrypt(b"notes").unwrap();
+
+        keyring.change_passphrase("old secret", "new secret").unwrap();
+        assert!(matches!(keyring.unlock("old secret"), Err(LocalMindError::WrongPassphrase)));
+        assert_eq!(keyring.unlock("new secret").unwrap().decrypt(&sealed).unwrap(), b"notes");
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_earlier_backups_restorable() {
+        use crate::storage::file_storage::{AgentStorage, BackupStorage};
+        use crate::storage::paths::{get_data_dir, set_data_dir};
+        use crate::types::Agent;
+
+        let _lock = crate::storage::lock_global_storage();
+        let previous_dir = get_data_dir();
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig::with_data_dir(dir.path().to_path_buf());
+        set_data_dir(dir.path()).unwrap();
+
+        let (keyring, cipher) = Keyring::create_with_params("secret", test_params()).unwrap();
+        keyring.save(&get_keyring_file_path().unwrap()).unwrap();
+        set_active_cipher(Some(Arc::new(cipher)));
+
+        let agent = Agent::new("Backed up".to_string(), "general".to_string(), "friendly".to_string(), None);
+        AgentStorage::save(&vec![agent]).await.unwrap();
+        let backup = BackupStorage::create_backup(None).await.unwrap();
+        let archive = dir.path().join("archive").join("messages.json");
+        fs::create_dir_all(archive.parent().unwrap()).unwrap();
+        write_file(&archive, b"{}").unwrap();
+
+        let key = rotate_data_key("secret").unwrap();
+        let keyring = Keyring::load(&get_keyring_file_path().unwrap()).unwrap().unwrap();
+        assert_eq!(keyring.keys.len(), 2);
+        let cipher = keyring.unlock("secret").unwrap();
+        finish_rotation(&config, keyring, cipher, &get_keyring_file_path().unwrap()).await.unwrap();
+        assert_eq!(Keyring::load(&get_keyring_file_path().unwrap()).unwrap().unwrap().keys.len(), 1);
+
+        AgentStorage::save(&Vec::new()).await.unwrap();
+        let restored = BackupStorage::restore_backup(&backup, None).await;
+        let agents = AgentStorage::load().await;
+        let archived = read_file(&archive);
+        set_active_cipher(None);
+        set_data_dir(&previous_dir).unwrap();
+
+        restored.unwrap();
+        assert_eq!(agents.unwrap()[0].name, "Backed up");
+        assert_eq!(archived.unwrap(), b"{}");
+        assert_eq!(key, 2);
+        let sealed = fs::read(&backup).unwrap();
+        assert_eq!(u32::from_le_bytes(sealed[4..8].try_into().unwrap()), key);
+    }
+
+    #[test]
+    fn test_rotation_reads_old_data_until_retired() {
+        let (mut keyring, cipher) = Keyring::create_with_params("secret", test_params()).unwrap();
+        let old = cipher.encrypt(b"before rotation").unwrap();
+
+        let rotated = keyring.add_key("secret").unwrap();
+        assert_eq!(rotated.current_key_id(), 2);
+        assert_eq!(rotated.decrypt(&old).unwrap(), b"before rotation");
+        let new = rotated.encrypt(b"after rotation").unwrap();
+
+        assert_eq!(keyring.retire_old_keys(), 1);
+        let unlocked = keyring.unlock("secret").unwrap();
+        assert_eq!(unlocked.decrypt(&new).unwrap(), b"after rotation");
+        assert!(unlocked.decrypt(&old).is_err());
+    }
+}
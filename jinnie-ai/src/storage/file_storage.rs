@@ -3,7 +3,9 @@ use std::fs;
 use std::path::PathBuf;
 
 use crate::types::{Agent, Message, Document};
+use crate::vector::{CollectionBackup, VectorStore};
 use crate::utils::error::{LocalMindError, Result};
+use super::encryption;
 use super::paths::{get_agents_file_path, get_messages_file_path, get_documents_file_path};
 
 /// Agent storage operations
@@ -17,8 +19,7 @@ impl AgentStorage {
         let json = serde_json::to_string_pretty(agents)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to serialize agents: {}", e)))?;
         
-        fs::write(&agents_file, json)
-            .map_err(|e| LocalMindError::storage_write_failed(&agents_file.display().to_string()))?;
+        encryption::write_file(&agents_file, json.as_bytes())?;
         
         Ok(())
     }
@@ -31,8 +32,7 @@ impl AgentStorage {
             return Ok(Vec::new());
         }
         
-        let json = fs::read_to_string(&agents_file)
-            .map_err(|e| LocalMindError::storage_read_failed(&agents_file.display().to_string()))?;
+        let json = encryption::read_to_string(&agents_file)?;
         
         let agents: Vec<Agent> = serde_json::from_str(&json)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to parse agents file: {}", e)))?;
@@ -64,8 +64,7 @@ impl MessageStorage {
         let json = serde_json::to_string_pretty(messages)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to serialize messages: {}", e)))?;
         
-        fs::write(&messages_file, json)
-            .map_err(|e| LocalMindError::storage_write_failed(&messages_file.display().to_string()))?;
+        encryption::write_file(&messages_file, json.as_bytes())?;
         
         Ok(())
     }
@@ -78,8 +77,7 @@ impl MessageStorage {
             return Ok(HashMap::new());
         }
         
-        let json = fs::read_to_string(&messages_file)
-            .map_err(|e| LocalMindError::storage_read_failed(&messages_file.display().to_string()))?;
+        let json = encryption::read_to_string(&messages_file)?;
         
         let messages: HashMap<String, Vec<Message>> = serde_json::from_str(&json)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to parse messages file: {}", e)))?;
@@ -137,8 +135,7 @@ impl DocumentStorage {
         let json = serde_json::to_string_pretty(documents)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to serialize documents: {}", e)))?;
         
-        fs::write(&documents_file, json)
-            .map_err(|e| LocalMindError::storage_write_failed(&documents_file.display().to_string()))?;
+        encryption::write_file(&documents_file, json.as_bytes())?;
         
         Ok(())
     }
@@ -151,8 +148,7 @@ impl DocumentStorage {
             return Ok(Vec::new());
         }
         
-        let json = fs::read_to_string(&documents_file)
-            .map_err(|e| LocalMindError::storage_read_failed(&documents_file.display().to_string()))?;
+        let json = encryption::read_to_string(&documents_file)?;
         
         let documents: Vec<Document> = serde_json::from_str(&json)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to parse documents file: {}", e)))?;
@@ -204,35 +200,49 @@ impl DocumentStorage {
 pub struct BackupStorage;
 
 impl BackupStorage {
-    /// Create a backup of all data
-    pub async fn create_backup() -> Result<PathBuf> {
+    /// Create a backup of all data; it is encrypted like the rest of the data directory.
+    /// With a vector store, its collections are saved to a `_vectors` directory next to
+    /// the backup file, with their payloads sealed the same way.
+    pub async fn create_backup(vectors: Option<&VectorStore>) -> Result<PathBuf> {
         use crate::storage::paths::get_exports_dir;
         
         let exports_dir = get_exports_dir()?;
         let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
         let backup_file = exports_dir.join(format!("backup_{}.json", timestamp));
         
-        let backup_data = serde_json::json!({
+        let mut backup_data = serde_json::json!({
             "version": "1.0",
             "created_at": chrono::Utc::now().to_rfc3339(),
             "agents": AgentStorage::load().await?,
             "messages": MessageStorage::load().await?,
             "documents": DocumentStorage::load().await?,
         });
+
+        if let Some(vector_store) = vectors {
+            let directory = format!("backup_{}_vectors", timestamp);
+            let collections = vector_store
+                .backup_collections(&exports_dir.join(&directory))
+                .await
+                .map_err(|e| LocalMindError::Storage(format!("Failed to back up vectors: {}", e)))?;
+            backup_data["vectors"] = serde_json::json!({
+                "backend": vector_store.backend_name(),
+                "directory": directory,
+                "collections": collections,
+            });
+        }
         
         let json = serde_json::to_string_pretty(&backup_data)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to serialize backup: {}", e)))?;
         
-        fs::write(&backup_file, json)
-            .map_err(|e| LocalMindError::storage_write_failed(&backup_file.display().to_string()))?;
+        encryption::write_file(&backup_file, json.as_bytes())?;
         
         Ok(backup_file)
     }
 
-    /// Restore from a backup file
-    pub async fn restore_backup(backup_path: &PathBuf) -> Result<()> {
-        let json = fs::read_to_string(backup_path)
-            .map_err(|e| LocalMindError::storage_read_failed(&backup_path.display().to_string()))?;
+    /// Restore from a backup file. Its vector section, if any, is restored only when a
+    /// vector store on the same backend is given.
+    pub async fn restore_backup(backup_path: &PathBuf, vectors: Option<&VectorStore>) -> Result<()> {
+        let json = encryption::read_to_string(backup_path)?;
         
         let backup_data: serde_json::Value = serde_json::from_str(&json)
             .map_err(|e| LocalMindError::Serialization(format!("Failed to parse backup file: {}", e)))?;
@@ -257,6 +267,33 @@ impl BackupStorage {
                 .map_err(|e| LocalMindError::Serialization(format!("Failed to parse documents from backup: {}", e)))?;
             DocumentStorage::save(&documents).await?;
         }
+
+        // Extract and restore vector collections
+        match (backup_data.get("vectors"), vectors) {
+            (Some(vectors_data), Some(vector_store)) => {
+                let backend = vectors_data["backend"].as_str().unwrap_or_default();
+                if backend != vector_store.backend_name() {
+                    return Err(LocalMindError::Validation(format!(
+                        "Backup vectors were saved from {}, but the vector store uses {}",
+                        backend,
+                        vector_store.backend_name()
+                    )));
+                }
+                let directory = vectors_data["directory"].as_str().unwrap_or_default();
+                if directory.is_empty() || directory.contains(['/', '\\']) || directory.starts_with('.') {
+                    return Err(LocalMindError::Validation(format!("Invalid vectors directory in backup: {}", directory)));
+                }
+                let collections: Vec<CollectionBackup> = serde_json::from_value(vectors_data["collections"].clone())
+                    .map_err(|e| LocalMindError::Serialization(format!("Failed to parse vectors from backup: {}", e)))?;
+                let vectors_dir = backup_path.parent().map(|dir| dir.join(directory)).unwrap_or_else(|| PathBuf::from(directory));
+                vector_store
+                    .restore_collections(&vectors_dir, &collections)
+                    .await
+                    .map_err(|e| LocalMindError::Storage(format!("Failed to restore vectors: {}", e)))?;
+            }
+            (Some(_), None) => log::warn!("Backup contains vectors, but no vector store was given to restore them into"),
+            _ => {}
+        }
         
         Ok(())
     }
@@ -269,6 +306,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_agent_storage() {
+        let _lock = crate::storage::lock_global_storage();
         let test_agent = Agent::new(
             "Test Agent".to_string(),
             "general".to_string(),
@@ -290,6 +328,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_message_storage() {
+        let _lock = crate::storage::lock_global_storage();
         let agent_id = "test_agent_id".to_string();
         let test_message = Message::new_user_message(
             "Hello world".to_string(),
@@ -307,4 +346,67 @@ mod tests {
         assert_eq!(loaded_messages.len(), 1);
         assert_eq!(loaded_messages[0].content, test_message.content);
     }
+
+    /// Every file under `dir`, recursively
+    fn files_under(dir: &std::path::Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(files_under(&path));
+            } else {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_backup_seals_vector_payloads() {
+        use crate::config::AppConfig;
+        use crate::storage::encryption::{set_active_cipher, KdfParams, Keyring};
+        use crate::storage::paths::{get_data_dir, set_data_dir};
+        use crate::vector::{CollectionSchema, VectorPoint};
+        use std::sync::Arc;
+
+        let _lock = crate::storage::lock_global_storage();
+        let previous_dir = get_data_dir();
+        let dir = tempfile::tempdir().unwrap();
+        set_data_dir(dir.path()).unwrap();
+        let (_, cipher) = Keyring::create_with_params("secret", KdfParams::with_costs(64, 1, 1)).unwrap();
+        set_active_cipher(Some(Arc::new(cipher)));
+
+        let mut config = AppConfig::with_data_dir(dir.path().to_path_buf());
+        config.vector.index_backend = "embedded".to_string();
+        config.vector.embedding_backend = "hash".to_string();
+        let store = VectorStore::new(config).await.unwrap();
+        store.initialize().await.unwrap();
+        let collection = CollectionSchema::memory_collection().name;
+        let secret = "Gate code is 4417 for the north entrance";
+        let id = uuid::Uuid::new_v4();
+        let point = VectorPoint {
+            id,
+            vector: store.generate_embedding(secret).await.unwrap(),
+            payload: HashMap::from([("content".to_string(), serde_json::json!(secret))]),
+        };
+        store.upsert_points(&collection, vec![point]).await.unwrap();
+
+        let backup = BackupStorage::create_backup(Some(&store)).await.unwrap();
+        let exports = backup.parent().unwrap().to_path_buf();
+        let leaked: Vec<PathBuf> = files_under(&exports)
+            .into_iter()
+            .filter(|file| String::from_utf8_lossy(&fs::read(file).unwrap()).contains("north entrance"))
+            .collect();
+
+        store.delete_point(&collection, id).await.unwrap();
+        let restored = BackupStorage::restore_backup(&backup, Some(&store)).await;
+        let found = store.search_text(&collection, secret, 1).await;
+        set_active_cipher(None);
+        set_data_dir(&previous_dir).unwrap();
+
+        assert!(exports.join(format!("{}_vectors", backup.file_stem().unwrap().to_string_lossy())).is_dir());
+        assert!(leaked.is_empty(), "plaintext payload in {:?}", leaked);
+        restored.unwrap();
+        assert_eq!(found.unwrap()[0].payload["content"], secret);
+    }
 }
\ No newline at end of file
@@ -1,8 +1,17 @@
 // Storage module - File-based persistence and data management
 
+pub mod encryption;
 pub mod file_storage;
 pub mod paths;
 
 // Re-export storage functionality
+pub use encryption::{active_cipher, set_active_cipher, unlock_at_startup, DataCipher, Keyring};
 pub use file_storage::{AgentStorage, MessageStorage, DocumentStorage};
-pub use paths::{get_data_dir, ensure_data_dir, get_agents_file_path, get_messages_file_path};
\ No newline at end of file
+pub use paths::{get_data_dir, set_data_dir, move_legacy_data, ensure_data_dir, get_agents_file_path, get_messages_file_path};
+
+/// Serializes tests that switch the data directory or the active cipher
+#[cfg(test)]
+pub(crate) fn lock_global_storage() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
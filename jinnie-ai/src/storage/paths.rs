@@ -1,8 +1,53 @@
-use std::path::PathBuf;
+use once_cell::sync::Lazy;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 use crate::utils::error::{LocalMindError, Result};
 
+/// Data files kept directly in the data directory before it followed the config
+const LEGACY_ENTRIES: [&str; 5] = ["agents.json", "messages.json", "documents.json", "keyring.json", "exports"];
+
+static DATA_DIR: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
 /// Get the application data directory
 pub fn get_data_dir() -> PathBuf {
+    DATA_DIR
+        .read()
+        .ok()
+        .and_then(|dir| dir.clone())
+        .unwrap_or_else(legacy_data_dir)
+}
+
+/// Root every data file at `dir` (the configured `paths.data_dir`), so the keyring, the
+/// JSON stores and the memory database live together
+pub fn set_data_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| LocalMindError::Storage(format!("Failed to create data directory: {}", e)))?;
+    if let Ok(mut current) = DATA_DIR.write() {
+        *current = Some(dir.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Move files left in the old default location into `dir`, unless it has its own copy
+pub fn move_legacy_data(dir: &Path) -> Result<()> {
+    let legacy = legacy_data_dir();
+    if legacy == dir || !legacy.is_dir() {
+        return Ok(());
+    }
+    for entry in LEGACY_ENTRIES {
+        let (from, to) = (legacy.join(entry), dir.join(entry));
+        if from.exists() && !to.exists() {
+            std::fs::rename(&from, &to).map_err(|e| LocalMindError::Storage(format!(
+                "Failed to move {} to {}: {}", from.display(), to.display(), e
+            )))?;
+            log::info!("Moved {} into the data directory {}", entry, dir.display());
+        }
+    }
+    Ok(())
+}
+
+/// Where data lived before `set_data_dir`, and where it goes if that is never called
+fn legacy_data_dir() -> PathBuf {
     let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
     path.push("LocalMind");
     path
@@ -104,7 +149,7 @@ mod tests {
 
     #[test]
     fn test_get_data_dir() {
-        let data_dir = get_data_dir();
+        let data_dir = legacy_data_dir();
         assert!(data_dir.to_string_lossy().contains("LocalMind"));
     }
 
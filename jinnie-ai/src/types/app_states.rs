@@ -31,6 +31,9 @@ pub struct AppState {
     
     /// Vector store (optional, requires features)
     pub vector_store: Option<Arc<VectorStore>>,
+    
+    /// Chat session id for this run, recorded on stored memories
+    pub session_id: String,
 }
 
 /// Status of external services
@@ -64,6 +67,7 @@ impl AppState {
             service_status: Arc::new(Mutex::new(ServiceStatus::default())),
             memory_system: None,
             vector_store: None,
+            session_id: uuid::Uuid::new_v4().to_string(),
         }
     }
     
@@ -21,13 +21,15 @@ pub struct MessageAttachment {
     pub file_path: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MessageMetadata {
     pub model_used: Option<String>,
     pub response_time_ms: Option<u64>,
     pub token_count: Option<u32>,
     pub memory_accessed: Option<Vec<String>>,
     pub confidence_score: Option<f32>,
+    #[serde(default)]
+    pub reasoning: Option<String>, // Model's thinking trace, shown collapsed in the UI
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +80,26 @@ impl Message {
         self.metadata = Some(metadata);
         self
     }
+
+    /// Attach the model's reasoning trace without touching the visible content
+    pub fn with_reasoning(mut self, reasoning: Option<String>) -> Self {
+        if reasoning.is_some() {
+            self.metadata.get_or_insert_with(MessageMetadata::default).reasoning = reasoning;
+        }
+        self
+    }
+
+    /// Record which memories were recalled into the prompt for this message
+    pub fn with_memory_accessed(mut self, memory_ids: Vec<String>) -> Self {
+        if !memory_ids.is_empty() {
+            self.metadata.get_or_insert_with(MessageMetadata::default).memory_accessed = Some(memory_ids);
+        }
+        self
+    }
+
+    pub fn reasoning(&self) -> Option<&str> {
+        self.metadata.as_ref().and_then(|m| m.reasoning.as_deref())
+    }
     
     pub fn add_attachment(&mut self, attachment: MessageAttachment) {
         if let Some(ref mut attachments) = self.attachments {
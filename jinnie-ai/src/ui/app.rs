@@ -4,6 +4,7 @@ use crate::ui::{
         header::Header,
         sidebar::{HistorySidebar, AgentSidebar},
         chat::ChatContainer,
+        memory::MemoryInspector,
     },
     theme::{global_styles, JINNIE_THEME},
     state::ui_state::UIState,
@@ -11,7 +12,8 @@ use crate::ui::{
 
 pub fn App() -> Element {
     // Initialize UI state as a context signal
-    use_context_provider(|| Signal::new(UIState::new()));
+    let ui_state = use_context_provider(|| Signal::new(UIState::new()));
+    let inspector_open = ui_state.read().memory_inspector_open;
     
     rsx! {
         style { "{global_styles()}" }
@@ -43,8 +45,12 @@ pub fn App() -> Element {
                 // Header
                 Header {}
                 
-                // Chat Container
-                ChatContainer {}
+                // Chat Container, or the memory inspector when it is open
+                if inspector_open {
+                    MemoryInspector {}
+                } else {
+                    ChatContainer {}
+                }
             }
             
             // Agent Sidebar (right)
@@ -43,8 +43,9 @@ pub fn MessageInput() -> Element {
         spawn(async move {
             match app_state.send_message_to_agent(current_agent_id, message_content).await {
                 Ok(response) => {
-                    // Add AI response
-                    ui_state.write().add_message(response, MessageRole::Assistant);
+                    // Add AI response with its reasoning, if the model produced any
+                    let reasoning = response.reasoning().map(|r| r.to_string());
+                    ui_state.write().add_message_with_reasoning(response.content, MessageRole::Assistant, reasoning);
                 }
                 Err(e) => {
                     ui_state.write().set_error(Some(format!("Failed to send message: {}", e)));
@@ -50,7 +50,39 @@ pub fn MessageItem(cx: Scope<MessageItemProps>) -> Element {
                     flex: 1;
                     max-width: calc(100% - 80px);
                 ",
-                
+
+                // Model reasoning, collapsed by default
+                if let Some(reasoning) = &cx.props.message.reasoning {
+                    rsx! {
+                        details {
+                            class: "message-reasoning",
+                            style: "
+                                margin-bottom: 0.5rem;
+                                padding: 0.5rem 0.75rem;
+                                border-radius: 0.5rem;
+                                border: 1px dashed {JINNIE_THEME.border};
+                                color: {JINNIE_THEME.text_muted};
+                                font-size: 0.875rem;
+                            ",
+
+                            summary {
+                                style: "cursor: pointer; user-select: none;",
+                                "💭 Thinking"
+                            }
+
+                            div {
+                                style: "
+                                    margin-top: 0.5rem;
+                                    line-height: 1.5;
+                                    white-space: pre-wrap;
+                                    word-wrap: break-word;
+                                ",
+                                "{reasoning}"
+                            }
+                        }
+                    }
+                }
+
                 div {
                     style: "
                         background: {if is_user { JINNIE_THEME.primary } else { JINNIE_THEME.surface }};
@@ -152,6 +152,17 @@ pub fn Header(cx: Scope) -> Element {
                     }
                 }
                 
+                // Memory inspector
+                button {
+                    style: "{button_styles(\"ghost\")}",
+                    title: "Memory",
+                    onclick: move |_| {
+                        ui_state.write().toggle_memory_inspector();
+                    },
+                    
+                    "🧠"
+                }
+                
                 // Settings button
                 button {
                     style: "{button_styles(\"ghost\")}",
@@ -0,0 +1,246 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+use crate::ui::{
+    theme::{JINNIE_THEME, button_styles, input_styles},
+    state::ui_state::MemoryView,
+};
+
+/// One association as shown in the card's detail view
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssociationView {
+    pub kind: String,
+    pub other: String,
+    pub strength: f32,
+}
+
+/// An edit submitted from a card
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryEdit {
+    pub id: Uuid,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub importance: f32,
+}
+
+#[component]
+pub fn MemoryCard(
+    memory: MemoryView,
+    selected: bool,
+    expanded: bool,
+    associations: Vec<AssociationView>,
+    on_select: EventHandler<Uuid>,
+    on_expand: EventHandler<Uuid>,
+    on_pin: EventHandler<(Uuid, bool)>,
+    on_forget: EventHandler<Uuid>,
+    on_rate: EventHandler<(Uuid, f32)>,
+    on_save: EventHandler<MemoryEdit>,
+) -> Element {
+    let mut editing = use_signal(|| false);
+    let mut draft_content = use_signal(String::new);
+    let mut draft_tags = use_signal(String::new);
+    let mut draft_importance = use_signal(String::new);
+
+    let id = memory.id;
+    let pinned = memory.pinned;
+    let importance = format!("{:.2}", memory.importance);
+    let tags = memory.tags.join(", ");
+    let border = if selected { JINNIE_THEME.border_focus } else { JINNIE_THEME.border };
+
+    let start_editing = {
+        let memory = memory.clone();
+        move |_| {
+            draft_content.set(memory.content.clone());
+            draft_tags.set(memory.tags.join(", "));
+            draft_importance.set(format!("{:.2}", memory.importance));
+            editing.set(true);
+        }
+    };
+
+    let save = move |_| {
+        let tags = draft_tags.read()
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        let importance = draft_importance.read().trim().parse::<f32>()
+            .map(|value| value.clamp(0.0, 1.0))
+            .unwrap_or(memory.importance);
+
+        on_save.call(MemoryEdit {
+            id,
+            content: draft_content.read().trim().to_string(),
+            tags,
+            importance,
+        });
+        editing.set(false);
+    };
+
+    rsx! {
+        div {
+            class: "memory-card",
+            style: "
+                background: {JINNIE_THEME.surface};
+                border: 1px solid {border};
+                border-radius: 0.75rem;
+                padding: 1rem;
+                display: flex;
+                gap: 0.75rem;
+                align-items: flex-start;
+            ",
+
+            input {
+                r#type: "checkbox",
+                checked: selected,
+                style: "margin-top: 0.25rem;",
+                onchange: move |_| on_select.call(id),
+            }
+
+            div {
+                style: "flex: 1; min-width: 0;",
+
+                if editing() {
+                    div {
+                        style: "display: flex; flex-direction: column; gap: 0.5rem;",
+
+                        textarea {
+                            style: "{input_styles()}; min-height: 72px; resize: vertical; font-family: inherit;",
+                            value: "{draft_content}",
+                            oninput: move |event| draft_content.set(event.value()),
+                        }
+
+                        div {
+                            style: "display: flex; gap: 0.5rem;",
+
+                            input {
+                                style: "{input_styles()}; flex: 1;",
+                                placeholder: "Tags, comma separated",
+                                value: "{draft_tags}",
+                                oninput: move |event| draft_tags.set(event.value()),
+                            }
+                            input {
+                                style: "{input_styles()}; width: 6rem;",
+                                title: "Importance (0-1)",
+                                value: "{draft_importance}",
+                                oninput: move |event| draft_importance.set(event.value()),
+                            }
+                        }
+
+                        div {
+                            style: "display: flex; gap: 0.5rem; justify-content: flex-end;",
+
+                            button {
+                                style: "{button_styles(\"ghost\")}",
+                                onclick: move |_| editing.set(false),
+                                "Cancel"
+                            }
+                            button {
+                                style: "{button_styles(\"primary\")}",
+                                disabled: draft_content.read().trim().is_empty(),
+                                onclick: save,
+                                "Save"
+                            }
+                        }
+                    }
+                } else {
+                    div {
+                        style: "
+                            color: {JINNIE_THEME.text_primary};
+                            font-size: 0.9rem;
+                            line-height: 1.4;
+                            white-space: pre-wrap;
+                            cursor: pointer;
+                        ",
+                        onclick: move |_| on_expand.call(id),
+
+                        if pinned {
+                            span { title: "Pinned", "📌 " }
+                        }
+                        "{memory.content}"
+                    }
+
+                    div {
+                        style: "
+                            display: flex;
+                            flex-wrap: wrap;
+                            gap: 0.75rem;
+                            margin-top: 0.5rem;
+                            font-size: 0.75rem;
+                            color: {JINNIE_THEME.text_muted};
+                        ",
+
+                        span { "Importance {importance}" }
+                        span { "Accessed {memory.access_count}×" }
+                        span { "Links {memory.association_count}" }
+                        span { "{memory.source}" }
+                        span { "{memory.created_at}" }
+                        if !tags.is_empty() {
+                            span {
+                                style: "color: {JINNIE_THEME.accent};",
+                                "#{tags}"
+                            }
+                        }
+                    }
+
+                    if expanded {
+                        div {
+                            style: "
+                                margin-top: 0.75rem;
+                                padding-top: 0.75rem;
+                                border-top: 1px solid {JINNIE_THEME.border};
+                                font-size: 0.8rem;
+                                color: {JINNIE_THEME.text_secondary};
+                            ",
+
+                            if associations.is_empty() {
+                                span { "No associations" }
+                            }
+                            for association in associations.iter() {
+                                div {
+                                    "{association.kind} ({association.strength:.2}) → {association.other}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !editing() {
+                div {
+                    style: "display: flex; flex-direction: column; gap: 0.25rem;",
+
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.25rem 0.5rem;",
+                        title: if pinned { "Unpin" } else { "Pin so maintenance never prunes it" },
+                        onclick: move |_| on_pin.call((id, !pinned)),
+                        if pinned { "Unpin" } else { "Pin" }
+                    }
+                    div {
+                        style: "display: flex; gap: 0.25rem;",
+                        button {
+                            style: "{button_styles(\"ghost\")}; padding: 0.25rem 0.5rem;",
+                            title: "This matters: remember more like it",
+                            onclick: move |_| on_rate.call((id, 1.0)),
+                            "👍"
+                        }
+                        button {
+                            style: "{button_styles(\"ghost\")}; padding: 0.25rem 0.5rem;",
+                            title: "Not important",
+                            onclick: move |_| on_rate.call((id, 0.0)),
+                            "👎"
+                        }
+                    }
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.25rem 0.5rem;",
+                        onclick: start_editing,
+                        "Edit"
+                    }
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.25rem 0.5rem; color: {JINNIE_THEME.error};",
+                        onclick: move |_| on_forget.call(id),
+                        "Forget"
+                    }
+                }
+            }
+        }
+    }
+}
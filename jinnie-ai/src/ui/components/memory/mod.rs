@@ -0,0 +1,484 @@
+use std::collections::HashSet;
+use dioxus::prelude::*;
+use uuid::Uuid;
+use crate::{
+    memory::{MemoryLayer, MemoryQuery, MemoryUpdate},
+    ui::{
+        theme::{JINNIE_THEME, input_styles, button_styles},
+        state::ui_state::{UIState, MemoryView},
+    },
+    use_app_state,
+};
+
+pub mod memory_card;
+
+use memory_card::{AssociationView, MemoryCard, MemoryEdit};
+
+/// Most memories the inspector loads at once
+const INSPECTOR_LIMIT: usize = 500;
+
+/// Search and filter settings for the inspector
+#[derive(Debug, Clone, Default, PartialEq)]
+struct MemoryFilters {
+    text: String,
+    layer: Option<MemoryLayer>,
+    tags: String,
+    min_importance: Option<f32>,
+    pinned_only: bool,
+}
+
+impl MemoryFilters {
+    fn to_query(&self, agent_id: &str) -> MemoryQuery {
+        let mut query = MemoryQuery::new().with_agent(agent_id.to_string());
+
+        let text = self.text.trim();
+        if !text.is_empty() {
+            query.text_query = Some(text.to_string());
+        }
+
+        let tags: Vec<String> = self.tags
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        if !tags.is_empty() {
+            query.tags = Some(tags);
+        }
+
+        query.layers = self.layer.map(|layer| vec![layer]);
+        query.importance_threshold = self.min_importance;
+        query.limit = Some(INSPECTOR_LIMIT);
+        query.include_rejected = true;
+        query
+    }
+}
+
+/// Bulk operations on the selected memories
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MemoryAction {
+    Pin,
+    Unpin,
+    Forget,
+}
+
+/// Question asked before memories are deleted for good
+fn forget_prompt(count: usize) -> String {
+    if count == 1 {
+        "Forget this memory? It will be deleted for good.".to_string()
+    } else {
+        format!("Forget {} memories? They will be deleted for good.", count)
+    }
+}
+
+fn layer_label(layer: MemoryLayer) -> &'static str {
+    match layer {
+        MemoryLayer::Working => "Working",
+        MemoryLayer::ShortTerm => "Short-term",
+        MemoryLayer::LongTerm => "Long-term",
+        MemoryLayer::Episodic => "Episodic",
+        MemoryLayer::Semantic => "Semantic",
+        MemoryLayer::Reflective => "Reflective",
+    }
+}
+
+/// Lists what the assistant remembers about the user for the current agent
+pub fn MemoryInspector() -> Element {
+    let mut ui_state = use_context::<Signal<UIState>>();
+    let app_state = use_app_state();
+    let app_state = use_signal(move || app_state);
+
+    let mut memories = use_signal(Vec::<MemoryView>::new);
+    let mut filters = use_signal(MemoryFilters::default);
+    let mut selected = use_signal(HashSet::<Uuid>::new);
+    let mut expanded = use_signal(|| None::<Uuid>);
+    let mut associations = use_signal(Vec::<AssociationView>::new);
+    let mut loading = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+    let mut refresh = use_signal(|| 0u32);
+    // Memories waiting for the user to confirm they should be forgotten
+    let mut confirm_forget = use_signal(|| None::<Vec<Uuid>>);
+
+    // Reload whenever the agent, the filters or the refresh counter change
+    use_effect(move || {
+        let _ = refresh();
+        let agent_id = ui_state.read().current_agent_id.clone();
+        let query = filters.read().to_query(&agent_id);
+        let backend = app_state();
+
+        spawn(async move {
+            loading.set(true);
+            match backend.browse_memories(query).await {
+                Ok(found) => {
+                    memories.set(found.into_iter().map(MemoryView::from).collect());
+                    error.set(None);
+                }
+                Err(e) => {
+                    error.set(Some(format!("Failed to load memories: {}", e)));
+                    log::error!("Failed to load memories: {}", e);
+                }
+            }
+            loading.set(false);
+        });
+    });
+
+    let mut run_action = move |action: MemoryAction, ids: Vec<Uuid>| {
+        if ids.is_empty() {
+            return;
+        }
+        let backend = app_state();
+
+        spawn(async move {
+            let result = match action {
+                MemoryAction::Pin => backend.pin_memories(ids, true).await,
+                MemoryAction::Unpin => backend.pin_memories(ids, false).await,
+                MemoryAction::Forget => backend.forget_memories(ids).await,
+            };
+            match result {
+                Ok(_) => {
+                    selected.write().clear();
+                    refresh += 1;
+                }
+                Err(e) => error.set(Some(format!("Failed to update memories: {}", e))),
+            }
+        });
+    };
+
+    let rate = move |(id, rating): (Uuid, f32)| {
+        let backend = app_state();
+        spawn(async move {
+            match backend.rate_memory(id, rating).await {
+                Ok(_) => refresh += 1,
+                Err(e) => error.set(Some(format!("Failed to rate memory: {}", e))),
+            }
+        });
+    };
+
+    let save_edit = move |edit: MemoryEdit| {
+        let backend = app_state();
+        let update = MemoryUpdate {
+            content: Some(edit.content),
+            importance_score: Some(edit.importance),
+            tags: Some(edit.tags),
+            associations: None,
+            metadata_updates: None,
+            pinned: None,
+        };
+
+        spawn(async move {
+            match backend.update_memory(edit.id, update).await {
+                Ok(_) => refresh += 1,
+                Err(e) => error.set(Some(format!("Failed to save memory: {}", e))),
+            }
+        });
+    };
+
+    let toggle_expanded = move |id: Uuid| {
+        if expanded() == Some(id) {
+            expanded.set(None);
+            return;
+        }
+        expanded.set(Some(id));
+        associations.set(Vec::new());
+        let backend = app_state();
+
+        spawn(async move {
+            match backend.memory_associations(id).await {
+                Ok(found) => {
+                    let views = found.into_iter().map(|association| {
+                        let other_id = if association.memory_a == id { association.memory_b } else { association.memory_a };
+                        let other = memories.read()
+                            .iter()
+                            .find(|memory| memory.id == other_id)
+                            .map(|memory| memory.content.chars().take(80).collect())
+                            .unwrap_or_else(|| other_id.to_string());
+                        AssociationView {
+                            kind: format!("{:?}", association.association_type),
+                            other,
+                            strength: association.strength,
+                        }
+                    }).collect();
+                    associations.set(views);
+                }
+                Err(e) => error.set(Some(format!("Failed to load associations: {}", e))),
+            }
+        });
+    };
+
+    let toggle_selected = move |id: Uuid| {
+        let mut selected = selected.write();
+        if !selected.remove(&id) {
+            selected.insert(id);
+        }
+    };
+
+    let pinned_only = filters.read().pinned_only;
+    let visible: Vec<MemoryView> = memories.read()
+        .iter()
+        .filter(|memory| !pinned_only || memory.pinned)
+        .cloned()
+        .collect();
+    let visible_ids: Vec<Uuid> = visible.iter().map(|memory| memory.id).collect();
+    let selected_ids: Vec<Uuid> = selected.read().iter().copied().collect();
+    let selection_count = selected_ids.len();
+    let groups: Vec<(MemoryLayer, Vec<MemoryView>)> = MemoryLayer::ALL
+        .into_iter()
+        .map(|layer| (layer, visible.iter().filter(|memory| memory.layer == layer).cloned().collect::<Vec<_>>()))
+        .filter(|(_, memories)| !memories.is_empty())
+        .collect();
+    let current_filters = filters.read().clone();
+    let layer_value = current_filters.layer.map(|layer| layer.as_str()).unwrap_or("all");
+    let importance_value = current_filters.min_importance.map(|value| format!("{:.1}", value)).unwrap_or_default();
+
+    rsx! {
+        div {
+            class: "memory-inspector",
+            style: "
+                flex: 1;
+                display: flex;
+                flex-direction: column;
+                height: calc(100vh - 64px);
+                background: {JINNIE_THEME.bg_primary};
+            ",
+
+            // Title bar
+            div {
+                style: "
+                    display: flex;
+                    align-items: center;
+                    justify-content: space-between;
+                    padding: 1rem 1.5rem;
+                    border-bottom: 1px solid {JINNIE_THEME.border};
+                ",
+
+                h2 {
+                    style: "font-size: 1.125rem; font-weight: 600; color: {JINNIE_THEME.text_primary}; margin: 0;",
+                    "What I remember"
+                }
+
+                div {
+                    style: "display: flex; gap: 0.5rem;",
+
+                    button {
+                        style: "{button_styles(\"secondary\")}",
+                        onclick: move |_| refresh += 1,
+                        if loading() { "Loading..." } else { "Refresh" }
+                    }
+                    button {
+                        style: "{button_styles(\"ghost\")}",
+                        title: "Back to chat",
+                        onclick: move |_| ui_state.write().toggle_memory_inspector(),
+                        "×"
+                    }
+                }
+            }
+
+            // Search and filters
+            div {
+                style: "
+                    display: flex;
+                    flex-wrap: wrap;
+                    gap: 0.75rem;
+                    padding: 1rem 1.5rem;
+                    border-bottom: 1px solid {JINNIE_THEME.border};
+                    background: {JINNIE_THEME.surface};
+                ",
+
+                input {
+                    style: "{input_styles()}; flex: 2; min-width: 200px;",
+                    placeholder: "Search memories...",
+                    value: "{current_filters.text}",
+                    oninput: move |event| filters.write().text = event.value(),
+                }
+
+                select {
+                    style: "{input_styles()}",
+                    value: "{layer_value}",
+                    onchange: move |event| filters.write().layer = MemoryLayer::from_name(&event.value()),
+
+                    option { value: "all", "All layers" }
+                    for layer in MemoryLayer::ALL {
+                        option { value: "{layer.as_str()}", "{layer_label(layer)}" }
+                    }
+                }
+
+                input {
+                    style: "{input_styles()}; flex: 1; min-width: 140px;",
+                    placeholder: "Tags, comma separated",
+                    value: "{current_filters.tags}",
+                    oninput: move |event| filters.write().tags = event.value(),
+                }
+
+                select {
+                    style: "{input_styles()}",
+                    value: "{importance_value}",
+                    onchange: move |event| filters.write().min_importance = event.value().parse().ok(),
+
+                    option { value: "", "Any importance" }
+                    option { value: "0.3", "Importance ≥ 0.3" }
+                    option { value: "0.5", "Importance ≥ 0.5" }
+                    option { value: "0.8", "Importance ≥ 0.8" }
+                }
+
+                label {
+                    style: "display: flex; align-items: center; gap: 0.375rem; font-size: 0.875rem; color: {JINNIE_THEME.text_secondary};",
+                    input {
+                        r#type: "checkbox",
+                        checked: pinned_only,
+                        onchange: move |_| {
+                            let mut filters = filters.write();
+                            filters.pinned_only = !filters.pinned_only;
+                        },
+                    }
+                    "Pinned only"
+                }
+            }
+
+            // Bulk actions
+            div {
+                style: "
+                    display: flex;
+                    align-items: center;
+                    gap: 0.5rem;
+                    padding: 0.5rem 1.5rem;
+                    font-size: 0.875rem;
+                    color: {JINNIE_THEME.text_muted};
+                ",
+
+                span { "{visible.len()} memories · {selection_count} selected" }
+
+                button {
+                    style: "{button_styles(\"ghost\")}; padding: 0.375rem 0.75rem;",
+                    onclick: move |_| selected.set(visible_ids.iter().copied().collect()),
+                    "Select all"
+                }
+                if selection_count > 0 {
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.375rem 0.75rem;",
+                        onclick: {
+                            let ids = selected_ids.clone();
+                            move |_| run_action(MemoryAction::Pin, ids.clone())
+                        },
+                        "Pin"
+                    }
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.375rem 0.75rem;",
+                        onclick: {
+                            let ids = selected_ids.clone();
+                            move |_| run_action(MemoryAction::Unpin, ids.clone())
+                        },
+                        "Unpin"
+                    }
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.375rem 0.75rem; color: {JINNIE_THEME.error};",
+                        onclick: {
+                            let ids = selected_ids.clone();
+                            move |_| confirm_forget.set(Some(ids.clone()))
+                        },
+                        "Forget"
+                    }
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.375rem 0.75rem;",
+                        onclick: move |_| selected.write().clear(),
+                        "Clear selection"
+                    }
+                }
+            }
+
+            if let Some(ids) = confirm_forget() {
+                div {
+                    style: "
+                        display: flex;
+                        align-items: center;
+                        gap: 0.5rem;
+                        padding: 0.75rem 1.5rem;
+                        font-size: 0.875rem;
+                        color: {JINNIE_THEME.text_primary};
+                        background: {JINNIE_THEME.surface};
+                        border-top: 1px solid {JINNIE_THEME.error};
+                        border-bottom: 1px solid {JINNIE_THEME.error};
+                    ",
+
+                    span { style: "flex: 1;", "{forget_prompt(ids.len())}" }
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.375rem 0.75rem; color: {JINNIE_THEME.error};",
+                        onclick: move |_| {
+                            confirm_forget.set(None);
+                            run_action(MemoryAction::Forget, ids.clone());
+                        },
+                        "Forget"
+                    }
+                    button {
+                        style: "{button_styles(\"ghost\")}; padding: 0.375rem 0.75rem;",
+                        onclick: move |_| confirm_forget.set(None),
+                        "Cancel"
+                    }
+                }
+            }
+
+            if let Some(message) = error() {
+                div {
+                    style: "
+                        background: {JINNIE_THEME.error};
+                        color: white;
+                        padding: 0.75rem 1.5rem;
+                        font-size: 0.875rem;
+                    ",
+                    "{message}"
+                }
+            }
+
+            // Memories grouped by layer
+            div {
+                style: "flex: 1; overflow-y: auto; padding: 1rem 1.5rem;",
+
+                if groups.is_empty() && !loading() {
+                    div {
+                        style: "text-align: center; padding: 3rem 1rem; color: {JINNIE_THEME.text_muted};",
+                        "Nothing remembered yet"
+                    }
+                }
+
+                for (layer, layer_memories) in groups {
+                    div {
+                        key: "{layer.as_str()}",
+                        style: "margin-bottom: 1.5rem;",
+
+                        h3 {
+                            style: "
+                                font-size: 0.8rem;
+                                font-weight: 600;
+                                text-transform: uppercase;
+                                letter-spacing: 0.05em;
+                                color: {JINNIE_THEME.text_muted};
+                                margin: 0 0 0.75rem 0;
+                            ",
+                            "{layer_label(layer)} ({layer_memories.len()})"
+                        }
+
+                        div {
+                            style: "display: flex; flex-direction: column; gap: 0.5rem;",
+
+                            for memory in layer_memories {
+                                MemoryCard {
+                                    key: "{memory.id}",
+                                    selected: selected.read().contains(&memory.id),
+                                    expanded: expanded() == Some(memory.id),
+                                    associations: if expanded() == Some(memory.id) { associations() } else { Vec::new() },
+                                    memory,
+                                    on_select: toggle_selected,
+                                    on_expand: toggle_expanded,
+                                    on_pin: move |(id, pinned): (Uuid, bool)| {
+                                        run_action(if pinned { MemoryAction::Pin } else { MemoryAction::Unpin }, vec![id])
+                                    },
+                                    on_forget: move |id: Uuid| confirm_forget.set(Some(vec![id])),
+                                    on_rate: rate,
+                                    on_save: save_edit,
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod header;
 pub mod sidebar;
-pub mod chat;
\ No newline at end of file
+pub mod chat;
+pub mod memory;
+pub mod unlock;
\ No newline at end of file
@@ -0,0 +1,109 @@
+use dioxus::prelude::*;
+use crate::ui::theme::{JINNIE_THEME, global_styles, card_styles, input_styles, button_styles};
+
+/// Asks for the storage passphrase when the app was started without a terminal.
+/// With `setup` the passphrase is new, so it has to be entered twice.
+#[component]
+pub fn UnlockScreen(
+    setup: bool,
+    busy: bool,
+    error: Option<String>,
+    on_submit: EventHandler<String>,
+) -> Element {
+    let mut passphrase = use_signal(String::new);
+    let mut repeat = use_signal(String::new);
+
+    let mismatch = setup && !repeat.read().is_empty() && *repeat.read() != *passphrase.read();
+    let ready = !busy && !passphrase.read().is_empty() && (!setup || *repeat.read() == *passphrase.read());
+
+    let mut submit = move |_| {
+        if ready {
+            on_submit.call(passphrase.read().clone());
+            passphrase.set(String::new());
+            repeat.set(String::new());
+        }
+    };
+    let submit_on_enter = move |event: KeyboardEvent| {
+        if event.key() == Key::Enter {
+            event.prevent_default();
+            submit(());
+        }
+    };
+
+    let title = if setup { "Protect your data" } else { "Unlock Jinnie" };
+    let hint = if setup {
+        "Encryption is enabled. Choose a passphrase to protect your data; it cannot be recovered if lost."
+    } else {
+        "Your data is encrypted. Enter your passphrase to continue."
+    };
+
+    rsx! {
+        style { "{global_styles()}" }
+
+        div {
+            class: "unlock-screen",
+            style: "
+                display: flex;
+                height: 100vh;
+                align-items: center;
+                justify-content: center;
+                background: {JINNIE_THEME.bg_primary};
+            ",
+
+            div {
+                style: "{card_styles()}; width: 360px; display: flex; flex-direction: column; gap: 0.75rem;",
+
+                h2 {
+                    style: "margin: 0; color: {JINNIE_THEME.text_primary}; font-size: 1.25rem;",
+                    "🔐 {title}"
+                }
+                p {
+                    style: "margin: 0; color: {JINNIE_THEME.text_secondary}; font-size: 0.875rem;",
+                    "{hint}"
+                }
+
+                input {
+                    r#type: "password",
+                    style: "{input_styles()}",
+                    placeholder: if setup { "New passphrase" } else { "Passphrase" },
+                    autofocus: true,
+                    disabled: busy,
+                    value: "{passphrase}",
+                    oninput: move |event| passphrase.set(event.value()),
+                    onkeydown: submit_on_enter,
+                }
+                if setup {
+                    input {
+                        r#type: "password",
+                        style: "{input_styles()}",
+                        placeholder: "Repeat passphrase",
+                        disabled: busy,
+                        value: "{repeat}",
+                        oninput: move |event| repeat.set(event.value()),
+                        onkeydown: submit_on_enter,
+                    }
+                }
+
+                if mismatch {
+                    p {
+                        style: "margin: 0; color: {JINNIE_THEME.warning}; font-size: 0.8rem;",
+                        "Passphrases do not match"
+                    }
+                }
+                if let Some(error) = error {
+                    p {
+                        style: "margin: 0; color: {JINNIE_THEME.error}; font-size: 0.8rem;",
+                        "{error}"
+                    }
+                }
+
+                button {
+                    style: "{button_styles(\"primary\")}; opacity: {if ready { \"1\" } else { \"0.5\" }};",
+                    disabled: !ready,
+                    onclick: move |_| submit(()),
+                    if busy { "Unlocking…" } else if setup { "Encrypt and continue" } else { "Unlock" }
+                }
+            }
+        }
+    }
+}
@@ -7,6 +7,7 @@ use crate::types::{
     message::{Message as BackendMessage, MessageRole as BackendMessageRole},
     agent::Agent as BackendAgent,
 };
+use crate::memory::{Memory as BackendMemory, MemoryLayer};
 
 // UI-specific message type
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +17,8 @@ pub struct Message {
     pub role: MessageRole,
     pub timestamp: u64,
     pub agent_id: String,
+    #[serde(default)]
+    pub reasoning: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -59,6 +62,38 @@ impl From<BackendAgent> for Agent {
     }
 }
 
+// UI-specific memory type for the inspector
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryView {
+    pub id: Uuid,
+    pub layer: MemoryLayer,
+    pub content: String,
+    pub importance: f32,
+    pub access_count: u32,
+    pub tags: Vec<String>,
+    pub association_count: usize,
+    pub source: String,
+    pub pinned: bool,
+    pub created_at: String,
+}
+
+impl From<BackendMemory> for MemoryView {
+    fn from(memory: BackendMemory) -> Self {
+        Self {
+            id: memory.id,
+            layer: memory.layer,
+            content: memory.content,
+            importance: memory.importance_score,
+            access_count: memory.access_count,
+            tags: memory.tags,
+            association_count: memory.associations.len(),
+            source: format!("{:?}", memory.metadata.source),
+            pinned: memory.pinned,
+            created_at: memory.created_at.format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chat {
     pub id: String,
@@ -83,6 +118,7 @@ pub struct UIState {
     pub loading_agents: bool,
     pub loading_messages: bool,
     pub error_message: Option<String>,
+    pub memory_inspector_open: bool,
 }
 
 impl UIState {
@@ -99,12 +135,18 @@ impl UIState {
     }
     
     pub fn add_message(&mut self, content: String, role: MessageRole) {
+        self.add_message_with_reasoning(content, role, None);
+    }
+    
+    /// Add a message along with the model's thinking, shown collapsed under the bubble
+    pub fn add_message_with_reasoning(&mut self, content: String, role: MessageRole, reasoning: Option<String>) {
         let message = Message {
             id: Uuid::new_v4().to_string(),
             content,
             role,
             timestamp: chrono::Utc::now().timestamp_millis() as u64,
             agent_id: self.current_agent_id.clone(),
+            reasoning,
         };
         self.messages.push(message);
     }
@@ -129,6 +171,10 @@ impl UIState {
         self.agent_sidebar_collapsed = !self.agent_sidebar_collapsed;
     }
     
+    pub fn toggle_memory_inspector(&mut self) {
+        self.memory_inspector_open = !self.memory_inspector_open;
+    }
+    
     pub fn load_agents(&mut self, backend_agents: Vec<BackendAgent>) {
         self.agents = backend_agents.into_iter()
             .map(|agent| agent.into())
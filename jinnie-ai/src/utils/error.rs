@@ -36,6 +36,18 @@ pub enum LocalMindError {
     #[error("Serialization error: {0}")]
     Serialization(String),
 
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Wrong passphrase: the stored data could not be unlocked")]
+    WrongPassphrase,
+
+    #[error("Data is locked: {0}")]
+    Locked(String),
+
+    #[error("A passphrase is needed to open the stored data")]
+    PassphraseRequired { setup: bool },
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use candle_core::{Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokenizers::Tokenizer;
+
+use super::embedding_engine::{load_tokenizer, EmbeddingProvider};
+
+/// Weight files checked (in order) inside a model directory
+const SAFETENSORS_FILES: &[&str] = &["model.safetensors", "pytorch_model.safetensors"];
+
+/// Sentence-transformer BERT model run natively through candle
+pub struct CandleEmbeddingProvider {
+    name: String,
+    model: Arc<BertModel>,
+    tokenizer: Arc<Tokenizer>,
+    device: Device,
+    dimension: usize,
+}
+
+impl CandleEmbeddingProvider {
+    /// Whether a model directory has everything candle needs
+    pub fn has_model_files(model_dir: &Path) -> bool {
+        model_dir.join("config.json").is_file() && Self::find_weights(model_dir).is_some()
+    }
+
+    fn find_weights(model_dir: &Path) -> Option<PathBuf> {
+        SAFETENSORS_FILES
+            .iter()
+            .map(|file| model_dir.join(file))
+            .find(|path| path.is_file())
+    }
+
+    /// Load `config.json`, safetensors weights and `tokenizer.json` from a model directory
+    pub fn load(name: &str, model_dir: &Path, max_sequence_length: usize) -> Result<Self> {
+        let weights = Self::find_weights(model_dir)
+            .ok_or_else(|| anyhow!("No safetensors weights found in {}", model_dir.display()))?;
+
+        let config_text = std::fs::read_to_string(model_dir.join("config.json"))
+            .map_err(|e| anyhow!("Failed to read BERT config in {}: {}", model_dir.display(), e))?;
+        let config: Config = serde_json::from_str(&config_text)
+            .map_err(|e| anyhow!("Invalid BERT config in {}: {}", model_dir.display(), e))?;
+
+        // No padding: candle's BertModel takes no attention mask, so batches are grouped by length
+        let tokenizer = load_tokenizer(&model_dir.join("tokenizer.json"), max_sequence_length, false)?;
+
+        let device = Device::Cpu;
+        // Safety: the weights file is memory-mapped read-only and not modified while loaded
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[weights.clone()], DTYPE, &device)? };
+        let model = BertModel::load(vb, &config)
+            .map_err(|e| anyhow!("Failed to load BERT weights {}: {}", weights.display(), e))?;
+
+        let mut provider = Self {
+            name: name.to_string(),
+            model: Arc::new(model),
+            tokenizer: Arc::new(tokenizer),
+            device,
+            dimension: 0,
+        };
+
+        // Run one probe so the dimension is known up front
+        let probe = run_batch(&provider.model, &provider.tokenizer, &provider.device, &["probe".to_string()])?;
+        provider.dimension = probe.first().map(|v| v.len()).unwrap_or(0);
+
+        log::info!("Loaded candle BERT embedding model {} ({}D) from {}", name, provider.dimension, model_dir.display());
+        Ok(provider)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for CandleEmbeddingProvider {
+    fn name(&self) -> String {
+        format!("candle:{}", self.name)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let model = self.model.clone();
+        let tokenizer = self.tokenizer.clone();
+        let device = self.device.clone();
+        let texts = texts.to_vec();
+
+        // Inference is CPU-bound, keep it off the async workers
+        tokio::task::spawn_blocking(move || run_batch(&model, &tokenizer, &device, &texts))
+            .await
+            .map_err(|e| anyhow!("Candle embedding task failed: {}", e))?
+    }
+
+    async fn dimension(&self) -> Result<usize> {
+        Ok(self.dimension)
+    }
+
+    async fn is_ready(&self) -> bool {
+        true
+    }
+}
+
+fn run_batch(model: &BertModel, tokenizer: &Tokenizer, device: &Device, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    let encodings = tokenizer
+        .encode_batch(texts.to_vec(), true)
+        .map_err(|e| anyhow!("Tokenization failed: {}", e))?;
+
+    // Group inputs of equal token length so no padding is needed
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (index, encoding) in encodings.iter().enumerate() {
+        groups.entry(encoding.get_ids().len()).or_default().push(index);
+    }
+
+    let mut results: Vec<Vec<f32>> = vec![Vec::new(); texts.len()];
+    for (seq_len, indices) in groups {
+        let ids: Vec<u32> = indices.iter().flat_map(|&i| encodings[i].get_ids().to_vec()).collect();
+        let type_ids: Vec<u32> = indices.iter().flat_map(|&i| encodings[i].get_type_ids().to_vec()).collect();
+
+        let input_ids = Tensor::from_vec(ids, (indices.len(), seq_len), device)?;
+        let token_type_ids = Tensor::from_vec(type_ids, (indices.len(), seq_len), device)?;
+
+        // [batch, seq, hidden] -> mean over tokens
+        let hidden = model.forward(&input_ids, &token_type_ids)?;
+        let pooled = (hidden.sum(1)? / seq_len as f64)?;
+
+        for (embedding, index) in pooled.to_vec2::<f32>()?.into_iter().zip(indices) {
+            results[index] = embedding;
+        }
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,416 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::storage::encryption::{open_bytes, seal_bytes};
+use super::hnsw_index::{HnswIndex, HnswParams, Metric};
+use super::search_engine::SearchQuery;
+use super::{CollectionSchema, VectorPoint, VectorSearchResult};
+
+/// Changes since a collection was last saved in full, appended as length-prefixed
+/// (and, with encryption on, sealed) JSON records
+const LOG_FILE: &str = "updates.log";
+
+/// Logged points after which a collection is saved in full and its log cleared
+const CHECKPOINT_POINTS: usize = 512;
+
+/// One logged change, replayed on top of the saved index when the store opens
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum LogEntry {
+    Upsert { points: Vec<VectorPoint> },
+    Delete { ids: Vec<Uuid> },
+}
+
+impl LogEntry {
+    fn len(&self) -> usize {
+        match self {
+            Self::Upsert { points } => points.len(),
+            Self::Delete { ids } => ids.len(),
+        }
+    }
+
+    fn apply(self, index: &mut HnswIndex) -> Result<()> {
+        match self {
+            Self::Upsert { points } => {
+                for point in points {
+                    index.upsert(point)?;
+                }
+            }
+            Self::Delete { ids } => {
+                for id in ids {
+                    index.delete(id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Collection {
+    index: HnswIndex,
+    /// Points in the update log since the last full save
+    logged: usize,
+}
+
+/// In-process stand-in for `QdrantManager`: one HNSW index per collection, each
+/// saved under its own subdirectory. Changes are appended to the collection's
+/// update log and the index is saved in full only every `CHECKPOINT_POINTS`.
+pub struct EmbeddedVectorStore {
+    dir: PathBuf,
+    params: HnswParams,
+    collections: RwLock<HashMap<String, Collection>>,
+}
+
+impl EmbeddedVectorStore {
+    /// Open the store in `dir`, loading every collection saved there
+    pub fn open(dir: &Path, params: HnswParams) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+
+        let mut collections = HashMap::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !entry.file_type()?.is_dir() || validate_name(&name).is_err() {
+                continue;
+            }
+            match Self::load_collection(&entry.path()) {
+                Ok(Some(index)) => {
+                    collections.insert(name, Collection { index, logged: 0 });
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("Skipping unreadable vector collection '{}': {}", name, e),
+            }
+        }
+        log::info!("Opened embedded vector index with {} collections", collections.len());
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            params,
+            collections: RwLock::new(collections),
+        })
+    }
+
+    /// Create a collection; an existing one is kept unless its dimension or metric
+    /// changed, in which case its vectors are useless and it starts over empty
+    pub async fn create_collection(&self, schema: &CollectionSchema) -> Result<()> {
+        validate_name(&schema.name)?;
+        let metric = Metric::from_name(&schema.distance_metric)?;
+        let mut collections = self.collections.write().await;
+
+        if let Some(existing) = collections.get(&schema.name) {
+            if existing.index.dimension() == schema.vector_size && existing.index.metric() == metric {
+                return Ok(());
+            }
+            log::warn!(
+                "Rebuilding vector collection '{}' for {} dimensions (was {})",
+                schema.name,
+                schema.vector_size,
+                existing.index.dimension()
+            );
+        }
+
+        let mut collection = Collection { index: HnswIndex::new(schema.vector_size, metric, self.params), logged: 0 };
+        checkpoint(&mut collection, &self.collection_dir(&schema.name))?;
+        collections.insert(schema.name.clone(), collection);
+        log::info!("Created embedded vector collection: {}", schema.name);
+        Ok(())
+    }
+
+    pub async fn delete_collection(&self, collection_name: &str) -> Result<()> {
+        validate_name(collection_name)?;
+        self.collections.write().await.remove(collection_name);
+        let dir = self.collection_dir(collection_name);
+        if dir.exists() {
+            std::fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+
+    pub async fn list_collections(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.collections.read().await.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Number of live points in a collection
+    pub async fn point_count(&self, collection_name: &str) -> Result<usize> {
+        let collections = self.collections.read().await;
+        Ok(Self::collection(&collections, collection_name)?.len())
+    }
+
+    /// Insert or update points
+    pub async fn upsert_points(&self, collection_name: &str, points: Vec<VectorPoint>) -> Result<()> {
+        let mut collections = self.collections.write().await;
+        let collection = Self::collection_entry(&mut collections, collection_name)?;
+        // Checked up front so the log only holds changes that apply cleanly
+        let dimension = collection.index.dimension();
+        if let Some(point) = points.iter().find(|point| point.vector.len() != dimension) {
+            return Err(anyhow!(
+                "Vector has {} dimensions, collection expects {}",
+                point.vector.len(),
+                dimension
+            ));
+        }
+        self.record(collection, collection_name, LogEntry::Upsert { points })
+    }
+
+    /// Delete points; ids not in the collection are ignored
+    pub async fn delete_points(&self, collection_name: &str, point_ids: &[Uuid]) -> Result<()> {
+        let mut collections = self.collections.write().await;
+        let collection = Self::collection_entry(&mut collections, collection_name)?;
+        let ids: Vec<Uuid> = point_ids.iter().copied().filter(|&id| collection.index.contains(id)).collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        self.record(collection, collection_name, LogEntry::Delete { ids })
+    }
+
+    /// Id and payload of every point in a collection
+    pub async fn payloads(&self, collection_name: &str) -> Result<Vec<(Uuid, HashMap<String, serde_json::Value>)>> {
+        let collections = self.collections.read().await;
+        Ok(Self::collection(&collections, collection_name)?
+            .payloads()
+            .map(|(id, payload)| (id, payload.clone()))
+            .collect())
+    }
+
+    pub async fn get_point(&self, collection_name: &str, point_id: Uuid) -> Result<Option<VectorPoint>> {
+        let collections = self.collections.read().await;
+        Ok(Self::collection(&collections, collection_name)?.get(point_id))
+    }
+
+    /// Search a collection, applying the query's filter to point payloads
+    pub async fn search(&self, query: &SearchQuery) -> Result<Vec<VectorSearchResult>> {
+        let collections = self.collections.read().await;
+        Self::collection(&collections, &query.collection)?.search(
+            &query.vector,
+            query.limit,
+            query.threshold,
+            query.filter.as_ref(),
+        )
+    }
+
+    /// Save a copy of a collection into `dir`, for backups
+    pub async fn export_collection(&self, collection_name: &str, dir: &Path) -> Result<()> {
+        let mut collections = self.collections.write().await;
+        Self::collection_entry(&mut collections, collection_name)?.index.save(dir)
+    }
+
+    /// Replace a collection, or create it, from a copy saved by `export_collection`
+    pub async fn import_collection(&self, collection_name: &str, dir: &Path) -> Result<()> {
+        validate_name(collection_name)?;
+        let index = HnswIndex::load(dir)?
+            .ok_or_else(|| anyhow!("No vector collection saved in {}", dir.display()))?;
+        let mut collection = Collection { index, logged: 0 };
+        let mut collections = self.collections.write().await;
+        checkpoint(&mut collection, &self.collection_dir(collection_name))?;
+        collections.insert(collection_name.to_string(), collection);
+        log::info!("Imported embedded vector collection: {}", collection_name);
+        Ok(())
+    }
+
+    /// Rewrite every collection saved under `dir` with the active cipher, folding in
+    /// its update log; returns how many collections were rewritten
+    pub fn reseal(dir: &Path) -> Result<usize> {
+        if !dir.is_dir() {
+            return Ok(0);
+        }
+        let mut resealed = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if let Some(index) = Self::load_collection(&path)? {
+                checkpoint(&mut Collection { index, logged: 0 }, &path)?;
+                resealed += 1;
+            }
+        }
+        Ok(resealed)
+    }
+
+    /// Load a saved collection and replay its update log, saving the result so the
+    /// log starts over empty
+    fn load_collection(dir: &Path) -> Result<Option<HnswIndex>> {
+        let Some(index) = HnswIndex::load(dir)? else { return Ok(None) };
+        let mut collection = Collection { index, logged: 0 };
+
+        let log_path = dir.join(LOG_FILE);
+        if log_path.exists() {
+            for entry in read_log(&log_path)? {
+                entry.apply(&mut collection.index)?;
+            }
+            checkpoint(&mut collection, dir)?;
+        }
+        Ok(Some(collection.index))
+    }
+
+    /// Log a change, apply it, and save the collection in full once enough is logged
+    fn record(&self, collection: &mut Collection, collection_name: &str, entry: LogEntry) -> Result<()> {
+        let dir = self.collection_dir(collection_name);
+        append_log(&dir.join(LOG_FILE), &entry)?;
+        collection.logged += entry.len();
+        entry.apply(&mut collection.index)?;
+
+        if collection.logged >= CHECKPOINT_POINTS {
+            checkpoint(collection, &dir)?;
+        }
+        Ok(())
+    }
+
+    fn collection_dir(&self, collection_name: &str) -> PathBuf {
+        self.dir.join(collection_name)
+    }
+
+    fn collection<'a>(collections: &'a HashMap<String, Collection>, name: &str) -> Result<&'a HnswIndex> {
+        collections.get(name).map(|collection| &collection.index).ok_or_else(|| anyhow!("Collection not found: {}", name))
+    }
+
+    fn collection_entry<'a>(collections: &'a mut HashMap<String, Collection>, name: &str) -> Result<&'a mut Collection> {
+        collections.get_mut(name).ok_or_else(|| anyhow!("Collection not found: {}", name))
+    }
+}
+
+/// Save a collection in full under `dir` and drop its now redundant update log
+fn checkpoint(collection: &mut Collection, dir: &Path) -> Result<()> {
+    collection.index.save(dir)?;
+    let log_path = dir.join(LOG_FILE);
+    if log_path.exists() {
+        std::fs::remove_file(log_path)?;
+    }
+    collection.logged = 0;
+    Ok(())
+}
+
+fn append_log(path: &Path, entry: &LogEntry) -> Result<()> {
+    let record = seal_bytes(serde_json::to_vec(entry)?)?;
+    let mut bytes = Vec::with_capacity(4 + record.len());
+    bytes.extend_from_slice(&(record.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&record);
+
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&bytes)?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Every complete record in the log; a record cut short by a crash is dropped
+fn read_log(path: &Path) -> Result<Vec<LogEntry>> {
+    let bytes = std::fs::read(path)?;
+    let mut entries = Vec::new();
+    let mut rest = bytes.as_slice();
+    while rest.len() >= 4 {
+        let len = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let Some(record) = rest.get(4..4 + len) else { break };
+        entries.push(serde_json::from_slice(&open_bytes(record.to_vec())?)?);
+        rest = &rest[4 + len..];
+    }
+    if !rest.is_empty() {
+        log::warn!("Dropping a partly written update at the end of {}", path.display());
+    }
+    Ok(entries)
+}
+
+/// Collection names become directory names, so keep them to a safe alphabet
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid collection name: {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_collections_persist_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut schema = CollectionSchema::memory_collection();
+        schema.vector_size = 3;
+        let id = Uuid::new_v4();
+
+        {
+            let store = EmbeddedVectorStore::open(dir.path(), HnswParams::default()).unwrap();
+            store.create_collection(&schema).await.unwrap();
+            let payload = HashMap::from([("agent_id".to_string(), serde_json::json!("a"))]);
+            store
+                .upsert_points(&schema.name, vec![
+                    VectorPoint { id, vector: vec![1.0, 0.0, 0.0], payload },
+                    VectorPoint { id: Uuid::new_v4(), vector: vec![0.0, 1.0, 0.0], payload: HashMap::new() },
+                ])
+                .await
+                .unwrap();
+            assert!(store.create_collection(&CollectionSchema { name: "../x".to_string(), ..schema.clone() }).await.is_err());
+        }
+
+        let store = EmbeddedVectorStore::open(dir.path(), HnswParams::default()).unwrap();
+        assert_eq!(store.list_collections().await, vec![schema.name.clone()]);
+        // Re-creating with the same shape keeps the points
+        store.create_collection(&schema).await.unwrap();
+        assert_eq!(store.point_count(&schema.name).await.unwrap(), 2);
+
+        let query = SearchQuery::new(vec![0.9, 0.1, 0.0], schema.name.clone()).with_limit(1);
+        let results = store.search(&query).await.unwrap();
+        assert_eq!(results[0].id, id);
+        assert_eq!(results[0].payload["agent_id"], "a");
+
+        store.delete_points(&schema.name, &[id]).await.unwrap();
+        assert!(store.get_point(&schema.name, id).await.unwrap().is_none());
+
+        // An exported copy brings back a deleted collection
+        let backup = tempfile::tempdir().unwrap();
+        store.export_collection(&schema.name, backup.path()).await.unwrap();
+        store.delete_collection(&schema.name).await.unwrap();
+        store.import_collection(&schema.name, backup.path()).await.unwrap();
+        assert_eq!(store.point_count(&schema.name).await.unwrap(), 1);
+
+        // A new embedding size starts the collection over
+        schema.vector_size = 4;
+        store.create_collection(&schema).await.unwrap();
+        assert_eq!(store.point_count(&schema.name).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_logged_until_saved_in_full() {
+        let _lock = crate::storage::lock_global_storage();
+        let dir = tempfile::tempdir().unwrap();
+        let mut schema = CollectionSchema::memory_collection();
+        schema.vector_size = 2;
+        let log_path = dir.path().join(&schema.name).join(LOG_FILE);
+        let id = Uuid::new_v4();
+
+        {
+            let store = EmbeddedVectorStore::open(dir.path(), HnswParams::default()).unwrap();
+            store.create_collection(&schema).await.unwrap();
+            store
+                .upsert_points(&schema.name, vec![VectorPoint { id, vector: vec![1.0, 0.0], payload: HashMap::new() }])
+                .await
+                .unwrap();
+            assert!(log_path.exists());
+            assert!(store.upsert_points(&schema.name, vec![VectorPoint { id, vector: vec![1.0], payload: HashMap::new() }]).await.is_err());
+
+            // A crash in the middle of an append leaves a partial record behind
+            let mut log = std::fs::OpenOptions::new().append(true).open(&log_path).unwrap();
+            log.write_all(&[9, 0, 0, 0, 1]).unwrap();
+        }
+
+        // Opening replays the log into the saved index and starts a new log
+        let store = EmbeddedVectorStore::open(dir.path(), HnswParams::default()).unwrap();
+        assert_eq!(store.point_count(&schema.name).await.unwrap(), 1);
+        assert!(!log_path.exists());
+
+        let points: Vec<VectorPoint> = (0..CHECKPOINT_POINTS)
+            .map(|i| VectorPoint { id: Uuid::new_v4(), vector: vec![i as f32, 1.0], payload: HashMap::new() })
+            .collect();
+        store.upsert_points(&schema.name, points).await.unwrap();
+        assert!(!log_path.exists());
+        assert_eq!(HnswIndex::load(&dir.path().join(&schema.name)).unwrap().unwrap().len(), CHECKPOINT_POINTS + 1);
+    }
+}
@@ -0,0 +1,397 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::storage::encryption::{open_bytes, seal_bytes};
+
+/// File name of the cache database inside the cache directory
+const CACHE_DB_FILE: &str = "embeddings.sqlite";
+
+/// Fraction of the size limit the cache is trimmed down to when it overflows
+const EVICTION_TARGET_RATIO: f64 = 0.9;
+
+/// Hit/miss counters for the embedding cache
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: u64,
+    pub size_bytes: u64,
+}
+
+/// Persistent content-addressed cache of embeddings.
+///
+/// Entries are keyed by blake3 of (model id, normalized text), so the same chunk is
+/// only embedded once per model. Entries from other models are purged on open.
+/// Vectors are sealed when encryption is on; ones that can't be opened count as misses.
+pub struct EmbeddingCache {
+    conn: Mutex<Connection>,
+    path: PathBuf,
+    model_id: String,
+    max_bytes: u64,
+    total_bytes: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// Collapse whitespace so formatting-only edits map to the same cache entry
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Cache key for `text` embedded by `model_id`
+pub fn cache_key(model_id: &str, text: &str) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(model_id.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(normalize_text(text).as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+pub(crate) fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub(crate) fn decode_vector(bytes: &[u8]) -> Option<Vec<f32>> {
+    if bytes.len() % 4 != 0 {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect(),
+    )
+}
+
+impl EmbeddingCache {
+    /// Open (or create) the cache in `dir` for `model_id`, capped at `max_bytes`
+    pub fn open(dir: &Path, model_id: &str, max_bytes: u64) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(CACHE_DB_FILE);
+
+        let conn = Connection::open(&path)
+            .map_err(|e| anyhow!("Failed to open embedding cache {}: {}", path.display(), e))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             CREATE TABLE IF NOT EXISTS embeddings (
+                 key TEXT PRIMARY KEY,
+                 model_id TEXT NOT NULL,
+                 dimension INTEGER NOT NULL,
+                 vector BLOB NOT NULL,
+                 size INTEGER NOT NULL,
+                 last_access INTEGER NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_embeddings_last_access ON embeddings(last_access);
+             CREATE INDEX IF NOT EXISTS idx_embeddings_model ON embeddings(model_id);",
+        )?;
+
+        // The embedding model changed: entries from other models can never hit again
+        let purged = conn.execute("DELETE FROM embeddings WHERE model_id != ?1", params![model_id])?;
+        if purged > 0 {
+            log::info!("Embedding model changed to {}; purged {} cached embeddings", model_id, purged);
+        }
+
+        let total_bytes: i64 = conn.query_row("SELECT COALESCE(SUM(size), 0) FROM embeddings", [], |row| row.get(0))?;
+
+        let cache = Self {
+            conn: Mutex::new(conn),
+            path,
+            model_id: model_id.to_string(),
+            max_bytes,
+            total_bytes: AtomicU64::new(total_bytes as u64),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        };
+
+        // The limit may have been lowered since the last run
+        cache.evict_if_needed()?;
+        Ok(cache)
+    }
+
+    /// Model id this cache serves
+    pub fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    /// Path of the cache database
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Look up a single embedding
+    pub fn get(&self, text: &str) -> Result<Option<Vec<f32>>> {
+        Ok(self.get_many(&[text.to_string()])?.pop().flatten())
+    }
+
+    /// Look up several embeddings; misses are `None`, in input order
+    pub fn get_many(&self, texts: &[String]) -> Result<Vec<Option<Vec<f32>>>> {
+        let mut conn = self.lock()?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let tx = conn.transaction()?;
+        let mut results = Vec::with_capacity(texts.len());
+
+        {
+            let mut select = tx.prepare_cached("SELECT vector FROM embeddings WHERE key = ?1 AND model_id = ?2")?;
+            let mut touch = tx.prepare_cached("UPDATE embeddings SET last_access = ?1 WHERE key = ?2")?;
+
+            for text in texts {
+                let key = cache_key(&self.model_id, text);
+                let blob: Option<Vec<u8>> = select
+                    .query_row(params![key, self.model_id], |row| row.get(0))
+                    .optional()?;
+
+                match blob.and_then(|bytes| open_bytes(bytes).ok()).and_then(|bytes| decode_vector(&bytes)) {
+                    Some(vector) => {
+                        touch.execute(params![now, key])?;
+                        self.hits.fetch_add(1, Ordering::Relaxed);
+                        results.push(Some(vector));
+                    }
+                    None => {
+                        self.misses.fetch_add(1, Ordering::Relaxed);
+                        results.push(None);
+                    }
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(results)
+    }
+
+    /// Store a single embedding
+    pub fn put(&self, text: &str, embedding: &[f32]) -> Result<()> {
+        self.put_many(&[(text.to_string(), embedding.to_vec())])
+    }
+
+    /// Store several embeddings in one transaction, evicting old entries if over the limit
+    pub fn put_many(&self, entries: &[(String, Vec<f32>)]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        {
+            let mut conn = self.lock()?;
+            let now = chrono::Utc::now().timestamp_millis();
+            let tx = conn.transaction()?;
+
+            {
+                let mut existing = tx.prepare_cached("SELECT size FROM embeddings WHERE key = ?1")?;
+                let mut insert = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO embeddings (key, model_id, dimension, vector, size, last_access)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )?;
+
+                for (text, embedding) in entries {
+                    let key = cache_key(&self.model_id, text);
+                    let blob = seal_bytes(encode_vector(embedding))?;
+                    let size = (blob.len() + key.len()) as u64;
+
+                    let previous: Option<i64> = existing.query_row(params![key], |row| row.get(0)).optional()?;
+                    insert.execute(params![key, self.model_id, embedding.len() as i64, blob, size as i64, now])?;
+
+                    if let Some(previous) = previous {
+                        self.total_bytes.fetch_sub(previous as u64, Ordering::Relaxed);
+                    }
+                    self.total_bytes.fetch_add(size, Ordering::Relaxed);
+                }
+            }
+
+            tx.commit()?;
+        }
+
+        self.evict_if_needed()?;
+        Ok(())
+    }
+
+    /// Rewrite the cache in `dir` with the active cipher, dropping entries it can't open
+    pub fn reseal(dir: &Path) -> Result<usize> {
+        let path = dir.join(CACHE_DB_FILE);
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let mut conn = Connection::open(&path)
+            .map_err(|e| anyhow!("Failed to open embedding cache {}: {}", path.display(), e))?;
+        let tx = conn.transaction()?;
+        let rows: Vec<(String, Vec<u8>)> = tx
+            .prepare("SELECT key, vector FROM embeddings")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut resealed = 0;
+        for (key, blob) in rows {
+            match open_bytes(blob) {
+                Ok(vector) => {
+                    let blob = seal_bytes(vector)?;
+                    let size = (blob.len() + key.len()) as i64;
+                    tx.execute("UPDATE embeddings SET vector = ?2, size = ?3 WHERE key = ?1", params![key, blob, size])?;
+                    resealed += 1;
+                }
+                Err(_) => {
+                    tx.execute("DELETE FROM embeddings WHERE key = ?1", params![key])?;
+                }
+            }
+        }
+        tx.commit()?;
+
+        // Don't leave the old vectors behind in free pages or the WAL
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        Ok(resealed)
+    }
+
+    /// Remove every cached embedding
+    pub fn clear(&self) -> Result<()> {
+        let conn = self.lock()?;
+        conn.execute("DELETE FROM embeddings", [])?;
+        self.total_bytes.store(0, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Number of entries not used since `before`
+    pub fn count_unused_since(&self, before: chrono::DateTime<chrono::Utc>) -> Result<usize> {
+        let conn = self.lock()?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM embeddings WHERE last_access < ?1",
+            params![before.timestamp_millis()],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Remove entries not used since `before`, returning how many went
+    pub fn remove_unused_since(&self, before: chrono::DateTime<chrono::Utc>) -> Result<usize> {
+        let conn = self.lock()?;
+        let freed: i64 = conn.query_row(
+            "SELECT COALESCE(SUM(size), 0) FROM embeddings WHERE last_access < ?1",
+            params![before.timestamp_millis()],
+            |row| row.get(0),
+        )?;
+        let removed = conn.execute("DELETE FROM embeddings WHERE last_access < ?1", params![before.timestamp_millis()])?;
+        self.total_bytes.fetch_sub((freed as u64).min(self.total_bytes.load(Ordering::Relaxed)), Ordering::Relaxed);
+        Ok(removed)
+    }
+
+    /// Current cache statistics
+    pub fn stats(&self) -> Result<EmbeddingCacheStats> {
+        let conn = self.lock()?;
+        let entries: i64 = conn.query_row("SELECT COUNT(*) FROM embeddings", [], |row| row.get(0))?;
+
+        Ok(EmbeddingCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: entries as u64,
+            size_bytes: self.total_bytes.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Drop least recently used entries until the cache is back under its limit
+    fn evict_if_needed(&self) -> Result<usize> {
+        if self.total_bytes.load(Ordering::Relaxed) <= self.max_bytes {
+            return Ok(0);
+        }
+
+        let target = (self.max_bytes as f64 * EVICTION_TARGET_RATIO) as u64;
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        let mut evicted = 0;
+
+        {
+            let mut oldest = tx.prepare("SELECT key, size FROM embeddings ORDER BY last_access ASC")?;
+            let mut delete = tx.prepare_cached("DELETE FROM embeddings WHERE key = ?1")?;
+            let mut rows = oldest.query([])?;
+
+            while self.total_bytes.load(Ordering::Relaxed) > target {
+                let Some(row) = rows.next()? else { break };
+                let key: String = row.get(0)?;
+                let size: i64 = row.get(1)?;
+
+                delete.execute(params![key])?;
+                self.total_bytes.fetch_sub(size as u64, Ordering::Relaxed);
+                evicted += 1;
+            }
+        }
+
+        tx.commit()?;
+        self.evictions.fetch_add(evicted as u64, Ordering::Relaxed);
+        log::debug!("Evicted {} embeddings from cache", evicted);
+        Ok(evicted)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|_| anyhow!("Embedding cache lock poisoned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_and_keys() {
+        assert_eq!(normalize_text("  hello \n\t world "), "hello world");
+        assert_eq!(cache_key("m", "hello world"), cache_key("m", "hello   world\n"));
+        assert_ne!(cache_key("m1", "hello"), cache_key("m2", "hello"));
+    }
+
+    #[test]
+    fn test_roundtrip_and_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = EmbeddingCache::open(dir.path(), "ollama:nomic-embed-text", 1 << 20).unwrap();
+
+        assert!(cache.get("some text").unwrap().is_none());
+        cache.put("some text", &[0.25, -0.5, 1.0]).unwrap();
+        assert_eq!(cache.get("some  text").unwrap(), Some(vec![0.25, -0.5, 1.0]));
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[test]
+    fn test_persists_and_invalidates_on_model_change() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let cache = EmbeddingCache::open(dir.path(), "model-a", 1 << 20).unwrap();
+            cache.put("text", &[1.0, 2.0]).unwrap();
+        }
+
+        let reopened = EmbeddingCache::open(dir.path(), "model-a", 1 << 20).unwrap();
+        assert_eq!(reopened.get("text").unwrap(), Some(vec![1.0, 2.0]));
+        drop(reopened);
+
+        let other = EmbeddingCache::open(dir.path(), "model-b", 1 << 20).unwrap();
+        assert!(other.get("text").unwrap().is_none());
+        assert_eq!(other.stats().unwrap().entries, 0);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let dir = tempfile::tempdir().unwrap();
+        // Each entry is 64 bytes of key + 400 bytes of vector
+        let cache = EmbeddingCache::open(dir.path(), "model", 1500).unwrap();
+        let vector = vec![0.5f32; 100];
+
+        cache.put("first", &vector).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        cache.put("second", &vector).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        cache.put("third", &vector).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        cache.put("fourth", &vector).unwrap();
+
+        let stats = cache.stats().unwrap();
+        assert!(stats.size_bytes <= 1500);
+        assert!(stats.evictions >= 1);
+        assert!(cache.get("first").unwrap().is_none());
+        assert!(cache.get("fourth").unwrap().is_some());
+    }
+}
@@ -1,5 +1,12 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use super::embedding_cache::EmbeddingCache;
+use super::ollama_embeddings::{OllamaEmbeddingProvider, DEFAULT_OLLAMA_URL};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingModel {
@@ -14,6 +21,7 @@ pub enum EmbeddingModelType {
     SentenceTransformers,
     OpenAI,
     Local,
+    Ollama,
 }
 
 #[derive(Debug, Clone)]
@@ -23,16 +31,358 @@ pub struct EmbeddingResult {
     pub processing_time_ms: u64,
 }
 
-/// Engine for generating embeddings locally
+/// Backend that turns text into vectors
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Identifier of the backend and model, e.g. `ollama:nomic-embed-text`
+    fn name(&self) -> String;
+
+    /// Embed a batch of texts, returning one vector per input in order
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Dimension of the produced vectors, discovered from the backend if needed
+    async fn dimension(&self) -> Result<usize>;
+
+    /// Whether the backend can currently serve requests
+    async fn is_ready(&self) -> bool;
+
+    /// Score each document against the query with a cross-encoder, if the backend has one
+    async fn rerank(&self, _query: &str, _documents: &[String]) -> Result<Option<Vec<f32>>> {
+        Ok(None)
+    }
+}
+
+/// Engine for generating embeddings
 pub struct EmbeddingEngine {
     model: EmbeddingModel,
-    // In a real implementation, this would contain the actual model
-    // For now, we'll simulate embeddings
+    /// Real embedding backend; `None` falls back to deterministic hash vectors
+    provider: Option<Arc<dyn EmbeddingProvider>>,
+    batch_size: usize,
+    /// On-disk cache of previously computed embeddings
+    cache: Option<Arc<EmbeddingCache>>,
+}
+
+/// Default number of texts sent to a provider per request
+pub const DEFAULT_EMBEDDING_BATCH_SIZE: usize = 32;
+
+/// Known Ollama embedding models: (name, dimension, max sequence length)
+const OLLAMA_EMBEDDING_MODELS: &[(&str, usize, usize)] = &[
+    ("nomic-embed-text", 768, 8192),
+    ("mxbai-embed-large", 1024, 512),
+    ("all-minilm", 384, 256),
+];
+
+/// Known local sentence-transformer models: (name, dimension, max sequence length)
+const LOCAL_EMBEDDING_MODELS: &[(&str, usize, usize)] = &[
+    ("all-MiniLM-L6-v2", 384, 256),
+    ("all-MiniLM-L12-v2", 384, 256),
+    ("bge-small-en-v1.5", 384, 512),
+    ("bge-base-en-v1.5", 768, 512),
+    ("all-mpnet-base-v2", 768, 384),
+];
+
+/// Describe a model loaded from the models dir; unknown models get their dimension from the backend
+fn local_model_spec(model_name: &str, model_type: EmbeddingModelType) -> EmbeddingModel {
+    let base = model_name.rsplit('/').next().unwrap_or(model_name);
+    let (dimension, max_sequence_length) = LOCAL_EMBEDDING_MODELS
+        .iter()
+        .find(|(known, _, _)| known.eq_ignore_ascii_case(base))
+        .map(|(_, dimension, max_len)| (*dimension, *max_len))
+        .unwrap_or((0, 512));
+
+    EmbeddingModel {
+        name: model_name.to_string(),
+        dimension,
+        max_sequence_length,
+        model_type,
+    }
+}
+
+/// Find the directory holding a local copy of `model_name` (must contain `tokenizer.json`)
+pub fn local_model_dir(models_dir: &Path, model_name: &str) -> Option<PathBuf> {
+    let base = model_name.rsplit('/').next().unwrap_or(model_name);
+    [
+        models_dir.join(model_name),
+        models_dir.join(base),
+        models_dir.join("embeddings").join(base),
+    ]
+    .into_iter()
+    .find(|dir| dir.join("tokenizer.json").is_file())
+}
+
+/// Mean-pool token embeddings (`[batch, seq, dim]`, row-major) over the attention mask
+pub(crate) fn mean_pool(hidden: &[f32], mask: &[i64], batch: usize, seq_len: usize, dim: usize) -> Vec<Vec<f32>> {
+    (0..batch)
+        .map(|b| {
+            let mut pooled = vec![0.0f32; dim];
+            let mut count = 0.0f32;
+            for t in 0..seq_len {
+                if mask[b * seq_len + t] == 0 {
+                    continue;
+                }
+                let offset = (b * seq_len + t) * dim;
+                for (value, h) in pooled.iter_mut().zip(&hidden[offset..offset + dim]) {
+                    *value += h;
+                }
+                count += 1.0;
+            }
+            if count > 0.0 {
+                pooled.iter_mut().for_each(|v| *v /= count);
+            }
+            pooled
+        })
+        .collect()
+}
+
+/// Load a tokenizer truncating to the model's limit, optionally padding batches to the longest input
+#[cfg(any(feature = "onnx", feature = "basic-ai"))]
+pub(crate) fn load_tokenizer(path: &Path, max_sequence_length: usize, pad: bool) -> Result<tokenizers::Tokenizer> {
+    use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+    let mut tokenizer = Tokenizer::from_file(path)
+        .map_err(|e| anyhow!("Failed to load tokenizer {}: {}", path.display(), e))?;
+
+    tokenizer
+        .with_truncation(Some(TruncationParams {
+            max_length: max_sequence_length,
+            ..Default::default()
+        }))
+        .map_err(|e| anyhow!("Failed to configure tokenizer truncation: {}", e))?;
+
+    if pad {
+        tokenizer.with_padding(Some(PaddingParams {
+            strategy: PaddingStrategy::BatchLongest,
+            ..Default::default()
+        }));
+    } else {
+        tokenizer.with_padding(None);
+    }
+
+    Ok(tokenizer)
+}
+
+/// Resolve an Ollama model name (`ollama:` prefix and `:tag` suffix are optional)
+fn ollama_model_spec(model_name: &str) -> Option<EmbeddingModel> {
+    let explicit = model_name.strip_prefix("ollama:");
+    let name = explicit.unwrap_or(model_name);
+    let base = name.split(':').next().unwrap_or(name);
+
+    let known = OLLAMA_EMBEDDING_MODELS
+        .iter()
+        .find(|(known, _, _)| base == *known || base.starts_with(&format!("{}-", known)));
+
+    match (known, explicit) {
+        (Some((_, dimension, max_len)), _) => Some(EmbeddingModel {
+            name: name.to_string(),
+            dimension: *dimension,
+            max_sequence_length: *max_len,
+            model_type: EmbeddingModelType::Ollama,
+        }),
+        // Unknown model requested explicitly: dimension is discovered on first use
+        (None, Some(_)) => Some(EmbeddingModel {
+            name: name.to_string(),
+            dimension: 0,
+            max_sequence_length: 512,
+            model_type: EmbeddingModelType::Ollama,
+        }),
+        (None, None) => None,
+    }
 }
 
 impl EmbeddingEngine {
     /// Create a new embedding engine with specified model
     pub async fn new(model_name: &str) -> Result<Self> {
+        Self::with_endpoint(model_name, DEFAULT_OLLAMA_URL).await
+    }
+
+    /// Create an engine from the app config and check the model's real output
+    /// dimension against `vector.embedding_dimension`
+    pub async fn from_config(app_config: &AppConfig) -> Result<Self> {
+        let config = &app_config.vector;
+        let mut engine = Self::for_backend(
+            &config.embedding_backend,
+            &config.embedding_model,
+            &config.embedding_endpoint,
+            &config.ollama_embedding_model,
+            &app_config.models_dir_path(),
+        ).await?;
+        engine.batch_size = config.embedding_batch_size.max(1);
+
+        if engine.provider.is_some() {
+            if engine.is_ready().await {
+                engine.verify_dimension(config.embedding_dimension).await?;
+            } else {
+                log::warn!(
+                    "Embedding backend for '{}' is not reachable; dimension check deferred",
+                    engine.model.name
+                );
+            }
+        } else if engine.model.dimension != config.embedding_dimension {
+            return Err(anyhow!(
+                "Embedding model '{}' produces {}-dimensional vectors but vector.embedding_dimension is {}",
+                engine.model.name, engine.model.dimension, config.embedding_dimension
+            ));
+        }
+
+        // Hash vectors are cheaper to recompute than to look up
+        if config.embedding_cache_enabled && engine.provider.is_some() {
+            let cache_dir = app_config.cache_dir_path().join("embeddings");
+            let max_bytes = config.embedding_cache_max_mb * 1024 * 1024;
+            match EmbeddingCache::open(&cache_dir, &engine.model_id(), max_bytes) {
+                Ok(cache) => engine.cache = Some(Arc::new(cache)),
+                Err(e) => log::warn!("Embedding cache disabled: {}", e),
+            }
+        }
+
+        Ok(engine)
+    }
+
+    /// Pick the embedding backend: `ollama`, `onnx`, `candle`, `hash`, or `auto` to detect
+    /// from the model name and the files present in the models dir, then try
+    /// `ollama_model` before settling for hash embeddings
+    pub async fn for_backend(
+        backend: &str,
+        model_name: &str,
+        endpoint: &str,
+        ollama_model: &str,
+        models_dir: &Path,
+    ) -> Result<Self> {
+        match backend {
+            "ollama" => {
+                let model = ollama_model_spec(model_name)
+                    .or_else(|| ollama_model_spec(&format!("ollama:{}", model_name)))
+                    .ok_or_else(|| anyhow!("Invalid Ollama embedding model '{}'", model_name))?;
+                let provider = Arc::new(OllamaEmbeddingProvider::new(endpoint, &model.name));
+                Ok(Self::with_provider(model, provider))
+            }
+            "onnx" => {
+                let model_dir = local_model_dir(models_dir, model_name).ok_or_else(|| anyhow!(
+                    "Embedding model '{}' not found in {}", model_name, models_dir.display()
+                ))?;
+                Self::load_onnx(model_name, &model_dir).await
+            }
+            "candle" => {
+                let model_dir = local_model_dir(models_dir, model_name).ok_or_else(|| anyhow!(
+                    "Embedding model '{}' not found in {}", model_name, models_dir.display()
+                ))?;
+                Self::load_candle(model_name, &model_dir).await
+            }
+            "hash" => Ok(Self::hashed(model_name)),
+            _ => {
+                if ollama_model_spec(model_name).is_some() {
+                    return Self::with_endpoint(model_name, endpoint).await;
+                }
+
+                if let Some(model_dir) = local_model_dir(models_dir, model_name) {
+                    #[cfg(feature = "onnx")]
+                    if super::onnx_embeddings::OnnxEmbeddingProvider::find_model_file(&model_dir).is_some() {
+                        return Self::load_onnx(model_name, &model_dir).await;
+                    }
+
+                    #[cfg(feature = "basic-ai")]
+                    if super::candle_embeddings::CandleEmbeddingProvider::has_model_files(&model_dir) {
+                        return Self::load_candle(model_name, &model_dir).await;
+                    }
+
+                    log::info!("Found {} but no enabled backend can load it", model_dir.display());
+                }
+
+                let hashed = Self::hashed(model_name);
+                if let Some(engine) = Self::probe_ollama(ollama_model, endpoint, hashed.model.dimension).await {
+                    log::info!("Using Ollama embedding model '{}' for '{}'", ollama_model, model_name);
+                    return Ok(engine);
+                }
+
+                log::warn!(
+                    "No embedding backend available for '{}' (Ollama model '{}' at {} did not answer); \
+                     falling back to hash embeddings, which only match near-identical text",
+                    model_name,
+                    ollama_model,
+                    endpoint
+                );
+                Ok(hashed)
+            }
+        }
+    }
+
+    /// Ollama engine for `model_name` if it embeds a probe with `dimension` values, so
+    /// its vectors fit collections sized for the configured model
+    async fn probe_ollama(model_name: &str, endpoint: &str, dimension: usize) -> Option<Self> {
+        let model = ollama_model_spec(&format!("ollama:{}", model_name))?;
+        let provider = Arc::new(OllamaEmbeddingProvider::new(endpoint, &model.name));
+        match provider.dimension().await {
+            Ok(found) if found == dimension => Some(Self::with_provider(EmbeddingModel { dimension, ..model }, provider)),
+            Ok(found) => {
+                log::warn!(
+                    "Ollama embedding model '{}' produces {}-dimensional vectors, expected {}",
+                    model_name, found, dimension
+                );
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    /// Load a local ONNX model from `model_dir`
+    #[cfg(feature = "onnx")]
+    async fn load_onnx(model_name: &str, model_dir: &Path) -> Result<Self> {
+        let mut model = local_model_spec(model_name, EmbeddingModelType::SentenceTransformers);
+        let (name, dir, max_len) = (model.name.clone(), model_dir.to_path_buf(), model.max_sequence_length);
+
+        let provider = tokio::task::spawn_blocking(move || {
+            super::onnx_embeddings::OnnxEmbeddingProvider::load(&name, &dir, max_len)
+        })
+        .await
+        .map_err(|e| anyhow!("ONNX model loading task failed: {}", e))??;
+
+        model.dimension = provider.dimension().await?;
+        Ok(Self::with_provider(model, Arc::new(provider)))
+    }
+
+    #[cfg(not(feature = "onnx"))]
+    async fn load_onnx(model_name: &str, _model_dir: &Path) -> Result<Self> {
+        Err(anyhow!(
+            "Embedding model '{}' needs the ONNX backend; rebuild with the `onnx` feature",
+            model_name
+        ))
+    }
+
+    /// Load a local BERT model (safetensors + tokenizer.json) through candle
+    #[cfg(feature = "basic-ai")]
+    async fn load_candle(model_name: &str, model_dir: &Path) -> Result<Self> {
+        let mut model = local_model_spec(model_name, EmbeddingModelType::Local);
+        let (name, dir, max_len) = (model.name.clone(), model_dir.to_path_buf(), model.max_sequence_length);
+
+        let provider = tokio::task::spawn_blocking(move || {
+            super::candle_embeddings::CandleEmbeddingProvider::load(&name, &dir, max_len)
+        })
+        .await
+        .map_err(|e| anyhow!("Candle model loading task failed: {}", e))??;
+
+        model.dimension = provider.dimension().await?;
+        Ok(Self::with_provider(model, Arc::new(provider)))
+    }
+
+    #[cfg(not(feature = "basic-ai"))]
+    async fn load_candle(model_name: &str, _model_dir: &Path) -> Result<Self> {
+        Err(anyhow!(
+            "Embedding model '{}' needs the candle backend; rebuild with the `basic-ai` feature",
+            model_name
+        ))
+    }
+
+    /// Create an engine, using `endpoint` for Ollama-served models
+    pub async fn with_endpoint(model_name: &str, endpoint: &str) -> Result<Self> {
+        if let Some(model) = ollama_model_spec(model_name) {
+            let provider = Arc::new(OllamaEmbeddingProvider::new(endpoint, &model.name));
+            return Ok(Self::with_provider(model, provider));
+        }
+
+        Ok(Self::hashed(model_name))
+    }
+
+    /// Engine without a backend, producing deterministic hash-based vectors
+    pub fn hashed(model_name: &str) -> Self {
         let model = match model_name {
             "all-MiniLM-L6-v2" => EmbeddingModel {
                 name: "all-MiniLM-L6-v2".to_string(),
@@ -57,7 +407,37 @@ impl EmbeddingEngine {
             }
         };
 
-        Ok(Self { model })
+        Self { model, provider: None, batch_size: DEFAULT_EMBEDDING_BATCH_SIZE, cache: None }
+    }
+
+    /// Create an engine around an explicit provider
+    pub fn with_provider(model: EmbeddingModel, provider: Arc<dyn EmbeddingProvider>) -> Self {
+        Self { model, provider: Some(provider), batch_size: DEFAULT_EMBEDDING_BATCH_SIZE, cache: None }
+    }
+
+    /// Set how many texts are sent to the provider per request
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Serve repeated texts from a persistent cache
+    pub fn with_cache(mut self, cache: Arc<EmbeddingCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Identifier of the backend and model; changes whenever cached vectors become invalid
+    pub fn model_id(&self) -> String {
+        match &self.provider {
+            Some(provider) => provider.name(),
+            None => format!("hash:{}", self.model.name),
+        }
+    }
+
+    /// Get the embedding cache, if enabled
+    pub fn cache(&self) -> Option<Arc<EmbeddingCache>> {
+        self.cache.clone()
     }
 
     /// Generate embedding for a single text
@@ -67,42 +447,121 @@ impl EmbeddingEngine {
         // Truncate text if it's too long
         let truncated_text = self.truncate_text(text);
         
-        // In a real implementation, this would use an actual embedding model
-        // For now, we'll generate a deterministic "fake" embedding based on text content
-        let embedding = self.generate_deterministic_embedding(&truncated_text);
+        if let Some(cached) = self.cache_lookup(&[truncated_text.clone()]).pop().flatten() {
+            return Ok(cached);
+        }
+        
+        let embedding = match &self.provider {
+            Some(provider) => {
+                let mut embeddings = provider.embed_batch(&[truncated_text.clone()]).await?;
+                let mut embedding = embeddings.pop()
+                    .ok_or_else(|| anyhow!("Embedding backend returned no vector"))?;
+                self.check_dimension(&embedding)?;
+                self.normalize_vector(&mut embedding);
+                embedding
+            }
+            // Without a backend, fall back to a deterministic hash-based vector
+            None => self.generate_deterministic_embedding(&truncated_text),
+        };
+
+        self.cache_store(vec![(truncated_text, embedding.clone())]);
         
         let _processing_time = start_time.elapsed().as_millis() as u64;
         
         Ok(embedding)
     }
 
+    /// Cross-encoder scores from the backend; `None` without a backend or rerank support
+    pub async fn rerank(&self, query: &str, documents: &[String]) -> Result<Option<Vec<f32>>> {
+        match &self.provider {
+            Some(provider) => provider.rerank(query, documents).await,
+            None => Ok(None),
+        }
+    }
+
     /// Generate embeddings for multiple texts in batch
     pub async fn batch_generate_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
-        let mut embeddings = Vec::new();
-        
-        // In a real implementation, this would process in batches for efficiency
-        for text in texts {
-            let embedding = self.generate_embedding(&text).await?;
-            embeddings.push(embedding);
+        let provider = match &self.provider {
+            Some(provider) => provider,
+            None => {
+                return Ok(texts.iter()
+                    .map(|text| self.generate_deterministic_embedding(&self.truncate_text(text)))
+                    .collect());
+            }
+        };
+
+        let truncated: Vec<String> = texts.iter().map(|text| self.truncate_text(text)).collect();
+        let mut embeddings = self.cache_lookup(&truncated);
+
+        // Only texts missing from the cache go to the provider
+        let missing: Vec<usize> = (0..truncated.len()).filter(|&i| embeddings[i].is_none()).collect();
+
+        for batch in missing.chunks(self.batch_size) {
+            let batch_texts: Vec<String> = batch.iter().map(|&i| truncated[i].clone()).collect();
+            let mut computed = Vec::with_capacity(batch.len());
+
+            for (&index, mut embedding) in batch.iter().zip(provider.embed_batch(&batch_texts).await?) {
+                self.check_dimension(&embedding)?;
+                self.normalize_vector(&mut embedding);
+                computed.push((truncated[index].clone(), embedding.clone()));
+                embeddings[index] = Some(embedding);
+            }
+
+            self.cache_store(computed);
         }
         
-        Ok(embeddings)
+        embeddings.into_iter()
+            .map(|embedding| embedding.ok_or_else(|| anyhow!("Embedding backend returned too few vectors")))
+            .collect()
     }
 
     /// Get the embedding dimension
     pub async fn dimension(&self) -> Result<usize> {
-        Ok(self.model.dimension)
+        match &self.provider {
+            Some(provider) if self.model.dimension == 0 => provider.dimension().await,
+            _ => Ok(self.model.dimension),
+        }
+    }
+
+    /// Ask the backend for its real output dimension and compare it to `expected`
+    pub async fn verify_dimension(&mut self, expected: usize) -> Result<usize> {
+        let actual = match &self.provider {
+            Some(provider) => provider.dimension().await?,
+            None => self.model.dimension,
+        };
+
+        if actual != expected {
+            return Err(anyhow!(
+                "Embedding model '{}' produces {}-dimensional vectors but vector.embedding_dimension is {}; \
+                 update the config (existing collections must be re-indexed)",
+                self.model.name, actual, expected
+            ));
+        }
+
+        self.model.dimension = actual;
+        Ok(actual)
     }
 
     /// Get model information
     pub async fn model_info(&self) -> Result<String> {
-        Ok(format!("{} ({}D)", self.model.name, self.model.dimension))
+        let dimension = self.dimension().await.unwrap_or(self.model.dimension);
+        match &self.provider {
+            Some(provider) => Ok(format!("{} ({}D, {})", self.model.name, dimension, provider.name())),
+            None => Ok(format!("{} ({}D)", self.model.name, dimension)),
+        }
+    }
+
+    /// Get model description
+    pub fn model(&self) -> &EmbeddingModel {
+        &self.model
     }
 
     /// Check if the model is loaded and ready
     pub async fn is_ready(&self) -> bool {
-        // In a real implementation, this would check if the model is loaded
-        true
+        match &self.provider {
+            Some(provider) => provider.is_ready().await,
+            None => true,
+        }
     }
 
     /// Get maximum sequence length
@@ -112,6 +571,47 @@ impl EmbeddingEngine {
 
     // Private helper methods
 
+    /// Cache lookups never fail the request; errors count as misses
+    fn cache_lookup(&self, texts: &[String]) -> Vec<Option<Vec<f32>>> {
+        let Some(cache) = &self.cache else {
+            return vec![None; texts.len()];
+        };
+
+        match cache.get_many(texts) {
+            Ok(mut results) => {
+                // A stale entry with the wrong size is treated as a miss
+                for result in results.iter_mut() {
+                    if result.as_ref().map_or(false, |v| self.check_dimension(v).is_err()) {
+                        *result = None;
+                    }
+                }
+                results
+            }
+            Err(e) => {
+                log::warn!("Embedding cache lookup failed: {}", e);
+                vec![None; texts.len()]
+            }
+        }
+    }
+
+    fn cache_store(&self, entries: Vec<(String, Vec<f32>)>) {
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.put_many(&entries) {
+                log::warn!("Failed to write embedding cache: {}", e);
+            }
+        }
+    }
+
+    fn check_dimension(&self, embedding: &[f32]) -> Result<()> {
+        if self.model.dimension != 0 && embedding.len() != self.model.dimension {
+            return Err(anyhow!(
+                "Embedding model '{}' returned {} dimensions, expected {}",
+                self.model.name, embedding.len(), self.model.dimension
+            ));
+        }
+        Ok(())
+    }
+
     fn truncate_text(&self, text: &str) -> String {
         // Simple word-based truncation
         let words: Vec<&str> = text.split_whitespace().collect();
@@ -161,44 +661,6 @@ impl EmbeddingEngine {
     }
 }
 
-/// Real embedding engine that would use actual models
-#[allow(dead_code)]
-struct RealEmbeddingEngine {
-    // This would contain actual model implementations
-    // For example:
-    // - Candle-based local models
-    // - ONNX runtime models  
-    // - HTTP clients for remote APIs
-}
-
-#[allow(dead_code)]
-impl RealEmbeddingEngine {
-    /// This is how a real implementation might look
-    pub async fn _load_sentence_transformers_model(model_path: &str) -> Result<Self> {
-        // In a real implementation:
-        // 1. Load tokenizer from model path
-        // 2. Load ONNX model or PyTorch model
-        // 3. Initialize inference session
-        // 4. Warm up the model
-        
-        log::info!("Loading SentenceTransformers model from: {}", model_path);
-        
-        // Placeholder implementation
-        Ok(Self {})
-    }
-    
-    pub async fn _generate_real_embedding(&self, _text: &str) -> Result<Vec<f32>> {
-        // Real implementation would:
-        // 1. Tokenize the input text
-        // 2. Convert tokens to input tensors
-        // 3. Run inference through the model
-        // 4. Extract embeddings from output
-        // 5. Apply any post-processing (normalization, etc.)
-        
-        Ok(vec![]) // Placeholder
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,6 +728,9 @@ mod tests {
                 max_sequence_length: 256,
                 model_type: EmbeddingModelType::Local,
             },
+            provider: None,
+            batch_size: DEFAULT_EMBEDDING_BATCH_SIZE,
+            cache: None,
         };
         
         let mut vector = vec![3.0, 4.0, 0.0];
@@ -300,4 +765,130 @@ mod tests {
         let ready = engine.is_ready().await;
         assert!(ready);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ollama_model_resolution() {
+        let nomic = ollama_model_spec("nomic-embed-text:latest").unwrap();
+        assert_eq!(nomic.dimension, 768);
+        assert!(matches!(nomic.model_type, EmbeddingModelType::Ollama));
+
+        assert_eq!(ollama_model_spec("mxbai-embed-large").unwrap().dimension, 1024);
+        assert_eq!(ollama_model_spec("ollama:all-minilm").unwrap().dimension, 384);
+        assert_eq!(ollama_model_spec("ollama:custom-embedder").unwrap().dimension, 0);
+        assert!(ollama_model_spec("all-MiniLM-L6-v2").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ollama_backed_engine_batches() {
+        let url = crate::vector::ollama_embeddings::test_server::spawn(384, false).await;
+        let engine = EmbeddingEngine::with_endpoint("all-minilm", &url).await.unwrap()
+            .with_batch_size(2);
+
+        let texts = (0..5).map(|i| format!("text {}", i)).collect();
+        let embeddings = engine.batch_generate_embeddings(texts).await.unwrap();
+
+        assert_eq!(embeddings.len(), 5);
+        assert!(embeddings.iter().all(|e| e.len() == 384));
+        let magnitude: f32 = embeddings[0].iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((magnitude - 1.0).abs() < 1e-5);
+    }
+
+    #[tokio::test]
+    async fn test_dimension_mismatch_is_reported() {
+        let url = crate::vector::ollama_embeddings::test_server::spawn(768, false).await;
+        let mut engine = EmbeddingEngine::with_endpoint("ollama:custom-embedder", &url).await.unwrap();
+
+        assert_eq!(engine.dimension().await.unwrap(), 768);
+        let err = engine.verify_dimension(384).await.unwrap_err();
+        assert!(err.to_string().contains("768"));
+        assert_eq!(engine.verify_dimension(768).await.unwrap(), 768);
+    }
+
+    #[tokio::test]
+    async fn test_from_config_checks_dimension() {
+        let url = crate::vector::ollama_embeddings::test_server::spawn(768, false).await;
+        let mut config = crate::config::AppConfig::default();
+        config.vector.embedding_model = "nomic-embed-text".to_string();
+        config.vector.embedding_endpoint = url;
+        config.vector.embedding_dimension = 384;
+
+        assert!(EmbeddingEngine::from_config(&config).await.is_err());
+
+        config.vector.embedding_dimension = 768;
+        let engine = EmbeddingEngine::from_config(&config).await.unwrap();
+        assert_eq!(engine.dimension().await.unwrap(), 768);
+    }
+
+    #[test]
+    fn test_mean_pool_respects_mask() {
+        // batch of 1, 3 tokens, 2 dims; last token is padding
+        let hidden = vec![1.0, 2.0, 3.0, 4.0, 100.0, 100.0];
+        let mask = vec![1, 1, 0];
+
+        let pooled = mean_pool(&hidden, &mask, 1, 3, 2);
+        assert_eq!(pooled, vec![vec![2.0, 3.0]]);
+    }
+
+    #[test]
+    fn test_local_model_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let model_dir = dir.path().join("bge-small-en-v1.5");
+        std::fs::create_dir_all(&model_dir).unwrap();
+        std::fs::write(model_dir.join("tokenizer.json"), "{}").unwrap();
+
+        assert_eq!(local_model_dir(dir.path(), "BAAI/bge-small-en-v1.5"), Some(model_dir));
+        assert!(local_model_dir(dir.path(), "all-MiniLM-L6-v2").is_none());
+        assert_eq!(local_model_spec("BAAI/bge-small-en-v1.5", EmbeddingModelType::SentenceTransformers).dimension, 384);
+    }
+
+    #[tokio::test]
+    async fn test_auto_backend_falls_back_to_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        // Nothing listens on the discard port
+        let engine = EmbeddingEngine::for_backend("auto", "all-MiniLM-L6-v2", "http://127.0.0.1:9", "all-minilm", dir.path())
+            .await
+            .unwrap();
+
+        assert!(engine.provider.is_none());
+        assert_eq!(engine.dimension().await.unwrap(), 384);
+    }
+
+    #[tokio::test]
+    async fn test_auto_backend_prefers_ollama_over_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = crate::vector::ollama_embeddings::test_server::spawn(384, false).await;
+        let engine = EmbeddingEngine::for_backend("auto", "all-MiniLM-L6-v2", &url, "all-minilm", dir.path())
+            .await
+            .unwrap();
+        assert!(engine.provider.is_some());
+        assert_eq!(engine.model.name, "all-minilm");
+
+        // A model of another size would not fit the collections
+        let url = crate::vector::ollama_embeddings::test_server::spawn(768, false).await;
+        let engine = EmbeddingEngine::for_backend("auto", "all-MiniLM-L6-v2", &url, "nomic-embed-text", dir.path())
+            .await
+            .unwrap();
+        assert!(engine.provider.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cached_embeddings_skip_the_provider() {
+        let url = crate::vector::ollama_embeddings::test_server::spawn(384, false).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let engine = EmbeddingEngine::with_endpoint("all-minilm", &url).await.unwrap();
+        let cache = Arc::new(EmbeddingCache::open(dir.path(), &engine.model_id(), 1 << 20).unwrap());
+        let engine = engine.with_cache(cache.clone());
+
+        let first = engine.generate_embedding("cached text").await.unwrap();
+        let batch = engine
+            .batch_generate_embeddings(vec!["cached   text".to_string(), "new text".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(batch[0], first);
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.entries, 2);
+    }
+}
@@ -0,0 +1,908 @@
+//! Pure Rust HNSW index for one collection, used when no Qdrant server is reachable.
+//!
+//! A saved index is a directory holding a `CURRENT` file, which names the generation
+//! directory (`gen-<n>`) with the four files of the latest save:
+//! - `meta.json`: dimension, metric, parameters and graph entry point
+//! - `vectors.f32`: one little-endian f32 row of `dimension` values per slot
+//! - `graph.u32`: little-endian u32 words, laid out by `encode_graph`
+//! - `points.jsonl`: id, payload and tombstone flag per slot, in slot order
+//!
+//! Each save writes a new generation and then replaces `CURRENT`, so the four files
+//! always change together. The binary files have no framing beyond that. Vectors and points carry document
+//! and memory content, so with encryption on they are sealed like other data files.
+
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::io::Write;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::storage::encryption::{open_bytes, seal_bytes};
+use super::embedding_cache::{decode_vector, encode_vector};
+use super::search_engine::SearchFilter;
+use super::{VectorPoint, VectorSearchResult};
+
+/// Bumped whenever the on-disk layout changes
+const FORMAT_VERSION: u32 = 1;
+
+const CURRENT_FILE: &str = "CURRENT";
+const GENERATION_PREFIX: &str = "gen-";
+const META_FILE: &str = "meta.json";
+const VECTORS_FILE: &str = "vectors.f32";
+const GRAPH_FILE: &str = "graph.u32";
+const POINTS_FILE: &str = "points.jsonl";
+
+/// Levels above this are never drawn, which only matters for absurd `m` values
+const MAX_LEVEL: usize = 16;
+
+/// Tombstones are compacted away once they outnumber live points and exceed this
+const MIN_TOMBSTONES_TO_COMPACT: usize = 64;
+
+type Payload = HashMap<String, serde_json::Value>;
+
+/// Graph construction and search parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HnswParams {
+    /// Links per node on upper layers; layer 0 keeps twice as many
+    pub m: usize,
+    /// Candidate list size while inserting
+    pub ef_construction: usize,
+    /// Candidate list size while searching, raised to the limit when smaller
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+        }
+    }
+}
+
+/// Distance metric, named as in `CollectionSchema::distance_metric`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    Cosine,
+    Euclidean,
+    Dot,
+}
+
+impl Metric {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "cosine" => Ok(Self::Cosine),
+            "euclidean" | "euclid" => Ok(Self::Euclidean),
+            "dot" => Ok(Self::Dot),
+            other => Err(anyhow!("Unsupported distance metric: {}", other)),
+        }
+    }
+
+    /// Lower is closer; cosine rows are normalized on insert so a dot product suffices
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Self::Cosine => 1.0 - dot(a, b),
+            Self::Dot => -dot(a, b),
+            Self::Euclidean => a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt(),
+        }
+    }
+
+    /// Score as Qdrant reports it: similarity for cosine and dot, distance for euclidean
+    fn score(&self, distance: f32) -> f32 {
+        match self {
+            Self::Cosine => 1.0 - distance,
+            Self::Dot => -distance,
+            Self::Euclidean => distance,
+        }
+    }
+
+    fn passes_threshold(&self, score: f32, threshold: f32) -> bool {
+        match self {
+            Self::Euclidean => score <= threshold,
+            _ => score >= threshold,
+        }
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    slot: u32,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance).then(self.slot.cmp(&other.slot))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexMeta {
+    format_version: u32,
+    dimension: usize,
+    metric: Metric,
+    params: HnswParams,
+    slots: usize,
+    entry_point: Option<u32>,
+    max_level: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredPoint {
+    id: Uuid,
+    #[serde(default)]
+    payload: Payload,
+    #[serde(default)]
+    deleted: bool,
+}
+
+/// Hierarchical navigable small world graph over the points of one collection.
+///
+/// Deleted and replaced points stay in the graph as tombstones so the links
+/// through them keep working; they are dropped when the index is compacted.
+pub struct HnswIndex {
+    dimension: usize,
+    metric: Metric,
+    params: HnswParams,
+    /// Row-major, `dimension` values per slot
+    vectors: Vec<f32>,
+    ids: Vec<Uuid>,
+    payloads: Vec<Payload>,
+    deleted: Vec<bool>,
+    /// Neighbour slots per slot, per layer
+    links: Vec<Vec<Vec<u32>>>,
+    /// Live slot of each point id
+    slots: HashMap<Uuid, u32>,
+    entry_point: Option<u32>,
+    max_level: usize,
+    rng: StdRng,
+}
+
+impl HnswIndex {
+    pub fn new(dimension: usize, metric: Metric, params: HnswParams) -> Self {
+        Self {
+            dimension,
+            metric,
+            params,
+            vectors: Vec::new(),
+            ids: Vec::new(),
+            payloads: Vec::new(),
+            deleted: Vec::new(),
+            links: Vec::new(),
+            slots: HashMap::new(),
+            entry_point: None,
+            max_level: 0,
+            rng: StdRng::seed_from_u64(dimension as u64),
+        }
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    /// Number of live points
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn contains(&self, id: Uuid) -> bool {
+        self.slots.contains_key(&id)
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<VectorPoint> {
+        let slot = *self.slots.get(&id)? as usize;
+        Some(VectorPoint {
+            id,
+            vector: self.row(slot).to_vec(),
+            payload: self.payloads[slot].clone(),
+        })
+    }
+
+    /// Id and payload of every live point
+    pub fn payloads(&self) -> impl Iterator<Item = (Uuid, &Payload)> {
+        self.slots.iter().map(|(&id, &slot)| (id, &self.payloads[slot as usize]))
+    }
+
+    /// Insert a point, replacing any point with the same id
+    pub fn upsert(&mut self, point: VectorPoint) -> Result<()> {
+        if point.vector.len() != self.dimension {
+            return Err(anyhow!(
+                "Vector has {} dimensions, collection expects {}",
+                point.vector.len(),
+                self.dimension
+            ));
+        }
+        let vector = self.prepare(point.vector);
+
+        if let Some(&slot) = self.slots.get(&point.id) {
+            // Payload-only updates keep the node and its links
+            if self.row(slot as usize) == vector.as_slice() {
+                self.payloads[slot as usize] = point.payload;
+                return Ok(());
+            }
+            self.deleted[slot as usize] = true;
+            self.payloads[slot as usize].clear();
+        }
+
+        self.insert(point.id, vector, point.payload);
+        Ok(())
+    }
+
+    /// Remove a point, returning whether it existed
+    pub fn delete(&mut self, id: Uuid) -> bool {
+        match self.slots.remove(&id) {
+            Some(slot) => {
+                self.deleted[slot as usize] = true;
+                self.payloads[slot as usize].clear();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The `limit` closest live points passing `filter`, best first
+    pub fn search(
+        &self,
+        vector: &[f32],
+        limit: usize,
+        threshold: Option<f32>,
+        filter: Option<&SearchFilter>,
+    ) -> Result<Vec<VectorSearchResult>> {
+        if vector.len() != self.dimension {
+            return Err(anyhow!(
+                "Query has {} dimensions, collection expects {}",
+                vector.len(),
+                self.dimension
+            ));
+        }
+        if limit == 0 || self.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = self.prepare(vector.to_vec());
+        let accepts = |slot: u32| {
+            !self.deleted[slot as usize] && filter.map_or(true, |filter| filter.matches(&self.payloads[slot as usize]))
+        };
+
+        let mut candidates = match filter {
+            // A selective filter would starve the graph walk, so scan the few matches directly
+            Some(_) => {
+                let matching: Vec<u32> = (0..self.ids.len() as u32).filter(|&slot| accepts(slot)).collect();
+                if matching.len() <= self.params.ef_search.max(limit) * 4 {
+                    self.scan(&query, matching)
+                } else {
+                    self.graph_search(&query, limit, &accepts)
+                }
+            }
+            None => self.graph_search(&query, limit, &accepts),
+        };
+        candidates.truncate(limit);
+
+        Ok(candidates
+            .into_iter()
+            .map(|candidate| (candidate.slot as usize, self.metric.score(candidate.distance)))
+            .filter(|(_, score)| threshold.map_or(true, |threshold| self.metric.passes_threshold(*score, threshold)))
+            .map(|(slot, score)| VectorSearchResult {
+                id: self.ids[slot],
+                score,
+                payload: self.payloads[slot].clone(),
+            })
+            .collect())
+    }
+
+    /// Whether enough tombstones have piled up to be worth a rebuild
+    pub fn needs_compaction(&self) -> bool {
+        let tombstones = self.ids.len() - self.len();
+        tombstones >= MIN_TOMBSTONES_TO_COMPACT && tombstones > self.len()
+    }
+
+    /// Rebuild the graph from the live points only
+    pub fn compact(&mut self) {
+        let mut compacted = Self::new(self.dimension, self.metric, self.params);
+        for slot in 0..self.ids.len() {
+            if !self.deleted[slot] {
+                compacted.insert(self.ids[slot], self.row(slot).to_vec(), std::mem::take(&mut self.payloads[slot]));
+            }
+        }
+        *self = compacted;
+    }
+
+    /// Write the index to `dir`, compacting first if needed. The files go into a new
+    /// generation directory that a single rename of `CURRENT` then makes current, so a
+    /// crash leaves either the previous save or this one, never a mix of both.
+    pub fn save(&mut self, dir: &Path) -> Result<()> {
+        if self.needs_compaction() {
+            self.compact();
+        }
+        std::fs::create_dir_all(dir)?;
+
+        let number = current_generation(dir)?.map_or(1, |(_, number)| number + 1);
+        let generation = format!("{}{}", GENERATION_PREFIX, number);
+        let target = dir.join(&generation);
+        if target.exists() {
+            std::fs::remove_dir_all(&target)?;
+        }
+        std::fs::create_dir(&target)?;
+
+        write_synced(&target.join(VECTORS_FILE), &seal_bytes(encode_vector(&self.vectors))?)?;
+        let graph: Vec<u8> = encode_graph(&self.links).iter().flat_map(|word| word.to_le_bytes()).collect();
+        write_synced(&target.join(GRAPH_FILE), &graph)?;
+
+        let mut points = Vec::new();
+        for slot in 0..self.ids.len() {
+            let stored = StoredPoint {
+                id: self.ids[slot],
+                payload: self.payloads[slot].clone(),
+                deleted: self.deleted[slot],
+            };
+            serde_json::to_writer(&mut points, &stored)?;
+            points.push(b'\n');
+        }
+        write_synced(&target.join(POINTS_FILE), &seal_bytes(points)?)?;
+
+        let meta = IndexMeta {
+            format_version: FORMAT_VERSION,
+            dimension: self.dimension,
+            metric: self.metric,
+            params: self.params,
+            slots: self.ids.len(),
+            entry_point: self.entry_point,
+            max_level: self.max_level,
+        };
+        write_synced(&target.join(META_FILE), &serde_json::to_vec_pretty(&meta)?)?;
+        write_atomic(&dir.join(CURRENT_FILE), generation.as_bytes())?;
+
+        // Earlier generations, and files saved before generations existed, are now unused
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with(GENERATION_PREFIX) && name != generation {
+                std::fs::remove_dir_all(entry.path())?;
+            } else if [META_FILE, VECTORS_FILE, GRAPH_FILE, POINTS_FILE].contains(&name.as_str()) {
+                std::fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Load an index saved by `save`; `Ok(None)` when `dir` holds none
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        match current_generation(dir)? {
+            Some((generation, _)) => Self::load_files(&dir.join(generation)).map(Some),
+            // Indexes saved before generations keep their files directly in `dir`
+            None if dir.join(META_FILE).exists() => Self::load_files(dir).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn load_files(dir: &Path) -> Result<Self> {
+        let meta: IndexMeta = serde_json::from_slice(&std::fs::read(dir.join(META_FILE))?)?;
+        if meta.format_version != FORMAT_VERSION {
+            return Err(anyhow!(
+                "Index {} has format version {}, expected {}",
+                dir.display(),
+                meta.format_version,
+                FORMAT_VERSION
+            ));
+        }
+
+        let vectors = decode_vector(&open_bytes(std::fs::read(dir.join(VECTORS_FILE))?)?)
+            .ok_or_else(|| anyhow!("Truncated vector file in {}", dir.display()))?;
+        if vectors.len() != meta.slots * meta.dimension {
+            return Err(anyhow!("Vector file in {} does not match its metadata", dir.display()));
+        }
+
+        let graph = std::fs::read(dir.join(GRAPH_FILE))?;
+        if graph.len() % 4 != 0 {
+            return Err(anyhow!("Truncated graph file in {}", dir.display()));
+        }
+        let words: Vec<u32> = graph
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        let links = decode_graph(&words, meta.slots)
+            .ok_or_else(|| anyhow!("Corrupt graph file in {}", dir.display()))?;
+        // A bad entry point or layer count would only show up as a panic in the first search
+        if !graph_is_consistent(&links, meta.entry_point, meta.max_level) {
+            return Err(anyhow!("Graph in {} does not match its metadata", dir.display()));
+        }
+
+        let mut index = Self::new(meta.dimension, meta.metric, meta.params);
+        let points = String::from_utf8(open_bytes(std::fs::read(dir.join(POINTS_FILE))?)?)?;
+        for line in points.lines().filter(|line| !line.trim().is_empty()) {
+            let stored: StoredPoint = serde_json::from_str(line)?;
+            if !stored.deleted {
+                index.slots.insert(stored.id, index.ids.len() as u32);
+            }
+            index.ids.push(stored.id);
+            index.payloads.push(stored.payload);
+            index.deleted.push(stored.deleted);
+        }
+        if index.ids.len() != meta.slots {
+            return Err(anyhow!("Point file in {} does not match its metadata", dir.display()));
+        }
+
+        index.vectors = vectors;
+        index.links = links;
+        index.entry_point = meta.entry_point;
+        index.max_level = meta.max_level;
+        index.rng = StdRng::seed_from_u64(meta.dimension as u64 ^ meta.slots as u64);
+        Ok(index)
+    }
+
+    fn prepare(&self, mut vector: Vec<f32>) -> Vec<f32> {
+        if self.metric == Metric::Cosine {
+            let norm = dot(&vector, &vector).sqrt();
+            if norm > 0.0 {
+                vector.iter_mut().for_each(|v| *v /= norm);
+            }
+        }
+        vector
+    }
+
+    fn row(&self, slot: usize) -> &[f32] {
+        &self.vectors[slot * self.dimension..(slot + 1) * self.dimension]
+    }
+
+    fn distance_to(&self, query: &[f32], slot: u32) -> f32 {
+        self.metric.distance(query, self.row(slot as usize))
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 { self.params.m * 2 } else { self.params.m }
+    }
+
+    fn random_level(&mut self) -> usize {
+        let scale = 1.0 / (self.params.m.max(2) as f64).ln();
+        let uniform: f64 = 1.0 - self.rng.gen::<f64>();
+        ((-uniform.ln() * scale) as usize).min(MAX_LEVEL)
+    }
+
+    fn insert(&mut self, id: Uuid, vector: Vec<f32>, payload: Payload) {
+        let slot = self.ids.len() as u32;
+        let level = self.random_level();
+        self.vectors.extend_from_slice(&vector);
+        self.ids.push(id);
+        self.payloads.push(payload);
+        self.deleted.push(false);
+        self.links.push(vec![Vec::new(); level + 1]);
+        self.slots.insert(id, slot);
+
+        let Some(mut entry) = self.entry_point else {
+            self.entry_point = Some(slot);
+            self.max_level = level;
+            return;
+        };
+
+        for layer in (level + 1..=self.max_level).rev() {
+            entry = self.greedy_closest(&vector, entry, layer);
+        }
+
+        let mut entries = vec![entry];
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let candidates = self.search_layer(&vector, &entries, self.params.ef_construction, layer);
+            let neighbours = self.select_neighbours(&candidates, self.max_links(layer));
+            for &neighbour in &neighbours {
+                self.link(neighbour, slot, layer);
+            }
+            self.links[slot as usize][layer] = neighbours;
+            entries = candidates.iter().map(|candidate| candidate.slot).collect();
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry_point = Some(slot);
+        }
+    }
+
+    /// Add `target` to `slot`'s links on `layer`, pruning back to the link budget
+    fn link(&mut self, slot: u32, target: u32, layer: usize) {
+        let max_links = self.max_links(layer);
+        let links = &mut self.links[slot as usize][layer];
+        links.push(target);
+        if links.len() <= max_links {
+            return;
+        }
+
+        let base = self.row(slot as usize).to_vec();
+        let mut candidates: Vec<Candidate> = self.links[slot as usize][layer]
+            .iter()
+            .map(|&neighbour| Candidate { distance: self.distance_to(&base, neighbour), slot: neighbour })
+            .collect();
+        candidates.sort();
+        self.links[slot as usize][layer] = self.select_neighbours(&candidates, max_links);
+    }
+
+    /// The HNSW neighbour heuristic: prefer candidates closer to the new node than to
+    /// any neighbour already picked, then fill up with the closest of the rest
+    fn select_neighbours(&self, candidates: &[Candidate], max_links: usize) -> Vec<u32> {
+        let mut selected: Vec<u32> = Vec::with_capacity(max_links);
+        let mut skipped = Vec::new();
+        for candidate in candidates {
+            if selected.len() >= max_links {
+                break;
+            }
+            let row = self.row(candidate.slot as usize);
+            let diverse = selected
+                .iter()
+                .all(|&chosen| self.distance_to(row, chosen) > candidate.distance);
+            if diverse {
+                selected.push(candidate.slot);
+            } else {
+                skipped.push(candidate.slot);
+            }
+        }
+        for slot in skipped {
+            if selected.len() >= max_links {
+                break;
+            }
+            selected.push(slot);
+        }
+        selected
+    }
+
+    fn greedy_closest(&self, query: &[f32], mut current: u32, layer: usize) -> u32 {
+        let mut best = self.distance_to(query, current);
+        loop {
+            let mut moved = false;
+            for &neighbour in &self.links[current as usize][layer] {
+                let distance = self.distance_to(query, neighbour);
+                if distance < best {
+                    best = distance;
+                    current = neighbour;
+                    moved = true;
+                }
+            }
+            if !moved {
+                return current;
+            }
+        }
+    }
+
+    /// Best-first search of one layer, returning up to `ef` nodes closest first
+    fn search_layer(&self, query: &[f32], entries: &[u32], ef: usize, layer: usize) -> Vec<Candidate> {
+        let mut visited: HashSet<u32> = HashSet::new();
+        let mut frontier = BinaryHeap::new();
+        let mut nearest = BinaryHeap::new();
+
+        for &entry in entries {
+            if visited.insert(entry) {
+                let candidate = Candidate { distance: self.distance_to(query, entry), slot: entry };
+                frontier.push(Reverse(candidate));
+                nearest.push(candidate);
+            }
+        }
+        while nearest.len() > ef {
+            nearest.pop();
+        }
+
+        while let Some(Reverse(current)) = frontier.pop() {
+            let furthest = nearest.peek().map_or(f32::INFINITY, |candidate: &Candidate| candidate.distance);
+            if current.distance > furthest && nearest.len() >= ef {
+                break;
+            }
+            for &neighbour in &self.links[current.slot as usize][layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let distance = self.distance_to(query, neighbour);
+                let furthest = nearest.peek().map_or(f32::INFINITY, |candidate| candidate.distance);
+                if nearest.len() < ef || distance < furthest {
+                    let candidate = Candidate { distance, slot: neighbour };
+                    frontier.push(Reverse(candidate));
+                    nearest.push(candidate);
+                    if nearest.len() > ef {
+                        nearest.pop();
+                    }
+                }
+            }
+        }
+
+        nearest.into_sorted_vec()
+    }
+
+    /// Walk the graph, widening the beam until `limit` accepted points turn up
+    /// or the whole graph has been considered
+    fn graph_search(&self, query: &[f32], limit: usize, accepts: &dyn Fn(u32) -> bool) -> Vec<Candidate> {
+        let Some(mut entry) = self.entry_point else {
+            return vec![];
+        };
+        for layer in (1..=self.max_level).rev() {
+            entry = self.greedy_closest(query, entry, layer);
+        }
+
+        let mut ef = self.params.ef_search.max(limit);
+        loop {
+            let found: Vec<Candidate> = self
+                .search_layer(query, &[entry], ef, 0)
+                .into_iter()
+                .filter(|candidate| accepts(candidate.slot))
+                .collect();
+            if found.len() >= limit || ef >= self.ids.len() {
+                return found;
+            }
+            ef *= 2;
+        }
+    }
+
+    fn scan(&self, query: &[f32], slots: Vec<u32>) -> Vec<Candidate> {
+        let mut candidates: Vec<Candidate> = slots
+            .into_iter()
+            .map(|slot| Candidate { distance: self.distance_to(query, slot), slot })
+            .collect();
+        candidates.sort();
+        candidates
+    }
+}
+
+/// Flatten the graph into u32 words: the slot count, then `slots + 1` offsets (in
+/// words, from the start of the record area) and finally one record per slot of
+/// `[layer count, then per layer: link count, link slots...]`
+fn encode_graph(links: &[Vec<Vec<u32>>]) -> Vec<u32> {
+    let mut records = Vec::new();
+    let mut offsets = Vec::with_capacity(links.len() + 1);
+    for layers in links {
+        offsets.push(records.len() as u32);
+        records.push(layers.len() as u32);
+        for layer in layers {
+            records.push(layer.len() as u32);
+            records.extend_from_slice(layer);
+        }
+    }
+    offsets.push(records.len() as u32);
+
+    let mut words = Vec::with_capacity(1 + offsets.len() + records.len());
+    words.push(links.len() as u32);
+    words.extend(offsets);
+    words.extend(records);
+    words
+}
+
+fn decode_graph(words: &[u32], slots: usize) -> Option<Vec<Vec<Vec<u32>>>> {
+    if words.first().copied()? as usize != slots {
+        return None;
+    }
+    let offsets = words.get(1..slots + 2)?;
+    let records = &words[slots + 2..];
+
+    let mut links = Vec::with_capacity(slots);
+    for slot in 0..slots {
+        let record = records.get(offsets[slot] as usize..offsets[slot + 1] as usize)?;
+        let (&layer_count, mut rest) = record.split_first()?;
+        let mut layers = Vec::with_capacity(layer_count as usize);
+        for _ in 0..layer_count {
+            let (&count, tail) = rest.split_first()?;
+            let layer = tail.get(..count as usize)?;
+            if layer.iter().any(|&neighbour| neighbour as usize >= slots) {
+                return None;
+            }
+            layers.push(layer.to_vec());
+            rest = &tail[count as usize..];
+        }
+        links.push(layers);
+    }
+    Some(links)
+}
+
+/// Whether the entry point and every link refer to slots that have the layer in question
+fn graph_is_consistent(links: &[Vec<Vec<u32>>], entry_point: Option<u32>, max_level: usize) -> bool {
+    let entry_valid = match entry_point {
+        Some(entry) => links.get(entry as usize).map_or(false, |layers| layers.len() == max_level + 1),
+        None => links.is_empty(),
+    };
+    entry_valid
+        && links.iter().all(|layers| {
+            !layers.is_empty()
+                && layers.len() <= max_level + 1
+                && layers.iter().enumerate().all(|(layer, neighbours)| {
+                    neighbours.iter().all(|&neighbour| links[neighbour as usize].len() > layer)
+                })
+        })
+}
+
+/// Name and number of the generation `CURRENT` points at, if any
+fn current_generation(dir: &Path) -> Result<Option<(String, u64)>> {
+    let path = dir.join(CURRENT_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let generation = std::fs::read_to_string(&path)?.trim().to_string();
+    let number = generation
+        .strip_prefix(GENERATION_PREFIX)
+        .and_then(|number| number.parse().ok())
+        .ok_or_else(|| anyhow!("Corrupt {} file in {}", CURRENT_FILE, dir.display()))?;
+    Ok(Some((generation, number)))
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::search_engine::FilterCondition;
+
+    fn random_vectors(count: usize, dimension: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| (0..dimension).map(|_| rng.gen_range(-1.0..1.0)).collect())
+            .collect()
+    }
+
+    fn point(vector: Vec<f32>, payload: serde_json::Value) -> VectorPoint {
+        VectorPoint {
+            id: Uuid::new_v4(),
+            vector,
+            payload: serde_json::from_value(payload).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_search_matches_brute_force() {
+        let mut index = HnswIndex::new(16, Metric::Cosine, HnswParams::default());
+        let vectors = random_vectors(500, 16, 1);
+        let mut points = Vec::new();
+        for vector in &vectors {
+            let point = point(vector.clone(), serde_json::json!({}));
+            points.push(point.clone());
+            index.upsert(point).unwrap();
+        }
+
+        let mut hits = 0;
+        for query in random_vectors(20, 16, 2) {
+            let prepared = index.prepare(query.clone());
+            let mut exact: Vec<(f32, Uuid)> = points
+                .iter()
+                .map(|point| (index.metric.distance(&prepared, &index.prepare(point.vector.clone())), point.id))
+                .collect();
+            exact.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let expected: HashSet<Uuid> = exact.iter().take(10).map(|(_, id)| *id).collect();
+
+            let results = index.search(&query, 10, None, None).unwrap();
+            assert!(results.windows(2).all(|pair| pair[0].score >= pair[1].score));
+            hits += results.iter().filter(|result| expected.contains(&result.id)).count();
+        }
+        assert!(hits as f32 / 200.0 >= 0.95, "recall@10 was {}", hits as f32 / 200.0);
+    }
+
+    #[test]
+    fn test_filters_deletes_and_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut index = HnswIndex::new(8, Metric::Cosine, HnswParams::default());
+        let mut ids = Vec::new();
+        for (i, vector) in random_vectors(300, 8, 3).into_iter().enumerate() {
+            let agent = if i % 3 == 0 { "alice" } else { "bob" };
+            let point = point(vector, serde_json::json!({"agent_id": agent, "rank": i, "tags": ["t", format!("n{}", i)]}));
+            ids.push(point.id);
+            index.upsert(point).unwrap();
+        }
+        let query = random_vectors(1, 8, 4).remove(0);
+
+        let filter = SearchFilter::new()
+            .must(FilterCondition::equals("agent_id".to_string(), "alice".into()))
+            .must_not(FilterCondition::range("rank".to_string(), None, Some(30.0)));
+        let results = index.search(&query, 20, None, Some(&filter)).unwrap();
+        assert_eq!(results.len(), 20);
+        for result in &results {
+            assert_eq!(result.payload["agent_id"], "alice");
+            assert!(result.payload["rank"].as_u64().unwrap() > 30);
+        }
+
+        // Array payloads match on any element
+        let tagged = SearchFilter::new().must(FilterCondition::in_values("tags".to_string(), vec!["n7".into()]));
+        let results = index.search(&query, 5, None, Some(&tagged)).unwrap();
+        assert_eq!(results.iter().map(|result| result.id).collect::<Vec<_>>(), vec![ids[7]]);
+
+        assert!(index.delete(ids[7]));
+        assert!(index.search(&query, 5, None, Some(&tagged)).unwrap().is_empty());
+        assert!(index.search(&query, 300, None, None).unwrap().iter().all(|result| result.id != ids[7]));
+
+        // Replacing a point moves it rather than duplicating it
+        let moved = VectorPoint { id: ids[8], vector: query.clone(), payload: HashMap::new() };
+        index.upsert(moved).unwrap();
+        let results = index.search(&query, 3, None, None).unwrap();
+        assert_eq!(results[0].id, ids[8]);
+        assert!((results[0].score - 1.0).abs() < 1e-5);
+        assert_eq!(results.iter().filter(|result| result.id == ids[8]).count(), 1);
+
+        index.save(dir.path()).unwrap();
+        let reloaded = HnswIndex::load(dir.path()).unwrap().unwrap();
+        assert_eq!(reloaded.len(), 299);
+        assert!(reloaded.get(ids[7]).is_none());
+        let before = index.search(&query, 10, Some(0.2), Some(&filter)).unwrap();
+        let after = reloaded.search(&query, 10, Some(0.2), Some(&filter)).unwrap();
+        assert_eq!(
+            before.iter().map(|result| result.id).collect::<Vec<_>>(),
+            after.iter().map(|result| result.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_saves_switch_generations_and_bad_metadata_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut index = HnswIndex::new(4, Metric::Cosine, HnswParams::default());
+        for vector in random_vectors(20, 4, 6) {
+            index.upsert(point(vector, serde_json::json!({}))).unwrap();
+        }
+        index.save(dir.path()).unwrap();
+        index.upsert(point(vec![1.0, 0.0, 0.0, 0.0], serde_json::json!({}))).unwrap();
+
+        // A save that crashed before switching generations leaves the previous one in use
+        std::fs::create_dir(dir.path().join("gen-2")).unwrap();
+        std::fs::write(dir.path().join("gen-2").join(META_FILE), b"{").unwrap();
+        assert_eq!(HnswIndex::load(dir.path()).unwrap().unwrap().len(), 20);
+
+        index.save(dir.path()).unwrap();
+        assert_eq!(HnswIndex::load(dir.path()).unwrap().unwrap().len(), 21);
+        assert!(!dir.path().join("gen-1").exists());
+
+        // An entry point past the last slot is refused rather than panicking in a search
+        let meta_path = dir.path().join("gen-2").join(META_FILE);
+        let mut meta: serde_json::Value = serde_json::from_slice(&std::fs::read(&meta_path).unwrap()).unwrap();
+        meta["entry_point"] = 21.into();
+        std::fs::write(&meta_path, serde_json::to_vec(&meta).unwrap()).unwrap();
+        assert!(HnswIndex::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_compaction_drops_tombstones() {
+        let mut index = HnswIndex::new(4, Metric::Euclidean, HnswParams::default());
+        let points: Vec<VectorPoint> = random_vectors(200, 4, 5)
+            .into_iter()
+            .map(|vector| point(vector, serde_json::json!({})))
+            .collect();
+        for point in &points {
+            index.upsert(point.clone()).unwrap();
+        }
+        for point in &points[..150] {
+            index.delete(point.id);
+        }
+        assert!(index.needs_compaction());
+
+        index.compact();
+        assert_eq!((index.len(), index.ids.len()), (50, 50));
+        let results = index.search(&points[199].vector, 1, None, None).unwrap();
+        assert_eq!(results[0].id, points[199].id);
+        assert_eq!(results[0].score, 0.0);
+    }
+}
@@ -3,41 +3,88 @@
 //! This module provides vector storage and semantic search capabilities
 //! for LocalMind's memory system. It integrates with local Qdrant instances
 //! and manages embeddings for memories, documents, and other content.
+//! Without a reachable Qdrant it falls back to an embedded HNSW index kept
+//! in the data directory.
 
 pub mod qdrant_manager;
 pub mod embedding_engine;
+pub mod embedding_cache;
 pub mod collection_schema;
 pub mod search_engine;
+pub mod hnsw_index;
+pub mod embedded_store;
+pub mod text_index;
+pub mod ollama_embeddings;
+#[cfg(feature = "onnx")]
+pub mod onnx_embeddings;
+#[cfg(feature = "basic-ai")]
+pub mod candle_embeddings;
 
 // Re-export commonly used types and structs
-pub use qdrant_manager::{QdrantManager, QdrantConfig, QdrantStatus};
-pub use embedding_engine::{EmbeddingEngine, EmbeddingModel, EmbeddingResult};
+pub use qdrant_manager::{QdrantManager, QdrantConfig, QdrantStatus, SnapshotInfo};
+pub use embedding_engine::{EmbeddingEngine, EmbeddingModel, EmbeddingProvider, EmbeddingResult};
+pub use embedding_cache::{EmbeddingCache, EmbeddingCacheStats};
+pub use ollama_embeddings::OllamaEmbeddingProvider;
+#[cfg(feature = "onnx")]
+pub use onnx_embeddings::OnnxEmbeddingProvider;
+#[cfg(feature = "basic-ai")]
+pub use candle_embeddings::CandleEmbeddingProvider;
 pub use collection_schema::{CollectionSchema, VectorCollection, FieldType};
-pub use search_engine::{SemanticSearchEngine, SearchQuery, SearchResult, SimilarityMetric};
+pub use search_engine::{SemanticSearchEngine, SearchQuery, SearchResult, SimilarityMetric, HybridWeights, FusionMethod};
+pub use hnsw_index::{HnswIndex, HnswParams};
+pub use embedded_store::EmbeddedVectorStore;
+pub use text_index::{Bm25Index, Bm25Params};
 
 use anyhow::Result;
+use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::config::AppConfig;
+use crate::memory::ranking::CrossEncoder;
 use crate::state::AppState;
+use crate::storage::encryption;
+
+/// Directory under the data dir holding the embedded index
+pub(crate) const EMBEDDED_INDEX_DIR: &str = "vector_index";
+
+/// Points fetched per request when rebuilding the keyword index from Qdrant
+const SCROLL_PAGE_SIZE: usize = 256;
+
+/// File under the data dir recording which embedding model filled each collection
+const EMBEDDING_MODELS_FILE: &str = "vector_models.json";
+
+/// Where vectors are stored and searched
+enum VectorBackend {
+    Qdrant(Arc<Mutex<QdrantManager>>),
+    Embedded(Arc<EmbeddedVectorStore>),
+}
 
 /// Main vector store coordinator that manages all vector operations
 pub struct VectorStore {
-    qdrant_manager: Arc<Mutex<QdrantManager>>,
+    backend: VectorBackend,
     embedding_engine: Arc<EmbeddingEngine>,
     search_engine: Arc<SemanticSearchEngine>,
     collections: Arc<Mutex<std::collections::HashMap<String, VectorCollection>>>,
+    /// Where `EMBEDDING_MODELS_FILE` lives
+    models_file: std::path::PathBuf,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct VectorPoint {
     pub id: Uuid,
     pub vector: Vec<f32>,
     pub payload: std::collections::HashMap<String, serde_json::Value>,
 }
 
+/// A collection saved by `VectorStore::backup_collections`, relative to the backup directory
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CollectionBackup {
+    pub name: String,
+    pub path: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct VectorSearchResult {
     pub id: Uuid,
@@ -46,7 +93,8 @@ pub struct VectorSearchResult {
 }
 
 impl VectorStore {
-    /// Create a new vector store
+    /// Create a new vector store on Qdrant or the embedded index, per
+    /// `vector.index_backend`; "auto" uses Qdrant only when it answers
     pub async fn new(config: AppConfig) -> Result<Self> {
         let qdrant_config = QdrantConfig {
             host: config.vector.qdrant_host.clone(),
@@ -55,12 +103,22 @@ impl VectorStore {
             timeout_secs: 30,
         };
 
-        let qdrant_manager = Arc::new(Mutex::new(
-            QdrantManager::new(qdrant_config).await?
-        ));
+        let backend = match config.vector.index_backend.as_str() {
+            "qdrant" => VectorBackend::Qdrant(Arc::new(Mutex::new(QdrantManager::new(qdrant_config).await?))),
+            "embedded" => Self::embedded_backend(&config)?,
+            _ => match QdrantManager::new(qdrant_config).await {
+                Ok(manager) if manager.health_check().await.unwrap_or(false) => {
+                    VectorBackend::Qdrant(Arc::new(Mutex::new(manager)))
+                }
+                _ => {
+                    log::info!("Qdrant is not reachable, using the embedded vector index");
+                    Self::embedded_backend(&config)?
+                }
+            },
+        };
 
         let embedding_engine = Arc::new(
-            EmbeddingEngine::new(&config.vector.embedding_model).await?
+            EmbeddingEngine::from_config(&config).await?
         );
 
         let search_engine = Arc::new(SemanticSearchEngine::new());
@@ -68,33 +126,330 @@ impl VectorStore {
         let collections = Arc::new(Mutex::new(std::collections::HashMap::new()));
 
         Ok(Self {
-            qdrant_manager,
+            backend,
             embedding_engine,
             search_engine,
             collections,
+            models_file: config.data_dir_path().join(EMBEDDING_MODELS_FILE),
         })
     }
 
-    /// Initialize the vector store with required collections
+    fn embedded_backend(config: &AppConfig) -> Result<VectorBackend> {
+        let dir = config.data_dir_path().join(EMBEDDED_INDEX_DIR);
+        Ok(VectorBackend::Embedded(Arc::new(EmbeddedVectorStore::open(&dir, config.vector.hnsw)?)))
+    }
+
+    /// Initialize the vector store with required collections.
+    ///
+    /// A collection filled by a different embedding model than the current one is emptied,
+    /// since its vectors can't be compared with new queries; memories missing from the
+    /// index are embedded again by `MemoryManager::index_missing_memories`.
     pub async fn initialize(&self) -> Result<()> {
-        let manager = self.qdrant_manager.lock().await;
-        
+        let dimension = self.embedding_engine.dimension().await?;
+        let model_id = self.embedding_engine.model_id();
+        let mut models = self.recorded_models();
+
         // Create memory collection
-        let memory_schema = CollectionSchema::memory_collection();
-        manager.create_collection(&memory_schema).await?;
-        
+        let mut memory_schema = CollectionSchema::memory_collection();
+        memory_schema.vector_size = dimension;
+
         // Create document collection
-        let document_schema = CollectionSchema::document_collection();
-        manager.create_collection(&document_schema).await?;
+        let mut document_schema = CollectionSchema::document_collection();
+        document_schema.vector_size = dimension;
+
+        for schema in [&memory_schema, &document_schema] {
+            match models.get(&schema.name) {
+                Some(recorded) if *recorded != model_id => {
+                    log::warn!(
+                        "Embedding model changed from '{}' to '{}'; re-indexing collection '{}'",
+                        recorded, model_id, schema.name
+                    );
+                    self.delete_collection(&schema.name).await?;
+                }
+                _ => {}
+            }
+            models.insert(schema.name.clone(), model_id.clone());
+            self.create_collection(schema).await?;
+            self.rebuild_text_index(&schema.name).await?;
+        }
+        if let Some(parent) = self.models_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.models_file, serde_json::to_vec_pretty(&models)?)?;
         
-        log::info!("Vector store collections initialized");
+        log::info!("Vector store collections initialized ({})", self.backend_name());
         Ok(())
     }
     
     /// Check if the vector store is available
     pub async fn is_available(&self) -> bool {
-        let manager = self.qdrant_manager.lock().await;
-        manager.check_health().await.is_ok()
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => manager.lock().await.health_check().await.unwrap_or(false),
+            VectorBackend::Embedded(_) => true,
+        }
+    }
+
+    /// "qdrant" or "embedded"
+    pub fn backend_name(&self) -> &'static str {
+        match &self.backend {
+            VectorBackend::Qdrant(_) => "qdrant",
+            VectorBackend::Embedded(_) => "embedded",
+        }
+    }
+
+    /// Create a collection if it doesn't exist yet
+    pub async fn create_collection(&self, schema: &CollectionSchema) -> Result<()> {
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => {
+                let manager = manager.lock().await;
+                if manager.list_collections().await?.contains(&schema.name) {
+                    return Ok(());
+                }
+                manager.create_collection(schema).await
+            }
+            VectorBackend::Embedded(store) => {
+                store.create_collection(schema).await?;
+                // A rebuilt collection starts empty
+                if store.point_count(&schema.name).await? == 0 {
+                    self.search_engine.clear_text(&schema.name);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Drop a collection and its keyword index, if it exists
+    async fn delete_collection(&self, name: &str) -> Result<()> {
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => {
+                let manager = manager.lock().await;
+                if manager.list_collections().await?.iter().any(|collection| collection == name) {
+                    manager.delete_collection(name).await?;
+                }
+            }
+            VectorBackend::Embedded(store) => store.delete_collection(name).await?,
+        }
+        self.search_engine.clear_text(name);
+        Ok(())
+    }
+
+    /// Collection name -> id of the embedding model its vectors came from
+    fn recorded_models(&self) -> std::collections::HashMap<String, String> {
+        std::fs::read(&self.models_file)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save every collection into `dir`: a Qdrant snapshot file per collection, or a
+    /// copy of each embedded index. With encryption on, snapshots are sealed like the
+    /// rest of the data directory; embedded copies already seal their vectors and payloads.
+    pub async fn backup_collections(&self, dir: &std::path::Path) -> Result<Vec<CollectionBackup>> {
+        std::fs::create_dir_all(dir)?;
+        let mut backups = Vec::new();
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => {
+                let manager = manager.lock().await;
+                for name in manager.list_collections().await? {
+                    let snapshot = manager.create_snapshot(&name).await?;
+                    let path = format!("{}.snapshot", name);
+                    // Held in memory so only the sealed copy is ever written
+                    let bytes = manager.fetch_snapshot(&name, &snapshot.name).await?;
+                    encryption::write_file(&dir.join(&path), &bytes)?;
+                    // The downloaded copy is the backup; don't leave another on the server
+                    if let Err(e) = manager.delete_snapshot(&name, &snapshot.name).await {
+                        log::warn!("Failed to delete snapshot '{}' from Qdrant: {}", snapshot.name, e);
+                    }
+                    backups.push(CollectionBackup { name, path });
+                }
+            }
+            VectorBackend::Embedded(store) => {
+                for name in store.list_collections().await {
+                    store.export_collection(&name, &dir.join(&name)).await?;
+                    backups.push(CollectionBackup { path: name.clone(), name });
+                }
+            }
+        }
+        log::info!("Backed up {} vector collections ({})", backups.len(), self.backend_name());
+        Ok(backups)
+    }
+
+    /// Replace collections with copies saved by `backup_collections` on the same backend
+    pub async fn restore_collections(&self, dir: &std::path::Path, backups: &[CollectionBackup]) -> Result<()> {
+        for backup in backups {
+            let relative = std::path::Path::new(&backup.path);
+            if relative.components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+                return Err(anyhow::anyhow!("Invalid vector backup path: {}", backup.path));
+            }
+            let path = dir.join(relative);
+            match &self.backend {
+                VectorBackend::Qdrant(manager) => {
+                    // Decrypted in memory, so no plaintext copy is left on disk
+                    let bytes = encryption::read_file(&path)?;
+                    manager.lock().await.upload_snapshot(&backup.name, backup.path.clone(), bytes).await?
+                }
+                VectorBackend::Embedded(store) => store.import_collection(&backup.name, &path).await?,
+            }
+            self.rebuild_text_index(&backup.name).await?;
+        }
+        log::info!("Restored {} vector collections ({})", backups.len(), self.backend_name());
+        Ok(())
+    }
+
+    /// Re-read a collection's payloads into the keyword index
+    pub async fn rebuild_text_index(&self, collection: &str) -> Result<()> {
+        self.search_engine.clear_text(collection);
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => {
+                let manager = manager.lock().await;
+                let mut offset = None;
+                loop {
+                    let (points, next) = manager.scroll_points(collection, offset, SCROLL_PAGE_SIZE).await?;
+                    for point in points {
+                        self.search_engine.index_payload(collection, point.id, &point.payload);
+                    }
+                    match next {
+                        Some(next) => offset = Some(next),
+                        None => break,
+                    }
+                }
+            }
+            VectorBackend::Embedded(store) => {
+                for (id, payload) in store.payloads(collection).await? {
+                    self.search_engine.index_payload(collection, id, &payload);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert or update points in a collection, keyword indexing their text
+    pub async fn upsert_points(&self, collection: &str, points: Vec<VectorPoint>) -> Result<()> {
+        let texts: Vec<(Uuid, std::collections::HashMap<String, serde_json::Value>)> =
+            points.iter().map(|point| (point.id, point.payload.clone())).collect();
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => manager.lock().await.upsert_points(collection, points).await?,
+            VectorBackend::Embedded(store) => store.upsert_points(collection, points).await?,
+        }
+        for (id, payload) in texts {
+            self.search_engine.index_payload(collection, id, &payload);
+        }
+        Ok(())
+    }
+
+    /// Delete a point from a collection
+    pub async fn delete_point(&self, collection: &str, id: Uuid) -> Result<()> {
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => manager.lock().await.delete_point(collection, id).await?,
+            VectorBackend::Embedded(store) => store.delete_points(collection, &[id]).await?,
+        }
+        self.search_engine.remove_text(collection, id);
+        Ok(())
+    }
+
+    /// Whether a point with payload text is in a collection, answered from the keyword index
+    pub fn is_indexed(&self, collection: &str, id: Uuid) -> bool {
+        self.search_engine.has_text(collection, id)
+    }
+
+    /// Drop a memory's embedding from the memory collection
+    pub async fn delete_embedding(&self, id: Uuid) -> Result<()> {
+        self.delete_point(&CollectionSchema::memory_collection().name, id).await
+    }
+
+    /// Fetch a point with its vector and payload
+    pub async fn get_point(&self, collection: &str, id: Uuid) -> Result<Option<VectorPoint>> {
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => manager.lock().await.get_point(collection, id).await,
+            VectorBackend::Embedded(store) => store.get_point(collection, id).await,
+        }
+    }
+
+    /// Nearest points to the query vector that pass its filter
+    pub async fn search(&self, query: SearchQuery) -> Result<Vec<VectorSearchResult>> {
+        match &self.backend {
+            VectorBackend::Qdrant(manager) => {
+                let manager = manager.lock().await;
+                self.search_engine.search(&manager, query).await
+            }
+            VectorBackend::Embedded(store) => store.search(&query).await,
+        }
+    }
+
+    /// Generate an embedding with the configured model
+    pub async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>> {
+        self.embedding_engine.generate_embedding(text).await
+    }
+
+    /// Generate embeddings for several texts, batched per the config
+    pub async fn generate_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        self.embedding_engine.batch_generate_embeddings(texts).await
+    }
+
+    /// Get the embedding engine
+    pub fn embedding_engine(&self) -> Arc<EmbeddingEngine> {
+        self.embedding_engine.clone()
+    }
+
+    /// Vector search fused with BM25 matches for `text`; plain vector search without text
+    pub async fn hybrid_search(
+        &self,
+        query: SearchQuery,
+        text: Option<String>,
+        weights: HybridWeights,
+    ) -> Result<Vec<VectorSearchResult>> {
+        let store = match &self.backend {
+            VectorBackend::Qdrant(manager) => {
+                let manager = manager.lock().await;
+                return self.search_engine.hybrid_search(&manager, query, text, weights).await;
+            }
+            VectorBackend::Embedded(store) => store,
+        };
+        let text = match text {
+            Some(text) if !text.trim().is_empty() => text,
+            _ => return store.search(&query).await,
+        };
+
+        let limit = query.limit;
+        let filter = query.filter.clone();
+        let collection = query.collection.clone();
+        let vector_results = store.search(&query.with_limit(search_engine::hybrid_candidates(limit))).await?;
+
+        let text_results = self.search_engine.text_search(
+            &collection,
+            &text,
+            search_engine::hybrid_candidates(limit),
+            filter.as_ref(),
+        );
+
+        Ok(self.search_engine.fuse_results(vector_results, text_results, filter.as_ref(), &weights, limit))
+    }
+
+    /// Embed `text` and run a hybrid search for it in a collection
+    pub async fn hybrid_search_text(
+        &self,
+        collection: &str,
+        text: &str,
+        limit: usize,
+        weights: HybridWeights,
+    ) -> Result<Vec<VectorSearchResult>> {
+        let vector = self.embedding_engine.generate_embedding(text).await?;
+        let query = SearchQuery::new(vector, collection.to_string()).with_limit(limit);
+        self.hybrid_search(query, Some(text.to_string()), weights).await
+    }
+
+    /// Embed `text` and return the closest points in a collection
+    pub async fn search_text(&self, collection: &str, text: &str, limit: usize) -> Result<Vec<VectorSearchResult>> {
+        let vector = self.embedding_engine.generate_embedding(text).await?;
+        let query = SearchQuery::new(vector, collection.to_string()).with_limit(limit);
+        self.search(query).await
+    }
+}
+
+#[async_trait]
+impl CrossEncoder for VectorStore {
+    async fn score(&self, query: &str, documents: &[String]) -> Result<Option<Vec<f32>>> {
+        self.embedding_engine.rerank(query, documents).await
     }
 }
 
@@ -102,26 +457,19 @@ impl VectorStore {
 pub async fn initialize_vector_store(state: &AppState) -> Result<()> {
     log::info!("Initializing vector store...");
     
-    if state.vector_store.is_some() {
-        log::info!("Vector store already initialized");
-        return Ok(());
-    }
-    
-    // Check if Qdrant is available
-    let qdrant_available = if let Some(vector_store) = &state.vector_store {
-        vector_store.is_available().await
-    } else {
-        false
+    // Check if Qdrant is available; the embedded index doesn't count
+    let qdrant_available = match &state.vector_store {
+        Some(vector_store) if vector_store.backend_name() == "qdrant" => vector_store.is_available().await,
+        _ => false,
     };
     
     // Update service status
     state.update_service_status(None, None, Some(qdrant_available)).await;
     
-    if qdrant_available {
-        log::info!("Qdrant vector database is available");
-    } else {
-        log::warn!("Qdrant not available - vector search features will be disabled");
-        log::info!("To enable vector search, please install and run Qdrant from https://qdrant.tech");
+    match &state.vector_store {
+        Some(_) if qdrant_available => log::info!("Qdrant vector database is available"),
+        Some(vector_store) => log::info!("Vector search is using the {} index", vector_store.backend_name()),
+        None => log::warn!("Vector store not available - vector search features will be disabled"),
     }
     
     Ok(())
@@ -0,0 +1,364 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use super::embedding_engine::EmbeddingProvider;
+
+/// Default Ollama endpoint used for embeddings
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Embedding provider backed by Ollama's embeddings API
+pub struct OllamaEmbeddingProvider {
+    base_url: String,
+    model: String,
+    client: reqwest::Client,
+    /// 0 until the first response tells us the real dimension
+    discovered_dimension: AtomicUsize,
+    /// Set once `/api/embed` turns out to be missing (Ollama < 0.2)
+    legacy_only: AtomicBool,
+}
+
+#[derive(Debug, Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(Debug, Serialize)]
+struct LegacyEmbeddingRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+impl OllamaEmbeddingProvider {
+    /// Create a provider for the given Ollama model (e.g. `nomic-embed-text`)
+    pub fn new(base_url: &str, model: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            client,
+            discovered_dimension: AtomicUsize::new(0),
+            legacy_only: AtomicBool::new(false),
+        }
+    }
+
+    /// Model name as sent to Ollama
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Batch request against `/api/embed`; `Ok(None)` means the endpoint is not available
+    async fn embed_batch_api(&self, texts: &[String]) -> Result<Option<Vec<Vec<f32>>>> {
+        let url = format!("{}/api/embed", self.base_url);
+        let response = self.client
+            .post(&url)
+            .json(&EmbedRequest { model: &self.model, input: texts })
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to reach Ollama at {}: {}", self.base_url, e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            // Older Ollama builds only expose /api/embeddings; a missing model also
+            // returns 404 but with an error body mentioning the model
+            let body = response.text().await.unwrap_or_default();
+            if body.contains("model") {
+                return Err(anyhow!(
+                    "Ollama embedding model '{}' not found; run `ollama pull {}`",
+                    self.model, self.model
+                ));
+            }
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Ollama embedding request failed: {}", error_text));
+        }
+
+        let parsed: EmbedResponse = response.json().await
+            .map_err(|e| anyhow!("Failed to parse Ollama embedding response: {}", e))?;
+
+        Ok(Some(parsed.embeddings))
+    }
+
+    /// Single request against the legacy `/api/embeddings` endpoint
+    async fn embed_legacy(&self, text: &str) -> Result<Vec<f32>> {
+        let url = format!("{}/api/embeddings", self.base_url);
+        let response = self.client
+            .post(&url)
+            .json(&LegacyEmbeddingRequest { model: &self.model, prompt: text })
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to reach Ollama at {}: {}", self.base_url, e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Ollama embedding request failed: {}", error_text));
+        }
+
+        let parsed: LegacyEmbeddingResponse = response.json().await
+            .map_err(|e| anyhow!("Failed to parse Ollama embedding response: {}", e))?;
+
+        Ok(parsed.embedding)
+    }
+
+    fn record_dimension(&self, embeddings: &[Vec<f32>]) -> Result<()> {
+        for embedding in embeddings {
+            if embedding.is_empty() {
+                return Err(anyhow!("Ollama returned an empty embedding for model '{}'", self.model));
+            }
+
+            let previous = self.discovered_dimension
+                .compare_exchange(0, embedding.len(), Ordering::SeqCst, Ordering::SeqCst)
+                .unwrap_or_else(|current| current);
+
+            if previous != 0 && previous != embedding.len() {
+                return Err(anyhow!(
+                    "Ollama model '{}' returned {}-dimensional vectors, expected {}",
+                    self.model, embedding.len(), previous
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn name(&self) -> String {
+        format!("ollama:{}", self.model)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let embeddings = if self.legacy_only.load(Ordering::Relaxed) {
+            None
+        } else {
+            self.embed_batch_api(texts).await?
+        };
+
+        let embeddings = match embeddings {
+            Some(embeddings) => embeddings,
+            None => {
+                self.legacy_only.store(true, Ordering::Relaxed);
+                let mut embeddings = Vec::with_capacity(texts.len());
+                for text in texts {
+                    embeddings.push(self.embed_legacy(text).await?);
+                }
+                embeddings
+            }
+        };
+
+        if embeddings.len() != texts.len() {
+            return Err(anyhow!(
+                "Ollama returned {} embeddings for {} inputs",
+                embeddings.len(), texts.len()
+            ));
+        }
+
+        self.record_dimension(&embeddings)?;
+        Ok(embeddings)
+    }
+
+    async fn dimension(&self) -> Result<usize> {
+        let known = self.discovered_dimension.load(Ordering::SeqCst);
+        if known > 0 {
+            return Ok(known);
+        }
+
+        // Discover the dimension by embedding a short probe
+        let probe = self.embed_batch(&["dimension probe".to_string()]).await?;
+        Ok(probe[0].len())
+    }
+
+    async fn is_ready(&self) -> bool {
+        match self.client.get(&format!("{}/api/tags", self.base_url)).send().await {
+            Ok(response) => response.status().is_success(),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_server {
+    //! Minimal HTTP stand-in for Ollama's embedding endpoints
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Start a stand-in that answers `/api/embed`, `/api/embeddings` and `/api/tags`.
+    ///
+    /// Each text is embedded as `[len, 1.0, 0.0, ...]` padded to `dimension`. When
+    /// `legacy` is set, `/api/embed` returns 404 like older Ollama releases.
+    pub async fn spawn(dimension: usize, legacy: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else { break };
+                tokio::spawn(async move {
+                    let (path, body) = match read_request(&mut socket).await {
+                        Some(request) => request,
+                        None => return,
+                    };
+
+                    let embed = |text: &str| {
+                        let mut v = vec![0.0f32; dimension];
+                        v[0] = text.len() as f32;
+                        if dimension > 1 {
+                            v[1] = 1.0;
+                        }
+                        v
+                    };
+
+                    let (status, payload) = match path.as_str() {
+                        "/api/tags" => ("200 OK", serde_json::json!({ "models": [] })),
+                        "/api/embed" if legacy => ("404 Not Found", serde_json::json!({ "error": "404 page not found" })),
+                        "/api/embed" => {
+                            let inputs: Vec<String> = serde_json::from_value(body["input"].clone()).unwrap_or_default();
+                            let embeddings: Vec<Vec<f32>> = inputs.iter().map(|t| embed(t)).collect();
+                            ("200 OK", serde_json::json!({ "embeddings": embeddings }))
+                        }
+                        "/api/embeddings" => {
+                            let prompt = body["prompt"].as_str().unwrap_or_default();
+                            ("200 OK", serde_json::json!({ "embedding": embed(prompt) }))
+                        }
+                        _ => ("404 Not Found", serde_json::json!({ "error": "404 page not found" })),
+                    };
+
+                    write_response(&mut socket, status, &payload.to_string()).await;
+                });
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    pub async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<(String, serde_json::Value)> {
+        let (_, path, body) = read_raw_request(socket).await?;
+        let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+        Some((path, body))
+    }
+
+    /// Method, path and undecoded body of the next request
+    pub async fn read_raw_request(socket: &mut tokio::net::TcpStream) -> Option<(String, String, Vec<u8>)> {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 4096];
+
+        let header_end = loop {
+            let n = socket.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                return None;
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+            if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+
+        let headers = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+        let mut request_line = headers.split_whitespace();
+        let method = request_line.next()?.to_string();
+        let path = request_line.next()?.to_string();
+        let content_length = headers
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+            })
+            .unwrap_or(0);
+
+        while buffer.len() < header_end + content_length {
+            let n = socket.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                break;
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        }
+
+        Some((method, path, buffer.split_off(header_end)))
+    }
+
+    pub async fn write_response(socket: &mut tokio::net::TcpStream, status: &str, body: &str) {
+        write_bytes_response(socket, status, "application/json", body.as_bytes()).await;
+    }
+
+    pub async fn write_bytes_response(socket: &mut tokio::net::TcpStream, status: &str, content_type: &str, body: &[u8]) {
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            content_type,
+            body.len()
+        );
+        let _ = socket.write_all(head.as_bytes()).await;
+        let _ = socket.write_all(body).await;
+        let _ = socket.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_batch_embedding_via_embed_api() {
+        let url = test_server::spawn(8, false).await;
+        let provider = OllamaEmbeddingProvider::new(&url, "nomic-embed-text");
+
+        let texts = vec!["a".to_string(), "abc".to_string()];
+        let embeddings = provider.embed_batch(&texts).await.unwrap();
+
+        assert_eq!(embeddings.len(), 2);
+        assert_eq!(embeddings[0].len(), 8);
+        assert_eq!(embeddings[1][0], 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_legacy_endpoint() {
+        let url = test_server::spawn(4, true).await;
+        let provider = OllamaEmbeddingProvider::new(&url, "all-minilm");
+
+        let texts = vec!["one".to_string(), "three".to_string()];
+        let embeddings = provider.embed_batch(&texts).await.unwrap();
+
+        assert_eq!(embeddings.len(), 2);
+        assert_eq!(embeddings[1][0], 5.0);
+        assert!(provider.legacy_only.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_dimension_discovery() {
+        let url = test_server::spawn(16, false).await;
+        let provider = OllamaEmbeddingProvider::new(&url, "mxbai-embed-large");
+
+        assert_eq!(provider.dimension().await.unwrap(), 16);
+        assert!(provider.is_ready().await);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server() {
+        let provider = OllamaEmbeddingProvider::new("http://127.0.0.1:9", "nomic-embed-text");
+        assert!(!provider.is_ready().await);
+        assert!(provider.embed_batch(&["x".to_string()]).await.is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ndarray::Array2;
+use ort::{GraphOptimizationLevel, Session};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokenizers::Tokenizer;
+
+use super::embedding_engine::{load_tokenizer, mean_pool, EmbeddingProvider};
+
+/// File names checked (in order) for the exported model inside a model directory
+const ONNX_MODEL_FILES: &[&str] = &["model.onnx", "onnx/model.onnx", "model_quantized.onnx", "onnx/model_quantized.onnx"];
+
+/// Sentence-embedding model run locally through ONNX Runtime
+pub struct OnnxEmbeddingProvider {
+    name: String,
+    session: Arc<Session>,
+    tokenizer: Arc<Tokenizer>,
+    uses_token_type_ids: bool,
+    dimension: usize,
+}
+
+impl OnnxEmbeddingProvider {
+    /// Locate the ONNX file in a model directory, if there is one
+    pub fn find_model_file(model_dir: &Path) -> Option<PathBuf> {
+        ONNX_MODEL_FILES
+            .iter()
+            .map(|file| model_dir.join(file))
+            .find(|path| path.is_file())
+    }
+
+    /// Load `model.onnx` and `tokenizer.json` from a model directory
+    pub fn load(name: &str, model_dir: &Path, max_sequence_length: usize) -> Result<Self> {
+        let model_path = Self::find_model_file(model_dir)
+            .ok_or_else(|| anyhow!("No ONNX model found in {}", model_dir.display()))?;
+
+        let tokenizer = load_tokenizer(&model_dir.join("tokenizer.json"), max_sequence_length, true)?;
+
+        let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let session = Session::builder()
+            .and_then(|b| b.with_optimization_level(GraphOptimizationLevel::Level3))
+            .and_then(|b| b.with_intra_threads(threads))
+            .and_then(|b| b.commit_from_file(&model_path))
+            .map_err(|e| anyhow!("Failed to load ONNX model {}: {}", model_path.display(), e))?;
+
+        let uses_token_type_ids = session.inputs.iter().any(|input| input.name == "token_type_ids");
+
+        let mut provider = Self {
+            name: name.to_string(),
+            session: Arc::new(session),
+            tokenizer: Arc::new(tokenizer),
+            uses_token_type_ids,
+            dimension: 0,
+        };
+
+        // Run one probe so the dimension is known up front
+        let probe = run_batch(&provider.session, &provider.tokenizer, provider.uses_token_type_ids, &["probe".to_string()])?;
+        provider.dimension = probe.first().map(|v| v.len()).unwrap_or(0);
+
+        log::info!("Loaded ONNX embedding model {} ({}D) from {}", name, provider.dimension, model_path.display());
+        Ok(provider)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OnnxEmbeddingProvider {
+    fn name(&self) -> String {
+        format!("onnx:{}", self.name)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let session = self.session.clone();
+        let tokenizer = self.tokenizer.clone();
+        let uses_token_type_ids = self.uses_token_type_ids;
+        let texts = texts.to_vec();
+
+        // Inference is CPU-bound, keep it off the async workers
+        tokio::task::spawn_blocking(move || run_batch(&session, &tokenizer, uses_token_type_ids, &texts))
+            .await
+            .map_err(|e| anyhow!("ONNX embedding task failed: {}", e))?
+    }
+
+    async fn dimension(&self) -> Result<usize> {
+        Ok(self.dimension)
+    }
+
+    async fn is_ready(&self) -> bool {
+        true
+    }
+}
+
+fn run_batch(
+    session: &Session,
+    tokenizer: &Tokenizer,
+    uses_token_type_ids: bool,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    let encodings = tokenizer
+        .encode_batch(texts.to_vec(), true)
+        .map_err(|e| anyhow!("Tokenization failed: {}", e))?;
+
+    let batch = encodings.len();
+    let seq_len = encodings.first().map(|e| e.get_ids().len()).unwrap_or(0);
+
+    let mut ids = Vec::with_capacity(batch * seq_len);
+    let mut mask = Vec::with_capacity(batch * seq_len);
+    let mut type_ids = Vec::with_capacity(batch * seq_len);
+    for encoding in &encodings {
+        ids.extend(encoding.get_ids().iter().map(|&x| x as i64));
+        mask.extend(encoding.get_attention_mask().iter().map(|&x| x as i64));
+        type_ids.extend(encoding.get_type_ids().iter().map(|&x| x as i64));
+    }
+
+    let ids = Array2::from_shape_vec((batch, seq_len), ids)?;
+    let attention = Array2::from_shape_vec((batch, seq_len), mask.clone())?;
+
+    let outputs = if uses_token_type_ids {
+        let type_ids = Array2::from_shape_vec((batch, seq_len), type_ids)?;
+        session.run(ort::inputs![
+            "input_ids" => ids,
+            "attention_mask" => attention,
+            "token_type_ids" => type_ids,
+        ]?)?
+    } else {
+        session.run(ort::inputs![
+            "input_ids" => ids,
+            "attention_mask" => attention,
+        ]?)?
+    };
+
+    let output = outputs[0].try_extract_tensor::<f32>()?;
+    let shape = output.shape().to_vec();
+    let values: Vec<f32> = output.iter().copied().collect();
+
+    match shape.as_slice() {
+        // Token embeddings: [batch, seq, dim] -> mean-pool over the attention mask
+        [b, s, dim] if *b == batch && *s == seq_len => Ok(mean_pool(&values, &mask, batch, seq_len, *dim)),
+        // Model already pools: [batch, dim]
+        [b, dim] if *b == batch => Ok(values.chunks(*dim).map(|v| v.to_vec()).collect()),
+        _ => Err(anyhow!("Unexpected ONNX output shape {:?}", shape)),
+    }
+}
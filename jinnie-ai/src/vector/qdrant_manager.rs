@@ -1,6 +1,8 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::time::Duration;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
 use super::{VectorPoint, VectorSearchResult, CollectionSchema};
@@ -33,6 +35,16 @@ pub struct QdrantStatus {
     pub memory_usage_mb: f64,
 }
 
+/// A collection snapshot stored on the Qdrant server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    #[serde(default)]
+    pub creation_time: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+}
+
 /// Manager for Qdrant vector database operations
 pub struct QdrantManager {
     config: QdrantConfig,
@@ -339,6 +351,59 @@ impl QdrantManager {
         }
     }
 
+    /// One page of points with payloads but without vectors, plus the offset of the next page
+    pub async fn scroll_points(
+        &self,
+        collection_name: &str,
+        offset: Option<serde_json::Value>,
+        limit: usize,
+    ) -> Result<(Vec<VectorPoint>, Option<serde_json::Value>)> {
+        let url = format!("{}/collections/{}/points/scroll", self.base_url, collection_name);
+        
+        let mut scroll_request = serde_json::json!({
+            "limit": limit,
+            "with_payload": true,
+            "with_vector": false
+        });
+
+        if let Some(offset) = offset {
+            scroll_request["offset"] = offset;
+        }
+
+        let mut request = self.client.post(&url).json(&scroll_request);
+        
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("api-key", api_key);
+        }
+
+        let response = request.send().await?;
+        
+        if response.status().is_success() {
+            let scroll_response: serde_json::Value = response.json().await?;
+            let result = &scroll_response["result"];
+
+            let points = result["points"].as_array()
+                .map(|points| {
+                    points.iter()
+                        .filter_map(|point| {
+                            let id = Uuid::parse_str(point["id"].as_str()?).ok()?;
+                            let payload = point["payload"].as_object()
+                                .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+                                .unwrap_or_default();
+                            Some(VectorPoint { id, vector: vec![], payload })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let next_offset = Some(result["next_page_offset"].clone()).filter(|offset| !offset.is_null());
+            Ok((points, next_offset))
+        } else {
+            let error_text = response.text().await?;
+            Err(anyhow::anyhow!("Failed to scroll points: {}", error_text))
+        }
+    }
+
     /// Delete a point by ID
     pub async fn delete_point(&self, collection_name: &str, point_id: Uuid) -> Result<()> {
         let url = format!("{}/collections/{}/points/delete", self.base_url, collection_name);
@@ -472,6 +537,146 @@ impl QdrantManager {
         }
     }
 
+    /// Snapshot a collection on the server; the snapshot can then be downloaded
+    pub async fn create_snapshot(&self, collection_name: &str) -> Result<SnapshotInfo> {
+        let url = format!("{}/collections/{}/snapshots", self.base_url, collection_name);
+        
+        let mut request = self.client.post(&url);
+        
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("api-key", api_key);
+        }
+
+        let response = request.send().await?;
+        
+        if response.status().is_success() {
+            let snapshot_response: serde_json::Value = response.json().await?;
+            let snapshot: SnapshotInfo = serde_json::from_value(snapshot_response["result"].clone())?;
+            log::info!("Created snapshot '{}' of collection '{}'", snapshot.name, collection_name);
+            Ok(snapshot)
+        } else {
+            let error_text = response.text().await?;
+            Err(anyhow::anyhow!("Failed to create snapshot: {}", error_text))
+        }
+    }
+
+    /// Snapshots of a collection held by the server
+    pub async fn list_snapshots(&self, collection_name: &str) -> Result<Vec<SnapshotInfo>> {
+        let url = format!("{}/collections/{}/snapshots", self.base_url, collection_name);
+        
+        let mut request = self.client.get(&url);
+        
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("api-key", api_key);
+        }
+
+        let response = request.send().await?;
+        
+        if response.status().is_success() {
+            let snapshots_response: serde_json::Value = response.json().await?;
+            Ok(serde_json::from_value(snapshots_response["result"].clone()).unwrap_or_default())
+        } else {
+            let error_text = response.text().await?;
+            Err(anyhow::anyhow!("Failed to list snapshots: {}", error_text))
+        }
+    }
+
+    /// Stream a snapshot to `destination`, returning the number of bytes written
+    pub async fn download_snapshot(&self, collection_name: &str, snapshot_name: &str, destination: &Path) -> Result<u64> {
+        let mut response = self.snapshot_response(collection_name, snapshot_name).await?;
+
+        // Write beside the destination and rename, so a failed download leaves no partial file
+        let partial = destination.with_extension("partial");
+        let mut file = tokio::fs::File::create(&partial).await?;
+        let mut written = 0u64;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        file.sync_all().await?;
+        tokio::fs::rename(&partial, destination).await?;
+
+        Ok(written)
+    }
+
+    /// Download a snapshot into memory, e.g. to encrypt it before it touches the disk
+    pub async fn fetch_snapshot(&self, collection_name: &str, snapshot_name: &str) -> Result<Vec<u8>> {
+        let response = self.snapshot_response(collection_name, snapshot_name).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    async fn snapshot_response(&self, collection_name: &str, snapshot_name: &str) -> Result<reqwest::Response> {
+        let url = format!("{}/collections/{}/snapshots/{}", self.base_url, collection_name, snapshot_name);
+        
+        let mut request = self.client.get(&url);
+        
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("api-key", api_key);
+        }
+
+        let response = request.send().await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!("Failed to download snapshot: {}", error_text));
+        }
+        Ok(response)
+    }
+
+    /// Replace a collection with the snapshot file at `snapshot_path`, creating it if needed
+    pub async fn restore_snapshot(&self, collection_name: &str, snapshot_path: &Path) -> Result<()> {
+        let file_name = snapshot_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("{}.snapshot", collection_name));
+        let bytes = tokio::fs::read(snapshot_path).await?;
+        self.upload_snapshot(collection_name, file_name, bytes).await
+    }
+
+    /// Replace a collection with a snapshot held in memory, such as one decrypted from a backup
+    pub async fn upload_snapshot(&self, collection_name: &str, file_name: String, bytes: Vec<u8>) -> Result<()> {
+        let url = format!("{}/collections/{}/snapshots/upload?priority=snapshot", self.base_url, collection_name);
+        let form = reqwest::multipart::Form::new()
+            .part("snapshot", reqwest::multipart::Part::bytes(bytes).file_name(file_name));
+
+        // Uploads can take far longer than regular requests
+        let mut request = self.client.post(&url).multipart(form).timeout(Duration::from_secs(3600));
+        
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("api-key", api_key);
+        }
+
+        let response = request.send().await?;
+        
+        if response.status().is_success() {
+            log::info!("Restored collection '{}' from snapshot", collection_name);
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(anyhow::anyhow!("Failed to restore snapshot: {}", error_text))
+        }
+    }
+
+    /// Delete a snapshot from the server
+    pub async fn delete_snapshot(&self, collection_name: &str, snapshot_name: &str) -> Result<()> {
+        let url = format!("{}/collections/{}/snapshots/{}", self.base_url, collection_name, snapshot_name);
+        
+        let mut request = self.client.delete(&url);
+        
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("api-key", api_key);
+        }
+
+        let response = request.send().await?;
+        
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(anyhow::anyhow!("Failed to delete snapshot: {}", error_text))
+        }
+    }
+
     /// Shutdown the manager and close connections
     pub async fn shutdown(&self) -> Result<()> {
         // In a real implementation, we would close any persistent connections here
@@ -483,7 +688,146 @@ impl QdrantManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::vector::ollama_embeddings::test_server::{read_raw_request, write_bytes_response};
     use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Snapshots held by the stand-in server, and the bodies uploaded to it
+    #[derive(Default)]
+    struct SnapshotServer {
+        snapshots: Vec<(String, Vec<u8>)>,
+        uploads: Vec<(String, Vec<u8>)>,
+    }
+
+    /// Stand-in for Qdrant holding one empty "memories" collection, with its snapshot endpoints
+    async fn spawn_snapshot_server(state: Arc<Mutex<SnapshotServer>>) -> QdrantConfig {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else { break };
+                let state = state.clone();
+                tokio::spawn(async move {
+                    let Some((method, path, body)) = read_raw_request(&mut socket).await else { return };
+                    let snapshot_name = path.strip_prefix("/collections/memories/snapshots/").map(str::to_string);
+
+                    let json = |status, payload: serde_json::Value| (status, "application/json", payload.to_string().into_bytes());
+                    let (status, content_type, body) = {
+                        let mut state = state.lock().unwrap();
+                        let info = |name: &str, bytes: &[u8]| serde_json::json!({ "name": name, "creation_time": "2026-10-18T10:00:00", "size": bytes.len() });
+                        match (method.as_str(), path.as_str(), snapshot_name) {
+                            ("GET", "/", _) => json("200 OK", serde_json::json!({ "title": "qdrant" })),
+                            ("GET", "/collections", _) => {
+                                json("200 OK", serde_json::json!({ "result": { "collections": [{ "name": "memories" }] }, "status": "ok" }))
+                            }
+                            ("POST", "/collections/memories/points/scroll", _) => {
+                                json("200 OK", serde_json::json!({ "result": { "points": [], "next_page_offset": null }, "status": "ok" }))
+                            }
+                            ("POST", "/collections/memories/snapshots", _) => {
+                                let name = format!("memories-{}.snapshot", state.snapshots.len() + 1);
+                                let bytes = format!("vectors of {}", name).into_bytes();
+                                let result = info(&name, &bytes);
+                                state.snapshots.push((name, bytes));
+                                json("200 OK", serde_json::json!({ "result": result, "status": "ok" }))
+                            }
+                            ("GET", "/collections/memories/snapshots", _) => {
+                                let result: Vec<_> = state.snapshots.iter().map(|(name, bytes)| info(name, bytes)).collect();
+                                json("200 OK", serde_json::json!({ "result": result, "status": "ok" }))
+                            }
+                            ("POST", _, Some(name)) if name.starts_with("upload") => {
+                                state.uploads.push((path.clone(), body));
+                                json("200 OK", serde_json::json!({ "result": true, "status": "ok" }))
+                            }
+                            ("GET", _, Some(name)) => match state.snapshots.iter().find(|(n, _)| *n == name) {
+                                Some((_, bytes)) => ("200 OK", "application/octet-stream", bytes.clone()),
+                                None => json("404 Not Found", serde_json::json!({ "status": { "error": "Snapshot not found" } })),
+                            },
+                            ("DELETE", _, Some(name)) => {
+                                state.snapshots.retain(|(n, _)| *n != name);
+                                json("200 OK", serde_json::json!({ "result": true, "status": "ok" }))
+                            }
+                            _ => json("404 Not Found", serde_json::json!({ "status": { "error": "Not found" } })),
+                        }
+                    };
+
+                    write_bytes_response(&mut socket, status, content_type, &body).await;
+                });
+            }
+        });
+
+        QdrantConfig { host: "127.0.0.1".to_string(), port, ..QdrantConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let state = Arc::new(Mutex::new(SnapshotServer::default()));
+        let manager = QdrantManager::new(spawn_snapshot_server(state.clone()).await).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let snapshot = manager.create_snapshot("memories").await.unwrap();
+        assert_eq!(snapshot.name, "memories-1.snapshot");
+        assert_eq!(manager.list_snapshots("memories").await.unwrap(), vec![snapshot.clone()]);
+
+        let file = dir.path().join("memories.snapshot");
+        let written = manager.download_snapshot("memories", &snapshot.name, &file).await.unwrap();
+        assert_eq!(written, snapshot.size);
+        assert_eq!(std::fs::read(&file).unwrap(), b"vectors of memories-1.snapshot");
+        assert!(manager.download_snapshot("memories", "missing.snapshot", &dir.path().join("missing")).await.is_err());
+        assert!(!dir.path().join("missing.partial").exists());
+        assert_eq!(manager.fetch_snapshot("memories", &snapshot.name).await.unwrap(), b"vectors of memories-1.snapshot");
+
+        manager.restore_snapshot("memories", &file).await.unwrap();
+        {
+            let state = state.lock().unwrap();
+            let (path, body) = &state.uploads[0];
+            assert_eq!(path, "/collections/memories/snapshots/upload?priority=snapshot");
+            let body = String::from_utf8_lossy(body);
+            assert!(body.contains("name=\"snapshot\"; filename=\"memories.snapshot\""));
+            assert!(body.contains("vectors of memories-1.snapshot"));
+        }
+
+        manager.delete_snapshot("memories", &snapshot.name).await.unwrap();
+        assert!(manager.list_snapshots("memories").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_backups_are_sealed() {
+        use crate::storage::encryption::{set_active_cipher, DataCipher, KdfParams, Keyring};
+        use crate::vector::VectorStore;
+
+        let _lock = crate::storage::lock_global_storage();
+        let state = Arc::new(Mutex::new(SnapshotServer::default()));
+        let qdrant = spawn_snapshot_server(state.clone()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = crate::config::AppConfig::with_data_dir(dir.path().to_path_buf());
+        config.vector.index_backend = "qdrant".to_string();
+        config.vector.embedding_backend = "hash".to_string();
+        config.vector.qdrant_host = qdrant.host;
+        config.vector.qdrant_port = qdrant.port;
+        let store = VectorStore::new(config).await.unwrap();
+
+        let (_, cipher) = Keyring::create_with_params("secret", KdfParams::with_costs(64, 1, 1)).unwrap();
+        set_active_cipher(Some(Arc::new(cipher)));
+        let backup_dir = dir.path().join("vectors");
+        let backups = store.backup_collections(&backup_dir).await;
+        let sealed = std::fs::read(backup_dir.join("memories.snapshot"));
+        let restored = match &backups {
+            Ok(backups) => store.restore_collections(&backup_dir, backups).await,
+            Err(e) => Err(anyhow::anyhow!("{}", e)),
+        };
+        set_active_cipher(None);
+
+        assert_eq!(backups.unwrap().len(), 1);
+        let sealed = sealed.unwrap();
+        assert!(DataCipher::is_encrypted(&sealed));
+        assert!(!String::from_utf8_lossy(&sealed).contains("vectors of"));
+        restored.unwrap();
+        // Qdrant gets the snapshot as it was before sealing
+        let state = state.lock().unwrap();
+        assert!(String::from_utf8_lossy(&state.uploads[0].1).contains("vectors of memories-1.snapshot"));
+        assert!(state.snapshots.is_empty());
+    }
 
     #[tokio::test]
     async fn test_qdrant_manager_creation() {
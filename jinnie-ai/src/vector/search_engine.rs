@@ -1,9 +1,19 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::RwLock;
 use uuid::Uuid;
 
+use super::text_index::Bm25Index;
 use super::{QdrantManager, VectorSearchResult};
 
+/// Payload fields whose text is keyword indexed for hybrid search
+pub const TEXT_FIELDS: [&str; 2] = ["title", "content"];
+
+/// Hybrid search looks this many times deeper into each ranking before fusing
+const HYBRID_CANDIDATE_FACTOR: usize = 3;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchQuery {
     pub vector: Vec<f32>,
@@ -56,10 +66,20 @@ pub enum SimilarityMetric {
     DotProduct,
 }
 
+/// Keyword index of one collection, with the payloads its hits are filtered on
+#[derive(Default)]
+struct TextCollection {
+    index: Bm25Index<Uuid>,
+    /// Each indexed point's payload without its `TEXT_FIELDS`
+    payloads: HashMap<Uuid, HashMap<String, serde_json::Value>>,
+}
+
 /// Engine for performing semantic searches
 pub struct SemanticSearchEngine {
     default_similarity: SimilarityMetric,
     default_threshold: f32,
+    /// BM25 index of each collection's `TEXT_FIELDS`, kept in step by `VectorStore`
+    text_indexes: RwLock<HashMap<String, TextCollection>>,
 }
 
 impl SemanticSearchEngine {
@@ -68,9 +88,77 @@ impl SemanticSearchEngine {
         Self {
             default_similarity: SimilarityMetric::Cosine,
             default_threshold: 0.5,
+            text_indexes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Keyword index a point's payload text, or drop it when there is none
+    pub fn index_payload(&self, collection: &str, id: Uuid, payload: &HashMap<String, serde_json::Value>) {
+        let text = payload_text(payload);
+        let mut indexes = self.text_indexes.write().unwrap();
+        let text_collection = indexes.entry(collection.to_string()).or_default();
+        if text.is_empty() {
+            text_collection.index.remove(&id);
+            text_collection.payloads.remove(&id);
+        } else {
+            text_collection.index.insert(id, &text);
+            let fields = payload
+                .iter()
+                .filter(|(field, _)| !TEXT_FIELDS.contains(&field.as_str()))
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect();
+            text_collection.payloads.insert(id, fields);
         }
     }
 
+    pub fn remove_text(&self, collection: &str, id: Uuid) {
+        if let Some(text_collection) = self.text_indexes.write().unwrap().get_mut(collection) {
+            text_collection.index.remove(&id);
+            text_collection.payloads.remove(&id);
+        }
+    }
+
+    /// Whether a point's payload text is in the collection's keyword index
+    pub fn has_text(&self, collection: &str, id: Uuid) -> bool {
+        self.text_indexes
+            .read()
+            .unwrap()
+            .get(collection)
+            .is_some_and(|text_collection| text_collection.index.contains(&id))
+    }
+
+    pub fn clear_text(&self, collection: &str) {
+        self.text_indexes.write().unwrap().remove(collection);
+    }
+
+    /// The best `limit` BM25 matches for `text` in a collection among the points passing
+    /// `filter`. Their payloads leave out the indexed `TEXT_FIELDS`.
+    pub fn text_search(
+        &self,
+        collection: &str,
+        text: &str,
+        limit: usize,
+        filter: Option<&SearchFilter>,
+    ) -> Vec<VectorSearchResult> {
+        let indexes = self.text_indexes.read().unwrap();
+        let Some(text_collection) = indexes.get(collection) else { return vec![] };
+        let payload = |id: &Uuid| text_collection.payloads.get(id);
+
+        // Filtered before truncating, so points the filter rejects can't take up the limit
+        let mut hits: Vec<(Uuid, f32)> = text_collection
+            .index
+            .scores(text)
+            .into_iter()
+            .filter(|(id, _)| filter.map_or(true, |filter| payload(id).is_some_and(|payload| filter.matches(payload))))
+            .collect();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hits.truncate(limit);
+
+        hits.into_iter()
+            .map(|(id, score)| VectorSearchResult { id, score, payload: payload(&id).cloned().unwrap_or_default() })
+            .collect()
+    }
+
     /// Perform a semantic search using vector similarity
     pub async fn search(
         &self,
@@ -159,7 +247,7 @@ impl SemanticSearchEngine {
         self.deduplicate_and_rerank(all_results, limit)
     }
 
-    /// Hybrid search combining vector similarity and text matching
+    /// Hybrid search combining vector similarity and BM25 text matching, fused per `weights`
     pub async fn hybrid_search(
         &self,
         qdrant_manager: &QdrantManager,
@@ -167,23 +255,54 @@ impl SemanticSearchEngine {
         text_query: Option<String>,
         weights: HybridWeights,
     ) -> Result<Vec<VectorSearchResult>> {
-        // Get vector similarity results
-        let vector_results = self.search(qdrant_manager, vector_query).await?;
-        
-        if let Some(text) = text_query {
-            // In a real implementation, this would:
-            // 1. Perform full-text search on content fields
-            // 2. Combine vector and text scores using weights
-            // 3. Re-rank results based on combined score
-            
-            let _text = text; // Suppress unused warning
-            let _weights = weights; // Suppress unused warning
-            
-            // For now, just return vector results
-            Ok(vector_results)
-        } else {
-            Ok(vector_results)
+        let text = match text_query {
+            Some(text) if !text.trim().is_empty() => text,
+            _ => return self.search(qdrant_manager, vector_query).await,
+        };
+
+        let limit = vector_query.limit;
+        let collection = vector_query.collection.clone();
+        let filter = vector_query.filter.clone();
+        let vector_results = self
+            .search(qdrant_manager, vector_query.with_limit(hybrid_candidates(limit)))
+            .await?;
+
+        let text_results = self.text_search(&collection, &text, hybrid_candidates(limit), filter.as_ref());
+
+        Ok(self.fuse_results(vector_results, text_results, filter.as_ref(), &weights, limit))
+    }
+
+    /// Fuse vector and text results into the best `limit`, dropping text hits that fail
+    /// `filter`. Fused payloads come from the vector hit when there is one.
+    pub fn fuse_results(
+        &self,
+        vector_results: Vec<VectorSearchResult>,
+        text_results: Vec<VectorSearchResult>,
+        filter: Option<&SearchFilter>,
+        weights: &HybridWeights,
+        limit: usize,
+    ) -> Vec<VectorSearchResult> {
+        let text_results: Vec<VectorSearchResult> = text_results
+            .into_iter()
+            .filter(|result| filter.map_or(true, |filter| filter.matches(&result.payload)))
+            .collect();
+        let vector_scores: Vec<(Uuid, f32)> = vector_results.iter().map(|result| (result.id, result.score)).collect();
+        let text_scores: Vec<(Uuid, f32)> = text_results.iter().map(|result| (result.id, result.score)).collect();
+
+        let mut payloads: HashMap<Uuid, HashMap<String, serde_json::Value>> = HashMap::new();
+        for result in text_results.into_iter().chain(vector_results) {
+            payloads.insert(result.id, result.payload);
         }
+
+        fuse_scores(&vector_scores, &text_scores, weights)
+            .into_iter()
+            .take(limit)
+            .map(|(id, score)| VectorSearchResult {
+                id,
+                score,
+                payload: payloads.remove(&id).unwrap_or_default(),
+            })
+            .collect()
     }
 
     /// Search within a specific date range
@@ -437,10 +556,24 @@ impl Default for SemanticSearchEngine {
 
 // Supporting types
 
-#[derive(Debug, Clone)]
+/// How hybrid search merges the vector and text rankings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionMethod {
+    /// Weighted sum of vector similarity and BM25 scaled by the best text hit
+    Weighted,
+    /// Reciprocal rank fusion: weighted sum of `1 / (rrf_k + rank)`, ignoring raw scores
+    Rrf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct HybridWeights {
     pub vector_weight: f32,
     pub text_weight: f32,
+    pub fusion: FusionMethod,
+    /// Damps the lead of the top ranks under RRF
+    pub rrf_k: f32,
 }
 
 impl Default for HybridWeights {
@@ -448,8 +581,73 @@ impl Default for HybridWeights {
         Self {
             vector_weight: 0.7,
             text_weight: 0.3,
+            fusion: FusionMethod::Weighted,
+            rrf_k: 60.0,
+        }
+    }
+}
+
+/// Merge a vector ranking and a text ranking into fused scores in [0, 1], best first.
+/// Both inputs are `(key, score)` with higher scores better.
+pub fn fuse_scores<K: Clone + Eq + Hash + Ord>(
+    vector: &[(K, f32)],
+    text: &[(K, f32)],
+    weights: &HybridWeights,
+) -> Vec<(K, f32)> {
+    let total_weight = weights.vector_weight + weights.text_weight;
+    if total_weight <= 0.0 {
+        return vec![];
+    }
+
+    let ranked = |scores: &[(K, f32)]| {
+        let mut scores = scores.to_vec();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scores
+    };
+    let (vector, text) = (ranked(vector), ranked(text));
+
+    let mut fused: HashMap<K, f32> = HashMap::new();
+    match weights.fusion {
+        FusionMethod::Weighted => {
+            let best_text = text.first().map_or(0.0, |(_, score)| *score);
+            for (key, score) in &vector {
+                *fused.entry(key.clone()).or_insert(0.0) += weights.vector_weight * score.clamp(0.0, 1.0);
+            }
+            for (key, score) in &text {
+                let scaled = if best_text > 0.0 { score / best_text } else { 0.0 };
+                *fused.entry(key.clone()).or_insert(0.0) += weights.text_weight * scaled;
+            }
+            fused.values_mut().for_each(|score| *score /= total_weight);
+        }
+        FusionMethod::Rrf => {
+            for (ranking, weight) in [(&vector, weights.vector_weight), (&text, weights.text_weight)] {
+                for (rank, (key, _)) in ranking.iter().enumerate() {
+                    *fused.entry(key.clone()).or_insert(0.0) += weight / (weights.rrf_k + rank as f32 + 1.0);
+                }
+            }
+            // Scaled so first place in both rankings scores 1.0
+            let best = total_weight / (weights.rrf_k + 1.0);
+            fused.values_mut().for_each(|score| *score /= best);
         }
     }
+
+    let mut fused: Vec<(K, f32)> = fused.into_iter().collect();
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    fused
+}
+
+/// How deep hybrid search looks into each ranking for `limit` results
+pub fn hybrid_candidates(limit: usize) -> usize {
+    limit.saturating_mul(HYBRID_CANDIDATE_FACTOR)
+}
+
+/// The `TEXT_FIELDS` of a payload joined into one text
+fn payload_text(payload: &HashMap<String, serde_json::Value>) -> String {
+    TEXT_FIELDS
+        .iter()
+        .filter_map(|field| payload.get(*field).and_then(|value| value.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -523,6 +721,14 @@ impl SearchFilter {
         self.should.push(condition);
         self
     }
+
+    /// Evaluate the filter against a payload the way Qdrant does: every `must`,
+    /// no `must_not`, and at least one `should` when any are given
+    pub fn matches(&self, payload: &HashMap<String, serde_json::Value>) -> bool {
+        self.must.iter().all(|condition| condition.matches(payload))
+            && !self.must_not.iter().any(|condition| condition.matches(payload))
+            && (self.should.is_empty() || self.should.iter().any(|condition| condition.matches(payload)))
+    }
 }
 
 impl FilterCondition {
@@ -553,6 +759,77 @@ impl FilterCondition {
             condition: ConditionType::In(values),
         }
     }
+
+    /// Whether the payload satisfies this condition. `field` may be a dotted path, and
+    /// array values match when any element does
+    pub fn matches(&self, payload: &HashMap<String, serde_json::Value>) -> bool {
+        let values = payload_values(payload, &self.field);
+        let any = |predicate: &dyn Fn(&serde_json::Value) -> bool| values.iter().any(|&value| predicate(value));
+
+        match &self.condition {
+            ConditionType::Equals(expected) => any(&|value| values_equal(value, expected)),
+            ConditionType::NotEquals(excluded) => values.is_empty() || any(&|value| !values_equal(value, excluded)),
+            ConditionType::In(expected) => any(&|value| expected.iter().any(|e| values_equal(value, e))),
+            ConditionType::NotIn(excluded) => {
+                values.is_empty() || any(&|value| !excluded.iter().any(|e| values_equal(value, e)))
+            }
+            ConditionType::Range { gte, lte } => any(&|value| {
+                numeric_value(value).map_or(false, |number| {
+                    gte.map_or(true, |gte| number >= gte) && lte.map_or(true, |lte| number <= lte)
+                })
+            }),
+            ConditionType::Contains(text) => any(&|value| value.as_str().map_or(false, |s| s.contains(text.as_str()))),
+            ConditionType::StartsWith(prefix) => any(&|value| value.as_str().map_or(false, |s| s.starts_with(prefix.as_str()))),
+            ConditionType::EndsWith(suffix) => any(&|value| value.as_str().map_or(false, |s| s.ends_with(suffix.as_str()))),
+            ConditionType::Exists => !values.is_empty(),
+            ConditionType::NotExists => values.is_empty(),
+        }
+    }
+}
+
+/// Non-null values at a dotted path, with arrays flattened
+fn payload_values<'a>(payload: &'a HashMap<String, serde_json::Value>, path: &str) -> Vec<&'a serde_json::Value> {
+    let mut parts = path.split('.');
+    let mut current = match parts.next().and_then(|key| payload.get(key)) {
+        Some(value) => vec![value],
+        None => return vec![],
+    };
+    for key in parts {
+        current = current
+            .into_iter()
+            .flat_map(|value| match value {
+                serde_json::Value::Array(items) => items.iter().filter_map(|item| item.get(key)).collect(),
+                _ => value.get(key).into_iter().collect::<Vec<_>>(),
+            })
+            .collect();
+    }
+
+    current
+        .into_iter()
+        .flat_map(|value| match value {
+            serde_json::Value::Array(items) => items.iter().collect(),
+            _ => vec![value],
+        })
+        .filter(|value| !value.is_null())
+        .collect()
+}
+
+/// Numbers compare by value so `1` matches `1.0`
+fn values_equal(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+/// Numbers, plus RFC 3339 dates as Unix seconds to match `temporal_search`
+fn numeric_value(value: &serde_json::Value) -> Option<f64> {
+    value.as_f64().or_else(|| {
+        value
+            .as_str()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|date| date.timestamp() as f64)
+    })
 }
 
 #[cfg(test)]
@@ -586,6 +863,40 @@ mod tests {
         assert!(matches!(contains_condition.condition, ConditionType::Contains(_)));
     }
 
+    #[test]
+    fn test_filter_matches_payload() {
+        let payload: HashMap<String, serde_json::Value> = serde_json::from_value(serde_json::json!({
+            "agent_id": "a1",
+            "importance_score": 0.8,
+            "topics": ["rust", "music"],
+            "source": {"kind": "chat"},
+            "created_at": "2024-05-01T12:00:00Z",
+            "session_id": null
+        })).unwrap();
+        let condition = |field: &str, condition: ConditionType| FilterCondition { field: field.to_string(), condition };
+
+        assert!(SearchFilter::new().matches(&payload));
+        assert!(SearchFilter::new()
+            .must(FilterCondition::equals("topics".to_string(), "music".into()))
+            .must(FilterCondition::range("importance_score".to_string(), Some(0.5), None))
+            .must(condition("source.kind", ConditionType::StartsWith("ch".to_string())))
+            .must(condition("session_id", ConditionType::NotExists))
+            .matches(&payload));
+        assert!(!SearchFilter::new()
+            .must_not(condition("agent_id", ConditionType::NotIn(vec!["a2".into()])))
+            .matches(&payload));
+
+        // Dates compare as Unix seconds, like the temporal search range
+        let may = chrono::DateTime::parse_from_rfc3339("2024-05-01T00:00:00Z").unwrap().timestamp() as f64;
+        assert!(FilterCondition::range("created_at".to_string(), Some(may), Some(may + 86400.0)).matches(&payload));
+
+        let should = SearchFilter::new()
+            .should(FilterCondition::equals("agent_id".to_string(), "a2".into()))
+            .should(condition("missing", ConditionType::Exists));
+        assert!(!should.matches(&payload));
+        assert!(should.should(FilterCondition::contains("agent_id".to_string(), "1".to_string())).matches(&payload));
+    }
+
     #[test]
     fn test_similarity_metric_threshold_adjustment() {
         let engine = SemanticSearchEngine::new();
@@ -607,10 +918,65 @@ mod tests {
         let custom_weights = HybridWeights {
             vector_weight: 0.6,
             text_weight: 0.4,
+            ..Default::default()
         };
         assert_eq!(custom_weights.vector_weight + custom_weights.text_weight, 1.0);
     }
 
+    #[test]
+    fn test_fusion_surfaces_exact_text_matches() {
+        let engine = SemanticSearchEngine::new();
+        let (near, far, exact) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let payload = |content: &str, agent: &str| -> HashMap<String, serde_json::Value> {
+            HashMap::from([
+                ("content".to_string(), serde_json::json!(content)),
+                ("agent_id".to_string(), serde_json::json!(agent)),
+            ])
+        };
+        engine.index_payload("memories", near, &payload("connection problems with the server", "a"));
+        engine.index_payload("memories", far, &payload("lunch plans", "a"));
+        engine.index_payload("memories", exact, &payload("failed with ERR_CONN_RESET twice", "a"));
+
+        let text_results = engine.text_search("memories", "ERR_CONN_RESET", 10, None);
+        assert_eq!(text_results.iter().map(|result| result.id).collect::<Vec<_>>(), vec![exact]);
+        assert_eq!(text_results[0].payload["agent_id"], "a");
+        assert!(!text_results[0].payload.contains_key("content"));
+
+        // The embedding misses the error code entirely
+        let vector_results = vec![
+            VectorSearchResult { id: near, score: 0.62, payload: payload("connection problems with the server", "a") },
+            VectorSearchResult { id: far, score: 0.2, payload: payload("lunch plans", "a") },
+        ];
+
+        let weighted = HybridWeights { vector_weight: 0.5, text_weight: 0.5, ..Default::default() };
+        let fused = engine.fuse_results(vector_results.clone(), text_results.clone(), None, &weighted, 2);
+        assert_eq!(fused.iter().map(|result| result.id).collect::<Vec<_>>(), vec![exact, near]);
+        assert!((fused[0].score - 0.5).abs() < 1e-6);
+        assert_eq!(fused[0].payload["agent_id"], "a");
+
+        // Under RRF the two first places tie, whatever their raw scores
+        let rrf = HybridWeights { fusion: FusionMethod::Rrf, ..weighted };
+        let fused = engine.fuse_results(vector_results.clone(), text_results.clone(), None, &rrf, 3);
+        assert_eq!(fused[0].score, fused[1].score);
+        assert_eq!(fused[2].id, far);
+
+        // Text hits answer to the same filter as vector hits
+        let other_agent = SearchFilter::new().must(FilterCondition::equals("agent_id".to_string(), "b".into()));
+        let fused = engine.fuse_results(vector_results, text_results, Some(&other_agent), &weighted, 3);
+        assert!(fused.iter().all(|result| result.id != exact));
+
+        // Another agent's stronger matches don't use up the filtered search's limit
+        for _ in 0..5 {
+            engine.index_payload("memories", Uuid::new_v4(), &payload("ERR_CONN_RESET ERR_CONN_RESET", "b"));
+        }
+        let own_agent = SearchFilter::new().must(FilterCondition::equals("agent_id".to_string(), "a".into()));
+        let filtered = engine.text_search("memories", "ERR_CONN_RESET", 1, Some(&own_agent));
+        assert_eq!(filtered.iter().map(|result| result.id).collect::<Vec<_>>(), vec![exact]);
+
+        engine.remove_text("memories", exact);
+        assert!(engine.text_search("memories", "err_conn_reset", 10, Some(&own_agent)).is_empty());
+    }
+
     #[tokio::test]
     async fn test_deduplication_and_reranking() {
         let engine = SemanticSearchEngine::new();
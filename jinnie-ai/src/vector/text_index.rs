@@ -0,0 +1,206 @@
+//! BM25 inverted index for keyword search alongside embeddings.
+//!
+//! Embeddings blur exact strings, so identifiers, error codes and names are
+//! tokenized whole as well as split into their parts: `ERR_TIMEOUT` indexes
+//! `err_timeout`, `err` and `timeout`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Characters that join the parts of an identifier, like `E_OK`, `user-049` or `std::fs`
+const IDENTIFIER_JOINERS: [char; 6] = ['_', '-', '.', ':', '/', '#'];
+
+/// Lowercased terms of `text`, with identifiers kept whole and also split into parts
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for raw in text.split(|c: char| !c.is_alphanumeric() && !IDENTIFIER_JOINERS.contains(&c)) {
+        let token = raw.trim_matches(|c: char| !c.is_alphanumeric());
+        if token.is_empty() {
+            continue;
+        }
+        let token = token.to_lowercase();
+        let parts: Vec<&str> = token.split(|c: char| !c.is_alphanumeric()).filter(|part| !part.is_empty()).collect();
+        if parts.len() > 1 {
+            terms.extend(parts.iter().map(|part| part.to_string()));
+        }
+        terms.push(token);
+    }
+    terms
+}
+
+/// BM25 term-frequency saturation and length normalization
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bm25Params {
+    pub k1: f32,
+    pub b: f32,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self { k1: 1.2, b: 0.75 }
+    }
+}
+
+/// Inverted index over short texts, scored with Okapi BM25
+#[derive(Debug, Clone)]
+pub struct Bm25Index<K> {
+    params: Bm25Params,
+    /// Term to the documents containing it, with term frequency
+    postings: HashMap<String, HashMap<K, u32>>,
+    /// Length in terms and distinct terms of each document
+    documents: HashMap<K, (u32, Vec<String>)>,
+    total_length: u64,
+}
+
+impl<K: Clone + Eq + Hash + Ord> Default for Bm25Index<K> {
+    fn default() -> Self {
+        Self::new(Bm25Params::default())
+    }
+}
+
+impl<K: Clone + Eq + Hash + Ord> Bm25Index<K> {
+    pub fn new(params: Bm25Params) -> Self {
+        Self {
+            params,
+            postings: HashMap::new(),
+            documents: HashMap::new(),
+            total_length: 0,
+        }
+    }
+
+    /// Build an index from `(key, text)` pairs
+    pub fn from_texts<'a>(texts: impl IntoIterator<Item = (K, &'a str)>) -> Self {
+        let mut index = Self::default();
+        for (key, text) in texts {
+            index.insert(key, text);
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.documents.contains_key(key)
+    }
+
+    /// Index `text` under `key`, replacing what was there
+    pub fn insert(&mut self, key: K, text: &str) {
+        self.remove(&key);
+        let terms = tokenize(text);
+        if terms.is_empty() {
+            return;
+        }
+
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        for term in &terms {
+            *frequencies.entry(term.clone()).or_insert(0) += 1;
+        }
+        self.total_length += terms.len() as u64;
+        self.documents.insert(key.clone(), (terms.len() as u32, frequencies.keys().cloned().collect()));
+        for (term, frequency) in frequencies {
+            self.postings.entry(term).or_default().insert(key.clone(), frequency);
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> bool {
+        let Some((length, terms)) = self.documents.remove(key) else {
+            return false;
+        };
+        self.total_length -= length as u64;
+        for term in terms {
+            if let Some(posting) = self.postings.get_mut(&term) {
+                posting.remove(key);
+                if posting.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.postings.clear();
+        self.documents.clear();
+        self.total_length = 0;
+    }
+
+    /// Documents sharing at least one term with `query`, best first, at most `limit`
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(K, f32)> {
+        let mut scored: Vec<(K, f32)> = self.scores(query).into_iter().collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scored.truncate(limit);
+        scored
+    }
+
+    /// BM25 score of every document matching `query`
+    pub fn scores(&self, query: &str) -> HashMap<K, f32> {
+        let mut scores: HashMap<K, f32> = HashMap::new();
+        if self.documents.is_empty() {
+            return scores;
+        }
+
+        let count = self.documents.len() as f32;
+        let average_length = self.total_length as f32 / count;
+        let Bm25Params { k1, b } = self.params;
+
+        let unique_terms: HashSet<String> = tokenize(query).into_iter().collect();
+        for term in unique_terms {
+            let Some(posting) = self.postings.get(&term) else {
+                continue;
+            };
+            let frequency = posting.len() as f32;
+            let idf = (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln();
+            for (key, &term_frequency) in posting {
+                let length = self.documents[key].0 as f32;
+                let tf = term_frequency as f32;
+                let score = idf * tf * (k1 + 1.0) / (tf + k1 * (1.0 - b + b * length / average_length));
+                *scores.entry(key.clone()).or_insert(0.0) += score;
+            }
+        }
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_keeps_identifiers() {
+        assert_eq!(
+            tokenize("Got ERR_TIMEOUT from std::fs, see user-049."),
+            vec!["got", "err", "timeout", "err_timeout", "from", "std", "fs", "std::fs", "see", "user", "049", "user-049"]
+        );
+        assert_eq!(tokenize("Ana's (E0502)"), vec!["ana", "s", "e0502"]);
+    }
+
+    #[test]
+    fn test_bm25_ranks_rare_exact_terms_first() {
+        let mut index = Bm25Index::from_texts([
+            (1, "the build failed with error E0502 in the borrow checker"),
+            (2, "the build failed again and again and again"),
+            (3, "lunch with the team"),
+        ]);
+
+        let results = index.search("build error E0502", 10);
+        assert_eq!(results.iter().map(|(key, _)| *key).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(results[0].1 > results[1].1 * 2.0);
+
+        // Common words carry little weight
+        assert!(index.scores("the")[&3] < index.scores("lunch")[&3]);
+
+        index.remove(&1);
+        assert!(index.search("E0502", 10).is_empty());
+        index.insert(3, "error E0502 again");
+        assert_eq!(index.search("e0502", 10)[0].0, 3);
+        assert_eq!(index.len(), 2);
+    }
+}